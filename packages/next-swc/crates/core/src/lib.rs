@@ -38,6 +38,7 @@ use auto_cjs::contains_cjs;
 use either::Either;
 use fxhash::FxHashSet;
 use next_transform_font::next_font_loaders;
+use next_transform_use_cache::use_cache;
 use serde::Deserialize;
 use turbopack_binding::swc::{
     core::{
@@ -62,6 +63,7 @@ pub mod next_ssg;
 pub mod optimize_barrel;
 pub mod optimize_server_react;
 pub mod page_config;
+pub mod react_refresh_boundary;
 pub mod react_remove_properties;
 pub mod react_server_components;
 pub mod remove_console;
@@ -146,11 +148,17 @@ pub struct TransformOptions {
     #[serde(default)]
     pub server_actions: Option<server_actions::Config>,
 
+    #[serde(default)]
+    pub use_cache: Option<next_transform_use_cache::Config>,
+
     #[serde(default)]
     pub cjs_require_optimizer: Option<cjs_optimizer::Config>,
 
     #[serde(default)]
     pub optimize_server_react: Option<optimize_server_react::Config>,
+
+    #[serde(default)]
+    pub react_refresh_boundary: bool,
 }
 
 pub fn custom_before_pass<'a, C: Comments + 'a>(
@@ -226,6 +234,10 @@ where
             next_ssg::next_ssg(eliminated_packages),
             !opts.disable_next_ssg
         ),
+        Optional::new(
+            react_refresh_boundary::react_refresh_boundary(),
+            opts.react_refresh_boundary && !opts.is_server
+        ),
         amp_attributes::amp_attributes(),
         next_dynamic::next_dynamic(
             opts.is_development,
@@ -303,6 +315,10 @@ where
             Some(config) => Either::Left(next_font_loaders(config.clone())),
             None => Either::Right(noop()),
         },
+        match &opts.use_cache {
+            Some(config) => Either::Left(use_cache(config.clone())),
+            None => Either::Right(noop()),
+        },
         match &opts.server_actions {
             Some(config) => Either::Left(server_actions::server_actions(
                 &file.name,
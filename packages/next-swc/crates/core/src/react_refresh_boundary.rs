@@ -0,0 +1,233 @@
+// This transform determines whether a module is a React Refresh boundary, i.e.
+// whether all of its exports are React components, and marks the boundaries
+// with an `__N_REFRESH_BOUNDARY` export:
+//
+// export var __N_REFRESH_BOUNDARY = true;
+//
+// The Fast Refresh runtime uses it instead of inspecting the exports of the
+// module when deciding whether an update can be applied without reloading.
+// Modules whose exports can't be analyzed statically, like re-exports from
+// other modules, aren't marked and are still inspected at runtime.
+
+use fxhash::FxHashMap;
+use turbopack_binding::swc::core::{
+    common::DUMMY_SP,
+    ecma::{ast::*, utils::quote_ident, visit::Fold},
+};
+
+pub const REFRESH_BOUNDARY_EXPORT: &str = "__N_REFRESH_BOUNDARY";
+
+pub fn react_refresh_boundary() -> impl Fold {
+    ReactRefreshBoundary
+}
+
+struct ReactRefreshBoundary;
+
+impl Fold for ReactRefreshBoundary {
+    fn fold_module(&mut self, mut module: Module) -> Module {
+        let has_exports = module.body.iter().any(|item| {
+            matches!(
+                item,
+                ModuleItem::ModuleDecl(
+                    ModuleDecl::ExportDecl(_)
+                        | ModuleDecl::ExportDefaultDecl(_)
+                        | ModuleDecl::ExportDefaultExpr(_)
+                        | ModuleDecl::ExportNamed(_)
+                        | ModuleDecl::ExportAll(_)
+                )
+            )
+        });
+        // CommonJS modules, modules without exports and the ones which may
+        // not be boundaries are left to the runtime.
+        if !has_exports || !is_react_refresh_boundary(&module) {
+            return module;
+        }
+
+        module
+            .body
+            .push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                span: DUMMY_SP,
+                decl: Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Var,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(quote_ident!(REFRESH_BOUNDARY_EXPORT).into()),
+                        init: Some(Box::new(Expr::Lit(Lit::Bool(Bool {
+                            span: DUMMY_SP,
+                            value: true,
+                        })))),
+                        definite: false,
+                    }],
+                })),
+            })));
+        module
+    }
+}
+
+/// Whether the module has exports and all of them are likely React components.
+pub fn is_react_refresh_boundary(module: &Module) -> bool {
+    let locals = collect_local_components(module);
+    let mut has_exports = false;
+
+    for item in &module.body {
+        let ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+        let is_component = match decl {
+            ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+                Decl::Fn(FnDecl { ident, .. }) => {
+                    is_safe_export(&ident.sym) || is_component_name(&ident.sym)
+                }
+                Decl::Class(ClassDecl { ident, class, .. }) => {
+                    is_safe_export(&ident.sym) || is_class_component(class)
+                }
+                Decl::Var(var) => var.decls.iter().all(|decl| match &decl.name {
+                    Pat::Ident(ident) => {
+                        is_safe_export(&ident.id.sym)
+                            || is_component_init(&ident.id.sym, decl.init.as_deref())
+                    }
+                    _ => false,
+                }),
+                // Type declarations don't exist at runtime.
+                Decl::TsInterface(_) | Decl::TsTypeAlias(_) => continue,
+                _ => false,
+            },
+            ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl, .. }) => match decl {
+                DefaultDecl::Fn(FnExpr { ident, .. }) => ident
+                    .as_ref()
+                    .map_or(false, |ident| is_component_name(&ident.sym)),
+                DefaultDecl::Class(ClassExpr { class, .. }) => is_class_component(class),
+                DefaultDecl::TsInterfaceDecl(_) => continue,
+            },
+            ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, .. }) => match &**expr {
+                Expr::Ident(ident) => locals.get(&ident.to_id()).copied().unwrap_or(false),
+                Expr::Fn(FnExpr {
+                    ident: Some(ident), ..
+                }) => is_component_name(&ident.sym),
+                Expr::Class(ClassExpr { class, .. }) => is_class_component(class),
+                Expr::Call(call) => is_component_wrapper_call(call),
+                _ => false,
+            },
+            ModuleDecl::ExportNamed(NamedExport {
+                src: None,
+                specifiers,
+                type_only: false,
+                ..
+            }) => specifiers.iter().all(|specifier| match specifier {
+                ExportSpecifier::Named(ExportNamedSpecifier {
+                    orig: ModuleExportName::Ident(orig),
+                    exported,
+                    is_type_only,
+                    ..
+                }) => {
+                    let exported = match exported {
+                        Some(ModuleExportName::Ident(ident)) => &ident.sym,
+                        Some(ModuleExportName::Str(str)) => &str.value,
+                        None => &orig.sym,
+                    };
+                    *is_type_only
+                        || is_safe_export(exported)
+                        || locals.get(&orig.to_id()).copied().unwrap_or(false)
+                }
+                _ => false,
+            }),
+            ModuleDecl::ExportNamed(NamedExport {
+                type_only: true, ..
+            }) => continue,
+            ModuleDecl::ExportNamed(_)
+            | ModuleDecl::ExportAll(_)
+            | ModuleDecl::TsExportAssignment(_)
+            | ModuleDecl::TsNamespaceExport(_) => false,
+            ModuleDecl::Import(_) | ModuleDecl::TsImportEquals(_) => continue,
+        };
+        has_exports = true;
+        if !is_component {
+            return false;
+        }
+    }
+
+    has_exports
+}
+
+/// Collects whether the top level declarations of the module are likely React
+/// components. Imports are unknown and not included.
+fn collect_local_components(module: &Module) -> FxHashMap<Id, bool> {
+    let mut locals = FxHashMap::default();
+    for item in &module.body {
+        let decl = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => decl,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => decl,
+            _ => continue,
+        };
+        match decl {
+            Decl::Fn(FnDecl { ident, .. }) => {
+                locals.insert(ident.to_id(), is_component_name(&ident.sym));
+            }
+            Decl::Class(ClassDecl { ident, class, .. }) => {
+                locals.insert(ident.to_id(), is_class_component(class));
+            }
+            Decl::Var(var) => {
+                for decl in &var.decls {
+                    if let Pat::Ident(ident) = &decl.name {
+                        locals.insert(
+                            ident.id.to_id(),
+                            is_component_init(&ident.id.sym, decl.init.as_deref()),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    locals
+}
+
+/// Exports which are added by Next.js itself and don't affect whether a module
+/// is a boundary.
+fn is_safe_export(name: &str) -> bool {
+    matches!(
+        name,
+        "__esModule" | "__N_SSG" | "__N_SSP" | "config" | REFRESH_BOUNDARY_EXPORT
+    )
+}
+
+/// Like React Refresh, functions are considered components when their name
+/// starts with an uppercase letter.
+fn is_component_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+/// Classes are considered components when they extend another class, which
+/// is expected to be `React.Component`.
+fn is_class_component(class: &Class) -> bool {
+    class.super_class.is_some()
+}
+
+fn is_component_init(name: &str, init: Option<&Expr>) -> bool {
+    match init {
+        Some(Expr::Arrow(_) | Expr::Fn(_)) => is_component_name(name),
+        Some(Expr::Class(ClassExpr { class, .. })) => is_class_component(class),
+        Some(Expr::Call(call)) => is_component_wrapper_call(call),
+        Some(Expr::Paren(ParenExpr { expr, .. })) => is_component_init(name, Some(expr)),
+        _ => false,
+    }
+}
+
+/// `memo(...)` and `forwardRef(...)`, also as `React.memo(...)`, return
+/// component types.
+fn is_component_wrapper_call(call: &CallExpr) -> bool {
+    let Callee::Expr(callee) = &call.callee else {
+        return false;
+    };
+    let name = match &**callee {
+        Expr::Ident(ident) => &ident.sym,
+        Expr::Member(MemberExpr {
+            prop: MemberProp::Ident(prop),
+            ..
+        }) => &prop.sym,
+        _ => return false,
+    };
+    matches!(&**name, "memo" | "forwardRef")
+}
@@ -12,7 +12,10 @@ use turbopack_binding::swc::core::{
         ast::*,
         atoms::{js_word, JsWord},
         utils::{prepend_stmts, quote_ident, quote_str, ExprFactory},
-        visit::{as_folder, noop_visit_mut_type, Fold, VisitMut, VisitMutWith},
+        visit::{
+            as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitMutWith,
+            VisitWith,
+        },
     },
 };
 
@@ -50,6 +53,8 @@ struct ReactServerComponents<C: Comments> {
     invalid_client_imports: Vec<JsWord>,
     invalid_server_react_apis: Vec<JsWord>,
     invalid_server_react_dom_apis: Vec<JsWord>,
+    invalid_server_next_navigation_apis: Vec<JsWord>,
+    invalid_client_event_handler_apis: Vec<JsWord>,
     disable_checks: bool,
 }
 
@@ -377,6 +382,23 @@ impl<C: Comments> ReactServerComponents<C> {
                     }
                 }
             }
+            if source == *"next/navigation" {
+                for specifier in &import.specifiers {
+                    if self
+                        .invalid_server_next_navigation_apis
+                        .contains(&specifier.0)
+                    {
+                        HANDLER.with(|handler| {
+                            handler
+                                .struct_span_err(
+                                    specifier.1,
+                                    format!("NEXT_RSC_ERR_REACT_API: {}", &specifier.0).as_str(),
+                                )
+                                .emit()
+                        })
+                    }
+                }
+            }
         }
 
         self.assert_invalid_api(module, false);
@@ -427,6 +449,61 @@ impl<C: Comments> ReactServerComponents<C> {
         }
 
         self.assert_invalid_api(module, true);
+        self.assert_client_event_handler_apis(module);
+    }
+
+    // `redirect()` and `notFound()` throw to interrupt rendering, the error would
+    // be unhandled when thrown from an event handler.
+    fn assert_client_event_handler_apis(&self, module: &Module) {
+        let mut apis = vec![];
+        for item in &module.body {
+            let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+                continue;
+            };
+            if import.type_only || &*import.src.value != "next/navigation" {
+                continue;
+            }
+            for specifier in &import.specifiers {
+                if let ImportSpecifier::Named(named) = specifier {
+                    let imported = match &named.imported {
+                        Some(ModuleExportName::Ident(i)) => i.sym.clone(),
+                        Some(ModuleExportName::Str(s)) => s.value.clone(),
+                        None => named.local.sym.clone(),
+                    };
+                    if self.invalid_client_event_handler_apis.contains(&imported) {
+                        apis.push((named.local.sym.clone(), imported));
+                    }
+                }
+            }
+        }
+        if apis.is_empty() {
+            return;
+        }
+
+        let mut visitor = EventHandlerCallVisitor {
+            apis: &apis,
+            handler_names: vec![],
+            in_handler: false,
+            calls: vec![],
+        };
+        module.visit_with(&mut visitor);
+        // Visit again for handlers passed by name, e.g. `onClick={handleClick}`,
+        // which can be declared after their use.
+        module.visit_with(&mut visitor);
+
+        let mut calls = visitor.calls;
+        calls.sort_by_key(|(_, span)| span.lo);
+        calls.dedup();
+        for (api, span) in calls {
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_err(
+                        span,
+                        format!("NEXT_RSC_ERR_CLIENT_NAVIGATION_API: {}", api).as_str(),
+                    )
+                    .emit()
+            })
+        }
     }
 
     fn assert_invalid_api(&self, module: &Module, is_client_entry: bool) {
@@ -562,6 +639,95 @@ impl<C: Comments> ReactServerComponents<C> {
     }
 }
 
+/// Finds the calls of the given APIs inside of JSX event handlers, including
+/// the functions declared under the name of a handler passed by reference.
+struct EventHandlerCallVisitor<'a> {
+    /// The local and imported names of the APIs.
+    apis: &'a [(JsWord, JsWord)],
+    handler_names: Vec<JsWord>,
+    in_handler: bool,
+    calls: Vec<(JsWord, Span)>,
+}
+
+impl EventHandlerCallVisitor<'_> {
+    fn is_handler_name(&self, ident: &Ident) -> bool {
+        self.handler_names.contains(&ident.sym)
+    }
+
+    fn visit_handler<N: VisitWith<Self>>(&mut self, handler: &N) {
+        let in_handler = self.in_handler;
+        self.in_handler = true;
+        handler.visit_with(self);
+        self.in_handler = in_handler;
+    }
+}
+
+fn is_event_handler_prop(name: &str) -> bool {
+    name.strip_prefix("on")
+        .and_then(|rest| rest.chars().next())
+        .map_or(false, |c| c.is_ascii_uppercase())
+}
+
+impl Visit for EventHandlerCallVisitor<'_> {
+    noop_visit_type!();
+
+    fn visit_jsx_attr(&mut self, attr: &JSXAttr) {
+        let is_handler =
+            matches!(&attr.name, JSXAttrName::Ident(name) if is_event_handler_prop(&name.sym));
+        let Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+            expr: JSXExpr::Expr(expr),
+            ..
+        })) = &attr.value
+        else {
+            return attr.visit_children_with(self);
+        };
+        if !is_handler {
+            return expr.visit_with(self);
+        }
+        match &**expr {
+            Expr::Arrow(_) | Expr::Fn(_) => self.visit_handler(expr),
+            Expr::Ident(ident) => {
+                if !self.handler_names.contains(&ident.sym) {
+                    self.handler_names.push(ident.sym.clone());
+                }
+            }
+            _ => expr.visit_with(self),
+        }
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        if self.is_handler_name(&decl.ident) {
+            self.visit_handler(&decl.function);
+        } else {
+            decl.visit_children_with(self);
+        }
+    }
+
+    fn visit_var_declarator(&mut self, declarator: &VarDeclarator) {
+        if let (Pat::Ident(name), Some(init)) = (&declarator.name, &declarator.init) {
+            if self.is_handler_name(&name.id) && matches!(&**init, Expr::Arrow(_) | Expr::Fn(_)) {
+                return self.visit_handler(init);
+            }
+        }
+        declarator.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if self.in_handler {
+            if let Callee::Expr(callee) = &call.callee {
+                if let Expr::Ident(ident) = &**callee {
+                    if let Some((_, imported)) =
+                        self.apis.iter().find(|(local, _)| *local == ident.sym)
+                    {
+                        self.calls.push((imported.clone(), call.span));
+                    }
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
 pub fn server_components<C: Comments>(
     filename: FileName,
     config: Config,
@@ -594,6 +760,20 @@ pub fn server_components<C: Comments>(
             JsWord::from("experimental_useFormStatus"),
             JsWord::from("experimental_useOptimistic"),
         ],
+        invalid_server_next_navigation_apis: vec![
+            JsWord::from("useParams"),
+            JsWord::from("usePathname"),
+            JsWord::from("useRouter"),
+            JsWord::from("useSearchParams"),
+            JsWord::from("useSelectedLayoutSegment"),
+            JsWord::from("useSelectedLayoutSegments"),
+            JsWord::from("useServerInsertedHTML"),
+        ],
+        invalid_client_event_handler_apis: vec![
+            JsWord::from("notFound"),
+            JsWord::from("permanentRedirect"),
+            JsWord::from("redirect"),
+        ],
         invalid_server_react_apis: vec![
             JsWord::from("Component"),
             JsWord::from("createContext"),
@@ -10,6 +10,7 @@ use next_swc::{
     },
 };
 use next_transform_font::{next_font_loaders, Config as FontLoaderConfig};
+use next_transform_use_cache::{use_cache, Config as UseCacheConfig};
 use turbopack_binding::swc::{
     core::{
         common::{chain, FileName, Mark},
@@ -225,3 +226,26 @@ fn react_server_actions_client_errors(input: PathBuf) {
         },
     );
 }
+
+#[fixture("tests/errors/use-cache/server-graph/**/input.js")]
+fn use_cache_server_errors(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|_tr| {
+            chain!(
+                resolver(Mark::new(), Mark::new(), false),
+                use_cache(UseCacheConfig {
+                    is_server: true,
+                    relative_file_path_from_root: "app/item.js".into(),
+                })
+            )
+        },
+        &input,
+        &output,
+        FixtureTestConfig {
+            allow_error: true,
+            ..Default::default()
+        },
+    );
+}
@@ -0,0 +1,9 @@
+import { redirect, notFound } from 'next/navigation'
+
+function handleClick() {
+  notFound()
+}
+
+export default function () {
+  return <button onClick={() => redirect('/login')} onBlur={handleClick} />
+}
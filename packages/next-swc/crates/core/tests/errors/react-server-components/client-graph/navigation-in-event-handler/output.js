@@ -0,0 +1,7 @@
+import { redirect, notFound } from 'next/navigation';
+function handleClick() {
+    notFound();
+}
+export default function() {
+    return <button onClick={()=>redirect('/login')} onBlur={handleClick}/>;
+}
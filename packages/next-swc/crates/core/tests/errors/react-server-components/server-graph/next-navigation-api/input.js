@@ -0,0 +1,5 @@
+import { useRouter, redirect } from 'next/navigation'
+
+export default function () {
+  return null
+}
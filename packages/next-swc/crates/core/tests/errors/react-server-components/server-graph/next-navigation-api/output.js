@@ -0,0 +1,4 @@
+import { useRouter, redirect } from 'next/navigation';
+export default function() {
+    return null;
+}
@@ -0,0 +1,4 @@
+export function getUser(id) {
+  'use cache'
+  return db.user(id)
+}
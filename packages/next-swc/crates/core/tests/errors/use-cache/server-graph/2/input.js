@@ -0,0 +1,12 @@
+export async function getUser(id) {
+  'use cache'
+  return db.user(id)
+}
+
+export async function Page() {
+  async function getPosts() {
+    'use cache'
+    return db.posts()
+  }
+  return getUser(() => 1)
+}
@@ -0,0 +1,14 @@
+import { cacheWrapper as $$cache__ } from "private-next-rsc-cache-wrapper";
+export async function getUser(...args) {
+    return $$cache__("f400a9b095d97edc2adf91ec80c0e262bbccc577", $$cache_original__getUser)(...args);
+}
+async function $$cache_original__getUser(id) {
+    return db.user(id);
+}
+export async function Page() {
+    async function getPosts() {
+        'use cache';
+        return db.posts();
+    }
+    return getUser(()=>1);
+}
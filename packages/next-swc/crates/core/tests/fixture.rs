@@ -9,6 +9,7 @@ use next_swc::{
     optimize_barrel::optimize_barrel,
     optimize_server_react::optimize_server_react,
     page_config::page_config_test,
+    react_refresh_boundary::react_refresh_boundary,
     react_remove_properties::remove_properties,
     react_server_components::server_components,
     remove_console::remove_console,
@@ -18,6 +19,7 @@ use next_swc::{
     shake_exports::{shake_exports, Config as ShakeExportsConfig},
 };
 use next_transform_font::{next_font_loaders, Config as FontLoaderConfig};
+use next_transform_use_cache::{use_cache, Config as UseCacheConfig};
 use serde::de::DeserializeOwned;
 use turbopack_binding::swc::{
     core::{
@@ -442,6 +444,26 @@ fn server_actions_server_fixture(input: PathBuf) {
     );
 }
 
+#[fixture("tests/fixture/use-cache/server/**/input.js")]
+fn use_cache_server_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|_tr| {
+            chain!(
+                resolver(Mark::new(), Mark::new(), false),
+                use_cache(UseCacheConfig {
+                    is_server: true,
+                    relative_file_path_from_root: "app/item.js".into(),
+                })
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
 #[fixture("tests/fixture/server-actions/client/**/input.js")]
 fn server_actions_client_fixture(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
@@ -603,6 +625,18 @@ fn optimize_server_react_fixture(input: PathBuf) {
     );
 }
 
+#[fixture("tests/fixture/react-refresh-boundary/**/input.js")]
+fn react_refresh_boundary_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|_tr| react_refresh_boundary(),
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
 fn json<T>(s: &str) -> T
 where
     T: DeserializeOwned,
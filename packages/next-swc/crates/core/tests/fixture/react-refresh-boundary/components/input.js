@@ -0,0 +1,17 @@
+import { memo } from 'react'
+
+function Header() {
+  return null
+}
+
+export const Footer = () => null
+
+export const Sidebar = memo(function Sidebar() {
+  return null
+})
+
+export { Header }
+
+export default function Page() {
+  return null
+}
@@ -0,0 +1,7 @@
+export const revalidate = 60;
+export function formatTitle(title) {
+    return title.toUpperCase();
+}
+export default function Page() {
+    return null;
+}
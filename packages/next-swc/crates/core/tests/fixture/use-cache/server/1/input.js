@@ -0,0 +1,13 @@
+export async function getUser(id) {
+  'use cache'
+  return db.user(id)
+}
+
+export const getPosts = async () => {
+  'use cache'
+  return db.posts()
+}
+
+async function notCached() {
+  return 1
+}
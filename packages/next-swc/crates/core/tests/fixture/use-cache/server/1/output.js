@@ -0,0 +1,13 @@
+import { cacheWrapper as $$cache__ } from "private-next-rsc-cache-wrapper";
+export async function getUser(...args) {
+    return $$cache__("f400a9b095d97edc2adf91ec80c0e262bbccc577", $$cache_original__getUser)(...args);
+}
+async function $$cache_original__getUser(id) {
+    return db.user(id);
+}
+export const getPosts = $$cache__("e1d32b462c420bdb0f9f18ac7f52b0c59f41788b", async ()=>{
+    return db.posts();
+});
+async function notCached() {
+    return 1;
+}
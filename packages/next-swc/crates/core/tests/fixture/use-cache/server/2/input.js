@@ -0,0 +1,11 @@
+'use cache'
+
+async function getProduct(id) {
+  return db.product(id)
+}
+
+export default async function getProducts() {
+  return db.products()
+}
+
+export { getProduct }
@@ -0,0 +1,14 @@
+import { cacheWrapper as $$cache__ } from "private-next-rsc-cache-wrapper";
+async function getProduct(...args) {
+    return $$cache__("1f092bedd8b56094da6fb67e3cda7f7ac041f897", $$cache_original__getProduct)(...args);
+}
+async function $$cache_original__getProduct(id) {
+    return db.product(id);
+}
+export default async function getProducts(...args) {
+    return $$cache__("28eb491f7fbd64652c7cc86a68e288bb324386b8", $$cache_original__getProducts)(...args);
+}
+async function $$cache_original__getProducts() {
+    return db.products();
+}
+export { getProduct };
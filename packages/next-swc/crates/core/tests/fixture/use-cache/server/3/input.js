@@ -0,0 +1,6 @@
+const user = getUser(1)
+
+export async function getUser(id) {
+  'use cache'
+  return db.user(id)
+}
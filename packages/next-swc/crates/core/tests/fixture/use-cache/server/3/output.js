@@ -0,0 +1,8 @@
+import { cacheWrapper as $$cache__ } from "private-next-rsc-cache-wrapper";
+const user = getUser(1);
+export async function getUser(...args) {
+    return $$cache__("f400a9b095d97edc2adf91ec80c0e262bbccc577", $$cache_original__getUser)(...args);
+}
+async function $$cache_original__getUser(id) {
+    return db.user(id);
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, path::MAIN_SEPARATOR, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::{anyhow, Result};
 use napi::{
@@ -6,9 +6,12 @@ use napi::{
     threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
     JsFunction,
 };
-use next_core::app_structure::{
-    find_app_dir, get_entrypoints as get_entrypoints_impl, Components, Entrypoint, Entrypoints,
-    LoaderTree, MetadataWithAltItem,
+use next_core::{
+    app_structure::{
+        find_app_dir, get_entrypoints as get_entrypoints_impl, Components, Entrypoint, Entrypoints,
+        LoaderTree, MetadataWithAltItem,
+    },
+    project_relative_path::ProjectRelativePath,
 };
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{ReadRef, Vc};
@@ -271,12 +274,7 @@ async fn get_value(
 ) -> Result<Vc<OptionEntrypointsForJs>> {
     let page_extensions = Vc::cell(page_extensions);
     let fs = project_fs(root_dir.clone(), watching);
-    let project_relative = project_dir.strip_prefix(&root_dir).unwrap();
-    let project_relative = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/");
-    let project_path = fs.root().join(project_relative);
+    let project_path = ProjectRelativePath::new(&root_dir, &project_dir)?.join(fs.root());
 
     let app_dir = find_app_dir(project_path);
 
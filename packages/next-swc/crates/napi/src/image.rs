@@ -0,0 +1,63 @@
+use napi::bindgen_prelude::*;
+use next_core::next_image::optimizer::{
+    optimize_image as optimize_image_impl, supported_content_types, ImageOptimizerOptions,
+};
+
+use crate::util::MapErr;
+
+#[napi(object)]
+pub struct OptimizeImageOptions {
+    pub content_type: String,
+    pub width: u32,
+    pub height: Option<u32>,
+    pub quality: u32,
+}
+
+pub struct OptimizeImageTask {
+    buffer: Buffer,
+    options: OptimizeImageOptions,
+}
+
+#[napi]
+impl Task for OptimizeImageTask {
+    type Output = Vec<u8>;
+
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        optimize_image_impl(
+            &self.buffer,
+            &ImageOptimizerOptions {
+                content_type: &self.options.content_type,
+                width: self.options.width,
+                height: self.options.height,
+                quality: self.options.quality.clamp(1, 100) as u8,
+            },
+        )
+        .convert_err()
+    }
+
+    fn resolve(&mut self, _: napi::Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Resizes and re-encodes an image on the libuv thread pool, so the dev server
+/// can serve `/_next/image` without sharp.
+#[napi]
+pub fn optimize_image(
+    buffer: Buffer,
+    options: OptimizeImageOptions,
+    signal: Option<AbortSignal>,
+) -> AsyncTask<OptimizeImageTask> {
+    AsyncTask::with_optional_signal(OptimizeImageTask { buffer, options }, signal)
+}
+
+/// The content types `optimizeImage` can encode into.
+#[napi]
+pub fn optimize_image_content_types() -> Vec<String> {
+    supported_content_types()
+        .into_iter()
+        .map(|content_type| content_type.to_string())
+        .collect()
+}
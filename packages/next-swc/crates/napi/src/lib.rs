@@ -49,6 +49,7 @@ use turbopack_binding::swc::core::{
 };
 
 pub mod app_structure;
+pub mod image;
 pub mod mdx;
 pub mod minify;
 pub mod next_api;
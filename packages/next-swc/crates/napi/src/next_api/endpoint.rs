@@ -1,13 +1,20 @@
-use std::ops::Deref;
+use std::{ops::Deref, sync::Arc};
 
 use napi::{bindgen_prelude::External, JsFunction};
 use next_api::route::{Endpoint, WrittenEndpoint};
-use turbo_tasks::Vc;
-use turbopack_binding::turbopack::core::error::PrettyPrintError;
+use turbo_tasks::{ReadRef, TurboTasks, Vc};
+use turbopack_binding::{
+    turbo::{tasks_fs::FileSystemPath, tasks_memory::MemoryBackend},
+    turbopack::core::{error::PrettyPrintError, issue::PlainIssue},
+};
 
-use super::utils::{
-    get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue, RootTask, TurbopackResult,
-    VcArc,
+use super::{
+    on_demand_entries::OnDemandEntriesState,
+    snapshot::{has_errors, EndpointSnapshots},
+    utils::{
+        get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue, RootTask,
+        TurbopackResult, VcArc,
+    },
 };
 
 #[napi(object)]
@@ -58,7 +65,47 @@ impl From<&WrittenEndpoint> for NapiWrittenEndpoint {
 //    some async functions (in this case `endpoint_write_to_disk`) can cause
 //    higher-ranked lifetime errors. See https://github.com/rust-lang/rust/issues/102211
 // 2. the type_complexity clippy lint.
-pub struct ExternalEndpoint(pub VcArc<Vc<Box<dyn Endpoint>>>);
+pub struct ExternalEndpoint(pub VcArc<Vc<Box<dyn Endpoint>>>, pub Option<EndpointEntry>);
+
+/// The route entry an endpoint belongs to, for the on-demand compilation in
+/// dev.
+pub struct EndpointEntry {
+    pub pathname: String,
+    /// Which endpoint of the route this is, e.g. `html` or `data`.
+    pub name: &'static str,
+    pub state: Arc<OnDemandEntriesState>,
+    pub snapshots: Arc<EndpointSnapshots>,
+    /// The directory the paths of the written endpoint are relative to.
+    pub node_root: Vc<FileSystemPath>,
+}
+
+impl EndpointEntry {
+    fn snapshot_key(&self) -> String {
+        format!("{} {}", self.pathname, self.name)
+    }
+}
+
+impl ExternalEndpoint {
+    fn add_subscription(&self, root_task: &RootTask) {
+        if let Some(entry) = &self.1 {
+            entry.state.add_subscription(&entry.pathname, root_task);
+        }
+    }
+
+    /// Marks the route entry as requested and reports its status, for each
+    /// change sent to the browser.
+    fn report_change(&self) -> impl Fn(&[ReadRef<PlainIssue>]) + Send + Sync + 'static {
+        let entry = self
+            .1
+            .as_ref()
+            .map(|entry| (entry.state.clone(), entry.pathname.clone()));
+        move |issues| {
+            if let Some((state, pathname)) = &entry {
+                state.report_change(pathname, has_errors(issues));
+            }
+        }
+    }
+}
 
 impl Deref for ExternalEndpoint {
     type Target = VcArc<Vc<Box<dyn Endpoint>>>;
@@ -73,17 +120,80 @@ pub async fn endpoint_write_to_disk(
     #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
 ) -> napi::Result<TurbopackResult<NapiWrittenEndpoint>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
+    let entry = endpoint.1.as_ref();
     let endpoint = ***endpoint;
-    let (written, issues, diags) = turbo_tasks
-        .run_once(async move {
-            let write_to_disk = endpoint.write_to_disk();
-            let issues = get_issues(write_to_disk).await?;
-            let diags = get_diagnostics(write_to_disk).await?;
-            let written = write_to_disk.strongly_consistent().await?;
-            Ok((written, issues, diags))
-        })
-        .await
-        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    let compilation = turbo_tasks.run_once(async move {
+        let write_to_disk = endpoint.write_to_disk();
+        let issues = get_issues(write_to_disk).await?;
+        let diags = get_diagnostics(write_to_disk).await?;
+        let written = write_to_disk.strongly_consistent().await?;
+        Ok((written, issues, diags))
+    });
+    let result = match entry {
+        Some(entry) => {
+            entry
+                .state
+                .compile(&entry.pathname, compilation, |(_, issues, _)| {
+                    has_errors(issues)
+                })
+                .await
+        }
+        None => compilation.await,
+    };
+
+    // Keep serving the last good output of the route if the edit broke it.
+    if let Some(entry) = entry {
+        let key = entry.snapshot_key();
+        match &result {
+            Ok((written, issues, _)) if !has_errors(issues) => {
+                let snapshots = entry.snapshots.clone();
+                let node_root = entry.node_root;
+                let written = written.clone();
+                let saved = turbo_tasks
+                    .run_once(async move { snapshots.save(node_root, &key, written).await })
+                    .await;
+                if let Err(err) = saved {
+                    tracing::warn!(
+                        "unable to save the output of {}: {}",
+                        entry.pathname,
+                        PrettyPrintError(&err)
+                    );
+                }
+            }
+            Ok((_, issues, diags)) => {
+                if let Some(written) = restore_snapshot(&turbo_tasks, entry, key).await {
+                    return Ok(TurbopackResult {
+                        result: NapiWrittenEndpoint::from(&*written),
+                        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+                        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+                    });
+                }
+            }
+            Err(err) => {
+                if let Some(written) = restore_snapshot(&turbo_tasks, entry, key).await {
+                    return Ok(TurbopackResult {
+                        result: NapiWrittenEndpoint::from(&*written),
+                        issues: vec![NapiIssue {
+                            severity: "error".to_string(),
+                            category: "compile".to_string(),
+                            file_path: entry.pathname.clone(),
+                            title: "Compilation failed, serving the last successful output"
+                                .to_string(),
+                            description: PrettyPrintError(err).to_string(),
+                            detail: String::new(),
+                            source: None,
+                            documentation_link: String::new(),
+                            sub_issues: vec![],
+                        }],
+                        diagnostics: vec![],
+                    });
+                }
+            }
+        }
+    }
+
+    let (written, issues, diags) =
+        result.map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
     // TODO diagnostics
     Ok(TurbopackResult {
         result: NapiWrittenEndpoint::from(&*written),
@@ -92,14 +202,35 @@ pub async fn endpoint_write_to_disk(
     })
 }
 
+async fn restore_snapshot(
+    turbo_tasks: &Arc<TurboTasks<MemoryBackend>>,
+    entry: &EndpointEntry,
+    key: String,
+) -> Option<ReadRef<WrittenEndpoint>> {
+    let snapshots = entry.snapshots.clone();
+    turbo_tasks
+        .run_once(async move { snapshots.restore(&key).await })
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!(
+                "unable to restore the last good output of {}: {}",
+                entry.pathname,
+                PrettyPrintError(&err)
+            );
+            None
+        })
+}
+
 #[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
 pub fn endpoint_server_changed_subscribe(
     #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
     func: JsFunction,
 ) -> napi::Result<External<RootTask>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
+    let external_endpoint = &*endpoint;
+    let report_change = external_endpoint.report_change();
     let endpoint = ***endpoint;
-    subscribe(
+    let root_task = subscribe(
         turbo_tasks,
         func,
         move || async move {
@@ -109,15 +240,18 @@ pub fn endpoint_server_changed_subscribe(
             changed.strongly_consistent().await?;
             Ok((issues, diags))
         },
-        |ctx| {
+        move |ctx| {
             let (issues, diags) = ctx.value;
+            report_change(&issues);
             Ok(vec![TurbopackResult {
                 result: (),
                 issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
                 diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
             }])
         },
-    )
+    )?;
+    external_endpoint.add_subscription(&root_task);
+    Ok(root_task)
 }
 
 #[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
@@ -126,8 +260,10 @@ pub fn endpoint_client_changed_subscribe(
     func: JsFunction,
 ) -> napi::Result<External<RootTask>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
+    let external_endpoint = &*endpoint;
+    let report_change = external_endpoint.report_change();
     let endpoint = ***endpoint;
-    subscribe(
+    let root_task = subscribe(
         turbo_tasks,
         func,
         move || async move {
@@ -137,13 +273,16 @@ pub fn endpoint_client_changed_subscribe(
             changed.strongly_consistent().await?;
             Ok((issues, diags))
         },
-        |ctx| {
+        move |ctx| {
             let (issues, diags) = ctx.value;
+            report_change(&issues);
             Ok(vec![TurbopackResult {
                 result: (),
                 issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
                 diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
             }])
         },
-    )
+    )?;
+    external_endpoint.add_subscription(&root_task);
+    Ok(root_task)
 }
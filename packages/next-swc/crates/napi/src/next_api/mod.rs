@@ -1,3 +1,5 @@
 pub mod endpoint;
+pub mod on_demand_entries;
 pub mod project;
+pub mod snapshot;
 pub mod utils;
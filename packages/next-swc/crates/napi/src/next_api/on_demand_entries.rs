@@ -0,0 +1,143 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use next_api::on_demand_entries::OnDemandEntries;
+use next_core::next_config::OnDemandEntriesConfig;
+use tokio::sync::{broadcast, Semaphore};
+
+use super::utils::RootTask;
+
+/// The dev-mode on-demand compilation state of the route entries of a
+/// project: limits the number of concurrent compilations and finds the
+/// inactive entries, which the dev server can stop serving.
+pub struct OnDemandEntriesState {
+    entries: Mutex<OnDemandEntries>,
+    compilations: Mutex<Arc<Semaphore>>,
+    status_updates: broadcast::Sender<RouteStatusUpdate>,
+}
+
+/// The compilation status of a route entry.
+#[derive(Clone, Copy, Debug)]
+pub enum RouteStatus {
+    /// Waiting for a compilation slot.
+    Queued,
+    Compiling,
+    /// Compiled without errors, in the given time when it was compiled on
+    /// request rather than after a change.
+    Ready(Option<Duration>),
+    /// Compiled with errors or failed, in the given time when it was compiled
+    /// on request rather than after a change.
+    Errored(Option<Duration>),
+}
+
+#[derive(Clone, Debug)]
+pub struct RouteStatusUpdate {
+    pub pathname: String,
+    pub status: RouteStatus,
+}
+
+impl OnDemandEntriesState {
+    pub fn new(config: &OnDemandEntriesConfig) -> Self {
+        OnDemandEntriesState {
+            entries: Mutex::new(OnDemandEntries::new(config)),
+            compilations: Mutex::new(compilations_semaphore(config)),
+            status_updates: broadcast::channel(1024).0,
+        }
+    }
+
+    /// Updates the policy. Compilations which are already running keep the
+    /// previous concurrency limit.
+    pub fn set_config(&self, config: &OnDemandEntriesConfig) {
+        self.entries.lock().unwrap().set_config(config);
+        *self.compilations.lock().unwrap() = compilations_semaphore(config);
+    }
+
+    /// Compiles an entry once a compilation slot is available, reporting its
+    /// status along the way. `has_errors` tells whether a successful
+    /// compilation reported errors.
+    pub async fn compile<T>(
+        &self,
+        pathname: &str,
+        compilation: impl Future<Output = Result<T>>,
+        has_errors: impl FnOnce(&T) -> bool,
+    ) -> Result<T> {
+        self.send_status(pathname, RouteStatus::Queued);
+        let semaphore = self.compilations.lock().unwrap().clone();
+        let _permit = semaphore.acquire_owned().await;
+        self.entries.lock().unwrap().touch(pathname, Instant::now());
+
+        self.send_status(pathname, RouteStatus::Compiling);
+        let start = Instant::now();
+        let result = compilation.await;
+        let status = match &result {
+            Ok(value) if !has_errors(value) => RouteStatus::Ready(Some(start.elapsed())),
+            _ => RouteStatus::Errored(Some(start.elapsed())),
+        };
+        self.send_status(pathname, status);
+        result
+    }
+
+    /// Subscribes to the compilation status updates of all entries.
+    pub fn subscribe_status(&self) -> broadcast::Receiver<RouteStatusUpdate> {
+        self.status_updates.subscribe()
+    }
+
+    fn send_status(&self, pathname: &str, status: RouteStatus) {
+        // Sending only fails when nobody is subscribed.
+        let _ = self.status_updates.send(RouteStatusUpdate {
+            pathname: pathname.to_string(),
+            status,
+        });
+    }
+
+    /// Keeps the entry while JS is subscribed to its changes through the root
+    /// task.
+    pub fn add_subscription(self: &Arc<Self>, pathname: &str, root_task: &RootTask) {
+        self.entries
+            .lock()
+            .unwrap()
+            .subscribe(pathname, Instant::now());
+        let state = self.clone();
+        let pathname = pathname.to_string();
+        root_task.on_dispose(move || state.entries.lock().unwrap().unsubscribe(&pathname));
+    }
+
+    /// Marks the entry as requested now and reports its status after a
+    /// change of it was recompiled, e.g. for HMR.
+    pub fn report_change(&self, pathname: &str, has_errors: bool) {
+        self.entries.lock().unwrap().touch(pathname, Instant::now());
+        self.send_status(
+            pathname,
+            if has_errors {
+                RouteStatus::Errored(None)
+            } else {
+                RouteStatus::Ready(None)
+            },
+        );
+    }
+
+    /// Forgets the unsubscribed entries which have been inactive for too long
+    /// and returns these entries.
+    pub fn dispose_inactive(&self) -> Vec<String> {
+        self.entries
+            .lock()
+            .unwrap()
+            .dispose_inactive(Instant::now())
+    }
+
+    pub fn hot_entries(&self) -> Vec<String> {
+        self.entries.lock().unwrap().hot_entries()
+    }
+}
+
+fn compilations_semaphore(config: &OnDemandEntriesConfig) -> Arc<Semaphore> {
+    let permits = match config.max_concurrent_compilations {
+        Some(max) => (max as usize).max(1),
+        None => Semaphore::MAX_PERMITS,
+    };
+    Arc::new(Semaphore::new(permits))
+}
@@ -7,18 +7,19 @@ use napi::{
     JsFunction, Status,
 };
 use next_api::{
-    project::{Middleware, ProjectContainer, ProjectOptions},
+    project::{DevServerOptions, HttpsOptions, Middleware, ProjectContainer, ProjectOptions},
     route::{Endpoint, Route},
 };
 use next_core::tracing_presets::{
     TRACING_NEXT_TARGETS, TRACING_NEXT_TURBOPACK_TARGETS, TRACING_NEXT_TURBO_TASKS_TARGETS,
 };
+use tokio::sync::broadcast::error::RecvError;
 use tracing_subscriber::{
     prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry,
 };
 use turbo_tasks::{TransientInstance, TurboTasks, UpdateInfo, Vc};
 use turbopack_binding::{
-    turbo::tasks_memory::MemoryBackend,
+    turbo::{tasks_fs::FileSystemPath, tasks_memory::MemoryBackend},
     turbopack::{
         cli_utils::{
             exit::ExitGuard,
@@ -35,7 +36,9 @@ use turbopack_binding::{
 };
 
 use super::{
-    endpoint::ExternalEndpoint,
+    endpoint::{EndpointEntry, ExternalEndpoint},
+    on_demand_entries::{OnDemandEntriesState, RouteStatus, RouteStatusUpdate},
+    snapshot::{has_errors, EndpointSnapshots},
     utils::{
         get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue, RootTask,
         TurbopackResult, VcArc,
@@ -69,6 +72,29 @@ pub struct NapiProjectOptions {
 
     /// A map of environment variables to use when compiling code.
     pub env: Vec<NapiEnvVar>,
+
+    /// Only collect the app and pages routes whose pathnames start with one of
+    /// these prefixes.
+    pub route_prefixes: Option<Vec<String>>,
+
+    /// How the dev server is reached, when running in dev.
+    pub dev_server: Option<NapiDevServerOptions>,
+}
+
+#[napi(object)]
+pub struct NapiDevServerOptions {
+    pub hostname: String,
+    pub port: u32,
+    /// The key and certificate paths when the dev server uses HTTPS.
+    pub https: Option<NapiHttpsOptions>,
+    /// Hostnames other than the dev server itself which may connect to it.
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+#[napi(object)]
+pub struct NapiHttpsOptions {
+    pub key: String,
+    pub cert: String,
 }
 
 #[napi(object)]
@@ -90,6 +116,15 @@ impl From<NapiProjectOptions> for ProjectOptions {
                 .into_iter()
                 .map(|NapiEnvVar { name, value }| (name, value))
                 .collect(),
+            route_prefixes: val.route_prefixes,
+            dev_server: val.dev_server.map(|dev_server| DevServerOptions {
+                hostname: dev_server.hostname,
+                port: dev_server.port as u16,
+                https: dev_server
+                    .https
+                    .map(|NapiHttpsOptions { key, cert }| HttpsOptions { key, cert }),
+                allowed_origins: dev_server.allowed_origins,
+            }),
         }
     }
 }
@@ -97,6 +132,8 @@ impl From<NapiProjectOptions> for ProjectOptions {
 pub struct ProjectInstance {
     turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
     container: Vc<ProjectContainer>,
+    on_demand_entries: Arc<OnDemandEntriesState>,
+    snapshots: Arc<EndpointSnapshots>,
     #[allow(dead_code)]
     guard: Option<ExitGuard<TraceWriterGuard>>,
 }
@@ -156,19 +193,25 @@ pub async fn project_new(
             .map(|m| m as usize)
             .unwrap_or(usize::MAX),
     ));
+    let snapshots = Arc::new(EndpointSnapshots::default());
     let options = options.into();
-    let container = turbo_tasks
+    let (container, on_demand_entries_config) = turbo_tasks
         .run_once(async move {
             let project = ProjectContainer::new(options);
             let project = project.resolve().await?;
-            Ok(project)
+            let on_demand_entries_config =
+                project.project().next_config().on_demand_entries().await?;
+            Ok((project, on_demand_entries_config))
         })
         .await
         .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    let on_demand_entries = Arc::new(OnDemandEntriesState::new(&on_demand_entries_config));
     Ok(External::new_with_size_hint(
         ProjectInstance {
             turbo_tasks,
             container,
+            on_demand_entries,
+            snapshots,
             guard,
         },
         100,
@@ -183,16 +226,180 @@ pub async fn project_update(
     let turbo_tasks = project.turbo_tasks.clone();
     let options = options.into();
     let container = project.container;
-    turbo_tasks
+    let on_demand_entries_config = turbo_tasks
         .run_once(async move {
             container.update(options).await?;
-            Ok(())
+            Ok(container
+                .project()
+                .next_config()
+                .on_demand_entries()
+                .await?)
         })
         .await
         .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    project
+        .on_demand_entries
+        .set_config(&on_demand_entries_config);
     Ok(())
 }
 
+/// Compiles the routes matching the `experimental.turbo.warmUp` globs, next to
+/// the routes compiled on demand, and returns the routes which compiled
+/// successfully. Errors are reported when the route is requested.
+#[napi]
+pub async fn project_warm_up(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<Vec<String>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let routes = turbo_tasks
+        .run_once(async move { Ok(container.warm_up_routes().strongly_consistent().await?) })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+
+    let compilations = routes
+        .iter()
+        .filter_map(|(pathname, route)| {
+            let endpoint = route.primary_endpoint()?;
+            let turbo_tasks = turbo_tasks.clone();
+            let on_demand_entries = project.on_demand_entries.clone();
+            let pathname = pathname.clone();
+            Some(tokio::spawn(async move {
+                let compilation = turbo_tasks.run_once(async move {
+                    let write_to_disk = endpoint.write_to_disk();
+                    let issues = get_issues(write_to_disk).await?;
+                    write_to_disk.strongly_consistent().await?;
+                    Ok(issues)
+                });
+                let result = on_demand_entries
+                    .compile(&pathname, compilation, |issues| has_errors(issues))
+                    .await;
+                (pathname, result)
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    let mut warmed_up = vec![];
+    for compilation in compilations {
+        if let Ok((pathname, Ok(issues))) = compilation.await {
+            if !has_errors(&issues) {
+                warmed_up.push(pathname);
+            }
+        }
+    }
+    Ok(warmed_up)
+}
+
+/// Whether a browser with the given `Origin` header may connect to the dev
+/// server, e.g. for HMR.
+#[napi]
+pub async fn project_is_allowed_dev_origin(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    origin: String,
+) -> napi::Result<bool> {
+    let container = project.container;
+    let allowed = project
+        .turbo_tasks
+        .run_once(async move {
+            Ok(*container
+                .is_allowed_dev_origin(origin)
+                .strongly_consistent()
+                .await?)
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(allowed)
+}
+
+/// Returns, as JSON, the import chains through which the file at the given
+/// project-relative path ends up in the bundles of the endpoint.
+#[napi]
+pub async fn project_why_included(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+    path: String,
+) -> napi::Result<String> {
+    let container = project.container;
+    let endpoint = ***endpoint;
+    let why_included = project
+        .turbo_tasks
+        .run_once(async move {
+            let why_included = container
+                .why_included(endpoint, path)
+                .strongly_consistent()
+                .await?;
+            Ok(serde_json::to_string_pretty(&*why_included)?)
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(why_included)
+}
+
+/// Returns, as JSON, the chunk groups of an app endpoint: the shared chunks,
+/// the server component chunks and the chunks of every client reference.
+#[napi]
+pub async fn project_chunk_graph(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<String> {
+    let container = project.container;
+    let endpoint = ***endpoint;
+    let chunk_graph = project
+        .turbo_tasks
+        .run_once(async move {
+            let chunk_graph = container
+                .chunk_graph(endpoint)
+                .strongly_consistent()
+                .await?;
+            Ok(serde_json::to_string_pretty(&*chunk_graph)?)
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(chunk_graph)
+}
+
+/// Returns the chunk graph of an app endpoint as an HTML page.
+#[napi]
+pub async fn project_chunk_graph_html(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<String> {
+    let container = project.container;
+    let endpoint = ***endpoint;
+    let html = project
+        .turbo_tasks
+        .run_once(async move {
+            let chunk_graph = container
+                .chunk_graph(endpoint)
+                .strongly_consistent()
+                .await?;
+            Ok(chunk_graph.to_html())
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(html)
+}
+
+/// Returns the route entries which are currently kept compiled, the most
+/// recently requested first.
+#[napi]
+pub fn project_hot_entries(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> Vec<String> {
+    project.on_demand_entries.hot_entries()
+}
+
+/// Forgets the route entries which haven't been requested for longer than
+/// `onDemandEntries.maxInactiveAge` and returns them. Entries whose changes are
+/// still subscribed to are kept, the dev server unsubscribes from the returned
+/// ones to free their tasks.
+#[napi]
+pub fn project_dispose_inactive_entries(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> Vec<String> {
+    project.on_demand_entries.dispose_inactive()
+}
+
 #[napi(object)]
 #[derive(Default)]
 struct NapiRoute {
@@ -214,12 +421,22 @@ impl NapiRoute {
         pathname: String,
         value: Route,
         turbo_tasks: &Arc<TurboTasks<MemoryBackend>>,
+        on_demand_entries: &Arc<OnDemandEntriesState>,
+        snapshots: &Arc<EndpointSnapshots>,
+        node_root: Vc<FileSystemPath>,
     ) -> Self {
-        let convert_endpoint = |endpoint: Vc<Box<dyn Endpoint>>| {
-            Some(External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                endpoint,
-            ))))
+        let entry_pathname = pathname.clone();
+        let convert_endpoint = |name, endpoint: Vc<Box<dyn Endpoint>>| {
+            Some(External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), endpoint),
+                Some(EndpointEntry {
+                    pathname: entry_pathname.clone(),
+                    name,
+                    state: on_demand_entries.clone(),
+                    snapshots: snapshots.clone(),
+                    node_root,
+                }),
+            )))
         };
         match value {
             Route::Page {
@@ -228,14 +445,14 @@ impl NapiRoute {
             } => NapiRoute {
                 pathname,
                 r#type: "page",
-                html_endpoint: convert_endpoint(html_endpoint),
-                data_endpoint: convert_endpoint(data_endpoint),
+                html_endpoint: convert_endpoint("html", html_endpoint),
+                data_endpoint: convert_endpoint("data", data_endpoint),
                 ..Default::default()
             },
             Route::PageApi { endpoint } => NapiRoute {
                 pathname,
                 r#type: "page-api",
-                endpoint: convert_endpoint(endpoint),
+                endpoint: convert_endpoint("endpoint", endpoint),
                 ..Default::default()
             },
             Route::AppPage {
@@ -244,14 +461,14 @@ impl NapiRoute {
             } => NapiRoute {
                 pathname,
                 r#type: "app-page",
-                html_endpoint: convert_endpoint(html_endpoint),
-                rsc_endpoint: convert_endpoint(rsc_endpoint),
+                html_endpoint: convert_endpoint("html", html_endpoint),
+                rsc_endpoint: convert_endpoint("rsc", rsc_endpoint),
                 ..Default::default()
             },
             Route::AppRoute { endpoint } => NapiRoute {
                 pathname,
                 r#type: "app-route",
-                endpoint: convert_endpoint(endpoint),
+                endpoint: convert_endpoint("endpoint", endpoint),
                 ..Default::default()
             },
             Route::Conflict => NapiRoute {
@@ -274,16 +491,17 @@ impl NapiMiddleware {
         turbo_tasks: &Arc<TurboTasks<MemoryBackend>>,
     ) -> Result<Self> {
         Ok(NapiMiddleware {
-            endpoint: External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                value.endpoint,
-            ))),
+            endpoint: External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), value.endpoint),
+                None,
+            )),
         })
     }
 }
 #[napi(object)]
 struct NapiEntrypoints {
     pub routes: Vec<NapiRoute>,
+    pub public_assets: Vec<String>,
     pub middleware: Option<NapiMiddleware>,
     pub pages_document_endpoint: External<ExternalEndpoint>,
     pub pages_app_endpoint: External<ExternalEndpoint>,
@@ -297,6 +515,8 @@ pub fn project_entrypoints_subscribe(
 ) -> napi::Result<External<RootTask>> {
     let turbo_tasks = project.turbo_tasks.clone();
     let container = project.container;
+    let on_demand_entries = project.on_demand_entries.clone();
+    let snapshots = project.snapshots.clone();
     subscribe(
         turbo_tasks.clone(),
         func,
@@ -306,11 +526,12 @@ pub fn project_entrypoints_subscribe(
             let diags = get_diagnostics(entrypoints).await?;
 
             let entrypoints = entrypoints.strongly_consistent().await?;
+            let node_root = container.project().node_root().resolve().await?;
 
-            Ok((entrypoints, issues, diags))
+            Ok((entrypoints, issues, diags, node_root))
         },
         move |ctx| {
-            let (entrypoints, issues, diags) = ctx.value;
+            let (entrypoints, issues, diags, node_root) = ctx.value;
 
             Ok(vec![TurbopackResult {
                 result: NapiEntrypoints {
@@ -318,26 +539,34 @@ pub fn project_entrypoints_subscribe(
                         .routes
                         .iter()
                         .map(|(pathname, &route)| {
-                            NapiRoute::from_route(pathname.clone(), route, &turbo_tasks)
+                            NapiRoute::from_route(
+                                pathname.clone(),
+                                route,
+                                &turbo_tasks,
+                                &on_demand_entries,
+                                &snapshots,
+                                node_root,
+                            )
                         })
                         .collect::<Vec<_>>(),
+                    public_assets: entrypoints.public_assets.clone(),
                     middleware: entrypoints
                         .middleware
                         .as_ref()
                         .map(|m| NapiMiddleware::from_middleware(m, &turbo_tasks))
                         .transpose()?,
-                    pages_document_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_document_endpoint,
-                    ))),
-                    pages_app_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_app_endpoint,
-                    ))),
-                    pages_error_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_error_endpoint,
-                    ))),
+                    pages_document_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_document_endpoint),
+                        None,
+                    )),
+                    pages_app_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_app_endpoint),
+                        None,
+                    )),
+                    pages_error_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_error_endpoint),
+                        None,
+                    )),
                 },
                 issues: issues
                     .iter()
@@ -467,6 +696,63 @@ pub fn project_hmr_identifiers_subscribe(
     )
 }
 
+#[napi(object)]
+struct NapiClientAsset {
+    pub path: String,
+    pub hash: String,
+    pub size: u32,
+}
+
+#[napi(object)]
+struct ClientAssets {
+    pub assets: Vec<NapiClientAsset>,
+}
+
+/// Subscribes to the list of emitted client assets, which is sent again after
+/// every build or HMR update which emits new assets.
+#[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
+pub fn project_client_assets_subscribe(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    func: JsFunction,
+) -> napi::Result<External<RootTask>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    subscribe(
+        turbo_tasks.clone(),
+        func,
+        move || async move {
+            let client_assets = container.client_assets();
+            let issues = get_issues(client_assets).await?;
+            let diags = get_diagnostics(client_assets).await?;
+
+            let client_assets = client_assets.strongly_consistent().await?;
+
+            Ok((client_assets, issues, diags))
+        },
+        move |ctx| {
+            let (client_assets, issues, diags) = ctx.value;
+
+            Ok(vec![TurbopackResult {
+                result: ClientAssets {
+                    assets: client_assets
+                        .iter()
+                        .map(|asset| NapiClientAsset {
+                            path: asset.path.clone(),
+                            hash: asset.hash.clone(),
+                            size: asset.size as u32,
+                        })
+                        .collect(),
+                },
+                issues: issues
+                    .iter()
+                    .map(|issue| NapiIssue::from(&**issue))
+                    .collect(),
+                diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+            }])
+        },
+    )
+}
+
 #[napi(object)]
 struct NapiUpdateInfo {
     pub duration: u32,
@@ -508,3 +794,73 @@ pub fn project_update_info_subscribe(
     });
     Ok(())
 }
+
+/// Merges the JSON manifests written by build shards into one.
+#[napi]
+pub fn merge_manifests(manifests: Vec<String>) -> napi::Result<String> {
+    let merged = manifests
+        .iter()
+        .map(|manifest| serde_json::from_str(manifest).context("invalid manifest"))
+        .collect::<Result<Vec<_>>>()
+        .and_then(next_core::next_manifests::merge::merge_manifests)
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(merged.to_string())
+}
+
+#[napi(object)]
+struct NapiRouteStatus {
+    pub pathname: String,
+    /// One of `queued`, `compiling`, `ready` or `errored`.
+    pub status: &'static str,
+    /// The compilation time in milliseconds, for ready and errored routes
+    /// which were compiled on request rather than after a change.
+    pub duration: Option<u32>,
+}
+
+impl From<RouteStatusUpdate> for NapiRouteStatus {
+    fn from(update: RouteStatusUpdate) -> Self {
+        let (status, duration) = match update.status {
+            RouteStatus::Queued => ("queued", None),
+            RouteStatus::Compiling => ("compiling", None),
+            RouteStatus::Ready(duration) => ("ready", duration),
+            RouteStatus::Errored(duration) => ("errored", duration),
+        };
+        Self {
+            pathname: update.pathname,
+            status,
+            duration: duration.map(|duration| duration.as_millis() as u32),
+        }
+    }
+}
+
+/// Streams the compilation status of the individual routes.
+#[napi]
+pub fn project_route_status_subscribe(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    func: JsFunction,
+) -> napi::Result<()> {
+    let func: ThreadsafeFunction<RouteStatusUpdate> =
+        func.create_threadsafe_function(0, |ctx| {
+            let update = ctx.value;
+            Ok(vec![NapiRouteStatus::from(update)])
+        })?;
+    let mut updates = project.on_demand_entries.subscribe_status();
+    tokio::spawn(async move {
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                // Skip the updates a slow subscriber missed.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+
+            let status = func.call(Ok(update), ThreadsafeFunctionCallMode::NonBlocking);
+            if !matches!(status, Status::Ok) {
+                let error = anyhow!("Error calling JS function: {}", status);
+                eprintln!("{}", error);
+                break;
+            }
+        }
+    });
+    Ok(())
+}
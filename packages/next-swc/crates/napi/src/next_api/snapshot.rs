@@ -0,0 +1,99 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::Result;
+use next_api::route::WrittenEndpoint;
+use turbo_tasks::{ReadRef, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::issue::{IssueSeverity, PlainIssue},
+};
+
+/// The output of an endpoint the last time it was written without errors.
+struct Snapshot {
+    written: ReadRef<WrittenEndpoint>,
+    /// The server files and their contents.
+    files: Vec<(Vc<FileSystemPath>, ReadRef<FileContent>)>,
+}
+
+/// Keeps the last good output of the route endpoints in dev. When an edit
+/// introduces a compilation error, the server files of the last good output
+/// are written back, so the route keeps being served while the error is shown
+/// in the overlay.
+///
+/// Files are read and written through the turbo-tasks filesystem, which knows
+/// about its own writes, so restoring a file doesn't invalidate the endpoint
+/// again.
+#[derive(Default)]
+pub struct EndpointSnapshots {
+    snapshots: Mutex<HashMap<String, Snapshot>>,
+}
+
+impl EndpointSnapshots {
+    /// Records the server files of an endpoint which was written without
+    /// errors. The paths of the written endpoint are relative to `node_root`.
+    /// Must be called within a turbo-tasks context.
+    pub async fn save(
+        &self,
+        node_root: Vc<FileSystemPath>,
+        key: &str,
+        written: ReadRef<WrittenEndpoint>,
+    ) -> Result<()> {
+        let mut files = vec![];
+        for path in server_files(&written) {
+            let path = node_root.join(path.clone()).resolve().await?;
+            files.push((path, path.read().await?));
+        }
+        self.snapshots
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), Snapshot { written, files });
+        Ok(())
+    }
+
+    /// Writes the server files of the last good output of the endpoint back
+    /// and returns it, if there is one. Must be called within a turbo-tasks
+    /// context.
+    pub async fn restore(&self, key: &str) -> Result<Option<ReadRef<WrittenEndpoint>>> {
+        let Some((written, files)) = self
+            .snapshots
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|snapshot| (snapshot.written.clone(), snapshot.files.clone()))
+        else {
+            return Ok(None);
+        };
+        for (path, content) in files {
+            path.write((*content).clone().cell()).await?;
+        }
+        Ok(Some(written))
+    }
+}
+
+fn server_files(written: &WrittenEndpoint) -> Vec<&String> {
+    match written {
+        WrittenEndpoint::NodeJs {
+            server_entry_path,
+            server_paths,
+        } => [server_entry_path]
+            .into_iter()
+            .chain(server_paths)
+            .collect(),
+        WrittenEndpoint::Edge {
+            files,
+            server_paths,
+            ..
+        } => files.iter().chain(server_paths).collect(),
+    }
+}
+
+/// Whether the issues contain a compilation error which should not replace
+/// the last good output.
+pub fn has_errors(issues: &[ReadRef<PlainIssue>]) -> bool {
+    issues.iter().any(|issue| {
+        matches!(
+            issue.severity,
+            IssueSeverity::Bug | IssueSeverity::Fatal | IssueSeverity::Error
+        )
+    })
+}
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, future::Future, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::{anyhow, Context, Result};
 use napi::{
@@ -59,6 +64,14 @@ pub struct RootTask {
     turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
     #[allow(dead_code)]
     task_id: Option<TaskId>,
+    /// Called when JS disposes the root task.
+    on_dispose: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl RootTask {
+    pub fn on_dispose(&self, on_dispose: impl FnOnce() + Send + 'static) {
+        *self.on_dispose.lock().unwrap() = Some(Box::new(on_dispose));
+    }
 }
 
 impl Drop for RootTask {
@@ -69,8 +82,11 @@ impl Drop for RootTask {
 
 #[napi]
 pub fn root_task_dispose(
-    #[napi(ts_arg_type = "{ __napiType: \"RootTask\" }")] _root_task: External<RootTask>,
+    #[napi(ts_arg_type = "{ __napiType: \"RootTask\" }")] root_task: External<RootTask>,
 ) -> napi::Result<()> {
+    if let Some(on_dispose) = root_task.on_dispose.lock().unwrap().take() {
+        on_dispose();
+    }
     // TODO(alexkirsz) Implement. Not panicking here to avoid crashing the process
     // when testing.
     eprintln!("root_task_dispose not yet implemented");
@@ -272,5 +288,6 @@ pub fn subscribe<T: 'static + Send + Sync, F: Future<Output = Result<T>> + Send,
     Ok(External::new(RootTask {
         turbo_tasks,
         task_id: Some(task_id),
+        on_dispose: Mutex::new(None),
     }))
 }
@@ -27,6 +27,14 @@ pub struct NextBuildContext {
 
     /// The rewrites, as computed by Next.js.
     pub rewrites: Option<NapiRewrites>,
+
+    /// Whether to report the "use client" modules which aren't used by any
+    /// app route.
+    pub report_unused_client_components: Option<bool>,
+
+    /// The number of threads minifying JavaScript. Defaults to the number of
+    /// CPUs.
+    pub minify_threads: Option<u32>,
     // TODO(alexkirsz) These are detected directly by Turbopack for now.
     // pub app_dir: Option<String>,
     // pub pages_dir: Option<String>,
@@ -46,7 +54,12 @@ impl TryFrom<NextBuildContext> for NextBuildOptions {
             show_all: true,
             log_detail: true,
             full_stats: true,
+            report_unused_client_components: value
+                .report_unused_client_components
+                .unwrap_or_default(),
             memory_limit: None,
+            verify_determinism: false,
+            minify_threads: value.minify_threads.map(|threads| threads as usize),
             build_context: Some(BuildContext {
                 build_id: value
                     .build_id
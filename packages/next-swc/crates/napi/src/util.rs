@@ -26,7 +26,11 @@ IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 DEALINGS IN THE SOFTWARE.
 */
 
-use std::{cell::RefCell, env, path::PathBuf};
+use std::{
+    cell::RefCell,
+    env,
+    path::{Path, PathBuf},
+};
 
 use anyhow::anyhow;
 use napi::bindgen_prelude::{External, Status};
@@ -49,6 +53,14 @@ pub fn get_target_triple() -> String {
     crate::build::BUILD_TARGET.to_string()
 }
 
+/// The workspace root Turbopack infers for a project directory when no root is
+/// configured, see [next_core::find_workspace_root].
+#[napi]
+pub fn find_workspace_root(dir: String) -> Option<String> {
+    next_core::find_workspace_root(Path::new(&dir))
+        .and_then(|root| root.to_str().map(ToString::to_string))
+}
+
 pub trait MapErr<T>: Into<Result<T, anyhow::Error>> {
     fn convert_err(self) -> napi::Result<T> {
         self.into()
@@ -1,33 +1,45 @@
 use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
 use next_core::{
     all_server_paths,
     app_structure::{
-        get_entrypoints, Entrypoint as AppEntrypoint, Entrypoints as AppEntrypoints, LoaderTree,
+        get_entrypoints, get_global_metadata, get_sitemaps, Entrypoint as AppEntrypoint,
+        Entrypoints as AppEntrypoints, GlobalMetadata, LoaderTree, MetadataItem,
     },
     get_edge_resolve_options_context,
     mode::NextMode,
     next_app::{
         get_app_client_references_chunks, get_app_client_shared_chunks, get_app_page_entry,
-        get_app_route_entry, AppEntry, AppPage,
+        get_app_route_entry, get_app_route_favicon_entry, get_app_route_sitemap_entry, AppEntry,
+        AppPage, ClientReferencesChunks, DynamicRouteIssue, PageSegment, PageType,
     },
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
-        get_client_runtime_entries, ClientContextType, RuntimeEntries,
+        get_client_runtime_entries, top_level_await::check_client_top_level_await,
+        ClientContextType, RuntimeEntries,
     },
     next_client_reference::{
         ClientReferenceGraph, ClientReferenceType, NextEcmascriptClientReferenceTransition,
     },
     next_dynamic::{NextDynamicEntries, NextDynamicTransition},
-    next_edge::route_regex::get_named_middleware_regex,
+    next_edge::{
+        route_regex::get_named_middleware_regex, unsupported_apis::check_edge_runtime_apis,
+    },
     next_manifests::{
+        preload_hints::get_preload_hints,
+        subresource_integrity::{get_subresource_integrity_hashes, SUBRESOURCE_INTEGRITY_MANIFEST},
         AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest,
-        EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2, PagesManifest, Regions,
+        EarlyHintsManifest, EdgeFunctionDefinition, FunctionConfig, FunctionsConfigManifest,
+        MiddlewareMatcher, MiddlewaresManifestV2, PagesManifest, Regions,
+        SegmentBoundariesManifest, StaticMetadataManifest,
     },
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    next_shared::next_script::{validate_next_script_usage, NextScriptRouter},
     util::{get_asset_prefix_from_pathname, NextRuntime},
+    NextSegmentDynamic,
 };
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, Completion, TryFlatJoinIterExt, TryJoinIterExt, Value, Vc};
@@ -42,9 +54,12 @@ use turbopack_binding::{
             changed::any_content_changed_of_output_assets,
             chunk::{ChunkableModule, ChunkingContext, EvaluatableAssets},
             file_source::FileSource,
+            issue::IssueExt,
+            module::Modules,
             output::{OutputAsset, OutputAssets},
             virtual_output::VirtualOutputAsset,
         },
+        ecmascript::chunk::EcmascriptChunkingContext,
         turbopack::{
             module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
             transition::ContextTransition, ModuleAssetContext,
@@ -53,6 +68,8 @@ use turbopack_binding::{
 };
 
 use crate::{
+    chunk_graph::{chunk_graph_chunks, module_identifier, ChunkGraph, ChunkGroup},
+    nft_json::nft_json_asset,
     project::Project,
     route::{Endpoint, Route, Routes, WrittenEndpoint},
 };
@@ -115,7 +132,17 @@ impl AppProject {
 
     #[turbo_tasks::function]
     fn app_entrypoints(&self) -> Vc<AppEntrypoints> {
-        get_entrypoints(self.app_dir, self.project.next_config().page_extensions())
+        let next_config = self.project.next_config();
+        get_entrypoints(
+            self.app_dir,
+            next_config.page_extensions(),
+            next_config.app_i18n(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn global_metadata(&self) -> Vc<GlobalMetadata> {
+        get_global_metadata(self.app_dir, self.project.next_config().page_extensions())
     }
 
     #[turbo_tasks::function]
@@ -225,7 +252,7 @@ impl AppProject {
                 ECMASCRIPT_CLIENT_TRANSITION_NAME.to_string(),
                 Vc::upcast(NextEcmascriptClientReferenceTransition::new(
                     self.client_transition(),
-                    self.ssr_transition(),
+                    self.edge_ssr_transition(),
                 )),
             ),
             (
@@ -286,6 +313,27 @@ impl AppProject {
         )
     }
 
+    #[turbo_tasks::function]
+    async fn edge_ssr_resolve_options_context(self: Vc<Self>) -> Result<Vc<ResolveOptionsContext>> {
+        let this = self.await?;
+        Ok(get_edge_resolve_options_context(
+            self.project().project_path(),
+            Value::new(self.ssr_ty()),
+            this.mode,
+            self.project().next_config(),
+            self.project().execution_context(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn edge_ssr_transition(self: Vc<Self>) -> Vc<ContextTransition> {
+        ContextTransition::new(
+            self.project().edge_compile_time_info(),
+            self.ssr_module_options_context(),
+            self.edge_ssr_resolve_options_context(),
+        )
+    }
+
     #[turbo_tasks::function]
     async fn runtime_entries(self: Vc<Self>) -> Result<Vc<RuntimeEntries>> {
         let this = self.await?;
@@ -337,21 +385,80 @@ impl AppProject {
     #[turbo_tasks::function]
     pub async fn routes(self: Vc<Self>) -> Result<Vc<Routes>> {
         let app_entrypoints = self.app_entrypoints();
-        Ok(Vc::cell(
-            app_entrypoints
-                .await?
-                .iter()
-                .map(|(pathname, app_entrypoint)| async {
-                    Ok((
-                        pathname.clone(),
-                        *app_entry_point_to_route(self, app_entrypoint.clone()).await?,
-                    ))
-                })
-                .try_join()
-                .await?
-                .into_iter()
-                .collect(),
-        ))
+        let mut routes: IndexMap<_, _> = app_entrypoints
+            .await?
+            .iter()
+            .map(|(pathname, app_entrypoint)| async {
+                Ok((
+                    pathname.clone(),
+                    *app_entry_point_to_route(self, app_entrypoint.clone()).await?,
+                ))
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .collect();
+
+        // Like in builds, `favicon.ico` is generated from an `icon.png` when
+        // there's none.
+        let global_metadata = self.global_metadata().await?;
+        if let Some(favicon) = global_metadata.favicon.or(global_metadata.icon) {
+            if !routes.contains_key("/favicon.ico") {
+                routes.insert(
+                    "/favicon.ico".to_string(),
+                    Route::AppRoute {
+                        endpoint: Vc::upcast(
+                            AppEndpoint {
+                                ty: AppEndpointType::Favicon { favicon },
+                                app_project: self,
+                                page: AppPage::parse("/favicon.ico/route")?,
+                            }
+                            .cell(),
+                        ),
+                    },
+                );
+            }
+        }
+
+        // Sitemaps can be placed in any directory. Whether a dynamic one is
+        // served at `sitemap/[id].xml` depends on its exports, so the pathname
+        // is taken from its entry.
+        let sitemaps = get_sitemaps(
+            self.app_dir(),
+            self.project().next_config().page_extensions(),
+        );
+        for sitemap in sitemaps.await?.iter() {
+            let app_entry = get_app_route_sitemap_entry(
+                self.rsc_module_context(),
+                self.edge_rsc_module_context(),
+                sitemap.sitemap,
+                sitemap.directory.clone(),
+                self.project().project_path(),
+            )
+            .await?;
+            if routes.contains_key(&app_entry.pathname) {
+                continue;
+            }
+            routes.insert(
+                app_entry.pathname.clone(),
+                Route::AppRoute {
+                    endpoint: Vc::upcast(
+                        AppEndpoint {
+                            ty: AppEndpointType::Sitemap {
+                                sitemap: sitemap.sitemap,
+                                directory: sitemap.directory.clone(),
+                            },
+                            app_project: self,
+                            page: AppPage::parse(&app_entry.original_name)?
+                                .clone_push(PageSegment::PageType(PageType::Route))?,
+                        }
+                        .cell(),
+                    ),
+                },
+            );
+        }
+
+        Ok(Vc::cell(routes))
     }
 }
 
@@ -405,7 +512,7 @@ enum AppPageEndpointType {
     Rsc,
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug, TraceRawVcs)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, TraceRawVcs)]
 enum AppEndpointType {
     Page {
         ty: AppPageEndpointType,
@@ -414,10 +521,21 @@ enum AppEndpointType {
     Route {
         path: Vc<FileSystemPath>,
     },
+    /// The `/favicon.ico` route of a `favicon.ico` or `icon.png` in the root
+    /// of the app directory.
+    Favicon {
+        favicon: MetadataItem,
+    },
+    /// A `sitemap.xml` or a dynamic sitemap in the given directory of the app
+    /// directory.
+    Sitemap {
+        sitemap: MetadataItem,
+        directory: AppPage,
+    },
 }
 
 #[turbo_tasks::value]
-struct AppEndpoint {
+pub(crate) struct AppEndpoint {
     ty: AppEndpointType,
     app_project: Vc<AppProject>,
     page: AppPage,
@@ -426,15 +544,17 @@ struct AppEndpoint {
 #[turbo_tasks::value_impl]
 impl AppEndpoint {
     #[turbo_tasks::function]
-    fn app_page_entry(&self, loader_tree: Vc<LoaderTree>) -> Vc<AppEntry> {
-        get_app_page_entry(
+    async fn app_page_entry(&self, loader_tree: Vc<LoaderTree>) -> Result<Vc<AppEntry>> {
+        Ok(get_app_page_entry(
             self.app_project.rsc_module_context(),
             self.app_project.edge_rsc_module_context(),
             loader_tree,
             self.app_project.app_dir(),
             self.page.clone(),
             self.app_project.project().project_path(),
-        )
+            self.app_project.project().next_config(),
+            self.app_project.await?.mode,
+        ))
     }
 
     #[turbo_tasks::function]
@@ -448,6 +568,153 @@ impl AppEndpoint {
         )
     }
 
+    #[turbo_tasks::function]
+    fn app_favicon_entry(&self, favicon: MetadataItem) -> Vc<AppEntry> {
+        get_app_route_favicon_entry(
+            self.app_project.rsc_module_context(),
+            self.app_project.edge_rsc_module_context(),
+            favicon,
+            self.app_project.project().project_path(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn app_sitemap_entry(&self, sitemap: MetadataItem, directory: AppPage) -> Vc<AppEntry> {
+        get_app_route_sitemap_entry(
+            self.app_project.rsc_module_context(),
+            self.app_project.edge_rsc_module_context(),
+            sitemap,
+            directory,
+            self.app_project.project().project_path(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn app_entry(self: Vc<Self>) -> Result<Vc<AppEntry>> {
+        Ok(match &self.await?.ty {
+            AppEndpointType::Page { loader_tree, .. } => self.app_page_entry(*loader_tree),
+            AppEndpointType::Route { path } => self.app_route_entry(*path),
+            AppEndpointType::Favicon { favicon } => self.app_favicon_entry(*favicon),
+            AppEndpointType::Sitemap { sitemap, directory } => {
+                self.app_sitemap_entry(*sitemap, directory.clone())
+            }
+        })
+    }
+
+    /// The chunks of the endpoint, before they are written with its manifests.
+    /// The output and the chunk graph are both computed from them, so the chunk
+    /// graph always shows the chunks which are written.
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<AppEndpointChunks>> {
+        let this = self.await?;
+        let project = this.app_project.project();
+        let app_entry = self.app_entry();
+        let app_entry_ref = app_entry.await?;
+
+        let client_shared_chunks = get_app_client_shared_chunks(
+            this.app_project.client_runtime_entries(),
+            project.client_chunking_context(),
+        );
+
+        let runtime = app_entry_ref.config.await?.runtime.unwrap_or_default();
+        let ssr_chunking_context = match runtime {
+            NextRuntime::NodeJs => Vc::upcast(project.ssr_chunking_context()),
+            NextRuntime::Edge => project.edge_ssr_chunking_context(),
+        };
+
+        let client_reference_graph =
+            ClientReferenceGraph::new(Vc::cell(vec![Vc::upcast(app_entry_ref.rsc_entry)]));
+        let client_references_chunks = get_app_client_references_chunks(
+            client_reference_graph.types(),
+            project.client_chunking_context(),
+            ssr_chunking_context,
+        );
+
+        Ok(AppEndpointChunks {
+            app_entry,
+            runtime,
+            client_shared_chunks,
+            client_reference_graph,
+            ssr_chunking_context,
+            client_references_chunks,
+        }
+        .cell())
+    }
+
+    /// The chunks of the endpoint, grouped by what they are loaded for.
+    #[turbo_tasks::function]
+    pub(crate) async fn chunk_graph(self: Vc<Self>) -> Result<Vc<ChunkGraph>> {
+        let this = self.await?;
+        let project = this.app_project.project();
+        let chunks = self.chunks().await?;
+        let app_entry = chunks.app_entry.await?;
+        let rsc_entry = Vc::upcast(app_entry.rsc_entry);
+        let client_root = project.client_relative_path();
+        let node_root = project.node_root();
+
+        let mut chunk_groups = vec![ChunkGroup {
+            name: "shared".to_string(),
+            entry: None,
+            references: vec![],
+            client_chunks: chunk_graph_chunks(&chunks.client_shared_chunks.await?, client_root)
+                .await?,
+            server_chunks: vec![],
+        }];
+
+        chunk_groups.push(ChunkGroup {
+            name: "server components".to_string(),
+            entry: Some(module_identifier(rsc_entry).await?),
+            references: vec![],
+            client_chunks: vec![],
+            server_chunks: chunk_graph_chunks(
+                &self.output().server_component_assets().await?,
+                node_root,
+            )
+            .await?,
+        });
+
+        let client_references_chunks = chunks.client_references_chunks.await?;
+        for ty in chunks.client_reference_graph.types().await?.iter() {
+            let reference_chunks = client_references_chunks
+                .get(ty)
+                .context("client reference should have corresponding chunks")?;
+            let (entry, references) = match *ty {
+                ClientReferenceType::EcmascriptClientReference(reference) => {
+                    let reference_ref = reference.await?;
+                    (
+                        module_identifier(Vc::upcast(reference)).await?,
+                        vec![
+                            module_identifier(Vc::upcast(reference_ref.client_module)).await?,
+                            module_identifier(Vc::upcast(reference_ref.ssr_module)).await?,
+                        ],
+                    )
+                }
+                ClientReferenceType::CssClientReference(reference) => (
+                    module_identifier(Vc::upcast(reference)).await?,
+                    vec![module_identifier(Vc::upcast(reference.await?.client_module)).await?],
+                ),
+            };
+            chunk_groups.push(ChunkGroup {
+                name: "client reference".to_string(),
+                entry: Some(entry),
+                references,
+                client_chunks: chunk_graph_chunks(
+                    &reference_chunks.client_chunks.await?,
+                    client_root,
+                )
+                .await?,
+                server_chunks: chunk_graph_chunks(&reference_chunks.ssr_chunks.await?, node_root)
+                    .await?,
+            });
+        }
+
+        Ok(ChunkGraph {
+            page: app_entry.original_name.clone(),
+            chunk_groups,
+        }
+        .cell())
+    }
+
     #[turbo_tasks::function]
     fn output_assets(self: Vc<Self>) -> Vc<OutputAssets> {
         self.output().output_assets()
@@ -457,15 +724,16 @@ impl AppEndpoint {
     async fn output(self: Vc<Self>) -> Result<Vc<AppEndpointOutput>> {
         let this = self.await?;
 
-        let (app_entry, ty) = match this.ty {
-            AppEndpointType::Page { ty: _, loader_tree } => {
-                (self.app_page_entry(loader_tree), "page")
-            }
+        let ty = match this.ty {
+            AppEndpointType::Page { .. } => "page",
             // NOTE(alexkirsz) For routes, technically, a lot of the following code is not needed,
             // as we know we won't have any client references. However, for now, for simplicity's
             // sake, we just do the same thing as for pages.
-            AppEndpointType::Route { path } => (self.app_route_entry(path), "route"),
+            AppEndpointType::Route { .. }
+            | AppEndpointType::Favicon { .. }
+            | AppEndpointType::Sitemap { .. } => "route",
         };
+        let chunks = self.chunks().await?;
 
         let node_root = this.app_project.project().node_root();
 
@@ -477,13 +745,8 @@ impl AppEndpoint {
         let mut server_assets = vec![];
         let mut client_assets = vec![];
 
-        let client_shared_chunks = get_app_client_shared_chunks(
-            this.app_project.client_runtime_entries(),
-            this.app_project.project().client_chunking_context(),
-        );
-
         let mut client_shared_chunks_paths = vec![];
-        for chunk in client_shared_chunks.await?.iter().copied() {
+        for chunk in chunks.client_shared_chunks.await?.iter().copied() {
             client_assets.push(chunk);
 
             let chunk_path = chunk.ident().path().await?;
@@ -494,11 +757,22 @@ impl AppEndpoint {
             }
         }
 
-        let app_entry = app_entry.await?;
+        let app_entry = chunks.app_entry.await?;
         let rsc_entry = app_entry.rsc_entry;
 
         let rsc_entry_asset = Vc::upcast(rsc_entry);
-        let client_reference_graph = ClientReferenceGraph::new(Vc::cell(vec![rsc_entry_asset]));
+
+        if let AppEndpointType::Page { loader_tree, .. } = this.ty {
+            let root_layout = loader_tree.await?.components.await?.layout;
+            validate_next_script_usage(
+                rsc_entry_asset,
+                Vc::cell(root_layout),
+                NextScriptRouter::App,
+            )
+            .await?;
+        }
+
+        let client_reference_graph = chunks.client_reference_graph;
         let client_reference_types = client_reference_graph.types();
         let client_references = client_reference_graph.entry(rsc_entry_asset);
 
@@ -531,13 +805,24 @@ impl AppEndpoint {
             .entry(Vc::upcast(app_entry.rsc_entry))
             .await?;
 
-        let client_references_chunks = get_app_client_references_chunks(
-            client_reference_types,
-            this.app_project.project().client_chunking_context(),
-            this.app_project.project().ssr_chunking_context(),
-        );
+        let runtime = chunks.runtime;
+        let ssr_chunking_context = chunks.ssr_chunking_context;
+        let client_references_chunks = chunks.client_references_chunks;
         let client_references_chunks_ref = client_references_chunks.await?;
 
+        let project = this.app_project.project();
+        for client_reference in app_entry_client_references.iter() {
+            if let ClientReferenceType::EcmascriptClientReference(entry) = client_reference.ty() {
+                check_client_top_level_await(
+                    Vc::upcast(entry.await?.client_module),
+                    project.project_path(),
+                    project.next_config(),
+                    app_entry.original_name.clone(),
+                )
+                .await?;
+            }
+        }
+
         let mut entry_client_chunks = vec![];
         // TODO(alexkirsz) In which manifest does this go?
         let mut entry_ssr_chunks = vec![];
@@ -569,10 +854,14 @@ impl AppEndpoint {
             .collect();
         entry_client_chunks_paths.extend(client_shared_chunks_paths.iter().cloned());
 
+        let preload_hints = get_preload_hints(&client_assets, &client_relative_path_ref).await?;
         let app_build_manifest = AppBuildManifest {
             pages: [(app_entry.original_name.clone(), entry_client_chunks_paths)]
                 .into_iter()
                 .collect(),
+            preload_hints: [(app_entry.original_name.clone(), preload_hints.clone())]
+                .into_iter()
+                .collect(),
         };
         let manifest_path_prefix = get_asset_prefix_from_pathname(&app_entry.pathname);
         let app_build_manifest_output = Vc::upcast(VirtualOutputAsset::new(
@@ -604,7 +893,8 @@ impl AppEndpoint {
             client_references,
             client_references_chunks,
             this.app_project.project().client_chunking_context(),
-            Vc::upcast(this.app_project.project().ssr_chunking_context()),
+            ssr_chunking_context,
+            runtime,
         );
         server_assets.push(entry_manifest);
 
@@ -635,8 +925,119 @@ impl AppEndpoint {
             )))
         }
 
-        let endpoint_output = match app_entry.config.await?.runtime.unwrap_or_default() {
+        let config = app_entry.config.await?;
+
+        // Explain why a route won't be prerendered, unless its segment config
+        // already opts into dynamic rendering.
+        if this.app_project.await?.mode == NextMode::Build
+            && matches!(config.dynamic, None | Some(NextSegmentDynamic::Auto))
+        {
+            let usages = app_entry.dynamic_api_usages.await?;
+            if !usages.is_empty() {
+                DynamicRouteIssue {
+                    pathname: app_entry.pathname.clone(),
+                    usages: usages.clone_value(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+
+        let functions_config_manifest = FunctionsConfigManifest {
+            functions: [(
+                app_entry.original_name.clone(),
+                FunctionConfig {
+                    runtime: config.runtime.unwrap_or_default(),
+                    preferred_region: config.preferred_region.clone(),
+                    cache_control: config.cache_control(),
+                    streaming: app_entry.streaming,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let manifest_path_prefix = get_asset_prefix_from_pathname(&app_entry.pathname);
+        server_assets.push(Vc::upcast(VirtualOutputAsset::new(
+            node_root.join(format!(
+                "server/app{manifest_path_prefix}/{ty}/functions-config-manifest.json",
+            )),
+            AssetContent::file(
+                File::from(serde_json::to_string_pretty(&functions_config_manifest)?).into(),
+            ),
+        )));
+
+        let early_hints_manifest = EarlyHintsManifest {
+            pages: [(app_entry.original_name.clone(), preload_hints)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        server_assets.push(Vc::upcast(VirtualOutputAsset::new(
+            node_root.join(format!(
+                "server/app{manifest_path_prefix}/{ty}/early-hints-manifest.json",
+            )),
+            AssetContent::file(
+                File::from(serde_json::to_string_pretty(&early_hints_manifest)?).into(),
+            ),
+        )));
+
+        if let Some(static_metadata) = &*app_entry.static_metadata.await? {
+            let static_metadata_manifest = StaticMetadataManifest {
+                pages: [(app_entry.original_name.clone(), static_metadata.clone())]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            };
+            server_assets.push(Vc::upcast(VirtualOutputAsset::new(
+                node_root.join(format!(
+                    "server/app{manifest_path_prefix}/{ty}/static-metadata-manifest.json",
+                )),
+                AssetContent::file(
+                    File::from(serde_json::to_string_pretty(&static_metadata_manifest)?).into(),
+                ),
+            )));
+        }
+
+        if ty == "page" {
+            let segment_boundaries_manifest = SegmentBoundariesManifest {
+                pages: [(
+                    app_entry.original_name.clone(),
+                    app_entry.segment_boundaries.await?.clone_value(),
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            };
+            server_assets.push(Vc::upcast(VirtualOutputAsset::new(
+                node_root.join(format!(
+                    "server/app{manifest_path_prefix}/{ty}/segment-boundaries-manifest.json",
+                )),
+                AssetContent::file(
+                    File::from(serde_json::to_string_pretty(&segment_boundaries_manifest)?).into(),
+                ),
+            )));
+        }
+
+        if let Some(algorithm) = *project.next_config().sri_algorithm().await? {
+            let hashes = get_subresource_integrity_hashes(
+                &client_assets,
+                &client_relative_path_ref,
+                algorithm,
+            )
+            .await?;
+            server_assets.push(Vc::upcast(VirtualOutputAsset::new(
+                node_root.join(format!(
+                    "server/app{manifest_path_prefix}/{ty}/{SUBRESOURCE_INTEGRITY_MANIFEST}.json",
+                )),
+                AssetContent::file(File::from(serde_json::to_string_pretty(&hashes)?).into()),
+            )));
+        }
+
+        let endpoint_output = match config.runtime.unwrap_or_default() {
             NextRuntime::Edge => {
+                check_edge_runtime_apis(rsc_entry_asset).await?;
+
                 // create edge chunks
                 let chunking_context = this.app_project.project().edge_rsc_chunking_context();
                 let mut evaluatable_assets = this
@@ -707,7 +1108,8 @@ impl AppEndpoint {
                         .map(Regions::Single),
                     matchers: vec![matchers],
                     ..Default::default()
-                };
+                }
+                .with_wasm_bindings();
                 let middleware_manifest_v2 = MiddlewaresManifestV2 {
                     sorted_middleware: vec![app_entry.original_name.clone()],
                     middleware: Default::default(),
@@ -741,6 +1143,7 @@ impl AppEndpoint {
 
                 AppEndpointOutput::Edge {
                     files,
+                    server_component_assets: without_assets(&server_assets, &entry_ssr_chunks),
                     server_assets: Vc::cell(server_assets),
                     client_assets: Vc::cell(client_assets),
                 }
@@ -759,6 +1162,11 @@ impl AppEndpoint {
                         this.app_project.rsc_runtime_entries(),
                     );
                 server_assets.push(rsc_chunk);
+                server_assets.push(nft_json_asset(
+                    this.app_project.project(),
+                    rsc_chunk,
+                    app_entry.original_name.clone(),
+                ));
 
                 let app_paths_manifest_output = create_app_paths_manifest(
                     node_root,
@@ -775,6 +1183,7 @@ impl AppEndpoint {
 
                 AppEndpointOutput::NodeJs {
                     rsc_chunk,
+                    server_component_assets: without_assets(&server_assets, &entry_ssr_chunks),
                     server_assets: Vc::cell(server_assets),
                     client_assets: Vc::cell(client_assets),
                 }
@@ -865,15 +1274,37 @@ impl Endpoint for AppEndpoint {
         Ok(written_endpoint.cell())
     }
 
+    /// Only the assets of the server component layer are compared. Edits of
+    /// client components also change their SSR chunks, but these are updated
+    /// through the client chunks and must not trigger a server component
+    /// refresh.
     #[turbo_tasks::function]
     fn server_changed(self: Vc<Self>) -> Vc<Completion> {
-        any_content_changed_of_output_assets(self.output().server_assets())
+        any_content_changed_of_output_assets(self.output().server_component_assets())
     }
 
     #[turbo_tasks::function]
     fn client_changed(self: Vc<Self>) -> Vc<Completion> {
         any_content_changed_of_output_assets(self.output().client_assets())
     }
+
+    #[turbo_tasks::function]
+    async fn root_modules(self: Vc<Self>) -> Result<Vc<Modules>> {
+        Ok(Vc::cell(vec![Vc::upcast(
+            self.app_entry().await?.rsc_entry,
+        )]))
+    }
+}
+
+/// See [AppEndpoint::chunks].
+#[turbo_tasks::value]
+struct AppEndpointChunks {
+    app_entry: Vc<AppEntry>,
+    runtime: NextRuntime,
+    client_shared_chunks: Vc<OutputAssets>,
+    client_reference_graph: Vc<ClientReferenceGraph>,
+    ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    client_references_chunks: Vc<ClientReferencesChunks>,
 }
 
 #[turbo_tasks::value]
@@ -881,11 +1312,15 @@ enum AppEndpointOutput {
     NodeJs {
         rsc_chunk: Vc<Box<dyn OutputAsset>>,
         server_assets: Vc<OutputAssets>,
+        /// The server assets without the SSR chunks of client components.
+        server_component_assets: Vc<OutputAssets>,
         client_assets: Vc<OutputAssets>,
     },
     Edge {
         files: Vc<OutputAssets>,
         server_assets: Vc<OutputAssets>,
+        /// The server assets without the SSR chunks of client components.
+        server_component_assets: Vc<OutputAssets>,
         client_assets: Vc<OutputAssets>,
     },
 }
@@ -913,6 +1348,20 @@ impl AppEndpointOutput {
         }
     }
 
+    #[turbo_tasks::function]
+    pub fn server_component_assets(&self) -> Vc<OutputAssets> {
+        match *self {
+            AppEndpointOutput::NodeJs {
+                server_component_assets,
+                ..
+            }
+            | AppEndpointOutput::Edge {
+                server_component_assets,
+                ..
+            } => server_component_assets,
+        }
+    }
+
     #[turbo_tasks::function]
     pub fn client_assets(&self) -> Vc<OutputAssets> {
         match *self {
@@ -921,3 +1370,16 @@ impl AppEndpointOutput {
         }
     }
 }
+
+fn without_assets(
+    assets: &[Vc<Box<dyn OutputAsset>>],
+    excluded: &[Vc<Box<dyn OutputAsset>>],
+) -> Vc<OutputAssets> {
+    Vc::cell(
+        assets
+            .iter()
+            .copied()
+            .filter(|asset| !excluded.contains(asset))
+            .collect(),
+    )
+}
@@ -0,0 +1,236 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, ValueToString, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{introspect::Introspectable, module::Module, output::OutputAsset},
+};
+
+/// The chunk groups of an app endpoint, to debug why a chunk exists.
+#[turbo_tasks::value(shared)]
+#[derive(Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkGraph {
+    /// The original name of the app page or route, e.g. `/blog/[slug]/page`.
+    pub page: String,
+    pub chunk_groups: Vec<ChunkGroup>,
+}
+
+/// Chunks which are loaded together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkGroup {
+    /// `shared`, `server components` or `client reference`.
+    pub name: String,
+    /// The module the chunks are created for, as a module identifier which
+    /// includes the layer. `None` for the shared chunks.
+    pub entry: Option<String>,
+    /// The modules of other layers the entry stands for, i.e. the browser and
+    /// SSR modules of a client reference.
+    pub references: Vec<String>,
+    /// Chunks loaded in the browser, relative to `_next`.
+    pub client_chunks: Vec<Chunk>,
+    /// Chunks loaded on the server, relative to `.next`.
+    pub server_chunks: Vec<Chunk>,
+}
+
+/// A JavaScript chunk, relative to the output directory it is written to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub path: String,
+    /// The identifiers of the modules in the chunk.
+    pub modules: Vec<String>,
+}
+
+impl ChunkGraph {
+    /// Renders the chunk graph as an HTML page, with the modules of every chunk
+    /// in a collapsed list.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let page = escape_html(&self.page);
+        let _ = write!(
+            html,
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Chunk graph of \
+             {page}</title></head><body><h1>{page}</h1>"
+        );
+        for group in &self.chunk_groups {
+            let _ = write!(html, "<h2>{}</h2>", escape_html(&group.name));
+            if let Some(entry) = &group.entry {
+                let _ = write!(html, "<p>Entry: <code>{}</code></p>", escape_html(entry));
+            }
+            if !group.references.is_empty() {
+                html.push_str("<p>References:</p><ul>");
+                for reference in &group.references {
+                    let _ = write!(html, "<li><code>{}</code></li>", escape_html(reference));
+                }
+                html.push_str("</ul>");
+            }
+            for (title, chunks) in [
+                ("Client chunks", &group.client_chunks),
+                ("Server chunks", &group.server_chunks),
+            ] {
+                if chunks.is_empty() {
+                    continue;
+                }
+                let _ = write!(html, "<h3>{title}</h3>");
+                for chunk in chunks {
+                    let _ = write!(
+                        html,
+                        "<details><summary><code>{}</code> ({} modules)</summary><ul>",
+                        escape_html(&chunk.path),
+                        chunk.modules.len()
+                    );
+                    for module in &chunk.modules {
+                        let _ = write!(html, "<li><code>{}</code></li>", escape_html(module));
+                    }
+                    html.push_str("</ul></details>");
+                }
+            }
+        }
+        html.push_str("</body></html>");
+        html
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) async fn module_identifier(module: Vc<Box<dyn Module>>) -> Result<String> {
+    Ok(module.ident().to_string().await?.clone_value())
+}
+
+/// The JavaScript chunks among the given assets, relative to the given root,
+/// with their modules.
+pub(crate) async fn chunk_graph_chunks(
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    root: Vc<FileSystemPath>,
+) -> Result<Vec<Chunk>> {
+    let root = root.await?;
+    let chunks = chunks
+        .iter()
+        .map(|&chunk| async move {
+            let path = chunk.ident().path().await?;
+            if path.extension_ref() != Some("js") {
+                return Ok(None);
+            }
+            Ok(Some(Chunk {
+                path: root
+                    .get_path_to(&path)
+                    .map_or_else(|| path.path.clone(), |path| path.to_string()),
+                modules: chunk_modules(chunk).await?,
+            }))
+        })
+        .try_join()
+        .await?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+/// The modules of a chunk, as turbopack's introspection lists them: the
+/// chunk output asset has the chunk as a `chunk` child, which has its modules
+/// as `module` children.
+async fn chunk_modules(chunk: Vc<Box<dyn OutputAsset>>) -> Result<Vec<String>> {
+    let Some(introspectable) = Vc::try_resolve_sidecast::<Box<dyn Introspectable>>(chunk).await?
+    else {
+        return Ok(vec![]);
+    };
+
+    let mut modules = vec![];
+    let mut queue = vec![introspectable];
+    while let Some(introspectable) = queue.pop() {
+        for &(key, child) in introspectable.children().await?.iter() {
+            match key.await?.as_str() {
+                "chunk" => queue.push(child),
+                "module" => modules.push(child.title().await?.clone_value()),
+                _ => {}
+            }
+        }
+    }
+    modules.sort();
+    modules.dedup();
+    Ok(modules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_graph() -> ChunkGraph {
+        ChunkGraph {
+            page: "/blog/[slug]/page".to_string(),
+            chunk_groups: vec![
+                ChunkGroup {
+                    name: "shared".to_string(),
+                    entry: None,
+                    references: vec![],
+                    client_chunks: vec![Chunk {
+                        path: "static/chunks/main.js".to_string(),
+                        modules: vec!["[project]/node_modules/react/index.js".to_string()],
+                    }],
+                    server_chunks: vec![],
+                },
+                ChunkGroup {
+                    name: "client reference".to_string(),
+                    entry: Some("[project]/app/button.tsx <client reference>".to_string()),
+                    references: vec![
+                        "[project]/app/button.tsx [app-client] (ecmascript)".to_string(),
+                        "[project]/app/button.tsx [app-ssr] (ecmascript)".to_string(),
+                    ],
+                    client_chunks: vec![],
+                    server_chunks: vec![Chunk {
+                        path: "server/chunks/ssr/button.js".to_string(),
+                        modules: vec![
+                            "[project]/app/button.tsx [app-ssr] (ecmascript)".to_string(),
+                            "[project]/app/icon.tsx [app-ssr] (ecmascript)".to_string(),
+                        ],
+                    }],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_chunk_graph_json() {
+        let json = serde_json::to_value(chunk_graph()).unwrap();
+        assert_eq!(json["page"], "/blog/[slug]/page");
+        assert_eq!(json["chunkGroups"][0]["entry"], serde_json::Value::Null);
+        assert_eq!(
+            json["chunkGroups"][1]["serverChunks"][0]["modules"][1],
+            "[project]/app/icon.tsx [app-ssr] (ecmascript)"
+        );
+    }
+
+    #[test]
+    fn test_chunk_graph_html() {
+        let html = chunk_graph().to_html();
+        assert!(html.contains("<h1>/blog/[slug]/page</h1>"));
+        assert!(html.contains("<h2>client reference</h2>"));
+        assert!(html.contains(
+            "<p>Entry: <code>[project]/app/button.tsx &lt;client reference&gt;</code></p>"
+        ));
+        assert!(html.contains("<code>server/chunks/ssr/button.js</code> (2 modules)"));
+        // Groups without client chunks have no heading for them.
+        assert_eq!(html.matches("<h3>Client chunks</h3>").count(), 1);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">&</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
@@ -9,6 +9,9 @@ use crate::{
 #[turbo_tasks::value(shared)]
 pub struct Entrypoints {
     pub routes: IndexMap<String, Route>,
+    /// Pathnames of the files in the `public` directory, which are served as
+    /// they are.
+    pub public_assets: Vec<String>,
     pub middleware: Option<Middleware>,
     pub pages_document_endpoint: Vc<Box<dyn Endpoint>>,
     pub pages_app_endpoint: Vc<Box<dyn Endpoint>>,
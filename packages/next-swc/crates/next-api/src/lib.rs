@@ -3,12 +3,16 @@
 #![feature(async_fn_in_trait)]
 
 mod app;
+pub mod chunk_graph;
 mod entrypoints;
 mod middleware;
+mod nft_json;
+pub mod on_demand_entries;
 mod pages;
 pub mod project;
 pub mod route;
-mod versioned_content_map;
+pub mod versioned_content_map;
+pub mod why_included;
 
 // Declare build-time information variables generated in build.rs
 shadow_rs::shadow!(build);
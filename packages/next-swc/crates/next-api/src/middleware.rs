@@ -3,7 +3,7 @@ use next_core::{
     all_server_paths,
     middleware::get_middleware_module,
     mode::NextMode,
-    next_edge::entry::wrap_edge_entry,
+    next_edge::{entry::wrap_edge_entry, unsupported_apis::check_edge_runtime_apis},
     next_manifests::{EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2},
     next_server::{get_server_runtime_entries, ServerContextType},
     util::parse_config_from_source,
@@ -17,7 +17,7 @@ use turbopack_binding::{
             changed::any_content_changed_of_output_assets,
             chunk::{ChunkableModule, ChunkingContext},
             context::AssetContext,
-            module::Module,
+            module::{Module, Modules},
             output::{OutputAsset, OutputAssets},
             virtual_output::VirtualOutputAsset,
         },
@@ -55,6 +55,8 @@ impl MiddlewareEndpoint {
 
     #[turbo_tasks::function]
     async fn edge_files(&self) -> Result<Vc<OutputAssets>> {
+        check_edge_runtime_apis(self.userland_module).await?;
+
         let module = get_middleware_module(
             self.context,
             self.project.project_path(),
@@ -147,7 +149,8 @@ impl MiddlewareEndpoint {
             regions: None,
             matchers,
             ..Default::default()
-        };
+        }
+        .with_wasm_bindings();
         let middleware_manifest_v2 = MiddlewaresManifestV2 {
             sorted_middleware: Default::default(),
             middleware: [("/".to_string(), edge_function_definition)]
@@ -217,4 +220,9 @@ impl Endpoint for MiddlewareEndpoint {
     fn client_changed(self: Vc<Self>) -> Vc<Completion> {
         Completion::immutable()
     }
+
+    #[turbo_tasks::function]
+    fn root_modules(&self) -> Vc<Modules> {
+        Vc::cell(vec![self.userland_module])
+    }
 }
@@ -0,0 +1,316 @@
+use std::collections::{BTreeSet, HashSet};
+
+use anyhow::{Context, Result};
+use next_core::{
+    all_assets_from_entries, next_config::OutputFileTracingGlobs, util::primary_referenced_modules,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::json;
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{
+        glob::Glob, DirectoryContent, DirectoryEntry, File, FileContent, FileSystemPath,
+    },
+    turbopack::{
+        core::{
+            asset::{Asset, AssetContent},
+            context::AssetContext,
+            file_source::FileSource,
+            output::OutputAsset,
+            reference_type::{CommonJsReferenceSubType, ReferenceType},
+            resolve::{node::node_cjs_resolve_options, parse::Request, pattern::Pattern, resolve},
+            virtual_output::VirtualOutputAsset,
+        },
+        turbopack::{
+            module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+            ModuleAssetContext,
+        },
+    },
+};
+
+use crate::project::Project;
+
+/// Creates the `.nft.json` file next to a Node.js server entry, which lists
+/// the files the entry needs at runtime relative to itself. This is what the
+/// standalone output copies for the route.
+///
+/// Besides the chunks of the entry, it contains the files of the packages the
+/// chunks require as externals and the files matched by the
+/// `experimental.outputFileTracingIncludes` globs for the route, e.g. the
+/// native binaries of packages which are loaded without a `require` we could
+/// follow. Files matched by `experimental.outputFileTracingExcludes` are
+/// removed.
+#[turbo_tasks::function]
+pub async fn nft_json_asset(
+    project: Vc<Project>,
+    entry_chunk: Vc<Box<dyn OutputAsset>>,
+    page: String,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let next_config = project.next_config();
+    let project_path = project.project_path();
+    let project_dir = project_path.await?.path.clone();
+    let node_root = project.node_root().await?;
+
+    // Output files live in a separate filesystem rooted at the project
+    // directory, so all paths are mapped to paths in the project filesystem.
+    let to_project_path = |path: &FileSystemPath| -> Result<String> {
+        let path = node_root
+            .get_path_to(path)
+            .context("output asset must be inside the node root")?;
+        Ok(join_path(&project_dir, &format!(".next/{path}")))
+    };
+    let trace_path = entry_chunk.ident().path().append(".nft.json".to_string());
+    let trace_dir = to_project_path(&*trace_path.parent().await?)?;
+
+    let mut files = BTreeSet::new();
+    if *next_config.output_file_tracing().await? {
+        let mut externals = BTreeSet::new();
+        for &asset in all_assets_from_entries(Vc::cell(vec![entry_chunk]))
+            .await?
+            .iter()
+        {
+            if asset != entry_chunk {
+                files.insert(to_project_path(&*asset.ident().path().await?)?);
+            }
+            externals.extend(chunk_externals(asset).await?.iter().cloned());
+        }
+
+        for external in externals {
+            files.extend(trace_external(project, external).await?.iter().cloned());
+        }
+
+        for include in route_globs(next_config.output_file_tracing_includes(), &page).await? {
+            files.extend(expand_glob(project_path, include).await?.iter().cloned());
+        }
+
+        for exclude in route_globs(next_config.output_file_tracing_excludes(), &page).await? {
+            for file in expand_glob(project_path, exclude).await?.iter() {
+                files.remove(file);
+            }
+        }
+    }
+
+    let content = json!({
+        "version": 1,
+        "files": files
+            .iter()
+            .map(|file| relative_path(&trace_dir, file))
+            .collect::<Vec<_>>(),
+    });
+
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        trace_path,
+        AssetContent::file(FileContent::Content(File::from(content.to_string())).cell()),
+    )))
+}
+
+fn join_path(dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        path.to_string()
+    } else {
+        format!("{dir}/{path}")
+    }
+}
+
+/// Returns the path of `path` relative to the directory `dir`, both given
+/// relative to the same filesystem root.
+fn relative_path(dir: &str, path: &str) -> String {
+    let dir_segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let common = dir_segments
+        .iter()
+        .zip(&path_segments)
+        .take_while(|(a, b)| a == b)
+        .count();
+    std::iter::repeat("..")
+        .take(dir_segments.len() - common)
+        .chain(path_segments[common..].iter().copied())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+static EXTERNAL_REQUIRE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"__turbopack_external_(?:require|import)__\("((?:[^"\\]|\\.)+)""#).unwrap()
+});
+
+/// The requests of the externals a chunk loads at runtime, like
+/// `__turbopack_external_require__("sharp")`.
+fn external_requests(code: &str) -> Vec<String> {
+    EXTERNAL_REQUIRE
+        .captures_iter(code)
+        .filter_map(|captures| serde_json::from_str(&format!("\"{}\"", &captures[1])).ok())
+        .collect()
+}
+
+#[turbo_tasks::function]
+async fn chunk_externals(asset: Vc<Box<dyn OutputAsset>>) -> Result<Vc<Vec<String>>> {
+    if !asset.ident().path().await?.path.ends_with(".js") {
+        return Ok(Vc::cell(vec![]));
+    }
+    let FileContent::Content(file) = &*asset.content().file_content().await? else {
+        return Ok(Vc::cell(vec![]));
+    };
+    Ok(Vc::cell(external_requests(&file.content().to_str()?)))
+}
+
+/// A module context which only follows references the way Node.js would
+/// resolve them, without any Next.js transforms, to find the files of
+/// externals like @vercel/nft does.
+#[turbo_tasks::function]
+async fn externals_tracing_context(project: Vc<Project>) -> Result<Vc<ModuleAssetContext>> {
+    Ok(ModuleAssetContext::new(
+        Vc::cell(Default::default()),
+        project.server_compile_time_info(),
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext {
+            enable_node_modules: Some(project.project_path().root().resolve().await?),
+            enable_node_native_modules: true,
+            custom_conditions: vec!["node".to_string()],
+            ..Default::default()
+        }
+        .cell(),
+    ))
+}
+
+/// The files an external needs at runtime, relative to the root of the
+/// project filesystem. The external is resolved from the project directory,
+/// which is where the server chunks require it from.
+#[turbo_tasks::function]
+async fn trace_external(project: Vc<Project>, request: String) -> Result<Vc<Vec<String>>> {
+    let project_path = project.project_path();
+    let Some(source) = *resolve(
+        project_path,
+        Request::parse(Value::new(Pattern::Constant(request))),
+        node_cjs_resolve_options(project_path.root()),
+    )
+    .first_source()
+    .await?
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+    let module = externals_tracing_context(project).process(
+        source,
+        Value::new(ReferenceType::CommonJs(CommonJsReferenceSubType::Undefined)),
+    );
+
+    let mut files = vec![];
+    let mut visited = HashSet::new();
+    let mut queue = vec![module.resolve().await?];
+    while let Some(module) = queue.pop() {
+        if !visited.insert(module) {
+            continue;
+        }
+        files.push(module.ident().path().await?.path.clone());
+        queue.extend(primary_referenced_modules(module).await?.iter().copied());
+    }
+    files.sort();
+    files.dedup();
+    Ok(Vc::cell(files))
+}
+
+/// Collects the file globs configured for all route globs matching the page.
+async fn route_globs(config: Vc<OutputFileTracingGlobs>, page: &str) -> Result<Vec<String>> {
+    let mut globs = vec![];
+    for (route_glob, file_globs) in config.await?.iter() {
+        if Glob::new(route_glob.clone()).await?.execute(page) {
+            globs.extend(file_globs.iter().cloned());
+        }
+    }
+    Ok(globs)
+}
+
+/// Splits a glob into the directory before its first wildcard and the rest,
+/// which is matched against the paths relative to that directory.
+fn split_glob(glob: &str) -> (&str, &str) {
+    let glob = glob.strip_prefix("./").unwrap_or(glob);
+    let glob_start = glob.find(['*', '?', '[', '{']).unwrap_or(glob.len());
+    match glob[..glob_start].rfind('/') {
+        Some(index) => (&glob[..index], &glob[index + 1..]),
+        None => ("", glob),
+    }
+}
+
+/// Lists the files matching a glob relative to the project directory, relative
+/// to the root of the project filesystem. The part before the first wildcard
+/// may leave the project directory, e.g. `../../node_modules/sharp/**/*` in a
+/// monorepo.
+#[turbo_tasks::function]
+async fn expand_glob(project_path: Vc<FileSystemPath>, glob: String) -> Result<Vc<Vec<String>>> {
+    let (base, pattern) = split_glob(&glob);
+    let Some(base_dir) = &*project_path.try_join(base.to_string()).await? else {
+        return Ok(Vc::cell(vec![]));
+    };
+    let base_dir_path = base_dir.await?.path.clone();
+    let matcher = Glob::new(pattern.to_string()).await?;
+
+    let mut files = vec![];
+    let mut queue = vec![(*base_dir, String::new())];
+    while let Some((dir, prefix)) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+            continue;
+        };
+        for (name, entry) in entries.iter() {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{prefix}/{name}")
+            };
+            match entry {
+                DirectoryEntry::File(_) if matcher.execute(&path) => {
+                    files.push(join_path(&base_dir_path, &path))
+                }
+                DirectoryEntry::Directory(dir) => queue.push((*dir, path)),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    Ok(Vc::cell(files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path("app/.next/server/pages", "app/.next/server/chunks/1.js"),
+            "../chunks/1.js"
+        );
+        assert_eq!(
+            relative_path(
+                "apps/web/.next/server/pages/blog",
+                "node_modules/.prisma/client/libquery_engine.so.node"
+            ),
+            "../../../../../../node_modules/.prisma/client/libquery_engine.so.node"
+        );
+        assert_eq!(relative_path("", "a/b.js"), "a/b.js");
+    }
+
+    #[test]
+    fn test_split_glob() {
+        assert_eq!(split_glob("./public/**/*"), ("public", "**/*"));
+        assert_eq!(
+            split_glob("../../node_modules/sharp/**/*.node"),
+            ("../../node_modules/sharp", "**/*.node")
+        );
+        assert_eq!(
+            split_glob("prisma/schema.prisma"),
+            ("prisma", "schema.prisma")
+        );
+        assert_eq!(split_glob("*.json"), ("", "*.json"));
+    }
+
+    #[test]
+    fn test_external_requests() {
+        assert_eq!(
+            external_requests(
+                r#"const sharp = __turbopack_external_require__("sharp", true);
+                const mod = await __turbopack_external_import__("@scope/pkg/sub");"#
+            ),
+            vec!["sharp".to_string(), "@scope/pkg/sub".to_string()]
+        );
+    }
+}
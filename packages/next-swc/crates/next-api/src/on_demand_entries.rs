@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use indexmap::IndexMap;
+use next_core::next_config::OnDemandEntriesConfig;
+
+/// Tracks when the route entries compiled in dev were last requested, to find
+/// the inactive entries which can be disposed to free the memory of their
+/// tasks.
+pub struct OnDemandEntries {
+    max_inactive_age: Duration,
+    pages_buffer_length: usize,
+    /// The entries ordered by their last access, the most recent last.
+    last_accessed: IndexMap<String, Instant>,
+    /// The number of change subscriptions of each entry. Subscribed entries
+    /// are never disposed, as nothing would subscribe to their changes again.
+    subscriptions: HashMap<String, usize>,
+}
+
+impl OnDemandEntries {
+    pub fn new(config: &OnDemandEntriesConfig) -> Self {
+        let mut entries = OnDemandEntries {
+            max_inactive_age: Duration::ZERO,
+            pages_buffer_length: 0,
+            last_accessed: IndexMap::new(),
+            subscriptions: HashMap::new(),
+        };
+        entries.set_config(config);
+        entries
+    }
+
+    pub fn set_config(&mut self, config: &OnDemandEntriesConfig) {
+        self.max_inactive_age = Duration::from_millis(config.max_inactive_age.max(0.0) as u64);
+        self.pages_buffer_length = config.pages_buffer_length.max(0.0) as usize;
+    }
+
+    /// Marks the entry as requested at `now`.
+    pub fn touch(&mut self, entry: &str, now: Instant) {
+        self.last_accessed.shift_remove(entry);
+        self.last_accessed.insert(entry.to_string(), now);
+    }
+
+    /// Marks the entry as subscribed to at `now`.
+    pub fn subscribe(&mut self, entry: &str, now: Instant) {
+        *self.subscriptions.entry(entry.to_string()).or_default() += 1;
+        self.touch(entry, now);
+    }
+
+    /// Removes a subscription of the entry. The entry is forgotten once its
+    /// last subscription is gone.
+    pub fn unsubscribe(&mut self, entry: &str) {
+        let Some(count) = self.subscriptions.get_mut(entry) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.subscriptions.remove(entry);
+            self.last_accessed.shift_remove(entry);
+        }
+    }
+
+    /// Removes and returns the entries which haven't been requested for longer
+    /// than the max inactive age. The `pages_buffer_length` most recently
+    /// requested entries and the subscribed ones are always kept.
+    pub fn dispose_inactive(&mut self, now: Instant) -> Vec<String> {
+        let candidates = self
+            .last_accessed
+            .len()
+            .saturating_sub(self.pages_buffer_length);
+        let mut disposed = vec![];
+        self.last_accessed.retain(|entry, last_accessed| {
+            let dispose = disposed.len() < candidates
+                && !self.subscriptions.contains_key(entry)
+                && now.saturating_duration_since(*last_accessed) > self.max_inactive_age;
+            if dispose {
+                disposed.push(entry.clone());
+            }
+            !dispose
+        });
+        disposed
+    }
+
+    /// The entries which are currently kept compiled, the most recently
+    /// requested first.
+    pub fn hot_entries(&self) -> Vec<String> {
+        self.last_accessed.keys().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispose_inactive() {
+        let mut entries = OnDemandEntries::new(&OnDemandEntriesConfig {
+            max_inactive_age: 1000.0,
+            pages_buffer_length: 1.0,
+            max_concurrent_compilations: None,
+        });
+        let start = Instant::now();
+        entries.touch("/a", start);
+        entries.touch("/b", start);
+        entries.touch("/c", start + Duration::from_millis(1500));
+        entries.touch("/a", start + Duration::from_millis(1800));
+        assert_eq!(entries.hot_entries(), vec!["/a", "/c", "/b"]);
+
+        let now = start + Duration::from_millis(2000);
+        assert_eq!(entries.dispose_inactive(now), vec!["/b"]);
+
+        let now = start + Duration::from_millis(5000);
+        assert_eq!(entries.dispose_inactive(now), vec!["/c"]);
+        assert_eq!(entries.hot_entries(), vec!["/a"]);
+    }
+
+    #[test]
+    fn test_dispose_inactive_subscribed() {
+        let mut entries = OnDemandEntries::new(&OnDemandEntriesConfig {
+            max_inactive_age: 1000.0,
+            pages_buffer_length: 0.0,
+            max_concurrent_compilations: None,
+        });
+        let start = Instant::now();
+        entries.touch("/a", start);
+        entries.subscribe("/b", start);
+        entries.subscribe("/b", start);
+        entries.subscribe("/c", start);
+
+        let now = start + Duration::from_millis(2000);
+        assert_eq!(entries.dispose_inactive(now), vec!["/a"]);
+        assert_eq!(entries.hot_entries(), vec!["/c", "/b"]);
+
+        entries.unsubscribe("/c");
+        entries.unsubscribe("/b");
+        assert_eq!(entries.hot_entries(), vec!["/b"]);
+        assert!(entries.dispose_inactive(now).is_empty());
+
+        entries.unsubscribe("/b");
+        assert!(entries.hot_entries().is_empty());
+    }
+}
@@ -6,19 +6,26 @@ use next_core::{
     mode::NextMode,
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
-        get_client_runtime_entries, ClientContextType, RuntimeEntries,
+        get_client_runtime_entries, top_level_await::check_client_top_level_await,
+        ClientContextType, RuntimeEntries,
     },
     next_dynamic::NextDynamicTransition,
-    next_edge::route_regex::get_named_middleware_regex,
+    next_edge::{
+        route_regex::get_named_middleware_regex, unsupported_apis::check_edge_runtime_apis,
+    },
     next_manifests::{
-        BuildManifest, EdgeFunctionDefinition, MiddlewareMatcher, MiddlewaresManifestV2,
-        PagesManifest,
+        preload_hints::get_preload_hints, BuildManifest, EdgeFunctionDefinition, MiddlewareMatcher,
+        MiddlewaresManifestV2, PagesManifest,
     },
     next_pages::create_page_ssr_entry_module,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    next_shared::{
+        global_css::validate_global_css_imports,
+        next_script::{validate_next_script_usage, NextScriptRouter},
+    },
     pages_structure::{
         find_pages_structure, PagesDirectoryStructure, PagesStructure, PagesStructureItem,
     },
@@ -42,6 +49,7 @@ use turbopack_binding::{
             context::AssetContext,
             file_source::FileSource,
             issue::{IssueSeverity, OptionIssueSource},
+            module::Modules,
             output::{OutputAsset, OutputAssets},
             reference_type::{
                 EcmaScriptModulesReferenceSubType, EntryReferenceSubType, ReferenceType,
@@ -63,6 +71,7 @@ use turbopack_binding::{
 };
 
 use crate::{
+    nft_json::nft_json_asset,
     project::Project,
     route::{Endpoint, Route, Routes, WrittenEndpoint},
 };
@@ -556,6 +565,28 @@ impl PageEndpoint {
 
         let client_chunking_context = this.pages_project.project().client_chunking_context();
 
+        validate_global_css_imports(
+            Vc::upcast(client_module),
+            Vc::cell(Some(
+                this.pages_project
+                    .pages_structure()
+                    .await?
+                    .app
+                    .await?
+                    .project_path,
+            )),
+            this.original_name.await?.clone_value(),
+        )
+        .await?;
+
+        check_client_top_level_await(
+            Vc::upcast(client_module),
+            this.pages_project.project().project_path(),
+            this.pages_project.project().next_config(),
+            this.original_name.await?.clone_value(),
+        )
+        .await?;
+
         let client_entry_chunk = client_module.as_root_chunk(Vc::upcast(client_chunking_context));
 
         let mut client_chunks = client_chunking_context
@@ -602,6 +633,8 @@ impl PageEndpoint {
         let is_edge = matches!(config.runtime, NextRuntime::Edge);
 
         if is_edge {
+            check_edge_runtime_apis(ssr_module).await?;
+
             let ssr_module = create_page_ssr_entry_module(
                 this.pathname,
                 reference_type,
@@ -746,11 +779,12 @@ impl PageEndpoint {
         let node_root = this.pages_project.project().node_root();
         let client_relative_path = this.pages_project.project().client_relative_path();
         let client_relative_path_ref = client_relative_path.await?;
+        let pathname = this.pathname.await?;
+        let client_chunks = client_chunks.await?;
         let build_manifest = BuildManifest {
             pages: [(
-                this.pathname.await?.clone_value(),
+                pathname.clone_value(),
                 client_chunks
-                    .await?
                     .iter()
                     .copied()
                     .map(|chunk| {
@@ -768,9 +802,15 @@ impl PageEndpoint {
             )]
             .into_iter()
             .collect(),
+            preload_hints: [(
+                pathname.clone_value(),
+                get_preload_hints(&client_chunks, &client_relative_path_ref).await?,
+            )]
+            .into_iter()
+            .collect(),
             ..Default::default()
         };
-        let manifest_path_prefix = get_asset_prefix_from_pathname(&this.pathname.await?);
+        let manifest_path_prefix = get_asset_prefix_from_pathname(&pathname);
         Ok(Vc::upcast(VirtualOutputAsset::new(
             node_root.join(format!(
                 "server/pages{manifest_path_prefix}/build-manifest.json",
@@ -779,6 +819,35 @@ impl PageEndpoint {
         )))
     }
 
+    /// Validates the `next/script` usage of the page and returns the manifest
+    /// of its worker scripts, if it has any.
+    #[turbo_tasks::function]
+    async fn next_script_manifest(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let ssr_module = this.pages_project.ssr_module_context().process(
+            self.source(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Page)),
+        );
+        let document = this.pages_project.pages_structure().await?.document;
+        let manifest = validate_next_script_usage(
+            ssr_module,
+            Vc::cell(Some(document.await?.project_path)),
+            NextScriptRouter::Pages,
+        )
+        .await?;
+        if manifest.worker_scripts.is_empty() {
+            return Ok(OutputAssets::empty());
+        }
+
+        let manifest_path_prefix = get_asset_prefix_from_pathname(&this.pathname.await?);
+        Ok(Vc::cell(vec![Vc::upcast(VirtualOutputAsset::new(
+            this.pages_project.project().node_root().join(format!(
+                "server/pages{manifest_path_prefix}/next-script-manifest.json",
+            )),
+            AssetContent::file(File::from(serde_json::to_string_pretty(&*manifest)?).into()),
+        ))]))
+    }
+
     #[turbo_tasks::function]
     fn output_assets(self: Vc<Self>) -> Vc<OutputAssets> {
         self.output().output_assets()
@@ -797,11 +866,15 @@ impl PageEndpoint {
                 client_assets.extend(client_chunks.await?.iter().copied());
                 let build_manifest = self.build_manifest(client_chunks);
                 server_assets.push(build_manifest);
+                server_assets.extend(self.next_script_manifest().await?.iter().copied());
                 self.ssr_chunk()
             }
             PageEndpointType::Data => self.ssr_data_chunk(),
             PageEndpointType::Api => self.api_chunk(),
-            PageEndpointType::SsrOnly => self.ssr_chunk(),
+            PageEndpointType::SsrOnly => {
+                server_assets.extend(self.next_script_manifest().await?.iter().copied());
+                self.ssr_chunk()
+            }
         };
 
         let page_output = match *ssr_chunk.await? {
@@ -809,6 +882,13 @@ impl PageEndpoint {
                 let pages_manifest = self.pages_manifest(entry);
                 server_assets.push(pages_manifest);
                 server_assets.push(entry);
+                if !matches!(this.ty, PageEndpointType::Data) {
+                    server_assets.push(nft_json_asset(
+                        this.pages_project.project(),
+                        entry,
+                        get_asset_prefix_from_pathname(&this.pathname.await?),
+                    ));
+                }
 
                 PageEndpointOutput::NodeJs {
                     entry_chunk: entry,
@@ -854,7 +934,8 @@ impl PageEndpoint {
                     regions: None,
                     matchers: vec![matchers],
                     ..Default::default()
-                };
+                }
+                .with_wasm_bindings();
                 let middleware_manifest_v2 = MiddlewaresManifestV2 {
                     sorted_middleware: vec![pathname.to_string()],
                     middleware: Default::default(),
@@ -952,6 +1033,47 @@ impl Endpoint for PageEndpoint {
     fn client_changed(self: Vc<Self>) -> Vc<Completion> {
         any_content_changed_of_output_assets(self.output().client_assets())
     }
+
+    #[turbo_tasks::function]
+    async fn root_modules(self: Vc<Self>) -> Result<Vc<Modules>> {
+        let this = self.await?;
+        let (reference_subtype, module_context, edge_module_context) = match this.ty {
+            PageEndpointType::Html | PageEndpointType::SsrOnly => (
+                EntryReferenceSubType::Page,
+                this.pages_project.ssr_module_context(),
+                this.pages_project.edge_ssr_module_context(),
+            ),
+            PageEndpointType::Data => (
+                EntryReferenceSubType::Page,
+                this.pages_project.ssr_data_module_context(),
+                this.pages_project.edge_ssr_data_module_context(),
+            ),
+            PageEndpointType::Api => (
+                EntryReferenceSubType::PagesApi,
+                this.pages_project.ssr_module_context(),
+                this.pages_project.edge_ssr_module_context(),
+            ),
+        };
+        let reference_type = Value::new(ReferenceType::Entry(reference_subtype));
+
+        let mut ssr_module = module_context.process(self.source(), reference_type.clone());
+        if matches!(
+            parse_config_from_source(ssr_module).await?.runtime,
+            NextRuntime::Edge
+        ) {
+            ssr_module = edge_module_context.process(self.source(), reference_type);
+        }
+
+        let mut modules = vec![ssr_module];
+        if matches!(this.ty, PageEndpointType::Html) {
+            modules.push(create_page_loader_entry_module(
+                this.pages_project.client_module_context(),
+                self.source(),
+                this.pathname,
+            ));
+        }
+        Ok(Vc::cell(modules))
+    }
 }
 
 #[turbo_tasks::value]
@@ -1,21 +1,28 @@
-use std::path::MAIN_SEPARATOR;
+use std::net::IpAddr;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use indexmap::{map::Entry, IndexMap};
 use next_core::{
     all_assets_from_entries,
-    app_structure::find_app_dir,
+    app_structure::find_app_dir_if_enabled,
     emit_assets, get_edge_chunking_context, get_edge_compile_time_info,
     get_edge_resolve_options_context,
     middleware::middleware_files,
     mode::NextMode,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
-    next_config::{JsConfig, NextConfig},
+    next_client::{
+        get_browserslist_query, get_client_chunking_context, get_client_compile_time_info,
+    },
+    next_config::{JsConfig, NextConfig, ReactRemoveProperties},
     next_server::{
         get_server_chunking_context, get_server_compile_time_info,
         get_server_module_options_context, ServerContextType,
     },
     next_telemetry::NextFeatureTelemetry,
+    project_relative_path::ProjectRelativePath,
+    public_assets::{find_public_assets, PublicAssetConflictIssue},
+    route_conflicts::{find_route_conflicts, PagesRoute, RouteConflictIssue},
+    validate_tsconfig_paths,
+    zones::{find_zone_conflicts, ZoneConflictIssue},
 };
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{
@@ -25,7 +32,7 @@ use turbo_tasks::{
 use turbopack_binding::{
     turbo::{
         tasks_env::{EnvMap, ProcessEnv},
-        tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath, VirtualFileSystem},
+        tasks_fs::{glob::Glob, DiskFileSystem, FileSystem, FileSystemPath, VirtualFileSystem},
     },
     turbopack::{
         build::BuildChunkingContext,
@@ -36,6 +43,7 @@ use turbopack_binding::{
             diagnostics::DiagnosticExt,
             environment::ServerAddr,
             file_source::FileSource,
+            issue::IssueExt,
             output::OutputAssets,
             reference_type::{EntryReferenceSubType, ReferenceType},
             resolve::{find_context_file, FindContextFileResult},
@@ -51,13 +59,15 @@ use turbopack_binding::{
 };
 
 use crate::{
-    app::{AppProject, OptionAppProject},
+    app::{AppEndpoint, AppProject, OptionAppProject},
     build,
+    chunk_graph::ChunkGraph,
     entrypoints::Entrypoints,
     middleware::MiddlewareEndpoint,
     pages::PagesProject,
-    route::{Endpoint, Route},
-    versioned_content_map::{OutputAssetsOperation, VersionedContentMap},
+    route::{Endpoint, Route, Routes},
+    versioned_content_map::{EmittedAssets, OutputAssetsOperation, VersionedContentMap},
+    why_included::{why_included, WhyIncluded},
 };
 
 #[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
@@ -81,6 +91,84 @@ pub struct ProjectOptions {
 
     /// Whether to watch the filesystem for file changes.
     pub watch: bool,
+
+    /// Only collect the app and pages routes whose pathnames start with one of
+    /// these prefixes, e.g. `/dashboard`. This splits the build of a large app
+    /// into shards, whose manifests are merged afterwards.
+    #[serde(default)]
+    pub route_prefixes: Option<Vec<String>>,
+
+    /// How the dev server is reached, when running in dev.
+    #[serde(default)]
+    pub dev_server: Option<DevServerOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct DevServerOptions {
+    pub hostname: String,
+    pub port: u16,
+    /// The key and certificate when the dev server uses HTTPS, i.e.
+    /// `next dev --experimental-https`.
+    pub https: Option<HttpsOptions>,
+    /// Hostnames other than the dev server itself which may connect to it,
+    /// e.g. `dev.example.com` or `*.example.com`. Any origin may connect when
+    /// this isn't configured.
+    #[serde(default)]
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
+pub struct HttpsOptions {
+    /// The path of the private key.
+    pub key: String,
+    /// The path of the certificate.
+    pub cert: String,
+}
+
+impl DevServerOptions {
+    pub fn origin(&self) -> String {
+        let protocol = if self.https.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        format!("{protocol}://{}:{}", self.hostname, self.port)
+    }
+
+    /// Whether a browser with the given `Origin` may connect to the dev
+    /// server, e.g. for HMR. With `allowed_origins`, loopback addresses and
+    /// the dev server's own hostname are allowed as well. Other devices on the
+    /// network have to be listed, e.g. as `192.168.0.10`.
+    pub fn is_allowed_origin(&self, origin: &str) -> bool {
+        let Some(allowed_origins) = &self.allowed_origins else {
+            return true;
+        };
+        let Some(hostname) = origin_hostname(origin) else {
+            return false;
+        };
+        hostname == "localhost"
+            || hostname
+                .parse()
+                .map_or(false, |ip: IpAddr| ip.is_loopback())
+            || hostname == self.hostname.trim_start_matches('[').trim_end_matches(']')
+            || allowed_origins
+                .iter()
+                .any(|allowed| match allowed.strip_prefix("*.") {
+                    Some(domain) => hostname
+                        .strip_suffix(domain)
+                        .map_or(false, |subdomain| subdomain.ends_with('.')),
+                    None => hostname == allowed,
+                })
+    }
+}
+
+fn origin_hostname(origin: &str) -> Option<&str> {
+    let (_, host) = origin.split_once("://")?;
+    match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => host.split(':').next(),
+    }
 }
 
 #[derive(Serialize, Deserialize, TraceRawVcs, PartialEq, Eq, ValueDebugFormat)]
@@ -122,12 +210,11 @@ impl ProjectContainer {
             root_path: options.root_path.clone(),
             project_path: options.project_path.clone(),
             watch: options.watch,
+            route_prefixes: options.route_prefixes.clone(),
+            dev_server: options.dev_server.clone(),
             next_config,
             js_config,
             env: Vc::upcast(env),
-            browserslist_query: "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
-                                 versions, last 1 Edge versions"
-                .to_string(),
             mode: NextMode::Development,
             versioned_content_map: this.versioned_content_map,
         }
@@ -140,11 +227,45 @@ impl ProjectContainer {
         self.project().entrypoints()
     }
 
+    /// See [Project::warm_up_routes].
+    #[turbo_tasks::function]
+    pub fn warm_up_routes(self: Vc<Self>) -> Vc<Routes> {
+        self.project().warm_up_routes()
+    }
+
     /// See [Project::hmr_identifiers].
     #[turbo_tasks::function]
     pub fn hmr_identifiers(self: Vc<Self>) -> Vc<Vec<String>> {
         self.project().hmr_identifiers()
     }
+
+    /// See [Project::client_assets].
+    #[turbo_tasks::function]
+    pub fn client_assets(self: Vc<Self>) -> Vc<EmittedAssets> {
+        self.project().client_assets()
+    }
+
+    /// See [Project::is_allowed_dev_origin].
+    #[turbo_tasks::function]
+    pub fn is_allowed_dev_origin(self: Vc<Self>, origin: String) -> Vc<bool> {
+        self.project().is_allowed_dev_origin(origin)
+    }
+
+    /// See [Project::why_included].
+    #[turbo_tasks::function]
+    pub fn why_included(
+        self: Vc<Self>,
+        endpoint: Vc<Box<dyn Endpoint>>,
+        path: String,
+    ) -> Vc<WhyIncluded> {
+        self.project().why_included(endpoint, path)
+    }
+
+    /// See [Project::chunk_graph].
+    #[turbo_tasks::function]
+    pub fn chunk_graph(self: Vc<Self>, endpoint: Vc<Box<dyn Endpoint>>) -> Vc<ChunkGraph> {
+        self.project().chunk_graph(endpoint)
+    }
 }
 
 #[turbo_tasks::value]
@@ -159,6 +280,12 @@ pub struct Project {
     /// Whether to watch the filesystem for file changes.
     watch: bool,
 
+    /// The route prefixes of the build shard, if any.
+    route_prefixes: Option<Vec<String>>,
+
+    /// How the dev server is reached, when running in dev.
+    dev_server: Option<DevServerOptions>,
+
     /// Next config.
     next_config: Vc<NextConfig>,
 
@@ -168,8 +295,6 @@ pub struct Project {
     /// A map of environment variables to use when compiling code.
     env: Vc<Box<dyn ProcessEnv>>,
 
-    browserslist_query: String,
-
     mode: NextMode,
 
     versioned_content_map: Vc<VersionedContentMap>,
@@ -180,7 +305,7 @@ impl Project {
     #[turbo_tasks::function]
     async fn app_project(self: Vc<Self>) -> Result<Vc<OptionAppProject>> {
         let this = self.await?;
-        let app_dir = find_app_dir(self.project_path()).await?;
+        let app_dir = find_app_dir_if_enabled(self.project_path(), self.next_config()).await?;
 
         Ok(Vc::cell(if let Some(app_dir) = &*app_dir {
             Some(AppProject::new(self, *app_dir, this.mode))
@@ -223,7 +348,7 @@ impl Project {
     }
 
     #[turbo_tasks::function]
-    pub(super) fn node_root(self: Vc<Self>) -> Vc<FileSystemPath> {
+    pub fn node_root(self: Vc<Self>) -> Vc<FileSystemPath> {
         self.node_fs().root().join(".next".to_string())
     }
 
@@ -246,12 +371,8 @@ impl Project {
     pub(super) async fn project_path(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
         let this = self.await?;
         let root = self.project_root_path();
-        let project_relative = this.project_path.strip_prefix(&this.root_path).unwrap();
-        let project_relative = project_relative
-            .strip_prefix(MAIN_SEPARATOR)
-            .unwrap_or(project_relative)
-            .replace(MAIN_SEPARATOR, "/");
-        Ok(root.join(project_relative))
+        let project_relative = ProjectRelativePath::new(&this.root_path, &this.project_path)?;
+        Ok(project_relative.join(root))
     }
 
     #[turbo_tasks::function]
@@ -292,8 +413,27 @@ impl Project {
     }
 
     #[turbo_tasks::function]
-    pub(super) fn client_compile_time_info(&self) -> Vc<CompileTimeInfo> {
-        get_client_compile_time_info(self.mode, self.browserslist_query.clone())
+    pub(super) async fn browserslist_query(self: Vc<Self>) -> Result<Vc<String>> {
+        let this = self.await?;
+        Ok(get_browserslist_query(
+            self.project_path(),
+            this.mode,
+            this.next_config,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub(super) async fn client_compile_time_info(self: Vc<Self>) -> Result<Vc<CompileTimeInfo>> {
+        let this = self.await?;
+        let browserslist_query = self.browserslist_query().await?;
+        Ok(get_client_compile_time_info(
+            this.mode,
+            browserslist_query.clone_value(),
+            this.next_config,
+            this.dev_server
+                .as_ref()
+                .map(|dev_server| dev_server.origin()),
+        ))
     }
 
     #[turbo_tasks::function]
@@ -302,18 +442,67 @@ impl Project {
         Ok(get_server_compile_time_info(
             this.mode,
             self.env(),
-            // TODO(alexkirsz) Fill this out.
-            ServerAddr::empty(),
+            self.server_addr(),
         ))
     }
 
     #[turbo_tasks::function]
     pub(super) fn edge_compile_time_info(self: Vc<Self>) -> Vc<CompileTimeInfo> {
-        get_edge_compile_time_info(
-            self.project_path(),
-            // TODO(alexkirsz) Fill this out.
-            ServerAddr::empty(),
-        )
+        get_edge_compile_time_info(self.project_path(), self.server_addr())
+    }
+
+    /// The address of the dev server, if it is known and resolvable.
+    #[turbo_tasks::function]
+    async fn server_addr(self: Vc<Self>) -> Result<Vc<ServerAddr>> {
+        let this = self.await?;
+        let Some(dev_server) = &this.dev_server else {
+            return Ok(ServerAddr::empty());
+        };
+        // Resolves the hostname without blocking the turbo-tasks threads.
+        let addr = tokio::net::lookup_host((dev_server.hostname.as_str(), dev_server.port))
+            .await
+            .ok()
+            .and_then(|mut addrs| addrs.next());
+        Ok(match addr {
+            Some(addr) => ServerAddr::new(addr).cell(),
+            None => ServerAddr::empty(),
+        })
+    }
+
+    /// See [DevServerOptions::is_allowed_origin]. Any origin is allowed when
+    /// the dev server options are unknown.
+    #[turbo_tasks::function]
+    pub async fn is_allowed_dev_origin(self: Vc<Self>, origin: String) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .dev_server
+                .as_ref()
+                .map_or(true, |dev_server| dev_server.is_allowed_origin(&origin)),
+        ))
+    }
+
+    /// Why the file at the given project-relative path is bundled for the
+    /// endpoint, see [why_included].
+    #[turbo_tasks::function]
+    pub fn why_included(
+        self: Vc<Self>,
+        endpoint: Vc<Box<dyn Endpoint>>,
+        path: String,
+    ) -> Vc<WhyIncluded> {
+        why_included(self.project_path(), endpoint.root_modules(), path)
+    }
+
+    /// The chunk groups of an app page or route endpoint, with the modules
+    /// they are created for.
+    #[turbo_tasks::function]
+    pub async fn chunk_graph(
+        self: Vc<Self>,
+        endpoint: Vc<Box<dyn Endpoint>>,
+    ) -> Result<Vc<ChunkGraph>> {
+        let Some(endpoint) = Vc::try_resolve_downcast_type::<AppEndpoint>(endpoint).await? else {
+            bail!("the chunk graph is only available for app endpoints");
+        };
+        Ok(endpoint.chunk_graph())
     }
 
     #[turbo_tasks::function]
@@ -409,8 +598,10 @@ impl Project {
             .map(|c| c.styled_components.is_some())
             .unwrap_or_default();
         let react_remove_properties_enabled = compiler_options
-            .and_then(|c| c.react_remove_properties)
-            .unwrap_or_default();
+            .and_then(|c| c.react_remove_properties.as_ref())
+            .map_or(false, |config| {
+                !matches!(config, ReactRemoveProperties::Boolean(false))
+            });
         let remove_console_enabled = compiler_options
             .map(|c| c.remove_console.is_some())
             .unwrap_or_default();
@@ -490,7 +681,24 @@ impl Project {
             routes.extend(app_routes.await?.iter().map(|(k, v)| (k.clone(), *v)));
         }
 
-        for (pathname, page_route) in pages_project.routes().await?.iter() {
+        let pages_routes = pages_project.routes().await?;
+        let conflicts = find_route_conflicts(
+            routes.keys().map(|pathname| pathname.as_str()),
+            pages_routes.iter().map(|(pathname, route)| PagesRoute {
+                pathname,
+                is_api: matches!(route, Route::PageApi { .. }),
+            }),
+        );
+        if !conflicts.is_empty() {
+            RouteConflictIssue {
+                project_path: self.project_path(),
+                conflicts,
+            }
+            .cell()
+            .emit();
+        }
+
+        for (pathname, page_route) in pages_routes.iter() {
             match routes.entry(pathname.clone()) {
                 Entry::Occupied(mut entry) => {
                     *entry.get_mut() = Route::Conflict;
@@ -501,6 +709,45 @@ impl Project {
             }
         }
 
+        let public_assets = find_public_assets(self.project_path()).await?;
+        for pathname in public_assets.iter() {
+            if routes.contains_key(pathname) {
+                PublicAssetConflictIssue {
+                    project_path: self.project_path(),
+                    pathname: pathname.clone(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+
+        let zones = self.next_config().zones().await?;
+        if !zones.is_empty() {
+            let asset_prefix = self.next_config().asset_prefix().await?;
+            for conflict in
+                find_zone_conflicts(&zones, &asset_prefix, routes.keys().map(|k| k.as_str()))
+            {
+                ZoneConflictIssue {
+                    project_path: self.project_path(),
+                    conflict,
+                }
+                .cell()
+                .emit();
+            }
+        }
+
+        validate_tsconfig_paths(self.project_path()).await?;
+
+        // Conflicts are checked for all routes above, as the routes of other
+        // shards still share the same URL space.
+        if let Some(prefixes) = &self.await?.route_prefixes {
+            routes.retain(|pathname, _| {
+                prefixes
+                    .iter()
+                    .any(|prefix| has_path_prefix(pathname, prefix))
+            });
+        }
+
         let middleware = find_context_file(
             self.project_path(),
             middleware_files(self.next_config().page_extensions()),
@@ -516,6 +763,7 @@ impl Project {
 
         Ok(Entrypoints {
             routes,
+            public_assets: public_assets.clone_value(),
             middleware,
             pages_document_endpoint: self.pages_project().document_endpoint(),
             pages_app_endpoint: self.pages_project().app_endpoint(),
@@ -524,6 +772,26 @@ impl Project {
         .cell())
     }
 
+    /// The routes matching the `experimental.turbo.warmUp` globs, which are
+    /// compiled when the dev server starts instead of when they are first
+    /// requested.
+    #[turbo_tasks::function]
+    pub async fn warm_up_routes(self: Vc<Self>) -> Result<Vc<Routes>> {
+        let mut globs = vec![];
+        for glob in self.next_config().warm_up().await?.iter() {
+            globs.push(Glob::new(glob.clone()).await?);
+        }
+        let entrypoints = self.entrypoints().await?;
+        Ok(Vc::cell(
+            entrypoints
+                .routes
+                .iter()
+                .filter(|(pathname, _)| globs.iter().any(|glob| glob.execute(pathname)))
+                .map(|(pathname, route)| (pathname.clone(), *route))
+                .collect(),
+        ))
+    }
+
     #[turbo_tasks::function]
     fn middleware_context(self: Vc<Self>) -> Vc<Box<dyn AssetContext>> {
         Vc::upcast(ModuleAssetContext::new(
@@ -570,11 +838,18 @@ impl Project {
             .insert_output_assets(all_output_assets)
             .await?;
 
+        let lightningcss_targets = if *self.next_config().use_lightningcss().await? {
+            Some(self.browserslist_query())
+        } else {
+            None
+        };
+
         Ok(emit_assets(
             *all_output_assets.await?,
             self.node_root(),
             self.client_relative_path(),
             self.node_root(),
+            lightningcss_targets,
         ))
     }
 
@@ -637,6 +912,17 @@ impl Project {
             .versioned_content_map
             .keys_in_path(self.client_relative_path()))
     }
+
+    /// Gets the client assets emitted so far, with the hash and size of their
+    /// content. Paths are relative to the `_next` directory. This changes
+    /// after every build or HMR update which emits new assets.
+    #[turbo_tasks::function]
+    pub async fn client_assets(self: Vc<Self>) -> Result<Vc<EmittedAssets>> {
+        Ok(self
+            .await?
+            .versioned_content_map
+            .emitted_assets_in_path(self.client_relative_path()))
+    }
 }
 
 #[turbo_tasks::function]
@@ -653,3 +939,71 @@ fn all_assets_from_entries_operation(
 ) -> Vc<OutputAssetsOperation> {
     Vc::cell(all_assets_from_entries_operation_inner(operation))
 }
+
+/// Whether the pathname equals the prefix or is nested inside of it, matching
+/// whole segments only.
+fn has_path_prefix(pathname: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    prefix.is_empty()
+        || pathname
+            .strip_prefix(prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_path_prefix() {
+        assert!(has_path_prefix("/dashboard", "/dashboard"));
+        assert!(has_path_prefix("/dashboard/settings", "/dashboard"));
+        assert!(has_path_prefix("/dashboard/settings", "/dashboard/"));
+        assert!(has_path_prefix("/api/users", "/"));
+        assert!(has_path_prefix("/", ""));
+        assert!(!has_path_prefix("/dashboards", "/dashboard"));
+        assert!(!has_path_prefix("/blog", "/dashboard"));
+        assert!(!has_path_prefix("/", "/dashboard"));
+    }
+
+    #[test]
+    fn test_is_allowed_origin() {
+        let mut dev_server = DevServerOptions {
+            hostname: "dev.local".to_string(),
+            port: 3000,
+            https: None,
+            allowed_origins: None,
+        };
+        assert!(dev_server.is_allowed_origin("https://example.com"));
+        assert!(dev_server.is_allowed_origin("null"));
+
+        dev_server.allowed_origins = Some(vec![
+            "tunnel.example.org".to_string(),
+            "*.example.com".to_string(),
+            "192.168.0.10".to_string(),
+        ]);
+        assert!(dev_server.is_allowed_origin("http://localhost:3000"));
+        assert!(dev_server.is_allowed_origin("http://127.0.0.1:3000"));
+        assert!(dev_server.is_allowed_origin("http://[::1]:3000"));
+        assert!(dev_server.is_allowed_origin("https://dev.local:3000"));
+        assert!(dev_server.is_allowed_origin("http://192.168.0.10:3000"));
+        // Other hosts on the network have to be listed.
+        assert!(!dev_server.is_allowed_origin("http://192.168.0.11:3000"));
+        assert!(!dev_server.is_allowed_origin("http://10.0.0.5:3000"));
+        assert!(!dev_server.is_allowed_origin("http://169.254.0.1:3000"));
+        assert!(!dev_server.is_allowed_origin("http://[fd12::1]:3000"));
+        assert!(!dev_server.is_allowed_origin("http://[fe80::1]:3000"));
+        assert!(dev_server.is_allowed_origin("https://tunnel.example.org"));
+        assert!(dev_server.is_allowed_origin("https://app.example.com"));
+        assert!(!dev_server.is_allowed_origin("https://example.com"));
+        assert!(!dev_server.is_allowed_origin("https://notexample.com"));
+        assert!(!dev_server.is_allowed_origin("http://8.8.8.8:3000"));
+        assert!(!dev_server.is_allowed_origin("null"));
+
+        // An empty list only allows loopback addresses and the dev server itself.
+        dev_server.allowed_origins = Some(vec![]);
+        assert!(dev_server.is_allowed_origin("http://localhost:3000"));
+        assert!(dev_server.is_allowed_origin("https://dev.local:3000"));
+        assert!(!dev_server.is_allowed_origin("https://app.example.com"));
+    }
+}
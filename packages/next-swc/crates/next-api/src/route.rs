@@ -1,5 +1,6 @@
 use indexmap::IndexMap;
 use turbo_tasks::{Completion, Vc};
+use turbopack_binding::turbopack::core::module::Modules;
 
 #[turbo_tasks::value(shared)]
 #[derive(Copy, Clone, Debug)]
@@ -21,11 +22,28 @@ pub enum Route {
     Conflict,
 }
 
+impl Route {
+    /// The endpoint which is compiled when the route is requested, `None` for
+    /// conflicting routes.
+    pub fn primary_endpoint(&self) -> Option<Vc<Box<dyn Endpoint>>> {
+        match *self {
+            Route::Page { html_endpoint, .. } | Route::AppPage { html_endpoint, .. } => {
+                Some(html_endpoint)
+            }
+            Route::PageApi { endpoint } | Route::AppRoute { endpoint } => Some(endpoint),
+            Route::Conflict => None,
+        }
+    }
+}
+
 #[turbo_tasks::value_trait]
 pub trait Endpoint {
     fn write_to_disk(self: Vc<Self>) -> Vc<WrittenEndpoint>;
     fn server_changed(self: Vc<Self>) -> Vc<Completion>;
     fn client_changed(self: Vc<Self>) -> Vc<Completion>;
+    /// The modules the endpoint is bundled from, e.g. the page module in the
+    /// SSR layer and the client entry of the page.
+    fn root_modules(self: Vc<Self>) -> Vc<Modules>;
 }
 
 #[turbo_tasks::value(shared)]
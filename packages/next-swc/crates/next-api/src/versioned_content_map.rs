@@ -3,9 +3,12 @@ use std::collections::HashMap;
 use anyhow::{bail, Result};
 use turbo_tasks::{State, TryFlatJoinIterExt, TryJoinIterExt, ValueDefault, ValueToString, Vc};
 use turbopack_binding::{
-    turbo::tasks_fs::FileSystemPath,
+    turbo::{
+        tasks_fs::{FileContent, FileSystemPath},
+        tasks_hash::{encode_hex, hash_xxh3_hash64},
+    },
     turbopack::core::{
-        asset::Asset,
+        asset::{Asset, AssetContent},
         output::{OutputAsset, OutputAssets},
         version::VersionedContent,
     },
@@ -17,6 +20,24 @@ use turbopack_binding::{
 #[turbo_tasks::value(transparent)]
 pub struct OutputAssetsOperation(Vc<OutputAssets>);
 
+/// An emitted file, as listed for tooling like service worker precache
+/// manifests.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct EmittedAsset {
+    /// The path relative to the root the assets were listed for.
+    pub path: String,
+    /// The hex encoded xxh3 hash of the content.
+    pub hash: String,
+    pub size: u64,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionEmittedAsset(Option<EmittedAsset>);
+
+#[turbo_tasks::value(transparent)]
+pub struct EmittedAssets(Vec<EmittedAsset>);
+
 type VersionedContentMapInner =
     HashMap<Vc<FileSystemPath>, (Vc<Box<dyn VersionedContent>>, Vc<OutputAssets>)>;
 
@@ -105,4 +126,53 @@ impl VersionedContentMap {
             .await?;
         Ok(Vc::cell(keys))
     }
+
+    /// The assets emitted inside of `root`, sorted by path. This is
+    /// recomputed whenever new assets are emitted.
+    #[turbo_tasks::function]
+    pub async fn emitted_assets_in_path(
+        &self,
+        root: Vc<FileSystemPath>,
+    ) -> Result<Vc<EmittedAssets>> {
+        let entries = {
+            let map = self.map.get();
+            map.iter()
+                .map(|(path, (content, _))| (*path, *content))
+                .collect::<Vec<_>>()
+        };
+        let root = &root.await?;
+        let mut assets = entries
+            .into_iter()
+            .map(|(path, content)| async move {
+                let Some(path) = root.get_path_to(&*path.await?) else {
+                    return Ok(None);
+                };
+                Ok(emitted_asset(path.to_string(), content)
+                    .await?
+                    .clone_value())
+            })
+            .try_flat_join()
+            .await?;
+        assets.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Vc::cell(assets))
+    }
+}
+
+#[turbo_tasks::function]
+async fn emitted_asset(
+    path: String,
+    content: Vc<Box<dyn VersionedContent>>,
+) -> Result<Vc<OptionEmittedAsset>> {
+    let AssetContent::File(file) = &*content.content().await? else {
+        return Ok(Vc::cell(None));
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(Vc::cell(None));
+    };
+    let bytes = file.content().to_bytes()?;
+    Ok(Vc::cell(Some(EmittedAsset {
+        path,
+        hash: encode_hex(hash_xxh3_hash64(&*bytes)),
+        size: bytes.len() as u64,
+    })))
 }
@@ -0,0 +1,240 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use next_core::next_client_reference::{CssClientReferenceModule, EcmascriptClientReferenceModule};
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::module::{Module, Modules},
+};
+
+/// The maximum number of import chains returned by [why_included]. Modules
+/// imported all over a route, like React, can be reached through a huge
+/// number of chains.
+const MAX_CHAINS: usize = 50;
+
+/// The modules importing each module.
+type Importers<T = Vc<Box<dyn Module>>> = HashMap<T, IndexSet<T>>;
+
+/// Why a module is bundled for a route.
+#[turbo_tasks::value(shared)]
+#[derive(Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WhyIncluded {
+    /// The project-relative path of the module which was queried.
+    pub path: String,
+    /// The import chains from an entry of the route to the module. Every
+    /// chain starts with the entry and ends with the module, as module
+    /// identifiers which include the layer, e.g. `[app-client]`.
+    pub chains: Vec<Vec<String>>,
+    /// Whether chains were left out because of [MAX_CHAINS].
+    pub truncated: bool,
+}
+
+/// Finds the import chains from the root modules of a route to the modules of
+/// the file at the given project-relative path, in any layer. The chains of a
+/// client component continue from its server reference into the client and
+/// SSR layers.
+#[turbo_tasks::function]
+pub async fn why_included(
+    project_path: Vc<FileSystemPath>,
+    root_modules: Vc<Modules>,
+    path: String,
+) -> Result<Vc<WhyIncluded>> {
+    let project_path_ref = project_path.await?;
+    let target_path = path.strip_prefix("./").unwrap_or(&path);
+
+    let roots = root_modules
+        .await?
+        .iter()
+        .map(|module| module.resolve())
+        .try_join()
+        .await?;
+
+    // Walk the module graph of the route, recording who imports each module.
+    let mut importers = Importers::new();
+    let mut visited: HashSet<_> = roots.iter().copied().collect();
+    let mut targets = vec![];
+    let mut queue = roots.clone();
+    while !queue.is_empty() {
+        let edges = queue
+            .iter()
+            .map(|&module| async move {
+                let module_path = module.ident().path().await?;
+                let is_target = project_path_ref
+                    .get_path_to(&module_path)
+                    .map_or(false, |module_path| module_path == target_path);
+                Ok((module, is_target, referenced_modules(module).await?))
+            })
+            .try_join()
+            .await?;
+        queue = vec![];
+        for (module, is_target, referenced) in edges {
+            if is_target {
+                targets.push(module);
+            }
+            for referenced in referenced {
+                importers.entry(referenced).or_default().insert(module);
+                if visited.insert(referenced) {
+                    queue.push(referenced);
+                }
+            }
+        }
+    }
+
+    let roots: HashSet<_> = roots.into_iter().collect();
+    let mut chains = vec![];
+    let mut truncated = false;
+    for target in targets {
+        truncated |= !collect_chains(target, &importers, &roots, &mut vec![target], &mut chains);
+    }
+
+    let chains = chains
+        .into_iter()
+        .map(|chain| async move {
+            chain
+                .into_iter()
+                .rev()
+                .map(|module| async move { Ok(module.ident().to_string().await?.clone_value()) })
+                .try_join()
+                .await
+        })
+        .try_join()
+        .await?;
+
+    Ok(WhyIncluded {
+        path,
+        chains,
+        truncated,
+    }
+    .cell())
+}
+
+/// The modules a module pulls into the bundle. Client references are marker
+/// modules without references of their own, they are followed into the
+/// modules they stand for.
+async fn referenced_modules(module: Vc<Box<dyn Module>>) -> Result<Vec<Vc<Box<dyn Module>>>> {
+    let modules = if let Some(client_reference) =
+        Vc::try_resolve_downcast_type::<EcmascriptClientReferenceModule>(module).await?
+    {
+        let client_reference = client_reference.await?;
+        vec![
+            Vc::upcast(client_reference.client_module),
+            Vc::upcast(client_reference.ssr_module),
+        ]
+    } else if let Some(client_reference) =
+        Vc::try_resolve_downcast_type::<CssClientReferenceModule>(module).await?
+    {
+        vec![Vc::upcast(client_reference.await?.client_module)]
+    } else {
+        module
+            .references()
+            .await?
+            .iter()
+            .map(|reference| async move {
+                Ok(reference
+                    .resolve_reference()
+                    .primary_modules()
+                    .await?
+                    .clone_value())
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect()
+    };
+    modules
+        .into_iter()
+        .map(|module| module.resolve())
+        .try_join()
+        .await
+}
+
+/// Walks from the end of a chain towards the roots, collecting every chain
+/// which reaches a root without a cycle. The chains are collected in reverse.
+/// Returns false when [MAX_CHAINS] was reached.
+fn collect_chains<T: Copy + Eq + Hash>(
+    module: T,
+    importers: &Importers<T>,
+    roots: &HashSet<T>,
+    chain: &mut Vec<T>,
+    chains: &mut Vec<Vec<T>>,
+) -> bool {
+    if roots.contains(&module) {
+        if chains.len() == MAX_CHAINS {
+            return false;
+        }
+        chains.push(chain.clone());
+    }
+    for &importer in importers.get(&module).into_iter().flatten() {
+        if chain.contains(&importer) {
+            continue;
+        }
+        chain.push(importer);
+        let complete = collect_chains(importer, importers, roots, chain, chains);
+        chain.pop();
+        if !complete {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The chains from the roots to the target, in the order of the imports.
+    fn chains(edges: &[(u32, u32)], roots: &[u32], target: u32) -> (Vec<Vec<u32>>, bool) {
+        let mut importers = Importers::<u32>::new();
+        for &(importer, module) in edges {
+            importers.entry(module).or_default().insert(importer);
+        }
+        let roots = roots.iter().copied().collect();
+        let mut chains = vec![];
+        let complete = collect_chains(target, &importers, &roots, &mut vec![target], &mut chains);
+        let chains = chains
+            .into_iter()
+            .map(|chain| chain.into_iter().rev().collect())
+            .collect();
+        (chains, complete)
+    }
+
+    #[test]
+    fn test_collect_chains() {
+        assert_eq!(
+            chains(&[(0, 1), (0, 2), (1, 3), (2, 3)], &[0], 3),
+            (vec![vec![0, 1, 3], vec![0, 2, 3]], true)
+        );
+        // Both a root and imported by another root.
+        assert_eq!(
+            chains(&[(0, 1), (1, 2)], &[0, 1], 2),
+            (vec![vec![1, 2], vec![0, 1, 2]], true)
+        );
+        assert_eq!(chains(&[(0, 1)], &[0], 0), (vec![vec![0]], true));
+        assert_eq!(chains(&[(0, 1)], &[0], 2), (vec![], true));
+    }
+
+    #[test]
+    fn test_collect_chains_cycle() {
+        assert_eq!(
+            chains(&[(0, 1), (1, 2), (2, 1), (2, 3)], &[0], 3),
+            (vec![vec![0, 1, 2, 3]], true)
+        );
+    }
+
+    #[test]
+    fn test_collect_chains_truncated() {
+        let edges = (1..=MAX_CHAINS as u32 + 10)
+            .flat_map(|module| [(0, module), (module, 1000)])
+            .collect::<Vec<_>>();
+        let (chains, complete) = chains(&edges, &[0], 1000);
+        assert!(!complete);
+        assert_eq!(chains.len(), MAX_CHAINS);
+    }
+}
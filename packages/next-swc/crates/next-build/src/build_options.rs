@@ -2,9 +2,10 @@ use std::path::PathBuf;
 
 use next_core::{next_config::Rewrites, turbopack::core::issue::IssueSeverity};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct BuildOptions {
-    /// The root directory of the workspace.
+    /// The root directory of the workspace. Inferred from the lockfiles when
+    /// not provided, see [next_core::find_workspace_root].
     pub root: Option<PathBuf>,
 
     /// The project's directory.
@@ -13,6 +14,10 @@ pub struct BuildOptions {
     /// The maximum memory to use for the build.
     pub memory_limit: Option<usize>,
 
+    /// The number of threads minifying JavaScript. Defaults to the number of
+    /// CPUs.
+    pub minify_threads: Option<usize>,
+
     /// The log level to use for the build.
     pub log_level: Option<IssueSeverity>,
 
@@ -25,6 +30,14 @@ pub struct BuildOptions {
     /// Whether to compute full stats.
     pub full_stats: bool,
 
+    /// Whether to write `unused-client-components.json`, the `"use client"`
+    /// modules which aren't used by any app route.
+    pub report_unused_client_components: bool,
+
+    /// Whether to build twice and fail when the emitted files differ between
+    /// the builds.
+    pub verify_determinism: bool,
+
     /// The Next.js build context.
     pub build_context: Option<BuildContext>,
 }
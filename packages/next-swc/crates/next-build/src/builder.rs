@@ -0,0 +1,220 @@
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use dunce::canonicalize;
+use next_core::set_minify_threads;
+use serde_json::Value;
+use turbo_tasks::{ReadRef, StatsType, TransientInstance, TurboTasks, TurboTasksBackendApi};
+use turbopack_binding::{
+    turbo::{tasks::run_once, tasks_memory::MemoryBackend},
+    turbopack::core::issue::{IssueDescriptionExt, PlainIssue},
+};
+
+use crate::{
+    build_options::{BuildContext, BuildOptions},
+    determinism::{diff_snapshots, move_output_directory, snapshot_directory},
+    next_build::next_build,
+    register,
+};
+
+/// Runs a production build of a Next.js project from Rust, without the Node.js
+/// layer.
+///
+/// ```ignore
+/// let output = NextBuild::new("path/to/app", BuildOptions::default())
+///     .build()
+///     .await?;
+/// ```
+pub struct NextBuild {
+    options: BuildOptions,
+}
+
+/// The result of a successful [NextBuild].
+#[derive(Debug)]
+pub struct BuildOutput {
+    /// The manifests written to the `.next` directory.
+    pub manifests: BuildManifests,
+    /// The issues reported during the build, including warnings.
+    pub issues: Vec<ReadRef<PlainIssue>>,
+}
+
+#[derive(Debug)]
+pub struct BuildManifests {
+    /// `build-manifest.json`
+    pub build_manifest: Value,
+    /// `app-build-manifest.json`
+    pub app_build_manifest: Value,
+    /// `server/pages-manifest.json`
+    pub pages_manifest: Value,
+    /// `server/app-paths-manifest.json`
+    pub app_paths_manifest: Value,
+    /// `server/middleware-manifest.json`
+    pub middleware_manifest: Value,
+    /// `routes-manifest.json`
+    pub routes_manifest: Value,
+}
+
+impl NextBuild {
+    pub fn new(project_root: impl Into<PathBuf>, options: BuildOptions) -> Self {
+        NextBuild {
+            options: BuildOptions {
+                dir: Some(project_root.into()),
+                ..options
+            },
+        }
+    }
+
+    /// The root directory of the workspace, e.g. the monorepo root. Defaults
+    /// to the closest directory containing a lockfile, or the project root.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.options.root = Some(root.into());
+        self
+    }
+
+    /// The maximum memory to use for the build, in MB.
+    pub fn memory_limit(mut self, memory_limit: usize) -> Self {
+        self.options.memory_limit = Some(memory_limit);
+        self
+    }
+
+    /// The number of threads minifying JavaScript. Defaults to the number of
+    /// CPUs.
+    pub fn minify_threads(mut self, minify_threads: usize) -> Self {
+        self.options.minify_threads = Some(minify_threads);
+        self
+    }
+
+    /// Builds the project twice and fails when the emitted files differ.
+    pub fn verify_determinism(mut self) -> Self {
+        self.options.verify_determinism = true;
+        self
+    }
+
+    /// Writes `unused-client-components.json`, the `"use client"` modules
+    /// which aren't used by any app route.
+    pub fn report_unused_client_components(mut self) -> Self {
+        self.options.report_unused_client_components = true;
+        self
+    }
+
+    pub fn build_context(mut self, build_context: BuildContext) -> Self {
+        self.options.build_context = Some(build_context);
+        self
+    }
+
+    pub async fn build(self) -> Result<BuildOutput> {
+        register();
+
+        let options = self.options;
+        let project_dir = match &options.dir {
+            Some(dir) => canonicalize(dir),
+            None => current_dir(),
+        }
+        .context("project directory can't be found")?;
+
+        // 0 uses the number of CPUs, so a build without the option doesn't keep
+        // the number of an earlier build in the same process.
+        set_minify_threads(options.minify_threads.unwrap_or(0))?;
+
+        let dist_dir = project_dir.join(".next");
+        if options.verify_determinism {
+            // Both builds start from an output directory which only has the cache,
+            // so files of earlier builds don't show up as differences.
+            move_output_directory(&dist_dir, None)?;
+        }
+        let issues = run_build(options.clone()).await?;
+        if options.verify_determinism {
+            verify_determinism(options, &dist_dir).await?;
+        }
+
+        Ok(BuildOutput {
+            manifests: BuildManifests::read(&dist_dir)?,
+            issues,
+        })
+    }
+}
+
+async fn run_build(options: BuildOptions) -> Result<Vec<ReadRef<PlainIssue>>> {
+    let tt = TurboTasks::new(MemoryBackend::new(
+        options.memory_limit.map_or(usize::MAX, |l| l * 1024 * 1024),
+    ));
+    tt.set_stats_type(match options.full_stats {
+        true => StatsType::Full,
+        false => StatsType::Essential,
+    });
+
+    run_once(tt, async move {
+        let completion = next_build(TransientInstance::new(options));
+        completion.strongly_consistent().await?;
+        let issues = completion
+            .peek_issues_with_path()
+            .await?
+            .strongly_consistent()
+            .await?;
+        issues.get_plain_issues().await
+    })
+    .await
+}
+
+/// Builds the project a second time from scratch, with a fresh task graph,
+/// and fails when any emitted file differs from the first build. The output
+/// of the first build is moved aside meanwhile and removed afterwards, also
+/// when the second build fails. The cache stays in place for both builds.
+async fn verify_determinism(options: BuildOptions, dist_dir: &Path) -> Result<()> {
+    let first_dist_dir = dist_dir.with_file_name(".next-determinism");
+    if first_dist_dir.exists() {
+        std::fs::remove_dir_all(&first_dist_dir)
+            .with_context(|| format!("unable to remove {}", first_dist_dir.display()))?;
+    }
+
+    let nondeterministic = async {
+        move_output_directory(dist_dir, Some(&first_dist_dir))?;
+        let first = snapshot_directory(&first_dist_dir)?;
+        run_build(options).await?;
+        let second = snapshot_directory(dist_dir)?;
+        Ok::<_, anyhow::Error>(diff_snapshots(&first, &second))
+    }
+    .await;
+    let removed = if first_dist_dir.exists() {
+        std::fs::remove_dir_all(&first_dist_dir)
+    } else {
+        Ok(())
+    };
+    let nondeterministic = nondeterministic?;
+    removed.with_context(|| format!("unable to remove {}", first_dist_dir.display()))?;
+
+    if !nondeterministic.is_empty() {
+        bail!(
+            "the build is not deterministic, these files differ between two builds:\n{}",
+            nondeterministic
+                .iter()
+                .map(|path| format!("  {}", path.display()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+    Ok(())
+}
+
+impl BuildManifests {
+    fn read(dist_dir: &Path) -> Result<Self> {
+        let read = |path: &str| -> Result<Value> {
+            let path = dist_dir.join(path);
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("unable to parse {}", path.display()))
+        };
+        Ok(BuildManifests {
+            build_manifest: read("build-manifest.json")?,
+            app_build_manifest: read("app-build-manifest.json")?,
+            pages_manifest: read("server/pages-manifest.json")?,
+            app_paths_manifest: read("server/app-paths-manifest.json")?,
+            middleware_manifest: read("server/middleware-manifest.json")?,
+            routes_manifest: read("routes-manifest.json")?,
+        })
+    }
+}
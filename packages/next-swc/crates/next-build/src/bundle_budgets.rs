@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use next_core::{
+    next_client_reference::ClientReferenceType,
+    next_config::{BundleBudget, BundleBudgetLevel, BundleBudgets},
+    MinifyOptions,
+};
+use turbo_tasks::{Completion, TryJoinIterExt, ValueToString, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{glob::Glob, FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::AssetContent,
+        issue::{Issue, IssueExt, IssueSeverity},
+        output::OutputAsset,
+    },
+};
+
+use crate::next_build::output_content;
+
+/// The name shown for the chunks which every app route loads, like the
+/// runtime and React.
+const SHARED_CONTRIBUTOR: &str = "(shared by all app routes)";
+
+/// The client chunks an app route loads on the first visit, grouped by the
+/// client component they are loaded for.
+pub(crate) struct RouteClientChunks {
+    pub pathname: String,
+    pub shared_chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    pub client_references: Vec<(String, Vec<Vc<Box<dyn OutputAsset>>>)>,
+}
+
+/// The name of a client reference as reported in budget issues, `None` for
+/// client references which don't load JavaScript.
+pub(crate) async fn client_reference_name(ty: ClientReferenceType) -> Result<Option<String>> {
+    Ok(match ty {
+        ClientReferenceType::EcmascriptClientReference(reference) => Some(
+            reference
+                .await?
+                .server_ident
+                .path()
+                .to_string()
+                .await?
+                .clone_value(),
+        ),
+        ClientReferenceType::CssClientReference(_) => None,
+    })
+}
+
+/// The issues of the exceeded budgets.
+#[turbo_tasks::value(transparent)]
+pub(crate) struct BundleBudgetIssues(Vec<Vc<BundleBudgetIssue>>);
+
+/// Checks the First Load JS of the app routes against the budgets of
+/// `experimental.bundleBudgets`, measured as the size of the JavaScript
+/// written to disk. Returns an issue for every exceeded budget.
+pub(crate) async fn check_bundle_budgets(
+    project_path: Vc<FileSystemPath>,
+    budgets: Vc<BundleBudgets>,
+    routes: &[RouteClientChunks],
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vc<BundleBudgetIssues>> {
+    let budgets = budgets.await?;
+    let mut issues = vec![];
+    if budgets.is_empty() {
+        return Ok(Vc::cell(issues));
+    }
+
+    for route in routes {
+        let mut matching_budgets = vec![];
+        for budget in budgets.iter() {
+            if Glob::new(budget.path.clone())
+                .await?
+                .execute(&route.pathname)
+            {
+                matching_budgets.push(budget);
+            }
+        }
+        if matching_budgets.is_empty() {
+            continue;
+        }
+
+        let shared =
+            js_chunk_sizes(&route.shared_chunks, minify_options, lightningcss_targets).await?;
+        let client_references = route
+            .client_references
+            .iter()
+            .map(|(name, chunks)| async move {
+                Ok((
+                    name.as_str(),
+                    js_chunk_sizes(chunks, minify_options, lightningcss_targets).await?,
+                ))
+            })
+            .try_join()
+            .await?;
+        let contributors = [(SHARED_CONTRIBUTOR, shared)]
+            .into_iter()
+            .chain(client_references);
+        let (first_load_js, contributors) = attribute_first_load_js(contributors);
+
+        for budget in matching_budgets {
+            if first_load_js > budget.max_first_load_js {
+                issues.push(
+                    BundleBudgetIssue {
+                        project_path,
+                        pathname: route.pathname.clone(),
+                        budget: budget.clone(),
+                        first_load_js,
+                        contributors: contributors.clone(),
+                    }
+                    .cell(),
+                );
+            }
+        }
+    }
+
+    Ok(Vc::cell(issues))
+}
+
+/// Emits the issues of the exceeded budgets in their own task, so they can be
+/// handled without the issues of the chunks which were measured.
+#[turbo_tasks::function]
+pub(crate) async fn emit_bundle_budget_issues(
+    issues: Vc<BundleBudgetIssues>,
+) -> Result<Vc<Completion>> {
+    for &issue in issues.await?.iter() {
+        issue.emit();
+    }
+    Ok(Completion::new())
+}
+
+/// The paths and sizes of the JavaScript chunks among the given chunks.
+async fn js_chunk_sizes(
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vec<(String, u64)>> {
+    let sizes = chunks
+        .iter()
+        .map(|&chunk| async move {
+            let path = chunk.ident().path().await?;
+            if path.extension_ref() != Some("js") {
+                return Ok(None);
+            }
+            let content = output_content(chunk, minify_options, lightningcss_targets)
+                .await?
+                .await?;
+            let AssetContent::File(file) = &*content else {
+                return Ok(None);
+            };
+            let FileContent::Content(file) = &*file.await? else {
+                return Ok(None);
+            };
+            Ok(Some((path.path.clone(), file.content().len() as u64)))
+        })
+        .try_join()
+        .await?;
+    Ok(sizes.into_iter().flatten().collect())
+}
+
+/// Sums up the sizes of the chunks loaded by a route, counting every chunk
+/// once for the first contributor loading it. Returns the total and the
+/// contributors with a non-zero size, largest first.
+fn attribute_first_load_js<'a>(
+    contributors: impl IntoIterator<Item = (&'a str, Vec<(String, u64)>)>,
+) -> (u64, Vec<(String, u64)>) {
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    let mut sizes = vec![];
+    for (name, chunks) in contributors {
+        let size: u64 = chunks
+            .into_iter()
+            .filter(|(path, _)| seen.insert(path.clone()))
+            .map(|(_, size)| size)
+            .sum();
+        if size > 0 {
+            total += size;
+            sizes.push((name.to_string(), size));
+        }
+    }
+    sizes.sort_by(|(_, a), (_, b)| b.cmp(a));
+    (total, sizes)
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} kB", bytes as f64 / 1000.0)
+}
+
+fn budget_severity(level: BundleBudgetLevel) -> IssueSeverity {
+    match level {
+        BundleBudgetLevel::Warning => IssueSeverity::Warning,
+        BundleBudgetLevel::Error => IssueSeverity::Error,
+    }
+}
+
+fn budget_description(
+    budget: &BundleBudget,
+    first_load_js: u64,
+    contributors: &[(String, u64)],
+) -> String {
+    let mut description = format!(
+        "The route loads {} of JavaScript on the first visit, the budget for `{}` in \
+         `experimental.bundleBudgets` is {}.\n\nLargest contributors:",
+        format_size(first_load_js),
+        budget.path,
+        format_size(budget.max_first_load_js),
+    );
+    for (name, size) in contributors {
+        description.push_str(&format!("\n  {} {}", format_size(*size), name));
+    }
+    description
+}
+
+/// An app route loads more JavaScript on the first visit than its budget
+/// allows.
+#[turbo_tasks::value(shared)]
+pub struct BundleBudgetIssue {
+    pub project_path: Vc<FileSystemPath>,
+    pub pathname: String,
+    pub budget: BundleBudget,
+    pub first_load_js: u64,
+    /// The client components loaded by the route and their size, largest
+    /// first.
+    pub contributors: Vec<(String, u64)>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for BundleBudgetIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        budget_severity(self.budget.level).into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("build".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The First Load JS of {} exceeds its budget",
+            self.pathname
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(budget_description(
+            &self.budget,
+            self.first_load_js,
+            &self.contributors,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(chunks: &[(&str, u64)]) -> Vec<(String, u64)> {
+        chunks
+            .iter()
+            .map(|(path, size)| (path.to_string(), *size))
+            .collect()
+    }
+
+    #[test]
+    fn test_attribute_first_load_js() {
+        let (total, contributors) = attribute_first_load_js([
+            (SHARED_CONTRIBUTOR, chunks(&[("main.js", 80_000)])),
+            (
+                "app/chart.tsx",
+                chunks(&[("chart.js", 120_000), ("main.js", 80_000)]),
+            ),
+            ("app/button.tsx", chunks(&[("button.js", 2_000)])),
+            ("app/link.tsx", chunks(&[("button.js", 2_000)])),
+        ]);
+        assert_eq!(total, 202_000);
+        assert_eq!(
+            contributors,
+            vec![
+                ("app/chart.tsx".to_string(), 120_000),
+                (SHARED_CONTRIBUTOR.to_string(), 80_000),
+                ("app/button.tsx".to_string(), 2_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_budget_issue() {
+        let budget = BundleBudget {
+            path: "/dashboard/**".to_string(),
+            max_first_load_js: 150_000,
+            level: BundleBudgetLevel::Error,
+        };
+        assert_eq!(
+            budget_description(
+                &budget,
+                202_000,
+                &[
+                    ("app/chart.tsx".to_string(), 120_000),
+                    (SHARED_CONTRIBUTOR.to_string(), 82_000),
+                ]
+            ),
+            "The route loads 202.0 kB of JavaScript on the first visit, the budget for \
+             `/dashboard/**` in `experimental.bundleBudgets` is 150.0 kB.\n\nLargest \
+             contributors:\n  120.0 kB app/chart.tsx\n  82.0 kB (shared by all app routes)"
+        );
+        assert_eq!(budget_severity(budget.level), IssueSeverity::Error);
+        assert_eq!(
+            budget_severity(BundleBudgetLevel::default()),
+            IssueSeverity::Warning
+        );
+    }
+}
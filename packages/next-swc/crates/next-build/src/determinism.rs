@@ -0,0 +1,142 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+/// The directory of the output directory which is kept between builds, and
+/// isn't part of the output.
+const CACHE_DIR: &str = "cache";
+
+/// The SHA-256 of every file in a directory, keyed by their path relative to
+/// the directory.
+pub(crate) type Snapshot = BTreeMap<PathBuf, [u8; 32]>;
+
+/// Snapshots the files of an output directory, without its cache.
+pub(crate) fn snapshot_directory(dir: &Path) -> Result<Snapshot> {
+    let mut snapshot = Snapshot::new();
+    let mut queue = vec![dir.to_path_buf()];
+    while let Some(current) = queue.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("unable to read {}", current.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if current == dir && path.file_name() == Some(OsStr::new(CACHE_DIR)) {
+                continue;
+            }
+            if path.is_dir() {
+                queue.push(path);
+                continue;
+            }
+            let content = std::fs::read(&path)
+                .with_context(|| format!("unable to read {}", path.display()))?;
+            let relative_path = path.strip_prefix(dir)?.to_path_buf();
+            snapshot.insert(relative_path, Sha256::digest(content).into());
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Moves the output of a build to `to`, or removes it when `to` is `None`. The
+/// cache stays in the output directory, so the next build can still use it.
+pub(crate) fn move_output_directory(dir: &Path, to: Option<&Path>) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    if let Some(to) = to {
+        std::fs::create_dir_all(to)
+            .with_context(|| format!("unable to create {}", to.display()))?;
+    }
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("unable to read {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name() == CACHE_DIR {
+            continue;
+        }
+        let path = entry.path();
+        let result = match to {
+            Some(to) => std::fs::rename(&path, to.join(entry.file_name())),
+            None if entry.file_type()?.is_dir() => std::fs::remove_dir_all(&path),
+            None => std::fs::remove_file(&path),
+        };
+        result.with_context(|| format!("unable to move or remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The files which only exist in one of the snapshots, or whose content
+/// differs.
+pub(crate) fn diff_snapshots(a: &Snapshot, b: &Snapshot) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = a
+        .iter()
+        .filter(|(path, hash)| b.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .chain(b.keys().filter(|path| !a.contains_key(*path)).cloned())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_move_output_directory() {
+        let root = std::env::temp_dir().join(format!("next-determinism-{}", std::process::id()));
+        let dist_dir = root.join(".next");
+        fs::create_dir_all(dist_dir.join("cache/images")).unwrap();
+        fs::create_dir_all(dist_dir.join("server/app")).unwrap();
+        fs::write(dist_dir.join("cache/images/a.webp"), "image").unwrap();
+        fs::write(dist_dir.join("server/app/page.js"), "page").unwrap();
+        fs::write(dist_dir.join("build-manifest.json"), "{}").unwrap();
+
+        let snapshot = snapshot_directory(&dist_dir).unwrap();
+        assert_eq!(
+            snapshot.keys().collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("build-manifest.json"),
+                &PathBuf::from("server/app/page.js")
+            ]
+        );
+
+        let moved_dir = root.join(".next-determinism");
+        move_output_directory(&dist_dir, Some(&moved_dir)).unwrap();
+        assert_eq!(snapshot_directory(&moved_dir).unwrap(), snapshot);
+        assert!(snapshot_directory(&dist_dir).unwrap().is_empty());
+        assert!(dist_dir.join("cache/images/a.webp").exists());
+
+        move_output_directory(&moved_dir, None).unwrap();
+        assert_eq!(fs::read_dir(&moved_dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let a = Snapshot::from([
+            ("a.js".into(), [1; 32]),
+            ("b.js".into(), [2; 32]),
+            ("c.js".into(), [3; 32]),
+        ]);
+        let b = Snapshot::from([
+            ("a.js".into(), [1; 32]),
+            ("b.js".into(), [4; 32]),
+            ("d.js".into(), [5; 32]),
+        ]);
+        assert_eq!(
+            diff_snapshots(&a, &b),
+            vec![
+                PathBuf::from("b.js"),
+                PathBuf::from("c.js"),
+                PathBuf::from("d.js")
+            ]
+        );
+    }
+}
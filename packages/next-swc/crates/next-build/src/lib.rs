@@ -2,44 +2,31 @@
 #![feature(arbitrary_self_types)]
 #![feature(async_fn_in_trait)]
 
-use turbopack_binding::turbo::{
-    tasks::{run_once, TransientInstance, TurboTasks},
-    tasks_memory::MemoryBackend,
-};
+use std::env::current_dir;
 
 pub mod build_options;
+pub mod builder;
+pub(crate) mod bundle_budgets;
+pub(crate) mod determinism;
 pub(crate) mod next_app;
 pub(crate) mod next_build;
 pub(crate) mod next_pages;
 
 use anyhow::Result;
-use turbo_tasks::{StatsType, TurboTasksBackendApi};
 
-pub use self::build_options::BuildOptions;
+pub use self::{
+    build_options::BuildOptions,
+    builder::{BuildManifests, BuildOutput, NextBuild},
+};
 
 pub async fn build(options: BuildOptions) -> Result<()> {
     #[cfg(feature = "tokio_console")]
     console_subscriber::init();
-    register();
 
     setup_tracing();
 
-    let tt = TurboTasks::new(MemoryBackend::new(
-        options.memory_limit.map_or(usize::MAX, |l| l * 1024 * 1024),
-    ));
-
-    let stats_type = match options.full_stats {
-        true => StatsType::Full,
-        false => StatsType::Essential,
-    };
-    tt.set_stats_type(stats_type);
-
-    run_once(tt, async move {
-        next_build::next_build(TransientInstance::new(options)).await?;
-
-        Ok(())
-    })
-    .await?;
+    let project_root = options.dir.clone().map_or_else(current_dir, Ok)?;
+    NextBuild::new(project_root, options).build().await?;
 
     Ok(())
 }
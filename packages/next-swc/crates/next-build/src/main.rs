@@ -22,7 +22,8 @@ pub struct BuildCliArgs {
 
     /// The root directory of the project. Nothing outside of this directory can
     /// be accessed. e. g. the monorepo root.
-    /// If no directory is provided, `dir` will be used.
+    /// If no directory is provided, the closest directory containing a
+    /// lockfile is used, or `dir` if there is none.
     #[clap(long, value_parser)]
     pub root: Option<PathBuf>,
 
@@ -46,10 +47,23 @@ pub struct BuildCliArgs {
     #[clap(long)]
     pub full_stats: bool,
 
+    /// Report the "use client" modules which aren't used by any app route.
+    #[clap(long)]
+    pub report_unused_client_components: bool,
+
+    /// Build twice and fail when any emitted file differs between the builds.
+    #[clap(long)]
+    pub verify_determinism: bool,
+
     /// Enable experimental garbage collection with the provided memory limit in
     /// MB.
     #[clap(long)]
     pub memory_limit: Option<usize>,
+
+    /// The number of threads minifying JavaScript. Defaults to the number of
+    /// CPUs.
+    #[clap(long)]
+    pub minify_threads: Option<usize>,
 }
 
 fn main() {
@@ -95,10 +109,13 @@ async fn main_inner() -> Result<()> {
         dir: args.dir,
         root: args.root,
         memory_limit: args.memory_limit,
+        minify_threads: args.minify_threads,
         log_level: args.log_level.map(|l| l.0),
         show_all: args.show_all,
         log_detail: args.log_detail,
         full_stats: args.full_stats,
+        report_unused_client_components: args.report_unused_client_components,
+        verify_determinism: args.verify_determinism,
         build_context: None,
     })
     .await
@@ -1,12 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use next_core::{
-    app_structure::{find_app_dir_if_enabled, get_entrypoints, get_global_metadata, Entrypoint},
+    app_structure::{
+        find_app_dir_if_enabled, get_entrypoints, get_global_metadata, get_sitemaps, Entrypoint,
+        MetadataItem,
+    },
     mode::NextMode,
     next_app::{
         get_app_client_shared_chunks, get_app_page_entry, get_app_route_entry,
-        get_app_route_favicon_entry, AppEntry, ClientReferencesChunks,
+        get_app_route_favicon_entry, get_app_route_sitemap_entry,
+        get_app_route_static_metadata_entry, AppEntry, AppPage, ClientReferencesChunks,
     },
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
@@ -15,13 +19,23 @@ use next_core::{
     next_client_reference::{ClientReferenceGraph, NextEcmascriptClientReferenceTransition},
     next_config::NextConfig,
     next_dynamic::NextDynamicTransition,
-    next_manifests::{AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest},
+    next_edge::{
+        context::get_edge_resolve_options_context, route_regex::get_named_middleware_regex,
+        unsupported_apis::check_edge_runtime_apis,
+    },
+    next_manifests::{
+        preload_hints::get_preload_hints, AppBuildManifest, AppPathsManifest, BuildManifest,
+        ClientReferenceManifest, EdgeFunctionDefinition, FunctionConfig, FunctionsConfigManifest,
+        MiddlewareMatcher, MiddlewaresManifestV2, Regions, SegmentBoundariesManifest,
+        StaticMetadataManifest,
+    },
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    util::NextRuntime,
 };
-use turbo_tasks::{TryJoinIterExt, Value, Vc};
+use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, Value, Vc};
 use turbopack_binding::{
     turbo::{
         tasks_env::{CustomProcessEnv, ProcessEnv},
@@ -30,7 +44,9 @@ use turbopack_binding::{
     turbopack::{
         build::BuildChunkingContext,
         core::{
-            chunk::EvaluatableAssets, compile_time_info::CompileTimeInfo, file_source::FileSource,
+            chunk::{ChunkableModule, ChunkingContext, EvaluatableAssets},
+            compile_time_info::CompileTimeInfo,
+            file_source::FileSource,
             output::OutputAsset,
         },
         ecmascript::chunk::EcmascriptChunkingContext,
@@ -39,6 +55,8 @@ use turbopack_binding::{
     },
 };
 
+use crate::bundle_budgets::{client_reference_name, RouteClientChunks};
+
 #[turbo_tasks::value]
 pub struct AppEntries {
     /// All app entries.
@@ -46,6 +64,8 @@ pub struct AppEntries {
     /// The RSC runtime entries that should be evaluated before any app entry
     /// module when server rendering.
     pub rsc_runtime_entries: Vc<EvaluatableAssets>,
+    /// The RSC runtime entries of the app entries using the edge runtime.
+    pub edge_rsc_runtime_entries: Vc<EvaluatableAssets>,
     /// The client runtime entries that should be evaluated before any app entry
     /// module when client rendering.
     pub client_runtime_entries: Vc<EvaluatableAssets>,
@@ -59,19 +79,25 @@ pub async fn get_app_entries(
     env: Vc<Box<dyn ProcessEnv>>,
     client_compile_time_info: Vc<CompileTimeInfo>,
     server_compile_time_info: Vc<CompileTimeInfo>,
+    edge_compile_time_info: Vc<CompileTimeInfo>,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<AppEntries>> {
-    let app_dir = find_app_dir_if_enabled(project_root);
+    let app_dir = find_app_dir_if_enabled(project_root, next_config);
 
     let Some(&app_dir) = app_dir.await?.as_ref() else {
         return Ok(AppEntries::cell(AppEntries {
             entries: vec![],
             rsc_runtime_entries: EvaluatableAssets::empty(),
+            edge_rsc_runtime_entries: EvaluatableAssets::empty(),
             client_runtime_entries: EvaluatableAssets::empty(),
         }));
     };
 
-    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    let entrypoints = get_entrypoints(
+        app_dir,
+        next_config.page_extensions(),
+        next_config.app_i18n(),
+    );
 
     let mode = NextMode::Build;
 
@@ -138,6 +164,20 @@ pub async fn get_app_entries(
         ssr_resolve_options_context,
     );
 
+    let edge_ssr_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        ssr_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_ssr_transition = ContextTransition::new(
+        edge_compile_time_info,
+        ssr_module_options_context,
+        edge_ssr_resolve_options_context,
+    );
+
     const ECMASCRIPT_CLIENT_TRANSITION_NAME: &str = "next-ecmascript-client-reference";
 
     transitions.insert(
@@ -154,6 +194,17 @@ pub async fn get_app_entries(
         Vc::upcast(NextDynamicTransition::new(client_transition)),
     );
 
+    // Client components of edge entries are server rendered with the edge
+    // React builds.
+    let mut edge_transitions = transitions.clone();
+    edge_transitions.insert(
+        ECMASCRIPT_CLIENT_TRANSITION_NAME.to_string(),
+        Vc::upcast(NextEcmascriptClientReferenceTransition::new(
+            client_transition,
+            edge_ssr_transition,
+        )),
+    );
+
     let rsc_ty = Value::new(ServerContextType::AppRSC {
         app_dir,
         client_transition: Some(Vc::upcast(client_transition)),
@@ -184,6 +235,21 @@ pub async fn get_app_entries(
         rsc_resolve_options_context,
     );
 
+    let edge_rsc_resolve_options_context = get_edge_resolve_options_context(
+        project_root,
+        rsc_ty,
+        mode,
+        next_config,
+        execution_context,
+    );
+
+    let edge_rsc_context = ModuleAssetContext::new(
+        Vc::cell(edge_transitions),
+        edge_compile_time_info,
+        rsc_module_options_context,
+        edge_rsc_resolve_options_context,
+    );
+
     let mut entries = entrypoints
         .await?
         .iter()
@@ -191,17 +257,17 @@ pub async fn get_app_entries(
             Ok(match entrypoint {
                 Entrypoint::AppPage { page, loader_tree } => get_app_page_entry(
                     rsc_context,
-                    // TODO add edge support
-                    rsc_context,
+                    edge_rsc_context,
                     *loader_tree,
                     app_dir,
                     page.clone(),
                     project_root,
+                    next_config,
+                    mode,
                 ),
                 Entrypoint::AppRoute { page, path } => get_app_route_entry(
                     rsc_context,
-                    // TODO add edge support
-                    rsc_context,
+                    edge_rsc_context,
                     Vc::upcast(FileSource::new(*path)),
                     page.clone(),
                     project_root,
@@ -214,16 +280,39 @@ pub async fn get_app_entries(
     let global_metadata = get_global_metadata(app_dir, next_config.page_extensions());
     let global_metadata = global_metadata.await?;
 
-    if let Some(favicon) = global_metadata.favicon {
+    if let Some(favicon) = global_metadata.favicon.or(global_metadata.icon) {
         entries.push(get_app_route_favicon_entry(
             rsc_context,
-            // TODO add edge support
-            rsc_context,
+            edge_rsc_context,
             favicon,
             project_root,
         ));
     }
 
+    // Sitemaps can be placed in any directory, not only in the root.
+    for sitemap in get_sitemaps(app_dir, next_config.page_extensions())
+        .await?
+        .iter()
+    {
+        entries.push(get_app_route_sitemap_entry(
+            rsc_context,
+            edge_rsc_context,
+            sitemap.sitemap,
+            sitemap.directory.clone(),
+            project_root,
+        ));
+    }
+
+    if let Some(robots @ MetadataItem::Static { .. }) = global_metadata.robots {
+        entries.push(get_app_route_static_metadata_entry(
+            rsc_context,
+            edge_rsc_context,
+            robots,
+            AppPage::new(),
+            project_root,
+        ));
+    }
+
     let client_context = ModuleAssetContext::new(
         Vc::cell(Default::default()),
         client_compile_time_info,
@@ -243,6 +332,7 @@ pub async fn get_app_entries(
     Ok(AppEntries::cell(AppEntries {
         entries,
         rsc_runtime_entries: runtime_entries.resolve_entries(Vc::upcast(rsc_context)),
+        edge_rsc_runtime_entries: runtime_entries.resolve_entries(Vc::upcast(edge_rsc_context)),
         client_runtime_entries: client_runtime_entries.resolve_entries(Vc::upcast(client_context)),
     }))
 }
@@ -254,24 +344,33 @@ pub async fn compute_app_entries_chunks(
     app_entries: &AppEntries,
     app_client_reference_graph: Vc<ClientReferenceGraph>,
     app_client_references_chunks: Vc<ClientReferencesChunks>,
+    app_edge_client_references_chunks: Vc<ClientReferencesChunks>,
     rsc_chunking_context: Vc<BuildChunkingContext>,
+    edge_rsc_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    edge_ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     node_root: Vc<FileSystemPath>,
     client_relative_path: Vc<FileSystemPath>,
     app_paths_manifest_dir_path: &FileSystemPath,
     app_build_manifest: &mut AppBuildManifest,
     build_manifest: &mut BuildManifest,
     app_paths_manifest: &mut AppPathsManifest,
+    functions_config_manifest: &mut FunctionsConfigManifest,
+    middlewares_manifest: &mut MiddlewaresManifestV2,
+    static_metadata_manifest: &mut StaticMetadataManifest,
+    segment_boundaries_manifest: &mut SegmentBoundariesManifest,
     all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
+    route_client_chunks: &mut Vec<RouteClientChunks>,
 ) -> Result<()> {
     let client_relative_path_ref = client_relative_path.await?;
 
     let app_client_shared_chunks =
         get_app_client_shared_chunks(app_entries.client_runtime_entries, client_chunking_context);
 
+    let app_client_shared_chunks = app_client_shared_chunks.await?;
     let mut app_shared_client_chunks_paths = vec![];
-    for chunk in app_client_shared_chunks.await?.iter().copied() {
+    for chunk in app_client_shared_chunks.iter().copied() {
         all_chunks.push(chunk);
 
         let chunk_path = chunk.ident().path().await?;
@@ -283,38 +382,122 @@ pub async fn compute_app_entries_chunks(
         }
     }
 
-    let app_client_references_chunks_ref = app_client_references_chunks.await?;
+    // The chunks of a client reference are shared by the entries which use it.
+    let mut emitted_client_references = HashSet::new();
 
     for app_entry in app_entries.entries.iter().copied() {
         let app_entry = app_entry.await?;
+        let config = app_entry.config.await?;
+        let runtime = config.runtime.unwrap_or_default();
 
         let app_entry_client_references = app_client_reference_graph
             .entry(Vc::upcast(app_entry.rsc_entry))
             .await?;
 
-        let rsc_chunk = rsc_chunking_context.entry_chunk(
-            node_root.join(format!(
-                "server/app/{original_name}.js",
-                original_name = app_entry.original_name
-            )),
-            app_entry.rsc_entry,
-            app_entries.rsc_runtime_entries,
-        );
-        all_chunks.push(rsc_chunk);
+        let (client_references_chunks, entry_ssr_chunking_context) = match runtime {
+            NextRuntime::NodeJs => (app_client_references_chunks, ssr_chunking_context),
+            NextRuntime::Edge => (app_edge_client_references_chunks, edge_ssr_chunking_context),
+        };
+        let client_references_chunks_ref = client_references_chunks.await?;
+
+        let rsc_path = match runtime {
+            NextRuntime::NodeJs => {
+                let rsc_chunk = rsc_chunking_context.entry_chunk(
+                    node_root.join(format!(
+                        "server/app/{original_name}.js",
+                        original_name = app_entry.original_name
+                    )),
+                    app_entry.rsc_entry,
+                    app_entries.rsc_runtime_entries,
+                );
+                all_chunks.push(rsc_chunk);
+                rsc_chunk.ident().path()
+            }
+            NextRuntime::Edge => {
+                check_edge_runtime_apis(Vc::upcast(app_entry.rsc_entry)).await?;
+
+                let mut evaluatable_assets =
+                    app_entries.edge_rsc_runtime_entries.await?.clone_value();
+                let Some(evaluatable) = Vc::try_resolve_sidecast(app_entry.rsc_entry).await? else {
+                    bail!("Entry module must be evaluatable");
+                };
+                evaluatable_assets.push(evaluatable);
+                let files = edge_rsc_chunking_context
+                    .evaluated_chunk_group(
+                        app_entry
+                            .rsc_entry
+                            .as_root_chunk(Vc::upcast(edge_rsc_chunking_context)),
+                        Vc::cell(evaluatable_assets),
+                    )
+                    .await?;
+                all_chunks.extend(files.iter().copied());
+
+                let node_root_ref = node_root.await?;
+                let files_paths_from_root = files
+                    .iter()
+                    .map(move |&file| {
+                        let node_root_ref = node_root_ref.clone();
+                        async move {
+                            Ok(node_root_ref
+                                .get_path_to(&*file.ident().path().await?)
+                                .map(|path| path.to_string()))
+                        }
+                    })
+                    .try_flat_join()
+                    .await?;
+
+                middlewares_manifest.functions.insert(
+                    app_entry.original_name.clone(),
+                    EdgeFunctionDefinition {
+                        files: files_paths_from_root,
+                        name: app_entry.pathname.to_string(),
+                        page: app_entry.original_name.clone(),
+                        regions: config.preferred_region.clone().map(Regions::Single),
+                        matchers: vec![MiddlewareMatcher {
+                            regexp: Some(get_named_middleware_regex(&app_entry.pathname)),
+                            original_source: app_entry.pathname.clone(),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }
+                    .with_wasm_bindings(),
+                );
+
+                let Some(&base_file) = files.first() else {
+                    bail!("Edge entry {} has no chunks", app_entry.original_name);
+                };
+                base_file.ident().path()
+            }
+        };
 
         let mut app_entry_client_chunks = vec![];
         // TODO(alexkirsz) In which manifest should this go?
         let mut app_entry_ssr_chunks = vec![];
+        let mut app_entry_client_references_chunks = vec![];
 
         for client_reference in app_entry_client_references.iter() {
-            let client_reference_chunks = app_client_references_chunks_ref
+            let client_reference_chunks = client_references_chunks_ref
                 .get(client_reference.ty())
                 .expect("client reference should have corresponding chunks");
-            app_entry_client_chunks
-                .extend(client_reference_chunks.client_chunks.await?.iter().copied());
-            app_entry_ssr_chunks.extend(client_reference_chunks.ssr_chunks.await?.iter().copied());
+            let client_chunks = client_reference_chunks.client_chunks.await?;
+            let ssr_chunks = client_reference_chunks.ssr_chunks.await?;
+            if emitted_client_references.insert((runtime, *client_reference.ty())) {
+                all_chunks.extend(client_chunks.iter().copied());
+                all_chunks.extend(ssr_chunks.iter().copied());
+            }
+            app_entry_client_chunks.extend(client_chunks.iter().copied());
+            app_entry_ssr_chunks.extend(ssr_chunks.iter().copied());
+            if let Some(name) = client_reference_name(client_reference.ty()).await? {
+                app_entry_client_references_chunks.push((name, client_chunks.clone_value()));
+            }
         }
 
+        route_client_chunks.push(RouteClientChunks {
+            pathname: app_entry.pathname.clone(),
+            shared_chunks: app_client_shared_chunks.clone_value(),
+            client_references: app_entry_client_references_chunks,
+        });
+
         let app_entry_client_chunks_paths = app_entry_client_chunks
             .iter()
             .map(|chunk| chunk.ident().path())
@@ -336,22 +519,53 @@ pub async fn compute_app_entries_chunks(
             app_entry_client_chunks_paths,
         );
 
+        app_entry_client_chunks.extend(app_client_shared_chunks.iter().copied());
+        app_build_manifest.preload_hints.insert(
+            app_entry.original_name.clone(),
+            get_preload_hints(&app_entry_client_chunks, &client_relative_path_ref).await?,
+        );
+
         app_paths_manifest.node_server_app_paths.pages.insert(
             app_entry.original_name.clone(),
             app_paths_manifest_dir_path
-                .get_path_to(&*rsc_chunk.ident().path().await?)
+                .get_path_to(&*rsc_path.await?)
                 .expect("RSC chunk path should be within app paths manifest directory")
                 .to_string(),
         );
 
+        functions_config_manifest.functions.insert(
+            app_entry.original_name.clone(),
+            FunctionConfig {
+                runtime,
+                preferred_region: config.preferred_region.clone(),
+                cache_control: config.cache_control(),
+                streaming: app_entry.streaming,
+            },
+        );
+
+        if let Some(static_metadata) = &*app_entry.static_metadata.await? {
+            static_metadata_manifest
+                .pages
+                .insert(app_entry.original_name.clone(), static_metadata.clone());
+        }
+
+        let segment_boundaries = app_entry.segment_boundaries.await?;
+        if !segment_boundaries.is_empty() {
+            segment_boundaries_manifest.pages.insert(
+                app_entry.original_name.clone(),
+                segment_boundaries.clone_value(),
+            );
+        }
+
         let entry_manifest = ClientReferenceManifest::build_output(
             node_root,
             client_relative_path,
             app_entry.original_name.clone(),
             app_client_reference_graph.entry(Vc::upcast(app_entry.rsc_entry)),
-            app_client_references_chunks,
+            client_references_chunks,
             client_chunking_context,
-            ssr_chunking_context,
+            entry_ssr_chunking_context,
+            runtime,
         );
 
         all_chunks.push(entry_manifest);
@@ -1,26 +1,39 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use dunce::canonicalize;
+use indexmap::IndexSet;
 use next_core::{
+    find_workspace_root, lightningcss_asset_content, minify_asset_content, minify_asset_source_map,
     mode::NextMode,
-    next_app::get_app_client_references_chunks,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
-    next_client_reference::{ClientReferenceGraph, ClientReferenceType},
-    next_config::load_next_config,
+    next_app::{
+        get_app_client_references_chunks, route_fetch_cache, CacheTagsManifest, FetchCacheManifest,
+    },
+    next_client::{
+        get_browserslist_query, get_client_chunking_context, get_client_compile_time_info,
+    },
+    next_client_reference::{unused_client_components, ClientReferenceGraph, ClientReferenceType},
+    next_config::{load_headers, load_next_config, SubresourceIntegrityAlgorithm},
     next_dynamic::NextDynamicEntries,
+    next_edge::context::{get_edge_chunking_context, get_edge_compile_time_info},
     next_manifests::{
-        AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, FontManifest,
-        MiddlewaresManifest, NextFontManifest, PagesManifest, ReactLoadableManifest,
-        ServerReferenceManifest,
+        subresource_integrity::{integrity, SUBRESOURCE_INTEGRITY_MANIFEST},
+        AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, EarlyHintsManifest,
+        FontManifest, FunctionsConfigManifest, MiddlewaresManifest, MiddlewaresManifestV2,
+        NextFontManifest, PagesManifest, ReactLoadableManifest, SegmentBoundariesManifest,
+        ServerReferenceManifest, StaticMetadataManifest,
     },
     next_server::{get_server_chunking_context, get_server_compile_time_info},
+    project_relative_path::ProjectRelativePath,
+    route_headers::{route_header_table, RouteHeaderTable},
+    route_matcher_table::RouteMatcherTable,
     url_node::get_sorted_routes,
-    {self},
+    util::NextRuntime,
+    MinifyOptions, {self},
 };
 use serde::Serialize;
 use turbo_tasks::{
@@ -32,7 +45,7 @@ use turbopack_binding::{
     turbopack::{
         cli_utils::issue::{ConsoleUi, LogOptions},
         core::{
-            asset::Asset,
+            asset::{Asset, AssetContent},
             chunk::ChunkingContext,
             environment::ServerAddr,
             issue::{handle_issues, IssueReporter, IssueSeverity},
@@ -49,6 +62,7 @@ use turbopack_binding::{
 
 use crate::{
     build_options::{BuildContext, BuildOptions},
+    bundle_budgets::{check_bundle_budgets, emit_bundle_budget_issues},
     next_app::app_entries::{compute_app_entries_chunks, get_app_entries},
     next_pages::page_entries::{compute_page_entries_chunks, get_page_entries},
 };
@@ -74,14 +88,14 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
             .to_str()
             .context("root directory contains invalid characters")?
             .to_string()
+    } else if let Some(root) = find_workspace_root(Path::new(&project_root)) {
+        root.to_str()
+            .context("root directory contains invalid characters")?
+            .to_string()
     } else {
         project_root.clone()
     };
 
-    let browserslist_query = "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
-                              versions, last 1 Edge versions"
-        .to_string();
-
     let log_options = LogOptions {
         project_dir: PathBuf::from(project_root.clone()),
         current_dir: current_dir().unwrap(),
@@ -100,12 +114,8 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     // let client_public_fs = VirtualFileSystem::new();
     // let client_public_root = client_public_fs.root();
     let workspace_fs = workspace_fs(workspace_root.clone(), issue_reporter);
-    let project_relative = project_root.strip_prefix(&workspace_root).unwrap();
-    let project_relative = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/");
-    let project_root = workspace_fs.root().join(project_relative);
+    let project_root =
+        ProjectRelativePath::new(&workspace_root, &project_root)?.join(workspace_fs.root());
 
     let node_root_ref = node_root.await?;
 
@@ -127,8 +137,13 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let next_config = load_next_config(execution_context.with_layer("next_config".to_string()));
 
     let mode = NextMode::Build;
-    let client_compile_time_info = get_client_compile_time_info(mode, browserslist_query);
+    let browserslist_query = get_browserslist_query(project_root, mode, next_config)
+        .await?
+        .clone_value();
+    let client_compile_time_info =
+        get_client_compile_time_info(mode, browserslist_query, next_config, None);
     let server_compile_time_info = get_server_compile_time_info(mode, env, ServerAddr::empty());
+    let edge_compile_time_info = get_edge_compile_time_info(project_root, ServerAddr::empty());
 
     // TODO(alexkirsz) Pages should build their own routes, outside of a FS.
     let next_router_fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
@@ -149,6 +164,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         env,
         client_compile_time_info,
         server_compile_time_info,
+        edge_compile_time_info,
         next_config,
     );
 
@@ -172,6 +188,26 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let page_entries = page_entries.await?;
     let app_entries = app_entries.await?;
 
+    let app_pathnames = app_entries
+        .entries
+        .iter()
+        .map(|entry| async move { Ok(entry.await?.pathname.clone()) })
+        .try_join()
+        .await?;
+    let route_headers = route_header_table(
+        project_root,
+        load_headers(execution_context.with_layer("next_config".to_string())),
+        Vc::cell(app_pathnames.clone()),
+    );
+    handle_issues(
+        route_headers,
+        issue_reporter,
+        MIN_FAILING_SEVERITY.cell(),
+        None,
+        None,
+    )
+    .await?;
+
     let app_rsc_entries: Vec<_> = app_entries
         .entries
         .iter()
@@ -254,6 +290,15 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let rsc_chunking_context = server_chunking_context.with_layer("rsc".to_string());
     let ssr_chunking_context = server_chunking_context.with_layer("ssr".to_string());
 
+    let edge_chunking_context = get_edge_chunking_context(
+        project_root,
+        node_root,
+        client_root,
+        edge_compile_time_info.environment(),
+    );
+    let edge_rsc_chunking_context = edge_chunking_context.with_layer("edge rsc".to_string());
+    let edge_ssr_chunking_context = edge_chunking_context.with_layer("edge ssr".to_string());
+
     let mut all_chunks = vec![];
 
     let mut build_manifest: BuildManifest = Default::default();
@@ -293,39 +338,70 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let app_paths_manifest_path = node_root.join("server/app-paths-manifest.json".to_string());
     let app_paths_manifest_dir_path = app_paths_manifest_path.parent().await?;
 
+    let mut functions_config_manifest = FunctionsConfigManifest::default();
+    let mut middlewares_manifest = MiddlewaresManifestV2::default();
+    let mut static_metadata_manifest = StaticMetadataManifest::default();
+    let mut segment_boundaries_manifest = SegmentBoundariesManifest::default();
+
     // APP CLIENT REFERENCES CHUNKING
 
+    // The client references of edge entries are server rendered by the edge
+    // runtime, so they are chunked with the edge chunking context.
+    let mut app_node_client_reference_tys = IndexSet::new();
+    let mut app_edge_client_reference_tys = IndexSet::new();
+    for app_entry in app_entries.entries.iter().copied() {
+        let app_entry = app_entry.await?;
+        let client_reference_tys = match app_entry.config.await?.runtime.unwrap_or_default() {
+            NextRuntime::NodeJs => &mut app_node_client_reference_tys,
+            NextRuntime::Edge => &mut app_edge_client_reference_tys,
+        };
+        for client_reference in app_client_references
+            .entry(Vc::upcast(app_entry.rsc_entry))
+            .await?
+            .iter()
+        {
+            client_reference_tys.insert(*client_reference.ty());
+        }
+    }
+
     let app_client_references_chunks = get_app_client_references_chunks(
-        app_client_reference_tys,
+        Vc::cell(app_node_client_reference_tys),
         client_chunking_context,
-        ssr_chunking_context,
+        Vc::upcast(ssr_chunking_context),
+    );
+    let app_edge_client_references_chunks = get_app_client_references_chunks(
+        Vc::cell(app_edge_client_reference_tys),
+        client_chunking_context,
+        edge_ssr_chunking_context,
     );
-    let app_client_references_chunks_ref = app_client_references_chunks.await?;
-
-    for app_client_reference_chunks in app_client_references_chunks_ref.values() {
-        let client_chunks = &app_client_reference_chunks.client_chunks.await?;
-        let ssr_chunks = &app_client_reference_chunks.ssr_chunks.await?;
-        all_chunks.extend(client_chunks.iter().copied());
-        all_chunks.extend(ssr_chunks.iter().copied());
-    }
 
     // APP RSC CHUNKING
     // TODO(alexkirsz) Do some of that in parallel with the above.
 
+    let mut route_client_chunks = vec![];
+
     compute_app_entries_chunks(
         &app_entries,
         app_client_references,
         app_client_references_chunks,
+        app_edge_client_references_chunks,
         rsc_chunking_context,
+        edge_rsc_chunking_context,
         client_chunking_context,
         Vc::upcast(ssr_chunking_context),
+        edge_ssr_chunking_context,
         node_root,
         client_relative_path,
         &app_paths_manifest_dir_path,
         &mut app_build_manifest,
         &mut build_manifest,
         &mut app_paths_manifest,
+        &mut functions_config_manifest,
+        &mut middlewares_manifest,
+        &mut static_metadata_manifest,
+        &mut segment_boundaries_manifest,
         &mut all_chunks,
+        &mut route_client_chunks,
     )
     .await?;
 
@@ -404,19 +480,92 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         build_manifest.low_priority_files.push(client_manifest_path);
     }
 
+    completions.push(write_manifest(
+        RouteMatcherTable::new(
+            &app_pathnames,
+            &pages_manifest.pages.keys().cloned().collect::<Vec<_>>(),
+        )?,
+        node_root.join("server/route-matcher-table.json".to_string()),
+    )?);
+    let fetch_cache_manifest = FetchCacheManifest {
+        version: 1,
+        routes: app_entries
+            .entries
+            .iter()
+            .map(|&entry| async move {
+                let entry = entry.await?;
+                Ok((
+                    entry.original_name.clone(),
+                    route_fetch_cache(Vc::upcast(entry.rsc_entry))
+                        .await?
+                        .clone_value(),
+                ))
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .collect(),
+    };
+    completions.push(write_manifest(
+        CacheTagsManifest::new(&fetch_cache_manifest),
+        node_root.join("server/cache-tags-manifest.json".to_string()),
+    )?);
+    completions.push(write_manifest(
+        fetch_cache_manifest,
+        node_root.join("server/fetch-cache-manifest.json".to_string()),
+    )?);
     completions.push(write_manifest(pages_manifest, pages_manifest_path)?);
+    completions.push(write_manifest(
+        EarlyHintsManifest {
+            pages: app_build_manifest.preload_hints.clone(),
+            ..Default::default()
+        },
+        node_root.join("server/early-hints-manifest.json".to_string()),
+    )?);
+    completions.push(write_manifest(
+        functions_config_manifest,
+        node_root.join("server/functions-config-manifest.json".to_string()),
+    )?);
+    completions.push(write_manifest(
+        static_metadata_manifest,
+        node_root.join("server/static-metadata-manifest.json".to_string()),
+    )?);
+    completions.push(write_manifest(
+        segment_boundaries_manifest,
+        node_root.join("server/segment-boundaries-manifest.json".to_string()),
+    )?);
     completions.push(write_manifest(app_build_manifest, app_build_manifest_path)?);
     completions.push(write_manifest(app_paths_manifest, app_paths_manifest_path)?);
     completions.push(write_manifest(build_manifest, build_manifest_path)?);
+    completions.push(write_manifest(
+        routes_manifest_with_headers(
+            node_root.join("routes-manifest.json".to_string()),
+            &*route_headers.await?,
+        )
+        .await?,
+        node_root.join("routes-manifest.json".to_string()),
+    )?);
 
-    // Placeholder manifests.
+    if options.report_unused_client_components {
+        completions.push(write_manifest(
+            UnusedClientComponentsReport {
+                unused_client_components: &unused_client_components(
+                    project_root,
+                    app_client_reference_tys,
+                )
+                .await?,
+            },
+            node_root.join("unused-client-components.json".to_string()),
+        )?);
+    }
 
-    // TODO(alexkirsz) Proper middleware manifest with all (edge?) routes in it,
-    // experimental-edge pages?
+    // TODO(alexkirsz) Add the middleware and the experimental-edge pages.
     completions.push(write_manifest(
-        MiddlewaresManifest::default(),
+        MiddlewaresManifest::MiddlewaresManifestV2(middlewares_manifest),
         node_root.join("server/middleware-manifest.json".to_string()),
     )?);
+
+    // Placeholder manifests.
     completions.push(write_manifest(
         NextFontManifest::default(),
         node_root.join("server/next-font-manifest.json".to_string()),
@@ -434,12 +583,72 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         node_root.join("react-loadable-manifest.json".to_string()),
     )?);
 
+    let minify_options = if *next_config.swc_minify().await? {
+        Some(next_config.minify_options())
+    } else {
+        None
+    };
+    let lightningcss_targets = if *next_config.use_lightningcss().await? {
+        Some(get_browserslist_query(project_root, mode, next_config))
+    } else {
+        None
+    };
+
+    let bundle_budget_issues = check_bundle_budgets(
+        project_root,
+        next_config.bundle_budgets(),
+        &route_client_chunks,
+        minify_options,
+        lightningcss_targets,
+    )
+    .await?;
+    // Exceeded budgets are printed, and fail the build with `level: 'error'`.
+    handle_issues(
+        emit_bundle_budget_issues(bundle_budget_issues),
+        issue_reporter,
+        IssueSeverity::Error.cell(),
+        None,
+        None,
+    )
+    .await?;
+
+    if let Some(algorithm) = *next_config.sri_algorithm().await? {
+        let manifest = subresource_integrity_manifest(
+            all_chunks.clone(),
+            client_relative_path,
+            minify_options,
+            lightningcss_targets,
+            algorithm,
+        )
+        .await?;
+        let manifest_path = format!("server/{SUBRESOURCE_INTEGRITY_MANIFEST}");
+        // The `.js` variant is loaded by the edge runtime.
+        completions.push(
+            node_root.join(format!("{manifest_path}.js")).write(
+                FileContent::Content(
+                    format!(
+                        "self.__SUBRESOURCE_INTEGRITY_MANIFEST={}",
+                        StringifyJs(&serde_json::to_string(&manifest)?)
+                    )
+                    .into(),
+                )
+                .cell(),
+            ),
+        );
+        completions.push(write_manifest(
+            manifest,
+            node_root.join(format!("{manifest_path}.json")),
+        )?);
+    }
+
     completions.push(
         emit_all_assets(
             all_chunks,
             &node_root_ref,
             client_relative_path,
             client_root,
+            minify_options,
+            lightningcss_targets,
         )
         .await?,
     );
@@ -447,6 +656,12 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     Ok(Completions::all(completions))
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnusedClientComponentsReport<'a> {
+    unused_client_components: &'a [String],
+}
+
 #[turbo_tasks::function]
 async fn workspace_fs(
     workspace_root: String,
@@ -505,15 +720,33 @@ async fn emit_all_assets(
     node_root: &FileSystemPath,
     client_relative_path: Vc<FileSystemPath>,
     client_output_path: Vc<FileSystemPath>,
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
 ) -> Result<Vc<Completion>> {
     let all_assets = all_assets_from_entries(Vc::cell(chunks)).await?;
+    // Minification changes the chunks, their source maps have to be updated too.
+    let mut source_map_chunks = HashMap::new();
+    if minify_options.is_some() {
+        for &asset in all_assets.iter() {
+            if let Some(source_map) = chunk_source_map(asset).await? {
+                source_map_chunks.insert(source_map, asset);
+            }
+        }
+    }
+    let source_map_chunks = &source_map_chunks;
     Ok(Completions::all(
         all_assets
             .iter()
             .copied()
             .map(|asset| async move {
+                let source_map_of = source_map_chunks.get(&asset).copied();
                 if asset.ident().path().await?.is_inside_ref(node_root) {
-                    return Ok(emit(asset));
+                    return Ok(emit(
+                        asset,
+                        source_map_of,
+                        minify_options,
+                        lightningcss_targets,
+                    ));
                 } else if asset
                     .ident()
                     .path()
@@ -522,7 +755,14 @@ async fn emit_all_assets(
                 {
                     // Client assets are emitted to the client output path, which is prefixed with
                     // _next. We need to rebase them to remove that prefix.
-                    return Ok(emit_rebase(asset, client_relative_path, client_output_path));
+                    return Ok(emit_rebase(
+                        asset,
+                        source_map_of,
+                        client_relative_path,
+                        client_output_path,
+                        minify_options,
+                        lightningcss_targets,
+                    ));
                 }
 
                 Ok(Completion::immutable())
@@ -532,20 +772,139 @@ async fn emit_all_assets(
     ))
 }
 
+/// Computes the subresource integrity hashes of the client scripts reachable
+/// from the given chunks, keyed by their path relative to the client root.
+async fn subresource_integrity_manifest(
+    chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    client_relative_path: Vc<FileSystemPath>,
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+    algorithm: SubresourceIntegrityAlgorithm,
+) -> Result<BTreeMap<String, String>> {
+    let client_relative_path = &*client_relative_path.await?;
+    let all_assets = all_assets_from_entries(Vc::cell(chunks)).await?;
+    let hashes = all_assets
+        .iter()
+        .copied()
+        .map(|asset| async move {
+            let path = asset.ident().path().await?;
+            let Some(path) = client_relative_path.get_path_to(&path) else {
+                return Ok(None);
+            };
+            if !path.ends_with(".js") {
+                return Ok(None);
+            }
+            let path = path.to_string();
+            // The hash has to match the content which is written to disk.
+            let content = output_content(asset, minify_options, lightningcss_targets)
+                .await?
+                .await?;
+            let AssetContent::File(file) = &*content else {
+                return Ok(None);
+            };
+            let FileContent::Content(file) = &*file.await? else {
+                return Ok(None);
+            };
+            let bytes = file.content().to_bytes()?;
+            Ok(Some((path, integrity(algorithm, &bytes))))
+        })
+        .try_join()
+        .await?;
+    Ok(hashes.into_iter().flatten().collect())
+}
+
+/// The content of an asset as it should be written to disk.
+pub(crate) async fn output_content(
+    asset: Vc<Box<dyn OutputAsset>>,
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vc<AssetContent>> {
+    let path = asset.ident().path();
+    let mut content = asset.content();
+    if let Some(minify_options) = minify_options {
+        let source_map = chunk_source_map(asset).await?;
+        content = minify_asset_content(
+            path,
+            content,
+            source_map.map(|source_map| source_map.content()),
+            minify_options,
+        );
+    }
+    if let Some(lightningcss_targets) = lightningcss_targets {
+        content = lightningcss_asset_content(
+            path,
+            content,
+            lightningcss_targets,
+            minify_options.is_some(),
+        );
+    }
+    Ok(content)
+}
+
+/// The source map which is emitted next to a JavaScript chunk, if any.
+async fn chunk_source_map(
+    asset: Vc<Box<dyn OutputAsset>>,
+) -> Result<Option<Vc<Box<dyn OutputAsset>>>> {
+    let path = asset.ident().path().await?;
+    if path.extension_ref() != Some("js") {
+        return Ok(None);
+    }
+    let source_map_path = format!("{}.map", path.path);
+    for &reference in asset.references().await?.iter() {
+        if reference.ident().path().await?.path == source_map_path {
+            return Ok(Some(reference));
+        }
+    }
+    Ok(None)
+}
+
+/// The content of `asset`, or of the source map of the chunk `source_map_of`
+/// when it's that source map.
+async fn emitted_content(
+    asset: Vc<Box<dyn OutputAsset>>,
+    source_map_of: Option<Vc<Box<dyn OutputAsset>>>,
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vc<AssetContent>> {
+    match (source_map_of, minify_options) {
+        (Some(chunk), Some(minify_options)) => Ok(minify_asset_source_map(
+            chunk.ident().path(),
+            chunk.content(),
+            asset.content(),
+            minify_options,
+        )),
+        _ => output_content(asset, minify_options, lightningcss_targets).await,
+    }
+}
+
 #[turbo_tasks::function]
-fn emit(asset: Vc<Box<dyn OutputAsset>>) -> Vc<Completion> {
-    asset.content().write(asset.ident().path())
+async fn emit(
+    asset: Vc<Box<dyn OutputAsset>>,
+    source_map_of: Option<Vc<Box<dyn OutputAsset>>>,
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vc<Completion>> {
+    Ok(
+        emitted_content(asset, source_map_of, minify_options, lightningcss_targets)
+            .await?
+            .write(asset.ident().path()),
+    )
 }
 
 #[turbo_tasks::function]
-fn emit_rebase(
+async fn emit_rebase(
     asset: Vc<Box<dyn OutputAsset>>,
+    source_map_of: Option<Vc<Box<dyn OutputAsset>>>,
     from: Vc<FileSystemPath>,
     to: Vc<FileSystemPath>,
-) -> Vc<Completion> {
-    asset
-        .content()
-        .write(rebase(asset.ident().path(), from, to))
+    minify_options: Option<Vc<MinifyOptions>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Result<Vc<Completion>> {
+    Ok(
+        emitted_content(asset, source_map_of, minify_options, lightningcss_targets)
+            .await?
+            .write(rebase(asset.ident().path(), from, to)),
+    )
 }
 
 /// Walks the asset graph from multiple assets and collect all referenced
@@ -580,6 +939,39 @@ async fn get_referenced_assets(
         .into_iter())
 }
 
+/// Adds the `headers()` which can apply to each app route to the routes
+/// manifest written by `next build`. The compiled headers are only added when
+/// the manifest has none, the ones of `next build` also include the
+/// `basePath` and locales.
+///
+/// The result doesn't depend on the previous `appPathHeaders`, so writing the
+/// manifest again doesn't change it.
+async fn routes_manifest_with_headers(
+    path: Vc<FileSystemPath>,
+    route_headers: &RouteHeaderTable,
+) -> Result<serde_json::Value> {
+    let path_str = path.to_string().await?;
+    let mut manifest = match &*path.read().await? {
+        FileContent::Content(file) => serde_json::from_slice(&file.content().to_bytes()?)
+            .with_context(|| format!("unable to parse {path_str}"))?,
+        FileContent::NotFound => serde_json::json!({ "version": 3 }),
+    };
+    let Some(object) = manifest.as_object_mut() else {
+        bail!("{path_str} is not an object");
+    };
+    if !object.contains_key("headers") {
+        object.insert(
+            "headers".to_string(),
+            serde_json::to_value(&route_headers.routes)?,
+        );
+    }
+    object.insert(
+        "appPathHeaders".to_string(),
+        serde_json::to_value(&route_headers.app_paths)?,
+    );
+    Ok(manifest)
+}
+
 /// Writes a manifest to disk. This consumes the manifest to ensure we don't
 /// write to it afterwards.
 fn write_manifest<T>(manifest: T, manifest_path: Vc<FileSystemPath>) -> Result<Vc<Completion>>
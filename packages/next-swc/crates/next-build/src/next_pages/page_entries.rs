@@ -8,7 +8,7 @@ use next_core::{
     },
     next_config::NextConfig,
     next_dynamic::NextDynamicTransition,
-    next_manifests::{BuildManifest, PagesManifest},
+    next_manifests::{preload_hints::get_preload_hints, BuildManifest, PagesManifest},
     next_pages::create_page_ssr_entry_module,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
@@ -419,13 +419,19 @@ pub async fn compute_page_entries_chunks(
             .entry(pathname.clone_value())
             .or_default();
 
-        for chunk in client_chunks.await?.iter().copied() {
+        let client_chunks = client_chunks.await?;
+        for chunk in client_chunks.iter().copied() {
             all_chunks.push(chunk);
             let chunk_path = chunk.ident().path().await?;
             if let Some(asset_path) = client_relative_path.get_path_to(&chunk_path) {
                 build_manifest_pages_entry.push(asset_path.to_string());
             }
         }
+
+        build_manifest.preload_hints.insert(
+            pathname.clone_value(),
+            get_preload_hints(&client_chunks, client_relative_path).await?,
+        );
     }
     Ok(())
 }
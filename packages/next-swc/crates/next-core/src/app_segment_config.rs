@@ -26,7 +26,9 @@ use turbopack_binding::turbopack::{
     },
 };
 
-use crate::{app_structure::LoaderTree, util::NextRuntime};
+use crate::{
+    app_static_metadata::validate_metadata_exports, app_structure::LoaderTree, util::NextRuntime,
+};
 
 #[derive(Default, PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -81,6 +83,26 @@ impl ValueDefault for NextSegmentConfig {
 }
 
 impl NextSegmentConfig {
+    /// The `Cache-Control` header of responses of the route, if it can be
+    /// determined from the segment config alone. Without `revalidate` or a
+    /// forced `dynamic` mode it depends on which dynamic APIs are used while
+    /// rendering.
+    pub fn cache_control(&self) -> Option<String> {
+        match (self.dynamic, self.revalidate) {
+            (Some(NextSegmentDynamic::ForceDynamic), _)
+            | (_, Some(NextRevalidate::Frequency { seconds: 0 })) => {
+                Some("private, no-cache, no-store, max-age=0, must-revalidate".to_string())
+            }
+            (_, Some(NextRevalidate::Frequency { seconds })) => {
+                Some(format!("s-maxage={seconds}, stale-while-revalidate"))
+            }
+            (_, Some(NextRevalidate::Never)) | (Some(NextSegmentDynamic::ForceStatic), _) => {
+                Some("s-maxage=31536000, stale-while-revalidate".to_string())
+            }
+            _ => None,
+        }
+    }
+
     /// Applies the parent config to this config, setting any unset values to
     /// the parent's values.
     pub fn apply_parent_config(&mut self, parent: &Self) {
@@ -228,6 +250,8 @@ pub async fn parse_segment_config_from_source(
         return Ok(Default::default());
     };
 
+    validate_metadata_exports(module_ast, module.ident(), source);
+
     let mut config = NextSegmentConfig::default();
 
     for item in &module_ast.body {
@@ -407,3 +431,38 @@ pub async fn parse_segment_config_from_loader_tree(
     }
     Ok(config.cell())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_control() {
+        let config = |dynamic, revalidate| NextSegmentConfig {
+            dynamic,
+            revalidate,
+            ..Default::default()
+        };
+        assert_eq!(config(None, None).cache_control(), None);
+        assert_eq!(
+            config(None, Some(NextRevalidate::Frequency { seconds: 60 })).cache_control(),
+            Some("s-maxage=60, stale-while-revalidate".to_string())
+        );
+        assert_eq!(
+            config(Some(NextSegmentDynamic::ForceStatic), None).cache_control(),
+            Some("s-maxage=31536000, stale-while-revalidate".to_string())
+        );
+        assert_eq!(
+            config(
+                Some(NextSegmentDynamic::ForceDynamic),
+                Some(NextRevalidate::Frequency { seconds: 60 })
+            )
+            .cache_control(),
+            Some("private, no-cache, no-store, max-age=0, must-revalidate".to_string())
+        );
+        assert_eq!(
+            config(None, Some(NextRevalidate::ForceCache)).cache_control(),
+            None
+        );
+    }
+}
@@ -65,7 +65,10 @@ use crate::{
     fallback::get_fallback_page,
     loader_tree::{LoaderTreeModule, ServerComponentTransition},
     mode::NextMode,
-    next_app::{AppPage, AppPath, PathSegment, UnsupportedDynamicMetadataIssue},
+    next_app::{
+        get_favicon_ico_content, metadata_file_validation::validate_static_metadata_file, AppPage,
+        AppPath, PathSegment, UnsupportedDynamicMetadataIssue,
+    },
     next_client::{
         context::{
             get_client_assets_path, get_client_module_options_context,
@@ -598,7 +601,11 @@ pub async fn create_app_source(
     let Some(app_dir) = *app_dir.await? else {
         return Ok(Vc::upcast(NoContentSource::new()));
     };
-    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    let entrypoints = get_entrypoints(
+        app_dir,
+        next_config.page_extensions(),
+        next_config.app_i18n(),
+    );
     let metadata = get_global_metadata(app_dir, next_config.page_extensions());
 
     let context_ssr = app_context(
@@ -728,7 +735,9 @@ async fn create_global_metadata_source(
     let mut sources = Vec::new();
     for (server_path, item) in [
         ("robots.txt", metadata.robots),
-        ("favicon.ico", metadata.favicon),
+        // Like in builds, `favicon.ico` is generated from an `icon.png` when
+        // there's none.
+        ("favicon.ico", metadata.favicon.or(metadata.icon)),
         ("sitemap.xml", metadata.sitemap),
     ] {
         let Some(item) = item else {
@@ -736,10 +745,17 @@ async fn create_global_metadata_source(
         };
         match item {
             MetadataItem::Static { path } => {
-                let asset = FixedStaticAsset::new(
-                    server_root.join(server_path.to_string()),
-                    Vc::upcast(FileSource::new(path)),
-                );
+                validate_static_metadata_file(path).await?;
+                let source = if server_path == "favicon.ico" {
+                    Vc::upcast(VirtualSource::new(
+                        path,
+                        AssetContent::file(get_favicon_ico_content(path)),
+                    ))
+                } else {
+                    Vc::upcast(FileSource::new(path))
+                };
+                let asset =
+                    FixedStaticAsset::new(server_root.join(server_path.to_string()), source);
                 sources.push(Vc::upcast(AssetGraphContentSource::new_eager(
                     server_root,
                     Vc::upcast(asset),
@@ -0,0 +1,617 @@
+use std::ops::Deref;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Map, Value as JsonValue};
+use swc_core::{
+    common::{source_map::Pos, Span, Spanned},
+    ecma::ast::{Decl, Expr, Module as ModuleAst, Program, Prop, PropName, PropOrSpread},
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{
+        context::AssetContext,
+        file_source::FileSource,
+        ident::AssetIdent,
+        issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+        module::Module,
+        reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+        source::Source,
+    },
+    ecmascript::{
+        analyzer::{ConstantNumber, ConstantValue, JsValue, ObjectPart},
+        parse::ParseResult,
+        EcmascriptModuleAsset,
+    },
+};
+
+use crate::app_structure::{
+    visit_loader_tree, Components, LoaderTree, LoaderTreeVisitor, VisitControlFlow,
+};
+
+/// The fields which moved from the `metadata` export to the `viewport` export.
+const VIEWPORT_FIELDS: [&str; 3] = ["viewport", "themeColor", "colorScheme"];
+
+/// The metadata of a page, when it only depends on `export const metadata`
+/// objects which can be evaluated at compile time. `None` when any segment
+/// computes its metadata at runtime.
+#[turbo_tasks::value(transparent)]
+pub struct OptionStaticMetadata(Option<JsonValue>);
+
+/// Evaluates the `metadata` and `viewport` exports of a layout or page. Returns
+/// an empty object when the module doesn't export any metadata, and `None`
+/// when it exports `generateMetadata`, `generateViewport` or an object which
+/// isn't static.
+#[turbo_tasks::function]
+pub async fn parse_static_metadata_from_source(
+    module: Vc<Box<dyn Module>>,
+) -> Result<Vc<OptionStaticMetadata>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(None));
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        eval_context,
+        ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        return Ok(Vc::cell(None));
+    };
+
+    let mut metadata = Map::new();
+    let mut viewport = Map::new();
+    for item in &module_ast.body {
+        let Some(module_decl) = item.as_module_decl() else {
+            continue;
+        };
+        if let Some(export_decl) = module_decl.as_export_decl() {
+            if let Some(fn_decl) = export_decl.decl.as_fn_decl() {
+                if matches!(&*fn_decl.ident.sym, "generateMetadata" | "generateViewport") {
+                    return Ok(Vc::cell(None));
+                }
+            }
+            let Some(var_decl) = export_decl.decl.as_var() else {
+                continue;
+            };
+            for decl in &var_decl.decls {
+                let Some(ident) = decl.name.as_ident().map(|ident| ident.deref()) else {
+                    continue;
+                };
+                let target = match &*ident.sym {
+                    "generateMetadata" | "generateViewport" => return Ok(Vc::cell(None)),
+                    "metadata" => &mut metadata,
+                    "viewport" => &mut viewport,
+                    _ => continue,
+                };
+                let Some(JsonValue::Object(value)) = decl
+                    .init
+                    .as_ref()
+                    .and_then(|init| js_value_to_json(&eval_context.eval(init)))
+                else {
+                    return Ok(Vc::cell(None));
+                };
+                *target = value;
+            }
+        } else if module_decl.is_export_named() || module_decl.is_export_all() {
+            // Re-exports could export the metadata from another module.
+            return Ok(Vc::cell(None));
+        }
+    }
+
+    metadata.extend(viewport_to_metadata(viewport));
+    Ok(Vc::cell(Some(JsonValue::Object(metadata))))
+}
+
+/// Maps the fields of a `viewport` export to the `metadata` fields they
+/// replace, like `getDefinedMetadata` of
+/// `next/dist/lib/metadata/resolve-metadata` does at runtime.
+fn viewport_to_metadata(mut viewport: Map<String, JsonValue>) -> Map<String, JsonValue> {
+    let mut metadata = Map::new();
+    for field in ["themeColor", "colorScheme"] {
+        if let Some(value) = viewport.remove(field) {
+            metadata.insert(field.to_string(), value);
+        }
+    }
+    if !viewport.is_empty() {
+        metadata.insert("viewport".to_string(), JsonValue::Object(viewport));
+    }
+    metadata
+}
+
+/// Checks the metadata exports of a layout or page: viewport fields in the
+/// `metadata` export are deprecated, and a static export can't be combined
+/// with its `generate*` counterpart.
+pub(crate) fn validate_metadata_exports(
+    module_ast: &ModuleAst,
+    ident: Vc<AssetIdent>,
+    source: Vc<Box<dyn Source>>,
+) {
+    for problem in metadata_export_problems(module_ast) {
+        let (severity, title, description, span) = match problem {
+            MetadataExportProblem::ViewportField { field, span } => (
+                IssueSeverity::Warning,
+                format!("Unsupported metadata {field} is configured in the metadata export"),
+                format!(
+                    "{field} moved to the viewport export. Please move it there instead, \
+                     metadata.{field} will be removed in a future version."
+                ),
+                span,
+            ),
+            MetadataExportProblem::ConflictingExports {
+                name,
+                generate_name,
+                span,
+            } => (
+                IssueSeverity::Error,
+                format!("{name} and {generate_name} can't be exported at the same time"),
+                format!(
+                    "Only export one of them, {generate_name} can return values which depend on \
+                     the params of the route."
+                ),
+                span,
+            ),
+        };
+        MetadataExportIssue {
+            ident,
+            severity: severity.cell(),
+            title: Vc::cell(title),
+            description: Vc::cell(description),
+            source: IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize()),
+        }
+        .cell()
+        .emit();
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MetadataExportProblem {
+    /// A field of the `metadata` export which moved to the `viewport` export.
+    ViewportField { field: String, span: Span },
+    /// A static export together with its `generate*` counterpart, the span is
+    /// the one of the function.
+    ConflictingExports {
+        name: &'static str,
+        generate_name: &'static str,
+        span: Span,
+    },
+}
+
+fn metadata_export_problems(module_ast: &ModuleAst) -> Vec<MetadataExportProblem> {
+    let mut problems = vec![];
+    let mut exports = vec![];
+    for item in &module_ast.body {
+        let Some(export_decl) = item
+            .as_module_decl()
+            .and_then(|module_decl| module_decl.as_export_decl())
+        else {
+            continue;
+        };
+        match &export_decl.decl {
+            Decl::Fn(fn_decl) => exports.push((&*fn_decl.ident.sym, fn_decl.ident.span)),
+            Decl::Var(var_decl) => {
+                for decl in &var_decl.decls {
+                    let Some(name) = decl.name.as_ident() else {
+                        continue;
+                    };
+                    exports.push((&*name.id.sym, name.id.span));
+                    if &*name.id.sym != "metadata" {
+                        continue;
+                    }
+                    let Some(Expr::Object(object)) = decl.init.as_deref() else {
+                        continue;
+                    };
+                    for prop in &object.props {
+                        let PropOrSpread::Prop(prop) = prop else {
+                            continue;
+                        };
+                        let Prop::KeyValue(key_value) = &**prop else {
+                            continue;
+                        };
+                        let field = match &key_value.key {
+                            PropName::Ident(ident) => ident.sym.to_string(),
+                            PropName::Str(str) => str.value.to_string(),
+                            _ => continue,
+                        };
+                        if VIEWPORT_FIELDS.contains(&field.as_str()) {
+                            problems.push(MetadataExportProblem::ViewportField {
+                                field,
+                                span: key_value.span(),
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let export_span = |export_name: &str| {
+        exports
+            .iter()
+            .find(|(export, _)| *export == export_name)
+            .map(|(_, span)| *span)
+    };
+    for (name, generate_name) in [
+        ("metadata", "generateMetadata"),
+        ("viewport", "generateViewport"),
+    ] {
+        if let (Some(_), Some(span)) = (export_span(name), export_span(generate_name)) {
+            problems.push(MetadataExportProblem::ConflictingExports {
+                name,
+                generate_name,
+                span,
+            });
+        }
+    }
+    problems
+}
+
+#[turbo_tasks::value(shared)]
+struct MetadataExportIssue {
+    ident: Vc<AssetIdent>,
+    severity: Vc<IssueSeverity>,
+    title: Vc<String>,
+    description: Vc<String>,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MetadataExportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        self.title
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("metadata".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        self.description
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            "https://nextjs.org/docs/app/api-reference/functions/generate-viewport".to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.source)
+    }
+}
+
+/// Converts an evaluated value to JSON, if it's made of constants only.
+fn js_value_to_json(value: &JsValue) -> Option<JsonValue> {
+    Some(match value {
+        JsValue::Constant(constant) => match constant {
+            ConstantValue::Str(str) => JsonValue::String(str.as_str().to_string()),
+            ConstantValue::Num(ConstantNumber(num)) => JsonValue::from(*num),
+            ConstantValue::True => JsonValue::Bool(true),
+            ConstantValue::False => JsonValue::Bool(false),
+            ConstantValue::Null => JsonValue::Null,
+            _ => return None,
+        },
+        JsValue::Array { items, .. } => JsonValue::Array(
+            items
+                .iter()
+                .map(js_value_to_json)
+                .collect::<Option<Vec<_>>>()?,
+        ),
+        JsValue::Object { parts, .. } => {
+            let mut object = Map::new();
+            for part in parts {
+                match part {
+                    ObjectPart::KeyValue(key, value) => {
+                        let key = key.as_str()?.to_string();
+                        // Like `JSON.stringify`, `undefined` properties are
+                        // left out.
+                        if matches!(value, JsValue::Constant(ConstantValue::Undefined)) {
+                            object.remove(&key);
+                            continue;
+                        }
+                        object.insert(key, js_value_to_json(value)?);
+                    }
+                    ObjectPart::Spread(value) => {
+                        let JsonValue::Object(spread) = js_value_to_json(value)? else {
+                            return None;
+                        };
+                        object.extend(spread);
+                    }
+                }
+            }
+            JsonValue::Object(object)
+        }
+        JsValue::Concat(_, parts) => {
+            let mut str = String::new();
+            for part in parts {
+                match js_value_to_json(part)? {
+                    JsonValue::String(part) => str.push_str(&part),
+                    JsonValue::Number(part) => str.push_str(&part.to_string()),
+                    _ => return None,
+                }
+            }
+            JsonValue::String(str)
+        }
+        _ => return None,
+    })
+}
+
+/// Resolves the static metadata of a page from its layouts and the page
+/// itself. Pages with parallel routes or metadata files are left to the
+/// runtime.
+#[turbo_tasks::function]
+pub async fn resolve_static_metadata_from_loader_tree(
+    loader_tree: Vc<LoaderTree>,
+    context: Vc<Box<dyn AssetContext>>,
+) -> Result<Vc<OptionStaticMetadata>> {
+    let mut visitor = StaticMetadataVisitor {
+        context,
+        resolver: MetadataResolver::default(),
+    };
+    if visit_loader_tree(loader_tree, &mut visitor).await? == VisitControlFlow::Stop {
+        return Ok(Vc::cell(None));
+    }
+    Ok(Vc::cell(Some(visitor.resolver.finish())))
+}
+
+/// Collects the static metadata of the segments, stopping at the first one
+/// which isn't static.
+struct StaticMetadataVisitor {
+    context: Vc<Box<dyn AssetContext>>,
+    resolver: MetadataResolver,
+}
+
+#[async_trait]
+impl LoaderTreeVisitor for StaticMetadataVisitor {
+    async fn enter_segment(
+        &mut self,
+        tree: &LoaderTree,
+        components: &Components,
+    ) -> Result<VisitControlFlow> {
+        if !components.metadata.is_empty()
+            || tree.parallel_routes.keys().any(|key| key != "children")
+        {
+            return Ok(VisitControlFlow::Stop);
+        }
+        for component in [components.layout, components.page].into_iter().flatten() {
+            let source = Vc::upcast(FileSource::new(component));
+            let module = self.context.process(
+                source,
+                turbo_tasks::Value::new(ReferenceType::EcmaScriptModules(
+                    EcmaScriptModulesReferenceSubType::Undefined,
+                )),
+            );
+            let Some(metadata) = &*parse_static_metadata_from_source(module).await? else {
+                return Ok(VisitControlFlow::Stop);
+            };
+            self.resolver.apply_segment(metadata);
+        }
+        Ok(VisitControlFlow::Continue)
+    }
+}
+
+/// Merges the metadata of the segments of a page from the root layout to the
+/// page, like `resolveMetadata` of `next/dist/lib/metadata/resolve-metadata`:
+/// fields of later segments replace the ones of their parents, and a
+/// `title.template` applies to the titles of the segments below it.
+#[derive(Default)]
+struct MetadataResolver {
+    metadata: Map<String, JsonValue>,
+    title_template: Option<String>,
+}
+
+impl MetadataResolver {
+    fn apply_segment(&mut self, metadata: &JsonValue) {
+        let JsonValue::Object(metadata) = metadata else {
+            return;
+        };
+        let mut next_template = None;
+        for (key, value) in metadata {
+            if key == "title" {
+                let (title, template) = self.resolve_title(value);
+                self.metadata.insert(
+                    key.clone(),
+                    title.map_or(JsonValue::Null, JsonValue::String),
+                );
+                next_template = template;
+            } else {
+                self.metadata.insert(key.clone(), value.clone());
+            }
+        }
+        if let Some(template) = next_template {
+            self.title_template = Some(template);
+        }
+    }
+
+    /// Returns the resolved title and the template it defines for the segments
+    /// below.
+    fn resolve_title(&self, title: &JsonValue) -> (Option<String>, Option<String>) {
+        let apply_template = |title: &str| match &self.title_template {
+            Some(template) => template.replace("%s", title),
+            None => title.to_string(),
+        };
+        match title {
+            JsonValue::String(title) => (Some(apply_template(title)), None),
+            JsonValue::Object(title) => {
+                let template = title
+                    .get("template")
+                    .and_then(|template| template.as_str())
+                    .map(|template| template.to_string());
+                let resolved = if let Some(absolute) = title.get("absolute") {
+                    absolute.as_str().map(|absolute| absolute.to_string())
+                } else {
+                    title
+                        .get("default")
+                        .and_then(|default| default.as_str())
+                        .map(apply_template)
+                };
+                (resolved, template)
+            }
+            _ => (None, None),
+        }
+    }
+
+    fn finish(self) -> JsonValue {
+        JsonValue::Object(self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap, DUMMY_SP},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_module, Syntax},
+        },
+    };
+
+    use super::*;
+
+    /// The problems of a module, without their spans.
+    fn problems(code: &str) -> Vec<MetadataExportProblem> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let module = parse_file_as_module(
+            &fm,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        metadata_export_problems(&module)
+            .into_iter()
+            .map(|problem| match problem {
+                MetadataExportProblem::ViewportField { field, .. } => {
+                    MetadataExportProblem::ViewportField {
+                        field,
+                        span: DUMMY_SP,
+                    }
+                }
+                MetadataExportProblem::ConflictingExports {
+                    name,
+                    generate_name,
+                    ..
+                } => MetadataExportProblem::ConflictingExports {
+                    name,
+                    generate_name,
+                    span: DUMMY_SP,
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_metadata_export_problems() {
+        assert_eq!(
+            problems(
+                r#"
+                export const metadata = {
+                    title: "Acme",
+                    themeColor: "black",
+                    "colorScheme": "dark",
+                };
+                export const viewport = { width: "device-width" };
+                "#
+            ),
+            vec![
+                MetadataExportProblem::ViewportField {
+                    field: "themeColor".to_string(),
+                    span: DUMMY_SP,
+                },
+                MetadataExportProblem::ViewportField {
+                    field: "colorScheme".to_string(),
+                    span: DUMMY_SP,
+                },
+            ]
+        );
+
+        assert_eq!(
+            problems(
+                r#"
+                export const metadata = { title: "Acme" };
+                export async function generateMetadata() { return {}; }
+                export const viewport = { width: "device-width" };
+                export function generateViewport() { return {}; }
+                "#
+            ),
+            vec![
+                MetadataExportProblem::ConflictingExports {
+                    name: "metadata",
+                    generate_name: "generateMetadata",
+                    span: DUMMY_SP,
+                },
+                MetadataExportProblem::ConflictingExports {
+                    name: "viewport",
+                    generate_name: "generateViewport",
+                    span: DUMMY_SP,
+                },
+            ]
+        );
+
+        assert!(problems(
+            r#"
+            export const viewport = { themeColor: "black" };
+            export function generateMetadata() { return {}; }
+            "#
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_resolve_metadata() {
+        let mut resolver = MetadataResolver::default();
+        resolver.apply_segment(&json!({
+            "title": { "template": "%s | Acme", "default": "Acme" },
+            "description": "The Acme store",
+        }));
+        resolver.apply_segment(&json!({ "title": "Blog" }));
+        resolver.apply_segment(&json!({ "keywords": ["posts"] }));
+        assert_eq!(
+            resolver.finish(),
+            json!({
+                "title": "Blog | Acme",
+                "description": "The Acme store",
+                "keywords": ["posts"],
+            })
+        );
+
+        assert_eq!(
+            viewport_to_metadata(
+                json!({ "themeColor": "black", "width": "device-width" })
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            ),
+            *json!({ "themeColor": "black", "viewport": { "width": "device-width" } })
+                .as_object()
+                .unwrap()
+        );
+
+        let mut resolver = MetadataResolver::default();
+        resolver.apply_segment(&json!({ "title": { "template": "%s | Acme" } }));
+        resolver.apply_segment(&json!({ "title": { "absolute": "Home" } }));
+        assert_eq!(resolver.finish(), json!({ "title": "Home" }));
+    }
+}
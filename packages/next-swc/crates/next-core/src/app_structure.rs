@@ -1,6 +1,8 @@
 use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{bail, Result};
+use async_recursion::async_recursion;
+use async_trait::async_trait;
 use indexmap::{
     indexmap,
     map::{Entry, OccupiedEntry},
@@ -19,9 +21,10 @@ use turbopack_binding::{
 };
 
 use crate::{
-    next_app::{AppPage, AppPath},
-    next_config::NextConfig,
+    next_app::{AppPage, AppPath, AppRouterTrie, PageSegment},
+    next_config::{AppI18nConfig, NextConfig, OptionAppI18nConfig},
     next_import_map::get_next_package,
+    util::resolve_directory_entry,
 };
 
 /// A final route in the app directory.
@@ -170,6 +173,10 @@ pub struct GlobalMetadata {
     pub robots: Option<MetadataItem>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sitemap: Option<MetadataItem>,
+    /// A static `icon.png` in the root of the app directory, which
+    /// `favicon.ico` is generated from when there's none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<MetadataItem>,
 }
 
 impl GlobalMetadata {
@@ -178,8 +185,9 @@ impl GlobalMetadata {
             favicon,
             robots,
             sitemap,
+            icon,
         } = self;
-        favicon.is_none() && robots.is_none() && sitemap.is_none()
+        favicon.is_none() && robots.is_none() && sitemap.is_none() && icon.is_none()
     }
 }
 
@@ -231,13 +239,30 @@ impl OptionAppDir {
 }
 
 /// Finds and returns the [DirectoryTree] of the app directory if existing.
+/// `app` takes precedence over `src/app`, a warning is emitted when both
+/// exist.
 #[turbo_tasks::function]
 pub async fn find_app_dir(project_path: Vc<FileSystemPath>) -> Result<Vc<OptionAppDir>> {
     let app = project_path.join("app".to_string());
     let src_app = project_path.join("src/app".to_string());
-    let app_dir = if *app.get_type().await? == FileSystemEntryType::Directory {
+    let has_app = *app.get_type().await? == FileSystemEntryType::Directory;
+    let has_src_app = *src_app.get_type().await? == FileSystemEntryType::Directory;
+    if has_app && has_src_app {
+        DirectoryTreeIssue {
+            app_dir: src_app,
+            message: Vc::cell(
+                "Both `app` and `src/app` directories exist, `src/app` is ignored. Move the \
+                 routes into one of them."
+                    .to_string(),
+            ),
+            severity: IssueSeverity::Warning.cell(),
+        }
+        .cell()
+        .emit();
+    }
+    let app_dir = if has_app {
         app
-    } else if *src_app.get_type().await? == FileSystemEntryType::Directory {
+    } else if has_src_app {
         src_app
     } else {
         return Ok(Vc::cell(None));
@@ -249,10 +274,35 @@ pub async fn find_app_dir(project_path: Vc<FileSystemPath>) -> Result<Vc<OptionA
 }
 
 /// Finds and returns the [DirectoryTree] of the app directory if enabled and
-/// existing.
+/// existing. The location configured with `experimental.appDirPath` replaces
+/// the lookup of `app` and `src/app`.
 #[turbo_tasks::function]
-pub async fn find_app_dir_if_enabled(project_path: Vc<FileSystemPath>) -> Result<Vc<OptionAppDir>> {
-    Ok(find_app_dir(project_path))
+pub async fn find_app_dir_if_enabled(
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<OptionAppDir>> {
+    let Some(app_dir_path) = &*next_config.app_dir_path().await? else {
+        return Ok(find_app_dir(project_path));
+    };
+
+    let app_dir = *project_path.try_join(app_dir_path.clone()).await?;
+    if let Some(app_dir) = app_dir {
+        if *app_dir.get_type().await? == FileSystemEntryType::Directory {
+            return Ok(Vc::cell(Some(app_dir.resolve().await?)));
+        }
+    }
+
+    DirectoryTreeIssue {
+        app_dir: project_path,
+        message: Vc::cell(format!(
+            "The app directory configured with `experimental.appDirPath` doesn't exist: \
+             {app_dir_path}"
+        )),
+        severity: IssueSeverity::Error.cell(),
+    }
+    .cell()
+    .emit();
+    Ok(Vc::cell(None))
 }
 
 static STATIC_LOCAL_METADATA: Lazy<HashMap<&'static str, &'static [&'static str]>> =
@@ -315,7 +365,8 @@ async fn get_directory_tree(
     let mut metadata_favicon = Vec::new();
 
     for (basename, entry) in entries {
-        match *entry {
+        // Symlinked files and directories are followed to their real path.
+        match resolve_directory_entry(*entry).await? {
             DirectoryEntry::File(file) => {
                 if let Some((stem, ext)) = basename.split_once('.') {
                     if page_extensions_value.iter().any(|e| e == ext) {
@@ -389,7 +440,6 @@ async fn get_directory_tree(
                     subdirectories.insert(get_underscore_normalized_path(basename), result);
                 }
             }
-            // TODO(WEB-952) handle symlinks in app dir
             _ => {}
         }
     }
@@ -420,6 +470,71 @@ pub struct LoaderTree {
     pub components: Vc<Components>,
 }
 
+/// What to do after a [LoaderTreeVisitor] entered a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControlFlow {
+    /// Visit the parallel routes of the segment.
+    Continue,
+    /// Don't visit the parallel routes of the segment, but leave it and
+    /// continue with its siblings.
+    SkipChildren,
+    /// Stop visiting the tree.
+    Stop,
+}
+
+/// Walks a [LoaderTree] depth first. Segments are entered before their
+/// parallel routes are visited, and left afterwards.
+#[async_trait]
+pub trait LoaderTreeVisitor: Send {
+    async fn enter_segment(
+        &mut self,
+        tree: &LoaderTree,
+        components: &Components,
+    ) -> Result<VisitControlFlow>;
+
+    /// Called before the parallel route named `key`, e.g. `children`, is
+    /// visited.
+    async fn enter_parallel_route(&mut self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn leave_parallel_route(&mut self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn leave_segment(&mut self, _tree: &LoaderTree, _components: &Components) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Visits the loader tree with the visitor. Returns [VisitControlFlow::Stop]
+/// when the visitor stopped the walk.
+#[async_recursion]
+pub async fn visit_loader_tree(
+    loader_tree: Vc<LoaderTree>,
+    visitor: &mut dyn LoaderTreeVisitor,
+) -> Result<VisitControlFlow> {
+    let tree = loader_tree.await?;
+    let components = tree.components.await?;
+
+    match visitor.enter_segment(&tree, &components).await? {
+        VisitControlFlow::Continue => {
+            for (key, &parallel_route) in tree.parallel_routes.iter() {
+                visitor.enter_parallel_route(key).await?;
+                if visit_loader_tree(parallel_route, visitor).await? == VisitControlFlow::Stop {
+                    return Ok(VisitControlFlow::Stop);
+                }
+                visitor.leave_parallel_route(key).await?;
+            }
+        }
+        VisitControlFlow::SkipChildren => {}
+        VisitControlFlow::Stop => return Ok(VisitControlFlow::Stop),
+    }
+
+    visitor.leave_segment(&tree, &components).await?;
+    Ok(VisitControlFlow::Continue)
+}
+
 #[turbo_tasks::function]
 async fn merge_loader_trees(
     app_dir: Vc<FileSystemPath>,
@@ -614,16 +729,184 @@ fn add_app_route(
 pub fn get_entrypoints(
     app_dir: Vc<FileSystemPath>,
     page_extensions: Vc<Vec<String>>,
+    app_i18n: Vc<OptionAppI18nConfig>,
 ) -> Vc<Entrypoints> {
-    directory_tree_to_entrypoints(app_dir, get_directory_tree(app_dir, page_extensions))
+    localize_entrypoints(
+        directory_tree_to_entrypoints(app_dir, get_directory_tree(app_dir, page_extensions)),
+        app_i18n,
+    )
+}
+
+/// Adds a `/{locale}/...` variant of every page for each locale of
+/// `experimental.appI18n` other than the default one, which is served without
+/// a prefix. Pages whose first segment already is one of the locales, and
+/// route handlers, are kept as they are.
+///
+/// Every variant is a route of its own, so `generateStaticParams` runs for
+/// each locale.
+#[turbo_tasks::function]
+async fn localize_entrypoints(
+    entrypoints: Vc<Entrypoints>,
+    app_i18n: Vc<OptionAppI18nConfig>,
+) -> Result<Vc<Entrypoints>> {
+    let app_i18n = app_i18n.await?;
+    let Some(app_i18n) = &*app_i18n else {
+        return Ok(entrypoints);
+    };
+    let locales = prefixed_locales(app_i18n)?;
+
+    let entrypoints = entrypoints.await?;
+    let mut result = entrypoints.clone_value();
+    for entrypoint in entrypoints.values() {
+        let Entrypoint::AppPage { page, loader_tree } = entrypoint else {
+            continue;
+        };
+        if is_localized(page, app_i18n) {
+            continue;
+        }
+
+        for locale in &locales {
+            let localized_page = localized_page(page, locale)?;
+            // The app directory may provide its own variant of a page.
+            let pathname = AppPath::from(localized_page.clone()).to_string();
+            if result.contains_key(&pathname) {
+                continue;
+            }
+            result.insert(
+                pathname,
+                Entrypoint::AppPage {
+                    page: localized_page,
+                    loader_tree: localized_loader_tree(*loader_tree, locale.to_string()),
+                },
+            );
+        }
+    }
+
+    Ok(Vc::cell(result))
+}
+
+/// The locales whose pages are served under a prefix.
+fn prefixed_locales(app_i18n: &AppI18nConfig) -> Result<Vec<&str>> {
+    let mut locales = vec![];
+    for locale in &app_i18n.locales {
+        if !matches!(PageSegment::parse(locale), Ok(PageSegment::Static(_))) {
+            bail!("experimental.appI18n: `{locale}` can't be used as a path segment");
+        }
+        if *locale != app_i18n.default_locale && !locales.contains(&locale.as_str()) {
+            locales.push(locale.as_str());
+        }
+    }
+    Ok(locales)
+}
+
+/// Whether the first segment of the page is one of the locales.
+fn is_localized(page: &AppPage, app_i18n: &AppI18nConfig) -> bool {
+    page.iter()
+        .find(|segment| {
+            !matches!(
+                segment,
+                PageSegment::Group(_) | PageSegment::Parallel(_) | PageSegment::PageType(_)
+            )
+        })
+        .map_or(false, |segment| {
+            matches!(segment, PageSegment::Static(name) if app_i18n.locales.contains(name))
+        })
+}
+
+fn localized_page(page: &AppPage, locale: &str) -> Result<AppPage> {
+    let mut localized_page = AppPage::new();
+    localized_page.push(PageSegment::Static(locale.to_string()))?;
+    for segment in page.iter() {
+        localized_page.push(segment.clone())?;
+    }
+    Ok(localized_page)
+}
+
+/// Moves everything below the root of the loader tree into a segment for the
+/// locale, so the root layout wraps all localized pages.
+#[turbo_tasks::function]
+async fn localized_loader_tree(
+    loader_tree: Vc<LoaderTree>,
+    locale: String,
+) -> Result<Vc<LoaderTree>> {
+    let loader_tree = loader_tree.await?;
+    let locale_tree = LoaderTree {
+        segment: locale,
+        parallel_routes: loader_tree.parallel_routes.clone(),
+        components: Components::default().cell(),
+    }
+    .cell();
+    Ok(LoaderTree {
+        segment: loader_tree.segment.clone(),
+        parallel_routes: indexmap! {
+            "children".to_string() => locale_tree,
+        },
+        components: loader_tree.components,
+    }
+    .cell())
 }
 
 #[turbo_tasks::function]
-fn directory_tree_to_entrypoints(
+async fn directory_tree_to_entrypoints(
     app_dir: Vc<FileSystemPath>,
     directory_tree: Vc<DirectoryTree>,
-) -> Vc<Entrypoints> {
-    directory_tree_to_entrypoints_internal(app_dir, "".to_string(), directory_tree, AppPage::new())
+) -> Result<Vc<Entrypoints>> {
+    let entrypoints = directory_tree_to_entrypoints_internal(
+        app_dir,
+        "".to_string(),
+        directory_tree,
+        AppPage::new(),
+    );
+
+    validate_parallel_routes(app_dir, directory_tree, "".to_string()).await?;
+
+    // Entrypoints with the same pathname are already reported while they are
+    // collected. This catches the ones matching the same requests with
+    // different pathnames, like `/blog/[slug]` and `/blog/[id]`.
+    let mut trie = AppRouterTrie::new();
+    let entrypoints_ref = entrypoints.await?;
+    for entrypoint in entrypoints_ref.values() {
+        let (Entrypoint::AppPage { page, .. } | Entrypoint::AppRoute { page, .. }) = entrypoint;
+        if let Err(error) = trie.insert(AppPath::from(page.clone()), ()) {
+            DirectoryTreeIssue {
+                app_dir,
+                message: Vc::cell(error.to_string()),
+                severity: IssueSeverity::Error.cell(),
+            }
+            .cell()
+            .emit();
+        }
+    }
+
+    for (existing, pathname) in casing_conflicts(entrypoints_ref.keys().map(String::as_str)) {
+        DirectoryTreeIssue {
+            app_dir,
+            message: Vc::cell(format!(
+                "The routes {existing} and {pathname} only differ in casing. They can't both \
+                 exist on a case-insensitive file system, like the default ones of macOS and \
+                 Windows."
+            )),
+            severity: IssueSeverity::Warning.cell(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(entrypoints)
+}
+
+/// The pairs of pathnames which only differ in casing. `/About` and `/about`
+/// can both exist on Linux, but not on the case-insensitive file systems macOS
+/// and Windows use by default.
+fn casing_conflicts<'a>(pathnames: impl Iterator<Item = &'a str>) -> Vec<(&'a str, &'a str)> {
+    let mut lowercase_pathnames = HashMap::new();
+    pathnames
+        .filter_map(|pathname| {
+            lowercase_pathnames
+                .insert(pathname.to_lowercase(), pathname)
+                .map(|existing| (existing, pathname))
+        })
+        .collect()
 }
 
 #[turbo_tasks::function]
@@ -786,7 +1069,19 @@ async fn directory_tree_to_entrypoints_internal(
 
         let mut app_page = app_page.clone();
         if parallel_route_key.is_none() {
-            app_page.push_str(subdir_name)?;
+            if let Err(error) = app_page.push_str(subdir_name) {
+                DirectoryTreeIssue {
+                    app_dir,
+                    message: Vc::cell(format!(
+                        "The directory {subdir_name} in {app_page} is not a valid route segment: \
+                         {error}"
+                    )),
+                    severity: IssueSeverity::Error.cell(),
+                }
+                .cell()
+                .emit();
+                continue;
+            }
         }
 
         let map = directory_tree_to_entrypoints_internal(
@@ -827,6 +1122,183 @@ async fn directory_tree_to_entrypoints_internal(
     Ok(Vc::cell(result))
 }
 
+/// The pages and `default.js` files of a directory, as the segments relative
+/// to it. Route groups are left out, parallel routes nested below the
+/// directory aren't included.
+#[turbo_tasks::value]
+struct DirectoryRoutes {
+    pages: Vec<Vec<PageSegment>>,
+    defaults: Vec<Vec<PageSegment>>,
+}
+
+#[turbo_tasks::function]
+async fn directory_routes(directory_tree: Vc<DirectoryTree>) -> Result<Vc<DirectoryRoutes>> {
+    let directory_tree = directory_tree.await?;
+    let components = directory_tree.components.await?;
+
+    let mut pages = vec![];
+    let mut defaults = vec![];
+    if components.page.is_some() {
+        pages.push(vec![]);
+    }
+    if components.default.is_some() {
+        defaults.push(vec![]);
+    }
+    for (name, &subdirectory) in directory_tree.subdirectories.iter() {
+        let segment = match PageSegment::parse(name) {
+            Ok(PageSegment::Parallel(_)) | Err(_) => continue,
+            Ok(PageSegment::Group(_)) => None,
+            Ok(segment) => Some(segment),
+        };
+        let prefixed = |routes: &Vec<Vec<PageSegment>>| {
+            routes
+                .iter()
+                .map(|route| segment.iter().chain(route).cloned().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let routes = directory_routes(subdirectory).await?;
+        pages.extend(prefixed(&routes.pages));
+        defaults.extend(prefixed(&routes.defaults));
+    }
+
+    Ok(DirectoryRoutes { pages, defaults }.cell())
+}
+
+fn is_dynamic_segment(segment: &PageSegment) -> bool {
+    matches!(
+        segment,
+        PageSegment::Dynamic(_) | PageSegment::CatchAll(_) | PageSegment::OptionalCatchAll(_)
+    )
+}
+
+/// Whether the route of a slot matches a route of the children. With
+/// `is_prefix`, the slot route only needs to match the start of the children
+/// route, which is how a `default.js` applies to the routes below it.
+fn slot_route_matches(slot_route: &[PageSegment], route: &[PageSegment], is_prefix: bool) -> bool {
+    let Some((slot_segment, slot_rest)) = slot_route.split_first() else {
+        return is_prefix || route.is_empty();
+    };
+    match slot_segment {
+        PageSegment::CatchAll(_) => !route.is_empty(),
+        PageSegment::OptionalCatchAll(_) => true,
+        _ => {
+            let Some((segment, rest)) = route.split_first() else {
+                return false;
+            };
+            let segment_matches = match (slot_segment, segment) {
+                (PageSegment::Static(a), PageSegment::Static(b)) => a == b,
+                (PageSegment::Dynamic(_), PageSegment::Static(_) | PageSegment::Dynamic(_)) => true,
+                _ => false,
+            };
+            segment_matches && slot_route_matches(slot_rest, rest, is_prefix)
+        }
+    }
+}
+
+/// Whether two routes have the same structure, but name their dynamic params
+/// differently.
+fn has_mismatched_params(slot_route: &[PageSegment], route: &[PageSegment]) -> bool {
+    slot_route.len() == route.len()
+        && slot_route.iter().zip(route).all(|(a, b)| match (a, b) {
+            (PageSegment::Static(a), PageSegment::Static(b)) => a == b,
+            _ => std::mem::discriminant(a) == std::mem::discriminant(b),
+        })
+        && slot_route != route
+}
+
+fn format_route(route: &[PageSegment]) -> String {
+    route.iter().map(|segment| format!("/{segment}")).collect()
+}
+
+/// Checks that the parallel routes of every layout line up with its children.
+/// A slot without a page or `default.js` for a dynamic route of the children
+/// renders a 404 for that route, slot pages using other names for the same
+/// dynamic params as the children page receive different params.
+#[turbo_tasks::function]
+async fn validate_parallel_routes(
+    app_dir: Vc<FileSystemPath>,
+    directory_tree: Vc<DirectoryTree>,
+    path: String,
+) -> Result<Vc<Completion>> {
+    let tree = directory_tree.await?;
+
+    let slots = tree
+        .subdirectories
+        .iter()
+        .filter_map(|(name, &subdirectory)| Some((match_parallel_route(name)?, subdirectory)))
+        .collect::<Vec<_>>();
+    if !slots.is_empty() {
+        let children = directory_routes(directory_tree).await?;
+        for (slot, slot_tree) in slots {
+            let slot_routes = directory_routes(slot_tree).await?;
+            let slot_dir = app_dir.join(
+                format!("{path}/@{slot}")
+                    .trim_start_matches('/')
+                    .to_string(),
+            );
+
+            for route in children
+                .pages
+                .iter()
+                .filter(|route| route.iter().any(is_dynamic_segment))
+            {
+                let is_covered = slot_routes
+                    .pages
+                    .iter()
+                    .any(|slot_route| slot_route_matches(slot_route, route, false))
+                    || slot_routes
+                        .defaults
+                        .iter()
+                        .any(|slot_route| slot_route_matches(slot_route, route, true));
+                if !is_covered {
+                    DirectoryTreeIssue {
+                        app_dir: slot_dir,
+                        message: Vc::cell(format!(
+                            "The parallel route @{slot} at {path}/@{slot} has no page or \
+                             default.js matching {path}{}, which renders a 404 for it. Add \
+                             @{slot}{}/page or @{slot}/default.",
+                            format_route(route),
+                            format_route(route),
+                        )),
+                        severity: IssueSeverity::Error.cell(),
+                    }
+                    .cell()
+                    .emit();
+                }
+            }
+
+            for slot_route in slot_routes.pages.iter() {
+                if let Some(route) = children
+                    .pages
+                    .iter()
+                    .find(|route| has_mismatched_params(slot_route, route))
+                {
+                    DirectoryTreeIssue {
+                        app_dir: slot_dir,
+                        message: Vc::cell(format!(
+                            "The parallel route @{slot} names the dynamic params of {path}{} \
+                             differently than the children: @{slot}{} and {path}{}. Use the same \
+                             names so both receive the same params.",
+                            format_route(route),
+                            format_route(slot_route),
+                            format_route(route),
+                        )),
+                        severity: IssueSeverity::Warning.cell(),
+                    }
+                    .cell()
+                    .emit();
+                }
+            }
+        }
+    }
+
+    for (name, &subdirectory) in tree.subdirectories.iter() {
+        validate_parallel_routes(app_dir, subdirectory, format!("{path}/{name}")).await?;
+    }
+
+    Ok(Completion::new())
+}
+
 /// ref: https://github.com/vercel/next.js/blob/c390c1662bc79e12cf7c037dcb382ef5ead6e492/packages/next/src/build/entries.ts#L119
 /// if path contains %5F, replace it with _.
 fn get_underscore_normalized_path(path: &str) -> String {
@@ -845,8 +1317,14 @@ pub async fn get_global_metadata(
     let mut metadata = GlobalMetadata::default();
 
     for (basename, entry) in entries {
-        if let DirectoryEntry::File(file) = *entry {
+        if let DirectoryEntry::File(file) = resolve_directory_entry(*entry).await? {
             if let Some((stem, ext)) = basename.split_once('.') {
+                if stem == "icon" {
+                    if ext == "png" {
+                        metadata.icon = Some(MetadataItem::Static { path: file });
+                    }
+                    continue;
+                }
                 let list = match stem {
                     "favicon" => Some(&mut metadata.favicon),
                     "sitemap" => Some(&mut metadata.sitemap),
@@ -863,12 +1341,90 @@ pub async fn get_global_metadata(
                 }
             }
         }
-        // TODO(WEB-952) handle symlinks in app dir
     }
 
     Ok(metadata.cell())
 }
 
+/// A `sitemap.xml` or a dynamic `sitemap.(js|ts)` file, which is served below
+/// the path of its directory.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TraceRawVcs, ValueDebugFormat)]
+pub struct Sitemap {
+    /// The directory of the sitemap, e.g. `/(marketing)/blog`.
+    pub directory: AppPage,
+    pub sitemap: MetadataItem,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct Sitemaps(Vec<Sitemap>);
+
+/// Returns the sitemaps of an app directory. Unlike the other global metadata,
+/// sitemaps can be placed in any directory.
+#[turbo_tasks::function]
+pub async fn get_sitemaps(
+    app_dir: Vc<FileSystemPath>,
+    page_extensions: Vc<Vec<String>>,
+) -> Result<Vc<Sitemaps>> {
+    let page_extensions = page_extensions.await?;
+    let mut sitemaps = vec![];
+    let mut queue = vec![(app_dir, AppPage::new())];
+    while let Some((dir, directory)) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+            continue;
+        };
+        let mut static_sitemap = None;
+        let mut dynamic_sitemap = None;
+        for (basename, entry) in entries {
+            match resolve_directory_entry(*entry).await? {
+                DirectoryEntry::File(file) => {
+                    match match_sitemap_file(basename, &page_extensions) {
+                        Some(true) => dynamic_sitemap = Some(MetadataItem::Dynamic { path: file }),
+                        Some(false) => static_sitemap = Some(MetadataItem::Static { path: file }),
+                        None => {}
+                    }
+                }
+                DirectoryEntry::Directory(subdirectory) => {
+                    // appDir ignores paths starting with an underscore. Invalid segments are
+                    // reported with the routes.
+                    if basename.starts_with('_') {
+                        continue;
+                    }
+                    let Ok(subdirectory_page) =
+                        directory.clone_push_str(&get_underscore_normalized_path(basename))
+                    else {
+                        continue;
+                    };
+                    queue.push((subdirectory, subdirectory_page));
+                }
+                _ => {}
+            }
+        }
+        // Like for the global metadata, a static sitemap takes precedence.
+        if let Some(sitemap) = static_sitemap.or(dynamic_sitemap) {
+            sitemaps.push(Sitemap { directory, sitemap });
+        }
+    }
+    sitemaps.sort_by_cached_key(|sitemap| sitemap.directory.to_string());
+
+    Ok(Vc::cell(sitemaps))
+}
+
+/// Whether the file is a sitemap, and if so whether it's a dynamic one.
+fn match_sitemap_file(basename: &str, page_extensions: &[String]) -> Option<bool> {
+    let (stem, ext) = basename.split_once('.')?;
+    if stem != "sitemap" {
+        return None;
+    }
+    if page_extensions.iter().any(|e| e == ext) {
+        return Some(true);
+    }
+    STATIC_GLOBAL_METADATA
+        .get(stem)
+        .unwrap()
+        .contains(&ext)
+        .then_some(false)
+}
+
 #[turbo_tasks::value(shared)]
 struct DirectoryTreeIssue {
     pub severity: Vc<IssueSeverity>,
@@ -905,3 +1461,119 @@ impl Issue for DirectoryTreeIssue {
         self.message
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(route: &str) -> Vec<PageSegment> {
+        route
+            .split('/')
+            .map(|segment| PageSegment::parse(segment).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_slot_route_matches() {
+        assert!(slot_route_matches(
+            &route("photo/[id]"),
+            &route("photo/[id]"),
+            false
+        ));
+        assert!(slot_route_matches(
+            &route("[...all]"),
+            &route("photo/[id]"),
+            false
+        ));
+        assert!(slot_route_matches(
+            &route("photo"),
+            &route("photo/[id]"),
+            true
+        ));
+        assert!(!slot_route_matches(
+            &route("photo"),
+            &route("photo/[id]"),
+            false
+        ));
+        assert!(!slot_route_matches(
+            &route("[id]"),
+            &route("[...slug]"),
+            false
+        ));
+        assert!(!slot_route_matches(
+            &route("photo/[id]"),
+            &route("post/[id]"),
+            false
+        ));
+    }
+
+    #[test]
+    fn test_has_mismatched_params() {
+        assert!(has_mismatched_params(&route("[id]"), &route("[slug]")));
+        assert!(!has_mismatched_params(&route("[id]"), &route("[id]")));
+        assert!(!has_mismatched_params(&route("[id]"), &route("[...id]")));
+        assert!(!has_mismatched_params(&route("a/[id]"), &route("b/[slug]")));
+    }
+
+    fn app_i18n(locales: &[&str], default_locale: &str) -> AppI18nConfig {
+        AppI18nConfig {
+            locales: locales.iter().map(|locale| locale.to_string()).collect(),
+            default_locale: default_locale.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_match_sitemap_file() {
+        let page_extensions = vec!["tsx".to_string(), "ts".to_string()];
+        assert_eq!(
+            match_sitemap_file("sitemap.xml", &page_extensions),
+            Some(false)
+        );
+        assert_eq!(
+            match_sitemap_file("sitemap.ts", &page_extensions),
+            Some(true)
+        );
+        assert_eq!(match_sitemap_file("sitemap.js", &page_extensions), None);
+        assert_eq!(match_sitemap_file("sitemap.xml.ts", &page_extensions), None);
+        assert_eq!(match_sitemap_file("sitemap2.xml", &page_extensions), None);
+        assert_eq!(match_sitemap_file("robots.txt", &page_extensions), None);
+    }
+
+    #[test]
+    fn test_casing_conflicts() {
+        assert_eq!(
+            casing_conflicts(["/about", "/About", "/blog", "/blog/[slug]"].into_iter()),
+            vec![("/about", "/About")]
+        );
+        assert!(casing_conflicts(["/", "/blog", "/blog/[Slug]"].into_iter()).is_empty());
+    }
+
+    #[test]
+    fn test_prefixed_locales() {
+        assert_eq!(
+            prefixed_locales(&app_i18n(&["en", "fr", "de-CH", "fr"], "en")).unwrap(),
+            vec!["fr", "de-CH"]
+        );
+        assert!(prefixed_locales(&app_i18n(&["en", "[lang]"], "en")).is_err());
+        assert!(prefixed_locales(&app_i18n(&["en", "(fr)"], "en")).is_err());
+    }
+
+    #[test]
+    fn test_localized_page() {
+        let app_i18n = app_i18n(&["en", "fr"], "en");
+        let page = |page: &str| AppPage::parse(page).unwrap();
+
+        assert_eq!(
+            localized_page(&page("/blog/[slug]"), "fr").unwrap(),
+            page("/fr/blog/[slug]")
+        );
+        assert_eq!(
+            AppPath::from(localized_page(&page("/(shop)"), "fr").unwrap()).to_string(),
+            "/fr"
+        );
+        assert!(is_localized(&page("/fr/about"), &app_i18n));
+        assert!(is_localized(&page("/(marketing)/en"), &app_i18n));
+        assert!(!is_localized(&page("/about/fr"), &app_i18n));
+        assert!(!is_localized(&page("/[locale]"), &app_i18n));
+    }
+}
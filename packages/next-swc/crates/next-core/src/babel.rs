@@ -1,4 +1,5 @@
 use anyhow::Result;
+use serde_json::{json, Map as JsonMap};
 use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
     turbo::tasks_fs::{FileSystemEntryType, FileSystemPath},
@@ -9,13 +10,19 @@ use turbopack_binding::{
         },
         node::transforms::webpack::WebpackLoaderItem,
         turbopack::{
-            module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules},
+            condition::ContextCondition,
+            module_options::{
+                module_options_context::ModuleOptionsContext, LoaderRuleItem, OptionWebpackRules,
+                WebpackLoadersOptions, WebpackRules,
+            },
             resolve_options,
             resolve_options_context::ResolveOptionsContext,
         },
     },
 };
 
+use crate::next_config::OptionReactCompilerOptions;
+
 const BABEL_CONFIG_FILES: &[&str] = &[
     ".babelrc",
     ".babelrc.json",
@@ -30,25 +37,28 @@ const BABEL_CONFIG_FILES: &[&str] = &[
 
 /// If the user has a babel configuration file (see list above) alongside their
 /// `next.config.js` configuration, automatically add `babel-loader` as a
-/// webpack loader for each eligible file type if it doesn't already exist.
+/// webpack loader for each of the `babel_rules` if it doesn't already exist.
+/// These are all eligible file types unless `experimental.turbo.babelRules`
+/// narrows them down.
 #[turbo_tasks::function]
 pub async fn maybe_add_babel_loader(
     project_root: Vc<FileSystemPath>,
+    babel_rules: Vc<Vec<String>>,
     webpack_rules: Option<Vc<WebpackRules>>,
 ) -> Result<Vc<OptionWebpackRules>> {
-    let has_babel_config = {
-        let mut has_babel_config = false;
+    let babel_config = {
+        let mut babel_config = None;
         for filename in BABEL_CONFIG_FILES {
             let filetype = *project_root.join(filename.to_string()).get_type().await?;
             if matches!(filetype, FileSystemEntryType::File) {
-                has_babel_config = true;
+                babel_config = Some(*filename);
                 break;
             }
         }
-        has_babel_config
+        babel_config
     };
 
-    if has_babel_config {
+    if let Some(babel_config) = babel_config {
         let mut rules = if let Some(webpack_rules) = webpack_rules {
             webpack_rules.await?.clone_value()
         } else {
@@ -56,7 +66,7 @@ pub async fn maybe_add_babel_loader(
         };
         let mut has_emitted_babel_resolve_issue = false;
         let mut has_changed = false;
-        for pattern in ["*.js", "*.jsx", "*.ts", "*.tsx", "*.cjs", "*.mjs"] {
+        for pattern in babel_rules.await?.iter() {
             let rule = rules.get_mut(pattern);
             let has_babel_loader = if let Some(rule) = rule.as_ref() {
                 rule.loaders
@@ -110,6 +120,28 @@ pub async fn maybe_add_babel_loader(
             }
         }
 
+        let babel_rules = babel_rules.await?;
+        BabelIssue {
+            path: project_root.join(babel_config.to_string()),
+            title: Vc::cell(format!(
+                "Files are transformed with Babel because {babel_config} is present"
+            )),
+            description: Vc::cell(format!(
+                "Babel is used for the files matching {}.\n\nbabel-loader runs in a Node.js \
+                 process, which is much slower than the built-in SWC transforms. To only use \
+                 Babel for the files which need its plugins, list their rules in \
+                 `experimental.turbo.babelRules`, e. g. `[\"legacy/**/*.js\"]`.",
+                babel_rules
+                    .iter()
+                    .map(|rule| format!("`{rule}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            severity: IssueSeverity::Info.cell(),
+        }
+        .cell()
+        .emit();
+
         if has_changed {
             return Ok(Vc::cell(Some(Vc::cell(rules))));
         }
@@ -117,6 +149,107 @@ pub async fn maybe_add_babel_loader(
     Ok(Vc::cell(webpack_rules))
 }
 
+/// Adds `babel-loader` with only babel-plugin-react-compiler to the rules of
+/// JavaScript and TypeScript files when `experimental.reactCompiler` is
+/// enabled. The project's babel config is not used by this loader.
+///
+/// Loaders run from last to first, so the compiler sees the source before any
+/// configured loader changes it.
+#[turbo_tasks::function]
+pub async fn maybe_add_react_compiler_loader(
+    project_root: Vc<FileSystemPath>,
+    react_compiler: Vc<OptionReactCompilerOptions>,
+    webpack_rules: Option<Vc<WebpackRules>>,
+) -> Result<Vc<OptionWebpackRules>> {
+    let react_compiler = react_compiler.await?;
+    let Some(react_compiler) = &*react_compiler else {
+        return Ok(Vc::cell(webpack_rules));
+    };
+
+    if !*is_babel_loader_available(project_root).await? {
+        BabelIssue {
+            path: project_root,
+            title: Vc::cell(
+                "Unable to resolve babel-loader, but experimental.reactCompiler is enabled"
+                    .to_owned(),
+            ),
+            description: Vc::cell(
+                "Make sure babel-loader and babel-plugin-react-compiler are installed via your \
+                 package manager."
+                    .to_owned(),
+            ),
+            severity: IssueSeverity::Fatal.cell(),
+        }
+        .cell()
+        .emit();
+        return Ok(Vc::cell(webpack_rules));
+    }
+
+    let mut rules = if let Some(webpack_rules) = webpack_rules {
+        webpack_rules.await?.clone_value()
+    } else {
+        Default::default()
+    };
+    let plugin_options = serde_json::to_value(react_compiler)?;
+    for (pattern, syntax_plugins) in [
+        ("*.js", &["jsx"][..]),
+        ("*.jsx", &["jsx"]),
+        ("*.ts", &["typescript"]),
+        ("*.tsx", &["typescript", "jsx"]),
+    ] {
+        let mut options = JsonMap::new();
+        options.insert("babelrc".to_string(), false.into());
+        options.insert("configFile".to_string(), false.into());
+        options.insert(
+            "parserOpts".to_string(),
+            json!({ "plugins": syntax_plugins }),
+        );
+        options.insert(
+            "plugins".to_string(),
+            json!([["babel-plugin-react-compiler", plugin_options]]),
+        );
+        let loader = WebpackLoaderItem {
+            loader: "babel-loader".to_string(),
+            options,
+        };
+        if let Some(rule) = rules.get_mut(pattern) {
+            let mut loaders = rule.loaders.await?.clone_value();
+            loaders.push(loader);
+            rule.loaders = Vc::cell(loaders);
+        } else {
+            rules.insert(
+                pattern.to_string(),
+                LoaderRuleItem {
+                    loaders: Vc::cell(vec![loader]),
+                    rename_as: Some("*".to_string()),
+                },
+            );
+        }
+    }
+
+    Ok(Vc::cell(Some(Vc::cell(rules))))
+}
+
+/// Uses `node_modules_webpack_loaders`, the loaders without
+/// babel-plugin-react-compiler, for the packages in node_modules. Packages are
+/// published compiled, and the foreign code condition doesn't cover the ones
+/// listed in `transpilePackages`.
+pub fn exclude_react_compiler_from_node_modules(
+    context: ModuleOptionsContext,
+    node_modules_webpack_loaders: Option<Vc<WebpackLoadersOptions>>,
+) -> ModuleOptionsContext {
+    let node_modules_context = ModuleOptionsContext {
+        enable_webpack_loaders: node_modules_webpack_loaders,
+        ..context.clone()
+    };
+    let mut context = context;
+    context.rules.push((
+        ContextCondition::InDirectory("node_modules".to_string()),
+        node_modules_context.cell(),
+    ));
+    context
+}
+
 #[turbo_tasks::function]
 pub async fn is_babel_loader_available(project_path: Vc<FileSystemPath>) -> Result<Vc<bool>> {
     let result = resolve(
@@ -5,10 +5,12 @@ use turbo_tasks::{
 };
 use turbo_tasks_fs::{rebase, FileSystemPath};
 use turbopack_binding::turbopack::core::{
-    asset::Asset,
+    asset::{Asset, AssetContent},
     output::{OutputAsset, OutputAssets},
 };
 
+use crate::lightningcss_transform::lightningcss_asset_content;
+
 #[turbo_tasks::function]
 pub async fn all_server_paths(
     assets: Vc<OutputAssets>,
@@ -36,19 +38,22 @@ pub async fn all_server_paths(
 /// inside the node root or the client root.
 ///
 /// Assets inside the given client root are rebased to the given client output
-/// path.
+/// path. CSS assets are processed with Lightning CSS for the given browserslist
+/// query, if any.
 #[turbo_tasks::function]
 pub fn emit_all_assets(
     assets: Vc<OutputAssets>,
     node_root: Vc<FileSystemPath>,
     client_relative_path: Vc<FileSystemPath>,
     client_output_path: Vc<FileSystemPath>,
+    lightningcss_targets: Option<Vc<String>>,
 ) -> Vc<Completion> {
     emit_assets(
         all_assets_from_entries(assets),
         node_root,
         client_relative_path,
         client_output_path,
+        lightningcss_targets,
     )
 }
 
@@ -56,13 +61,15 @@ pub fn emit_all_assets(
 /// inside the node root or the client root.
 ///
 /// Assets inside the given client root are rebased to the given client output
-/// path.
+/// path. CSS assets are processed with Lightning CSS for the given browserslist
+/// query, if any.
 #[turbo_tasks::function]
 pub async fn emit_assets(
     assets: Vc<OutputAssets>,
     node_root: Vc<FileSystemPath>,
     client_relative_path: Vc<FileSystemPath>,
     client_output_path: Vc<FileSystemPath>,
+    lightningcss_targets: Option<Vc<String>>,
 ) -> Result<Vc<Completion>> {
     Ok(Completions::all(
         assets
@@ -76,7 +83,7 @@ pub async fn emit_assets(
                     .await?
                     .is_inside_ref(&*node_root.await?)
                 {
-                    return Ok(emit(asset));
+                    return Ok(emit(asset, lightningcss_targets));
                 } else if asset
                     .ident()
                     .path()
@@ -85,7 +92,12 @@ pub async fn emit_assets(
                 {
                     // Client assets are emitted to the client output path, which is prefixed with
                     // _next. We need to rebase them to remove that prefix.
-                    return Ok(emit_rebase(asset, client_relative_path, client_output_path));
+                    return Ok(emit_rebase(
+                        asset,
+                        client_relative_path,
+                        client_output_path,
+                        lightningcss_targets,
+                    ));
                 }
 
                 Ok(Completion::immutable())
@@ -95,9 +107,29 @@ pub async fn emit_assets(
     ))
 }
 
+/// The content of an asset as it's emitted. The output isn't minified, as
+/// production builds emit their assets themselves.
+fn emitted_content(
+    asset: Vc<Box<dyn OutputAsset>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Vc<AssetContent> {
+    match lightningcss_targets {
+        Some(browserslist_query) => lightningcss_asset_content(
+            asset.ident().path(),
+            asset.content(),
+            browserslist_query,
+            false,
+        ),
+        None => asset.content(),
+    }
+}
+
 #[turbo_tasks::function]
-fn emit(asset: Vc<Box<dyn OutputAsset>>) -> Vc<Completion> {
-    asset.content().write(asset.ident().path())
+fn emit(
+    asset: Vc<Box<dyn OutputAsset>>,
+    lightningcss_targets: Option<Vc<String>>,
+) -> Vc<Completion> {
+    emitted_content(asset, lightningcss_targets).write(asset.ident().path())
 }
 
 #[turbo_tasks::function]
@@ -105,10 +137,9 @@ fn emit_rebase(
     asset: Vc<Box<dyn OutputAsset>>,
     from: Vc<FileSystemPath>,
     to: Vc<FileSystemPath>,
+    lightningcss_targets: Option<Vc<String>>,
 ) -> Vc<Completion> {
-    asset
-        .content()
-        .write(rebase(asset.ident().path(), from, to))
+    emitted_content(asset, lightningcss_targets).write(rebase(asset.ident().path(), from, to))
 }
 
 /// Walks the asset graph from multiple assets and collect all referenced
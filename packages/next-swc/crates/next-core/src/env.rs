@@ -61,6 +61,11 @@ pub async fn env_for_js(
         map.insert("__NEXT_STRICT_MODE_APP".to_string(), "true".to_string());
     }
 
+    if next_config.experimental.taint.unwrap_or(false) {
+        map.insert("__NEXT_TAINT".to_string(), "true".to_string());
+        map.insert("__NEXT_EXPERIMENTAL_REACT".to_string(), "true".to_string());
+    }
+
     if !test_mode.is_empty() {
         map.insert("__NEXT_TEST_MODE".to_string(), "true".to_string());
     }
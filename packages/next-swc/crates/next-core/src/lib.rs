@@ -9,6 +9,7 @@
 mod app_render;
 mod app_segment_config;
 mod app_source;
+pub mod app_static_metadata;
 pub mod app_structure;
 mod babel;
 mod bootstrap;
@@ -17,8 +18,10 @@ mod embed_js;
 mod emit;
 pub mod env;
 mod fallback;
+mod lightningcss_transform;
 pub mod loader_tree;
 pub mod middleware;
+mod minify;
 pub mod mode;
 pub mod next_app;
 mod next_build;
@@ -42,29 +45,44 @@ pub mod next_telemetry;
 mod page_loader;
 mod page_source;
 pub mod pages_structure;
+pub mod project_relative_path;
+pub mod public_assets;
+pub mod route_conflicts;
+pub mod route_headers;
+pub mod route_matcher_table;
 pub mod router;
 pub mod router_source;
 mod runtime;
 mod sass;
+mod structured_data;
+mod svg_component;
 pub mod tracing_presets;
 mod transform_options;
 pub mod url_node;
 pub mod util;
 mod web_entry_source;
+mod workspace_root;
+pub mod zones;
 
 pub use app_segment_config::{
-    parse_segment_config_from_loader_tree, parse_segment_config_from_source,
+    parse_segment_config_from_loader_tree, parse_segment_config_from_source, NextSegmentDynamic,
 };
 pub use app_source::create_app_source;
 pub use emit::{all_assets_from_entries, all_server_paths, emit_all_assets, emit_assets};
+pub use lightningcss_transform::lightningcss_asset_content;
+pub use minify::{
+    minify_asset_content, minify_asset_source_map, set_minify_threads, MinifyOptions,
+};
 pub use next_edge::context::{
     get_edge_chunking_context, get_edge_compile_time_info, get_edge_resolve_options_context,
 };
 pub use page_loader::{create_page_loader_entry_module, PageLoaderAsset};
 pub use page_source::create_page_source;
+pub use transform_options::validate_tsconfig_paths;
 pub use turbopack_binding::{turbopack::node::source_map, *};
 pub use util::{get_asset_path_from_pathname, pathname_for_path, PathType};
 pub use web_entry_source::create_web_entry_source;
+pub use workspace_root::find_workspace_root;
 
 pub fn register() {
     turbo_tasks::register();
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use lightningcss::{
+    stylesheet::{MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, StyleSheet},
+    targets::{Browsers, Targets},
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::AssetContent,
+    issue::{Issue, IssueExt, IssueSeverity},
+};
+
+/// Transforms and minifies the content of a CSS output asset with Lightning
+/// CSS when `experimental.useLightningcss` is enabled. Nesting and custom media
+/// queries are lowered for the given browserslist query.
+///
+/// Lightning CSS is stricter than the default CSS pipeline. When it fails to
+/// process a file, a warning is emitted and the content produced by the
+/// default pipeline is kept.
+#[turbo_tasks::function]
+pub async fn lightningcss_asset_content(
+    path: Vc<FileSystemPath>,
+    content: Vc<AssetContent>,
+    browserslist_query: Vc<String>,
+    minify: bool,
+) -> Result<Vc<AssetContent>> {
+    let path_ref = path.await?;
+    if path_ref.extension_ref() != Some("css") {
+        return Ok(content);
+    }
+    let AssetContent::File(file) = &*content.await? else {
+        return Ok(content);
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(content);
+    };
+
+    let code = file.content().to_str()?;
+    match transform_css(
+        &code,
+        path_ref.path.clone(),
+        &browserslist_query.await?,
+        minify,
+    ) {
+        Ok(code) => Ok(AssetContent::file(FileContent::Content(code.into()).cell())),
+        Err(message) => {
+            LightningCssIssue { path, message }.cell().emit();
+            Ok(content)
+        }
+    }
+}
+
+/// Runs Lightning CSS on the output of the default CSS pipeline. CSS Modules
+/// have already been compiled at this point, so class names and keyframes are
+/// printed as they are.
+fn transform_css(
+    code: &str,
+    filename: String,
+    browserslist_query: &str,
+    minify: bool,
+) -> Result<String, String> {
+    let targets = match Browsers::from_browserslist([browserslist_query]) {
+        Ok(browsers) => Targets {
+            browsers,
+            ..Default::default()
+        },
+        Err(err) => return Err(format!("invalid browserslist query: {}", err)),
+    };
+
+    let mut stylesheet = StyleSheet::parse(
+        code,
+        ParserOptions {
+            filename,
+            flags: ParserFlags::NESTING | ParserFlags::CUSTOM_MEDIA,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| err.to_string())?;
+    stylesheet
+        .minify(MinifyOptions {
+            targets,
+            ..Default::default()
+        })
+        .map_err(|err| err.to_string())?;
+    stylesheet
+        .to_css(PrinterOptions {
+            minify,
+            targets,
+            ..Default::default()
+        })
+        .map(|output| output.code)
+        .map_err(|err| err.to_string())
+}
+
+#[turbo_tasks::value(shared)]
+struct LightningCssIssue {
+    path: Vc<FileSystemPath>,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for LightningCssIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("css".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Lightning CSS failed to process this file".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "{}\n\nThe output of the default CSS pipeline is used instead.",
+            self.message
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transform_css;
+
+    const QUERY: &str = "chrome 64, firefox 60, safari 11";
+
+    /// The output of the default pipeline for a CSS Module, with the class
+    /// names and keyframes already hashed.
+    const CSS_MODULE_OUTPUT: &str = r#"
+.Button-module__Bk5C2a__button {
+  animation: Button-module__Bk5C2a__spin 1s;
+
+  & .Button-module__Bk5C2a__icon {
+    color: red;
+  }
+}
+
+@keyframes Button-module__Bk5C2a__spin {
+  to {
+    transform: rotate(360deg);
+  }
+}
+"#;
+
+    #[test]
+    fn test_css_modules_names_are_kept() {
+        for minify in [false, true] {
+            let output = transform_css(
+                CSS_MODULE_OUTPUT,
+                "Button.module.css".to_string(),
+                QUERY,
+                minify,
+            )
+            .unwrap();
+            for name in [
+                ".Button-module__Bk5C2a__button",
+                ".Button-module__Bk5C2a__icon",
+                "Button-module__Bk5C2a__spin",
+                "@keyframes Button-module__Bk5C2a__spin",
+            ] {
+                assert!(output.contains(name), "{name} is missing from {output}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_nesting_is_lowered() {
+        let output = transform_css(
+            CSS_MODULE_OUTPUT,
+            "Button.module.css".to_string(),
+            QUERY,
+            true,
+        )
+        .unwrap();
+        assert!(output.contains(".Button-module__Bk5C2a__button .Button-module__Bk5C2a__icon{"));
+        assert!(!output.contains('&'));
+    }
+
+    #[test]
+    fn test_custom_media_is_lowered() {
+        let output = transform_css(
+            "@custom-media --small (max-width: 30em);\n@media (--small) { .a { color: red } }",
+            "media.css".to_string(),
+            QUERY,
+            true,
+        )
+        .unwrap();
+        assert!(!output.contains("--small"));
+        assert!(output.contains("max-width:30em"));
+    }
+
+    #[test]
+    fn test_invalid_browserslist_query() {
+        assert!(transform_css(".a{}", "a.css".to_string(), "not a browser 1", false).is_err());
+    }
+}
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
-use async_recursion::async_recursion;
+use async_trait::async_trait;
 use indexmap::IndexMap;
 use indoc::formatdoc;
 use turbo_tasks::{Value, ValueToString, Vc};
@@ -10,6 +12,7 @@ use turbopack_binding::turbopack::{
         file_source::FileSource,
         module::Module,
         reference_type::{EcmaScriptModulesReferenceSubType, InnerAssets, ReferenceType},
+        source::Source,
     },
     ecmascript::{magic_identifier, text::TextContentFileSource, utils::StringifyJs},
     r#static::StaticModuleAsset,
@@ -17,11 +20,50 @@ use turbopack_binding::turbopack::{
 };
 
 use crate::{
-    app_structure::{Components, LoaderTree, Metadata, MetadataItem, MetadataWithAltItem},
+    app_structure::{
+        visit_loader_tree, Components, LoaderTree, LoaderTreeVisitor, Metadata, MetadataItem,
+        MetadataWithAltItem, VisitControlFlow,
+    },
     mode::NextMode,
-    next_image::module::{BlurPlaceholderMode, StructuredImageModuleType},
+    next_app::metadata_file_validation::validate_static_metadata_file,
+    next_image::{
+        image_info,
+        module::{BlurPlaceholderMode, StructuredImageModuleType},
+    },
 };
 
+/// The content type of a metadata image, added to its `<link>` or `<meta>`
+/// tags like the webpack `next-metadata-image-loader` does.
+#[turbo_tasks::function]
+async fn metadata_image_content_type(path: Vc<FileSystemPath>) -> Result<Vc<Option<String>>> {
+    let extension = path.extension().await?;
+    Ok(Vc::cell(match extension.as_str() {
+        "ico" | "jpg" | "jpeg" | "png" | "svg" | "gif" | "webp" | "avif" => {
+            mime_guess::from_ext(&extension)
+                .first()
+                .map(|mime| mime.to_string())
+        }
+        _ => None,
+    }))
+}
+
+/// The `sizes` of an icon, its dimensions probed at compile time. SVGs and
+/// images whose size can't be read use `any`, so they scale to whatever size
+/// the browser needs.
+#[turbo_tasks::function]
+async fn metadata_icon_sizes(path: Vc<FileSystemPath>) -> Result<Vc<String>> {
+    if path.extension().await?.as_str() == "svg" {
+        return Ok(Vc::cell("any".to_string()));
+    }
+    let info = image_info(FileSource::new(path).ident(), path.read(), false).await;
+    Ok(Vc::cell(match info {
+        Ok(info) if info.width > 0 && info.height > 0 => {
+            format!("{}x{}", info.width, info.height)
+        }
+        _ => "any".to_string(),
+    }))
+}
+
 pub struct LoaderTreeBuilder {
     inner_assets: IndexMap<String, Vc<Box<dyn Module>>>,
     counter: usize,
@@ -32,6 +74,18 @@ pub struct LoaderTreeBuilder {
     mode: NextMode,
     server_component_transition: ServerComponentTransition,
     pages: Vec<Vc<FileSystemPath>>,
+    /// The components which are already imported, by their path. A layout or
+    /// template which is reached through multiple route groups or parallel
+    /// routes is only imported once, so it's a single module instance.
+    components: HashMap<String, ComponentIdentifiers>,
+}
+
+/// The identifiers a component is imported as.
+#[derive(Clone, Debug)]
+struct ComponentIdentifiers {
+    identifier: String,
+    /// The chunks of the component, only imported in development.
+    chunks_identifier: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -81,6 +135,7 @@ impl LoaderTreeBuilder {
             server_component_transition,
             mode,
             pages: Vec::new(),
+            components: HashMap::new(),
         }
     }
 
@@ -103,68 +158,100 @@ impl LoaderTreeBuilder {
             }
 
             let name = ty.name();
-            let i = self.unique_number();
-            let identifier = magic_identifier::mangle(&format!("{name} #{i}"));
+            let path = component.to_string().await?;
+            let ComponentIdentifiers {
+                identifier,
+                chunks_identifier,
+            } = match self.components.get(&*path) {
+                Some(identifiers) => identifiers.clone(),
+                None => {
+                    let identifiers = self.import_component(name, component);
+                    self.components.insert((*path).clone(), identifiers.clone());
+                    identifiers
+                }
+            };
 
-            match self.mode {
-                NextMode::Development | NextMode::DevServer => {
-                    let chunks_identifier =
-                        magic_identifier::mangle(&format!("chunks of {name} #{i}"));
+            match chunks_identifier {
+                Some(chunks_identifier) => {
                     writeln!(
                         self.loader_tree_code,
                         "  {name}: [() => {identifier}, JSON.stringify({chunks_identifier}) + \
                          '.js'],",
                         name = StringifyJs(name)
                     )?;
-                    self.imports.push(formatdoc!(
-                        r#"
-                            ("TURBOPACK {{ chunking-type: isolatedParallel }}");
-                            import {}, {{ chunks as {} }} from "COMPONENT_{}";
-                        "#,
-                        identifier,
-                        chunks_identifier,
-                        i
-                    ));
                 }
-                NextMode::Build => {
+                None => {
                     writeln!(
                         self.loader_tree_code,
                         "  {name}: [() => {identifier}, {path}],",
                         name = StringifyJs(name),
-                        path = StringifyJs(&component.to_string().await?)
+                        path = StringifyJs(&*path)
                     )?;
-
-                    self.imports.push(formatdoc!(
-                        r#"
-                        import {} from "COMPONENT_{}";
-                        "#,
-                        identifier,
-                        i
-                    ));
                 }
             }
+        }
+        Ok(())
+    }
+
+    /// Imports a layout, page or other component into the loader tree module.
+    fn import_component(
+        &mut self,
+        name: &str,
+        component: Vc<FileSystemPath>,
+    ) -> ComponentIdentifiers {
+        let i = self.unique_number();
+        let identifier = magic_identifier::mangle(&format!("{name} #{i}"));
+
+        let chunks_identifier = match self.mode {
+            NextMode::Development | NextMode::DevServer => {
+                let chunks_identifier = magic_identifier::mangle(&format!("chunks of {name} #{i}"));
+                self.imports.push(formatdoc!(
+                    r#"
+                        ("TURBOPACK {{ chunking-type: isolatedParallel }}");
+                        import {}, {{ chunks as {} }} from "COMPONENT_{}";
+                    "#,
+                    identifier,
+                    chunks_identifier,
+                    i
+                ));
+                Some(chunks_identifier)
+            }
+            NextMode::Build => {
+                self.imports.push(formatdoc!(
+                    r#"
+                    import {} from "COMPONENT_{}";
+                    "#,
+                    identifier,
+                    i
+                ));
+                None
+            }
+        };
 
-            let source = Vc::upcast(FileSource::new(component));
-            let reference_ty = Value::new(ReferenceType::EcmaScriptModules(
-                EcmaScriptModulesReferenceSubType::Undefined,
-            ));
+        let source = Vc::upcast(FileSource::new(component));
+        let reference_ty = Value::new(ReferenceType::EcmaScriptModules(
+            EcmaScriptModulesReferenceSubType::Undefined,
+        ));
 
-            let module = match &self.server_component_transition {
-                ServerComponentTransition::Transition(transition) => {
-                    transition.process(source, self.context, reference_ty)
-                }
-                ServerComponentTransition::TransitionName(transition_name) => self
-                    .context
-                    .with_transition(transition_name.clone())
-                    .process(source, reference_ty),
-            };
+        let module = match &self.server_component_transition {
+            ServerComponentTransition::Transition(transition) => {
+                transition.process(source, self.context, reference_ty)
+            }
+            ServerComponentTransition::TransitionName(transition_name) => self
+                .context
+                .with_transition(transition_name.clone())
+                .process(source, reference_ty),
+        };
 
-            self.inner_assets.insert(format!("COMPONENT_{i}"), module);
+        self.inner_assets.insert(format!("COMPONENT_{i}"), module);
+
+        ComponentIdentifiers {
+            identifier,
+            chunks_identifier,
         }
-        Ok(())
     }
 
-    fn write_metadata(&mut self, metadata: &Metadata) -> Result<()> {
+    async fn write_metadata(&mut self, metadata: &Metadata) -> Result<()> {
         if metadata.is_empty() {
             return Ok(());
         }
@@ -177,22 +264,25 @@ impl LoaderTreeBuilder {
             manifest,
         } = metadata;
         self.loader_tree_code += "  metadata: {";
-        self.write_metadata_items("icon", favicon.iter().chain(icon.iter()))?;
-        self.write_metadata_items("apple", apple.iter())?;
-        self.write_metadata_items("twitter", twitter.iter())?;
-        self.write_metadata_items("openGraph", open_graph.iter())?;
-        self.write_metadata_manifest(*manifest)?;
+        self.write_metadata_items("icon", favicon.iter().chain(icon.iter()))
+            .await?;
+        self.write_metadata_items("apple", apple.iter()).await?;
+        self.write_metadata_items("twitter", twitter.iter()).await?;
+        self.write_metadata_items("openGraph", open_graph.iter())
+            .await?;
+        self.write_metadata_manifest(*manifest).await?;
         self.loader_tree_code += "  },";
         Ok(())
     }
 
-    fn write_metadata_manifest(&mut self, manifest: Option<MetadataItem>) -> Result<()> {
+    async fn write_metadata_manifest(&mut self, manifest: Option<MetadataItem>) -> Result<()> {
         let Some(manifest) = manifest else {
             return Ok(());
         };
         match manifest {
             MetadataItem::Static { path } => {
                 use std::fmt::Write;
+                validate_static_metadata_file(path).await?;
                 let i = self.unique_number();
                 let identifier = magic_identifier::mangle(&format!("manifest #{i}"));
                 let inner_module_id = format!("METADATA_{i}");
@@ -215,7 +305,7 @@ impl LoaderTreeBuilder {
         Ok(())
     }
 
-    fn write_metadata_items<'a>(
+    async fn write_metadata_items<'a>(
         &mut self,
         name: &str,
         it: impl Iterator<Item = &'a MetadataWithAltItem>,
@@ -227,13 +317,13 @@ impl LoaderTreeBuilder {
         }
         writeln!(self.loader_tree_code, "    {name}: [")?;
         for item in it {
-            self.write_metadata_item(name, item)?;
+            self.write_metadata_item(name, item).await?;
         }
         writeln!(self.loader_tree_code, "    ],")?;
         Ok(())
     }
 
-    fn write_metadata_item(&mut self, name: &str, item: &MetadataWithAltItem) -> Result<()> {
+    async fn write_metadata_item(&mut self, name: &str, item: &MetadataWithAltItem) -> Result<()> {
         use std::fmt::Write;
         let i = self.unique_number();
         let identifier = magic_identifier::mangle(&format!("{name} #{i}"));
@@ -253,14 +343,23 @@ impl LoaderTreeBuilder {
                 );
                 writeln!(self.loader_tree_code, "{s}(async (props) => [{{")?;
                 writeln!(self.loader_tree_code, "{s}  url: {identifier}.src,")?;
+                if let Some(content_type) = metadata_image_content_type(*path).await?.as_deref() {
+                    writeln!(
+                        self.loader_tree_code,
+                        "{s}  type: {},",
+                        StringifyJs(content_type)
+                    )?;
+                }
                 let numeric_sizes = name == "twitter" || name == "openGraph";
                 if numeric_sizes {
                     writeln!(self.loader_tree_code, "{s}  width: {identifier}.width,")?;
                     writeln!(self.loader_tree_code, "{s}  height: {identifier}.height,")?;
                 } else {
+                    let sizes = metadata_icon_sizes(*path).await?;
                     writeln!(
                         self.loader_tree_code,
-                        "{s}  sizes: `${{{identifier}.width}}x${{{identifier}.height}}`,"
+                        "{s}  sizes: {},",
+                        StringifyJs(&*sizes)
                     )?;
                 }
                 if let Some(alt_path) = alt_path {
@@ -288,29 +387,53 @@ impl LoaderTreeBuilder {
         Ok(())
     }
 
-    #[async_recursion]
-    async fn walk_tree(&mut self, loader_tree: Vc<LoaderTree>) -> Result<()> {
-        use std::fmt::Write;
+    async fn build(mut self, loader_tree: Vc<LoaderTree>) -> Result<LoaderTreeModule> {
+        visit_loader_tree(loader_tree, &mut self).await?;
+        Ok(LoaderTreeModule {
+            imports: self.imports,
+            loader_tree_code: self.loader_tree_code,
+            inner_assets: self.inner_assets,
+            unsupported_metadata: self.unsupported_metadata,
+            pages: self.pages,
+        })
+    }
+}
 
-        let LoaderTree {
-            segment,
-            parallel_routes,
-            components,
-        } = &*loader_tree.await?;
+/// Writes the loader tree as JS, e.g.
+/// `["", { children: [...] }, { layout: [...], metadata: {...} }]`.
+#[async_trait]
+impl LoaderTreeVisitor for LoaderTreeBuilder {
+    async fn enter_segment(
+        &mut self,
+        tree: &LoaderTree,
+        _components: &Components,
+    ) -> Result<VisitControlFlow> {
+        use std::fmt::Write;
 
         writeln!(
             self.loader_tree_code,
             "[{segment}, {{",
-            segment = StringifyJs(segment)
+            segment = StringifyJs(&tree.segment)
         )?;
-        // add parallel_routes
-        for (key, &parallel_route) in parallel_routes.iter() {
-            write!(self.loader_tree_code, "{key}: ", key = StringifyJs(key))?;
-            self.walk_tree(parallel_route).await?;
-            writeln!(self.loader_tree_code, ",")?;
-        }
+        Ok(VisitControlFlow::Continue)
+    }
+
+    async fn enter_parallel_route(&mut self, key: &str) -> Result<()> {
+        use std::fmt::Write;
+
+        write!(self.loader_tree_code, "{key}: ", key = StringifyJs(key))?;
+        Ok(())
+    }
+
+    async fn leave_parallel_route(&mut self, _key: &str) -> Result<()> {
+        self.loader_tree_code += ",\n";
+        Ok(())
+    }
+
+    async fn leave_segment(&mut self, _tree: &LoaderTree, components: &Components) -> Result<()> {
+        use std::fmt::Write;
+
         writeln!(self.loader_tree_code, "}}, {{")?;
-        // add components
         let Components {
             page,
             default,
@@ -321,7 +444,7 @@ impl LoaderTreeBuilder {
             not_found,
             metadata,
             route: _,
-        } = &*components.await?;
+        } = components;
         self.write_component(ComponentType::Page, *page).await?;
         self.write_component(ComponentType::DefaultPage, *default)
             .await?;
@@ -333,21 +456,10 @@ impl LoaderTreeBuilder {
             .await?;
         self.write_component(ComponentType::NotFound, *not_found)
             .await?;
-        self.write_metadata(metadata)?;
+        self.write_metadata(metadata).await?;
         write!(self.loader_tree_code, "}}]")?;
         Ok(())
     }
-
-    async fn build(mut self, loader_tree: Vc<LoaderTree>) -> Result<LoaderTreeModule> {
-        self.walk_tree(loader_tree).await?;
-        Ok(LoaderTreeModule {
-            imports: self.imports,
-            loader_tree_code: self.loader_tree_code,
-            inner_assets: self.inner_assets,
-            unsupported_metadata: self.unsupported_metadata,
-            pages: self.pages,
-        })
-    }
 }
 
 pub struct LoaderTreeModule {
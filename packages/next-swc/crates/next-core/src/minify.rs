@@ -0,0 +1,273 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use futures::channel::oneshot;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde_json::json;
+use swc_core::{
+    base::{config::JsMinifyOptions, try_with_handler, Compiler, HandlerOpts},
+    common::{errors::ColorConfig, sync::Lrc, FileName, SourceMap, GLOBALS},
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack_binding::turbopack::core::asset::AssetContent;
+
+/// The minify thread pool and the number of threads it was configured with.
+static MINIFY_POOL: Mutex<Option<(usize, Arc<ThreadPool>)>> = Mutex::new(None);
+
+/// Sets the number of threads minifying JavaScript, 0 for the number of CPUs,
+/// which is the default. Every build sets it before it starts, so builds in the
+/// same process can use different numbers. The pool is only replaced when the
+/// number changes, and jobs which were queued on the previous pool still finish
+/// on it.
+pub fn set_minify_threads(threads: usize) -> Result<()> {
+    let mut pool = MINIFY_POOL
+        .lock()
+        .map_err(|_| anyhow!("the minify thread pool is poisoned"))?;
+    if !matches!(&*pool, Some((current, _)) if *current == threads) {
+        *pool = Some((threads, Arc::new(build_minify_pool(threads)?)));
+    }
+    Ok(())
+}
+
+fn minify_pool() -> Result<Arc<ThreadPool>> {
+    let mut pool = MINIFY_POOL
+        .lock()
+        .map_err(|_| anyhow!("the minify thread pool is poisoned"))?;
+    if pool.is_none() {
+        *pool = Some((0, Arc::new(build_minify_pool(0)?)));
+    }
+    Ok(pool.as_ref().unwrap().1.clone())
+}
+
+fn build_minify_pool(threads: usize) -> Result<ThreadPool> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("minify-{}", index))
+        .build()
+        .context("failed to build the minify thread pool")
+}
+
+/// Options for the production minifier. These mirror the terser options of
+/// the same name.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct MinifyOptions {
+    /// Don't drop or mangle function names.
+    pub keep_fnames: bool,
+    /// Don't drop or mangle class names.
+    pub keep_classnames: bool,
+    /// Identifiers which must not be mangled.
+    pub mangle_reserved: Vec<String>,
+    /// Functions whose calls can be dropped when their result is unused, e. g.
+    /// `console.info`.
+    pub pure_funcs: Vec<String>,
+}
+
+impl MinifyOptions {
+    fn to_swc_options(&self, input_source_map: Option<&str>) -> Result<JsMinifyOptions> {
+        let source_map = match input_source_map {
+            Some(content) => json!({ "content": content }),
+            None => json!(false),
+        };
+        serde_json::from_value(json!({
+            "compress": {
+                "keep_fnames": self.keep_fnames,
+                "keep_classnames": self.keep_classnames,
+                "pure_funcs": self.pure_funcs,
+            },
+            "mangle": {
+                "keep_fnames": self.keep_fnames,
+                "keep_classnames": self.keep_classnames,
+                "reserved": self.mangle_reserved,
+            },
+            "sourceMap": source_map,
+        }))
+        .context("invalid minify options")
+    }
+}
+
+/// Minifies the content of a JavaScript output asset. Other assets are passed
+/// through unchanged.
+///
+/// Every asset is minified in its own task, keyed by the asset rather than by
+/// its code, so rebuilds only minify the chunks whose content actually changed
+/// and the code isn't held a second time as a task argument.
+#[turbo_tasks::function]
+pub async fn minify_asset_content(
+    path: Vc<FileSystemPath>,
+    content: Vc<AssetContent>,
+    source_map: Option<Vc<AssetContent>>,
+    options: Vc<MinifyOptions>,
+) -> Result<Vc<AssetContent>> {
+    let Some(minified) = *minify_asset(path, content, source_map, options).await? else {
+        return Ok(content);
+    };
+    Ok(AssetContent::file(minified.await?.code))
+}
+
+/// The source map of a minified JavaScript output asset, which maps the
+/// minified code back to the sources the original source map points to.
+#[turbo_tasks::function]
+pub async fn minify_asset_source_map(
+    path: Vc<FileSystemPath>,
+    content: Vc<AssetContent>,
+    source_map: Vc<AssetContent>,
+    options: Vc<MinifyOptions>,
+) -> Result<Vc<AssetContent>> {
+    let Some(minified) = *minify_asset(path, content, Some(source_map), options).await? else {
+        return Ok(source_map);
+    };
+    Ok(match minified.await?.source_map {
+        Some(source_map) => AssetContent::file(source_map),
+        None => source_map,
+    })
+}
+
+#[turbo_tasks::value(shared)]
+struct MinifiedAsset {
+    code: Vc<FileContent>,
+    source_map: Option<Vc<FileContent>>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct OptionMinifiedAsset(Option<Vc<MinifiedAsset>>);
+
+#[turbo_tasks::function]
+async fn minify_asset(
+    path: Vc<FileSystemPath>,
+    content: Vc<AssetContent>,
+    source_map: Option<Vc<AssetContent>>,
+    options: Vc<MinifyOptions>,
+) -> Result<Vc<OptionMinifiedAsset>> {
+    let path = path.await?;
+    if !matches!(path.extension_ref(), Some("js" | "mjs" | "cjs")) {
+        return Ok(Vc::cell(None));
+    }
+    let Some(code) = file_content_string(content).await? else {
+        return Ok(Vc::cell(None));
+    };
+    let input_source_map = match source_map {
+        Some(source_map) => file_content_string(source_map).await?,
+        None => None,
+    };
+
+    let (code, source_map) = minify_code(code, input_source_map, &*options.await?)
+        .await
+        .with_context(|| format!("failed to minify {}", path.path))?;
+
+    Ok(Vc::cell(Some(
+        MinifiedAsset {
+            code: FileContent::Content(code.into()).cell(),
+            source_map: source_map.map(|source_map| FileContent::Content(source_map.into()).cell()),
+        }
+        .cell(),
+    )))
+}
+
+async fn file_content_string(content: Vc<AssetContent>) -> Result<Option<String>> {
+    let AssetContent::File(file) = &*content.await? else {
+        return Ok(None);
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(None);
+    };
+    Ok(Some(file.content().to_str()?.into_owned()))
+}
+
+/// Minifies JavaScript code on the minify thread pool, so it doesn't block the
+/// workers of turbo-tasks.
+async fn minify_code(
+    code: String,
+    input_source_map: Option<String>,
+    options: &MinifyOptions,
+) -> Result<(String, Option<String>)> {
+    let options = options.clone();
+
+    let (sender, receiver) = oneshot::channel();
+    minify_pool()?.spawn(move || {
+        // Nobody waits for the result anymore, e.g. because the build was
+        // stopped while the job was queued.
+        if sender.is_canceled() {
+            return;
+        }
+        let _ = sender.send(minify(code, input_source_map.as_deref(), &options));
+    });
+    receiver.await.context("minification was canceled")?
+}
+
+/// Minifies the code and, when the input has a source map, composes a source
+/// map for the minified code with it.
+fn minify(
+    code: String,
+    input_source_map: Option<&str>,
+    options: &MinifyOptions,
+) -> Result<(String, Option<String>)> {
+    // The minifier drops all comments, the reference to the source map has to be
+    // added back.
+    let source_mapping_url = input_source_map.and_then(|_| {
+        code.lines()
+            .rev()
+            .find(|line| line.starts_with("//# sourceMappingURL="))
+            .map(|line| line.to_string())
+    });
+
+    let cm: Lrc<SourceMap> = Default::default();
+    let compiler = Compiler::new(cm.clone());
+    let output = try_with_handler(
+        cm.clone(),
+        HandlerOpts {
+            color: ColorConfig::Never,
+            skip_filename: true,
+        },
+        |handler| {
+            GLOBALS.set(&Default::default(), || {
+                let fm = cm.new_source_file(FileName::Anon, code);
+                compiler.minify(fm, handler, &options.to_swc_options(input_source_map)?)
+            })
+        },
+    )?;
+
+    let code = match source_mapping_url {
+        Some(source_mapping_url) => format!("{}\n{}", output.code, source_mapping_url),
+        None => output.code,
+    };
+    Ok((code, output.map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minify_source_map() {
+        let code = "function add(first, second) {\n  return first + second;\n}\nadd(1, 2);\n//# \
+                    sourceMappingURL=chunk.js.map\n"
+            .to_string();
+        let input_source_map =
+            r#"{"version":3,"sources":["add.ts"],"names":[],"mappings":"AAAA;AACA;AACA;AACA"}"#;
+
+        let (minified, source_map) =
+            minify(code.clone(), Some(input_source_map), &Default::default()).unwrap();
+        assert!(minified.ends_with("\n//# sourceMappingURL=chunk.js.map"));
+        assert!(source_map.unwrap().contains("add.ts"));
+
+        let (minified, source_map) = minify(code, None, &Default::default()).unwrap();
+        assert!(!minified.contains("sourceMappingURL"));
+        assert!(source_map.is_none());
+    }
+
+    #[test]
+    fn test_set_minify_threads() {
+        set_minify_threads(2).unwrap();
+        let pool = minify_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), 2);
+
+        // Setting the same number again, e.g. for a second build, keeps the pool.
+        set_minify_threads(2).unwrap();
+        assert!(Arc::ptr_eq(&pool, &minify_pool().unwrap()));
+
+        set_minify_threads(1).unwrap();
+        assert_eq!(minify_pool().unwrap().current_num_threads(), 1);
+    }
+}
@@ -1,7 +1,8 @@
 use turbo_tasks::Vc;
 use turbopack_binding::turbopack::ecmascript::chunk::EcmascriptChunkPlaceable;
 
-use crate::app_segment_config::NextSegmentConfig;
+use super::{dynamic_api_usage::DynamicApiUsages, segment_boundaries::SegmentBoundaries};
+use crate::{app_segment_config::NextSegmentConfig, app_static_metadata::OptionStaticMetadata};
 
 /// The entry module asset for a Next.js app route or page.
 #[turbo_tasks::value(shared)]
@@ -15,4 +16,16 @@ pub struct AppEntry {
     pub rsc_entry: Vc<Box<dyn EcmascriptChunkPlaceable>>,
     /// The source code config for this entry.
     pub config: Vc<NextSegmentConfig>,
+    /// Whether the responses are streamed, so they shouldn't be buffered.
+    /// Pages are always streamed.
+    pub streaming: bool,
+    /// The dynamic APIs used by the server modules of the route, which make it
+    /// render at request time.
+    pub dynamic_api_usages: Vc<DynamicApiUsages>,
+    /// The metadata of a page which only uses static `metadata` exports,
+    /// resolved at compile time. Always `None` for routes.
+    pub static_metadata: Vc<OptionStaticMetadata>,
+    /// The segments of a page with error, loading or not found boundaries.
+    /// Always empty for routes.
+    pub segment_boundaries: Vc<SegmentBoundaries>,
 }
@@ -5,9 +5,19 @@ use base64::{display::Base64Display, engine::general_purpose::STANDARD};
 use indoc::writedoc;
 use turbo_tasks::{ValueToString, Vc};
 use turbopack_binding::{
-    turbo::tasks_fs::{rope::RopeBuilder, File, FileContent, FileSystemPath},
+    turbo::{
+        tasks_fs::{
+            rope::{Rope, RopeBuilder},
+            File, FileContent, FileSystemPath,
+        },
+        tasks_hash::{encode_hex, hash_xxh3_hash64},
+    },
     turbopack::{
-        core::{asset::AssetContent, virtual_source::VirtualSource},
+        core::{
+            asset::AssetContent,
+            issue::{Issue, IssueExt, IssueSeverity},
+            virtual_source::VirtualSource,
+        },
         ecmascript::utils::StringifyJs,
         turbopack::ModuleAssetContext,
     },
@@ -17,9 +27,12 @@ use super::app_route_entry::get_app_route_entry;
 use crate::{
     app_structure::MetadataItem,
     next_app::{AppEntry, AppPage, PageSegment},
+    next_image::ico::{encode_ico, FAVICON_SIZES},
 };
 
-/// Computes the entry for a Next.js favicon file.
+/// Computes the entry for a Next.js favicon file. A `favicon.ico` is served
+/// as it is, other images like an `icon.png` are converted into an `.ico`
+/// with multiple sizes.
 #[turbo_tasks::function]
 pub async fn get_app_route_favicon_entry(
     nodejs_context: Vc<ModuleAssetContext>,
@@ -35,21 +48,17 @@ pub async fn get_app_route_favicon_entry(
 
     let mut code = RopeBuilder::default();
 
-    let content_type = mime_guess::from_ext(&path.extension().await?)
-        .first_or_octet_stream()
-        .to_string();
-    let original_file_content = path.read().await?;
-    let original_file_content_b64 = match &*original_file_content {
-        FileContent::Content(content) => {
-            let content = content.content().to_bytes()?;
-            Base64Display::new(&content, &STANDARD).to_string()
-        }
-        FileContent::NotFound => {
-            bail!("favicon file not found: {}", &path.to_string().await?);
-        }
+    let content_type = "image/x-icon";
+    let file_content = get_favicon_ico_content(path).await?;
+    let FileContent::Content(file_content) = &*file_content else {
+        bail!("favicon file not found: {}", &path.to_string().await?);
     };
-    // Specific to favicon
+    let file_content = file_content.content().to_bytes()?;
+    let file_content_b64 = Base64Display::new(&file_content, &STANDARD).to_string();
+    // Specific to favicon. Browsers revalidate it on every request, the ETag
+    // lets them do that without downloading it again.
     let cache_control = "public, max-age=0, must-revalidate";
+    let etag = format!("\"{}\"", encode_hex(hash_xxh3_hash64(&*file_content)));
 
     // TODO(alexkirsz) Generalize this to any file.
     writedoc! {
@@ -59,13 +68,24 @@ pub async fn get_app_route_favicon_entry(
 
             const contentType = {content_type}
             const cacheControl = {cache_control}
-            const buffer = Buffer.from({original_file_content_b64}, 'base64')
+            const etag = {etag}
+            const buffer = Buffer.from({file_content_b64}, 'base64')
 
-            export function GET() {{
+            export function GET(request) {{
+                if (request.headers.get('if-none-match') === etag) {{
+                    return new NextResponse(null, {{
+                        status: 304,
+                        headers: {{
+                            'Cache-Control': cacheControl,
+                            ETag: etag,
+                        }},
+                    }})
+                }}
                 return new NextResponse(buffer, {{
                     headers: {{
                         'Content-Type': contentType,
                         'Cache-Control': cacheControl,
+                        ETag: etag,
                     }},
                 }})
             }}
@@ -74,7 +94,8 @@ pub async fn get_app_route_favicon_entry(
             "#,
         content_type = StringifyJs(&content_type),
         cache_control = StringifyJs(&cache_control),
-        original_file_content_b64 = StringifyJs(&original_file_content_b64),
+        etag = StringifyJs(&etag),
+        file_content_b64 = StringifyJs(&file_content_b64),
     }?;
 
     let file = File::from(code.build());
@@ -91,3 +112,71 @@ pub async fn get_app_route_favicon_entry(
         project_root,
     ))
 }
+
+/// The content of `/favicon.ico` for a favicon file of the app directory. A
+/// `favicon.ico` is used as it is, other images like an `icon.png` are
+/// converted into an `.ico` with multiple sizes. Images which can't be
+/// converted are reported and served as they are.
+#[turbo_tasks::function]
+pub async fn get_favicon_ico_content(path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+    let content = path.read();
+    if path.extension().await?.as_str() == "ico" {
+        return Ok(content);
+    }
+    let content_ref = content.await?;
+    let FileContent::Content(file) = &*content_ref else {
+        return Ok(content);
+    };
+    let ico = image::load_from_memory(&file.content().to_bytes()?)
+        .map_err(anyhow::Error::from)
+        .and_then(|image| encode_ico(&image, &FAVICON_SIZES));
+    match ico {
+        Ok(ico) => Ok(FileContent::Content(File::from(Rope::from(ico))).cell()),
+        Err(err) => {
+            FaviconConversionIssue {
+                path,
+                message: format!("{err:#}"),
+            }
+            .cell()
+            .emit();
+            Ok(content)
+        }
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct FaviconConversionIssue {
+    path: Vc<FileSystemPath>,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for FaviconConversionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("The icon couldn't be converted into a favicon.ico".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("metadata".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "{}\n\nThe image is served at /favicon.ico as it is.",
+            self.message
+        ))
+    }
+}
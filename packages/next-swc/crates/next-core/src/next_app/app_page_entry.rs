@@ -1,12 +1,14 @@
 use std::io::Write;
 
 use anyhow::{bail, Result};
+use indexmap::indexmap;
+use indoc::writedoc;
 use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
 use turbopack_binding::{
     turbo::tasks_fs::{rope::RopeBuilder, File, FileSystemPath},
     turbopack::{
         core::{
-            asset::AssetContent, context::AssetContext, issue::IssueExt,
+            asset::AssetContent, context::AssetContext, issue::IssueExt, module::Module,
             reference_type::ReferenceType, virtual_source::VirtualSource,
         },
         ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs},
@@ -16,10 +18,15 @@ use turbopack_binding::{
 
 use super::app_entry::AppEntry;
 use crate::{
+    app_static_metadata::resolve_static_metadata_from_loader_tree,
     app_structure::LoaderTree,
     loader_tree::{LoaderTreeModule, ServerComponentTransition},
     mode::NextMode,
-    next_app::{AppPage, AppPath, UnsupportedDynamicMetadataIssue},
+    next_app::{
+        dynamic_api_usage::find_dynamic_api_usages, segment_boundaries::segment_boundaries,
+        AppPage, AppPath, PageType, UnsupportedDynamicMetadataIssue,
+    },
+    next_config::NextConfig,
     next_server_component::NextServerComponentTransition,
     parse_segment_config_from_loader_tree,
     util::{load_next_js_template, virtual_next_js_template_path, NextRuntime},
@@ -34,8 +41,13 @@ pub async fn get_app_page_entry(
     app_dir: Vc<FileSystemPath>,
     page: AppPage,
     project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    mode: NextMode,
 ) -> Result<Vc<AppEntry>> {
     let config = parse_segment_config_from_loader_tree(loader_tree, Vc::upcast(nodejs_context));
+    let static_metadata =
+        resolve_static_metadata_from_loader_tree(loader_tree, Vc::upcast(nodejs_context));
+    let segment_boundaries = segment_boundaries(loader_tree);
     let is_edge = matches!(config.await?.runtime, Some(NextRuntime::Edge));
     let context = if is_edge {
         edge_context
@@ -76,12 +88,16 @@ pub async fn get_app_page_entry(
         writeln!(result, "{import}")?;
     }
 
+    let page_paths = pages
+        .iter()
+        .map(|page| async move { Ok(page.await?.path.clone()) })
+        .try_join()
+        .await?;
     let pages = pages.iter().map(|page| page.to_string()).try_join().await?;
 
-    let original_name = page.to_string();
     let pathname = AppPath::from(page.clone()).to_string();
 
-    let original_page_name = get_original_page_name(&original_name);
+    let original_page_name = page.to_page_path(PageType::Page);
 
     let template_file = "build/templates/app-page.js";
 
@@ -137,13 +153,21 @@ pub async fn get_app_page_entry(
 
     let source = VirtualSource::new(template_path, AssetContent::file(file.into()));
 
-    let rsc_entry = context.process(
+    let mut rsc_entry = context.process(
         Vc::upcast(source),
         Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
     );
 
     if is_edge {
-        todo!("edge pages are not supported yet")
+        rsc_entry = wrap_edge_page(
+            context,
+            project_root,
+            rsc_entry,
+            original_page_name.clone(),
+            pathname.clone(),
+            next_config,
+            mode,
+        );
     }
 
     let Some(rsc_entry) =
@@ -157,17 +181,95 @@ pub async fn get_app_page_entry(
         original_name: original_page_name,
         rsc_entry,
         config,
+        streaming: true,
+        dynamic_api_usages: find_dynamic_api_usages(Vc::upcast(rsc_entry), Vc::cell(page_paths)),
+        static_metadata,
+        segment_boundaries,
     }
     .cell())
 }
 
-// TODO(alexkirsz) This shouldn't be necessary. The loader tree should keep
-// track of this instead.
-fn get_original_page_name(pathname: &str) -> String {
-    match pathname {
-        "/" => "/page".to_string(),
-        "/_not-found" => "/_not-found".to_string(),
-        "/not-found" => "/not-found".to_string(),
-        _ => format!("{}/page", pathname),
+/// Wraps the page module in the edge SSR template, which renders it with the
+/// edge runtime adapter, and registers it as an edge function entry.
+#[turbo_tasks::function]
+async fn wrap_edge_page(
+    context: Vc<ModuleAssetContext>,
+    project_root: Vc<FileSystemPath>,
+    entry: Vc<Box<dyn Module>>,
+    page: String,
+    pathname: String,
+    next_config: Vc<NextConfig>,
+    mode: NextMode,
+) -> Result<Vc<Box<dyn Module>>> {
+    let template_file = "build/templates/edge-ssr-app.js";
+
+    // Load the file from the next.js codebase.
+    let file = load_next_js_template(project_root, template_file.to_string()).await?;
+
+    let sri_enabled = next_config.sri_algorithm().await?.is_some();
+
+    let mut file = file
+        .to_str()?
+        .replace("\"VAR_PAGE\"", &StringifyJs(&page).to_string())
+        .replace(
+            "// INJECT:nextConfig",
+            format!("const nextConfig = {};", StringifyJs(&*next_config.await?)).as_str(),
+        )
+        .replace(
+            "// INJECT:sriEnabled",
+            format!("const sriEnabled = {};", sri_enabled).as_str(),
+        )
+        .replace(
+            "// INJECT:dev",
+            format!("const dev = {};", mode != NextMode::Build).as_str(),
+        );
+
+    // Ensure that the last line is a newline.
+    if !file.ends_with('\n') {
+        file.push('\n');
     }
+
+    let file = File::from(file);
+
+    let template_path = virtual_next_js_template_path(project_root, template_file.to_string());
+
+    let source = VirtualSource::new(template_path, AssetContent::file(file.into()));
+
+    let inner_assets = indexmap! {
+        "VAR_USERLAND".to_string() => entry
+    };
+
+    let wrapped = context.process(
+        Vc::upcast(source),
+        Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
+    );
+
+    let mut source = RopeBuilder::default();
+    writedoc!(
+        source,
+        r#"
+            import * as module from "MODULE"
+
+            self._ENTRIES ||= {{}}
+            self._ENTRIES[{}] = {{
+                ComponentMod: module.ComponentMod,
+                default: module.default,
+            }}
+        "#,
+        StringifyJs(&format_args!("middleware_{}", pathname))
+    )?;
+    let file = File::from(source.build());
+    // TODO(alexkirsz) Figure out how to name this virtual asset.
+    let virtual_source = VirtualSource::new(
+        project_root.join("edge-wrapper.js".to_string()),
+        AssetContent::file(file.into()),
+    );
+    let inner_assets = indexmap! {
+        "MODULE".to_string() => wrapped
+    };
+
+    Ok(context.process(
+        Vc::upcast(virtual_source),
+        Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
+    ))
 }
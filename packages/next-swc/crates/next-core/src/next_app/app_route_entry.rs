@@ -21,7 +21,10 @@ use turbopack_binding::{
 };
 
 use crate::{
-    next_app::{AppEntry, AppPage, AppPath},
+    next_app::{
+        dynamic_api_usage::find_dynamic_api_usages, route_handler_methods::route_handler_methods,
+        route_handler_streaming::is_streaming_route_handler, AppEntry, AppPage, AppPath, PageType,
+    },
     parse_segment_config_from_source,
     util::{load_next_js_template, virtual_next_js_template_path, NextRuntime},
 };
@@ -54,7 +57,7 @@ pub async fn get_app_route_entry(
     let original_name = page.to_string();
     let pathname = AppPath::from(page.clone()).to_string();
 
-    let original_page_name = get_original_route_name(&original_name);
+    let original_page_name = page.to_page_path(PageType::Route);
     let path = source.ident().path();
 
     let template_file = "build/templates/app-route.js";
@@ -112,6 +115,11 @@ pub async fn get_app_route_entry(
         Value::new(ReferenceType::Entry(EntryReferenceSubType::AppRoute)),
     );
 
+    // Emits issues for missing or misspelled HTTP method exports, following
+    // re-exports from other modules.
+    let methods = route_handler_methods(userland_module, source);
+    let streaming = *is_streaming_route_handler(methods).await?;
+
     let inner_assets = indexmap! {
         "VAR_USERLAND".to_string() => userland_module
     };
@@ -136,6 +144,10 @@ pub async fn get_app_route_entry(
         original_name: original_page_name,
         rsc_entry,
         config,
+        streaming,
+        dynamic_api_usages: find_dynamic_api_usages(Vc::upcast(rsc_entry), Vc::cell(vec![])),
+        static_metadata: Vc::cell(None),
+        segment_boundaries: Vc::cell(Vec::new()),
     }
     .cell())
 }
@@ -177,10 +189,3 @@ pub async fn wrap_edge_entry(
         Value::new(ReferenceType::Internal(Vc::cell(inner_assets))),
     ))
 }
-
-fn get_original_route_name(pathname: &str) -> String {
-    match pathname {
-        "/" => "/route".to_string(),
-        _ => format!("{}/route", pathname),
-    }
-}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use thiserror::Error;
+use turbopack_binding::turbopack::node::route_matcher::Param;
+
+use super::{AppPath, PathSegment};
+
+#[derive(Debug, Error)]
+pub enum AppRouterTrieError {
+    #[error(
+        "You cannot use different slug names for the same dynamic path ('{existing}' !== \
+         '{name}') at {path}."
+    )]
+    DifferentSlugNames {
+        path: AppPath,
+        existing: String,
+        name: String,
+    },
+    #[error("Catch-all must be the last part of the URL ({0}).")]
+    CatchAllNotLast(AppPath),
+    #[error("{0} and {1} match the same pathnames.")]
+    Conflict(AppPath, AppPath),
+}
+
+/// The entry matching a pathname in an [AppRouterTrie].
+pub struct AppRouterMatch<'a, T> {
+    pub path: &'a AppPath,
+    pub value: &'a T,
+    pub params: IndexMap<String, Param>,
+}
+
+/// A trie over the segments of [AppPath]s. Looking up a pathname takes time
+/// proportional to its number of segments instead of the number of routes.
+///
+/// Static segments are preferred over dynamic segments, which are preferred
+/// over catch-all segments, like the router does.
+pub struct AppRouterTrie<T> {
+    root: Node<T>,
+}
+
+struct Node<T> {
+    value: Option<(AppPath, T)>,
+    static_children: HashMap<String, Node<T>>,
+    dynamic_child: Option<(String, Box<Node<T>>)>,
+    catch_all: Option<CatchAll<T>>,
+}
+
+struct CatchAll<T> {
+    name: String,
+    optional: bool,
+    path: AppPath,
+    value: T,
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Node {
+            value: None,
+            static_children: HashMap::new(),
+            dynamic_child: None,
+            catch_all: None,
+        }
+    }
+}
+
+impl<T> Default for AppRouterTrie<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AppRouterTrie<T> {
+    pub fn new() -> Self {
+        AppRouterTrie {
+            root: Node::default(),
+        }
+    }
+
+    /// Inserts the entry of a path. Fails when an entry matching the same
+    /// pathnames exists, e.g. `/blog/[slug]` and `/blog/[id]`, or `/docs` and
+    /// `/docs/[[...path]]`.
+    pub fn insert(&mut self, path: AppPath, value: T) -> Result<(), AppRouterTrieError> {
+        let mut node = &mut self.root;
+        for (index, segment) in path.iter().enumerate() {
+            match segment {
+                PathSegment::Static(segment) => {
+                    node = node.static_children.entry(segment.clone()).or_default();
+                }
+                PathSegment::Dynamic(name) => {
+                    let (existing, child) = node
+                        .dynamic_child
+                        .get_or_insert_with(|| (name.clone(), Default::default()));
+                    if existing != name {
+                        return Err(AppRouterTrieError::DifferentSlugNames {
+                            existing: existing.clone(),
+                            name: name.clone(),
+                            path: path.clone(),
+                        });
+                    }
+                    node = child;
+                }
+                PathSegment::CatchAll(name) | PathSegment::OptionalCatchAll(name) => {
+                    if index != path.len() - 1 {
+                        return Err(AppRouterTrieError::CatchAllNotLast(path.clone()));
+                    }
+                    let optional = matches!(segment, PathSegment::OptionalCatchAll(_));
+                    if let Some(existing) = &node.catch_all {
+                        return Err(if existing.name != *name {
+                            AppRouterTrieError::DifferentSlugNames {
+                                existing: existing.name.clone(),
+                                name: name.clone(),
+                                path: path.clone(),
+                            }
+                        } else {
+                            AppRouterTrieError::Conflict(existing.path.clone(), path.clone())
+                        });
+                    }
+                    if let Some((existing, _)) = node.value.as_ref().filter(|_| optional) {
+                        return Err(AppRouterTrieError::Conflict(existing.clone(), path.clone()));
+                    }
+                    node.catch_all = Some(CatchAll {
+                        name: name.clone(),
+                        optional,
+                        path: path.clone(),
+                        value,
+                    });
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some((existing, _)) = &node.value {
+            return Err(AppRouterTrieError::Conflict(existing.clone(), path));
+        }
+        if let Some(existing) = node.catch_all.as_ref().filter(|c| c.optional) {
+            return Err(AppRouterTrieError::Conflict(existing.path.clone(), path));
+        }
+        node.value = Some((path, value));
+        Ok(())
+    }
+
+    /// Finds the entry matching a pathname, along with the values of its
+    /// dynamic segments. The pathname is taken as it appears in a request
+    /// URL, its segments are percent-decoded before they are matched.
+    pub fn lookup(&self, pathname: &str) -> Option<AppRouterMatch<'_, T>> {
+        let segments = pathname
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                // Segments which don't decode to UTF-8 are matched as they are.
+                urlencoding::decode(segment)
+                    .map_or_else(|_| segment.to_string(), |segment| segment.into_owned())
+            })
+            .collect::<Vec<_>>();
+        let mut params = vec![];
+        let (path, value) = self.root.lookup(&segments, &mut params)?;
+        Some(AppRouterMatch {
+            path,
+            value,
+            params: params.into_iter().collect(),
+        })
+    }
+}
+
+impl<T> Node<T> {
+    fn lookup<'a>(
+        &'a self,
+        segments: &[String],
+        params: &mut Vec<(String, Param)>,
+    ) -> Option<(&'a AppPath, &'a T)> {
+        let Some((segment, rest)) = segments.split_first() else {
+            if let Some((path, value)) = &self.value {
+                return Some((path, value));
+            }
+            return self
+                .catch_all
+                .as_ref()
+                .filter(|catch_all| catch_all.optional)
+                .map(|catch_all| (&catch_all.path, &catch_all.value));
+        };
+
+        if let Some(found) = self
+            .static_children
+            .get(segment)
+            .and_then(|child| child.lookup(rest, params))
+        {
+            return Some(found);
+        }
+
+        if let Some((name, child)) = &self.dynamic_child {
+            params.push((name.clone(), Param::Single(segment.to_string())));
+            if let Some(found) = child.lookup(rest, params) {
+                return Some(found);
+            }
+            params.pop();
+        }
+
+        let catch_all = self.catch_all.as_ref()?;
+        params.push((catch_all.name.clone(), Param::Multi(segments.to_vec())));
+        Some((&catch_all.path, &catch_all.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(segments: &[PathSegment]) -> AppPath {
+        AppPath(segments.to_vec())
+    }
+
+    #[test]
+    fn test_app_router_trie() {
+        use PathSegment::*;
+
+        let mut trie = AppRouterTrie::new();
+        trie.insert(path(&[]), "home").unwrap();
+        trie.insert(path(&[Static("blog".into()), Static("new".into())]), "new")
+            .unwrap();
+        trie.insert(
+            path(&[Static("blog".into()), Dynamic("slug".into())]),
+            "post",
+        )
+        .unwrap();
+        trie.insert(
+            path(&[Static("docs".into()), OptionalCatchAll("path".into())]),
+            "docs",
+        )
+        .unwrap();
+
+        assert_eq!(*trie.lookup("/").unwrap().value, "home");
+        assert_eq!(*trie.lookup("/blog/new").unwrap().value, "new");
+        let post = trie.lookup("/blog/hello/").unwrap();
+        assert_eq!(*post.value, "post");
+        assert!(matches!(&post.params["slug"], Param::Single(slug) if slug == "hello"));
+        assert!(trie.lookup("/blog").is_none());
+        assert!(trie.lookup("/docs").unwrap().params.is_empty());
+        let docs = trie.lookup("/docs/a/b").unwrap();
+        assert!(matches!(&docs.params["path"], Param::Multi(path) if path == &["a", "b"]));
+
+        assert!(matches!(
+            trie.insert(path(&[Static("blog".into()), Dynamic("id".into())]), "id"),
+            Err(AppRouterTrieError::DifferentSlugNames { .. })
+        ));
+        assert!(matches!(
+            trie.insert(path(&[Static("docs".into())]), "docs index"),
+            Err(AppRouterTrieError::Conflict(..))
+        ));
+        assert!(matches!(
+            trie.insert(
+                path(&[CatchAll("rest".into()), Static("edit".into())]),
+                "edit"
+            ),
+            Err(AppRouterTrieError::CatchAllNotLast(_))
+        ));
+    }
+
+    #[test]
+    fn test_app_router_trie_percent_encoding() {
+        use PathSegment::*;
+
+        let mut trie = AppRouterTrie::new();
+        trie.insert(path(&[Static("café".into())]), "cafe").unwrap();
+        trie.insert(
+            path(&[Static("blog".into()), Dynamic("slug".into())]),
+            "post",
+        )
+        .unwrap();
+
+        assert_eq!(*trie.lookup("/caf%C3%A9").unwrap().value, "cafe");
+        assert_eq!(*trie.lookup("/café").unwrap().value, "cafe");
+        let post = trie.lookup("/blog/hello%20world").unwrap();
+        assert!(matches!(&post.params["slug"], Param::Single(slug) if slug == "hello world"));
+        let post = trie.lookup("/blog/%FF").unwrap();
+        assert!(matches!(&post.params["slug"], Param::Single(slug) if slug == "%FF"));
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::Result;
+use indoc::formatdoc;
+use swc_core::ecma::ast::{Decl, ModuleDecl, ModuleExportName, ModuleItem, Program};
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{File, FileSystemPath},
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            context::AssetContext,
+            file_source::FileSource,
+            module::Module,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+            virtual_source::VirtualSource,
+        },
+        ecmascript::{parse::ParseResult, utils::StringifyJs, EcmascriptModuleAsset},
+        turbopack::ModuleAssetContext,
+    },
+};
+
+use super::{
+    app_route_entry::get_app_route_entry,
+    app_static_metadata_entry::get_app_route_static_metadata_entry,
+};
+use crate::{
+    app_structure::MetadataItem,
+    next_app::{AppEntry, AppPage, PageSegment},
+};
+
+/// The name of the dynamic segment of the routes of a sitemap which exports
+/// `generateSitemaps`, the same one webpack uses.
+const METADATA_ID_SEGMENT: &str = "__metadata_id__";
+
+/// Computes the entry for a sitemap in the given directory of the app
+/// directory. A `sitemap.xml` is served as it is, like the other static
+/// metadata files.
+///
+/// A dynamic `sitemap.(js|ts)` is served from a route handler which calls it.
+/// When the sitemap exports `generateSitemaps`, it's served at
+/// `<directory>/sitemap/[id].xml` for each of the ids it returns instead of at
+/// `<directory>/sitemap.xml`. The entry then exports `generateStaticParams`, so
+/// the ids are expanded during static generation and listed in the prerender
+/// manifest.
+#[turbo_tasks::function]
+pub async fn get_app_route_sitemap_entry(
+    nodejs_context: Vc<ModuleAssetContext>,
+    edge_context: Vc<ModuleAssetContext>,
+    sitemap: MetadataItem,
+    directory: AppPage,
+    project_root: Vc<FileSystemPath>,
+) -> Result<Vc<AppEntry>> {
+    let path = match sitemap {
+        MetadataItem::Static { .. } => {
+            return Ok(get_app_route_static_metadata_entry(
+                nodejs_context,
+                edge_context,
+                sitemap,
+                directory,
+                project_root,
+            ));
+        }
+        MetadataItem::Dynamic { path } => path,
+    };
+
+    let sitemap_module = nodejs_context.process(
+        Vc::upcast(FileSource::new(path)),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::AppRoute)),
+    );
+    let has_generate_sitemaps = *exports_generate_sitemaps(sitemap_module).await?;
+
+    let import_path = format!("./{}", path.await?.file_name());
+    let code = sitemap_route_code(&import_path, has_generate_sitemaps);
+
+    let file = File::from(code);
+    // The entry is placed next to the sitemap so it can import it relatively.
+    let source = VirtualSource::new(
+        path.parent().join("sitemap--route-entry.tsx".to_string()),
+        AssetContent::file(file.into()),
+    );
+
+    Ok(get_app_route_entry(
+        nodejs_context,
+        edge_context,
+        Vc::upcast(source),
+        sitemap_page(&directory, has_generate_sitemaps)?,
+        project_root,
+    ))
+}
+
+/// The page of the sitemap in the directory.
+fn sitemap_page(directory: &AppPage, has_generate_sitemaps: bool) -> Result<AppPage> {
+    if has_generate_sitemaps {
+        directory
+            .clone_push(PageSegment::Static("sitemap".to_string()))?
+            .clone_push(PageSegment::Dynamic(METADATA_ID_SEGMENT.to_string()))
+    } else {
+        directory.clone_push(PageSegment::Static("sitemap.xml".to_string()))
+    }
+}
+
+/// The code of the route handler which serves the sitemap imported from
+/// `import_path`.
+fn sitemap_route_code(import_path: &str, has_generate_sitemaps: bool) -> String {
+    let static_generation_code = if has_generate_sitemaps {
+        formatdoc! {
+            r#"
+                export async function generateStaticParams() {{
+                    const sitemaps = await generateSitemaps()
+                    return sitemaps.map((item) => ({{
+                        {segment}: item.id.toString() + '.xml',
+                    }}))
+                }}
+            "#,
+            segment = METADATA_ID_SEGMENT,
+        }
+    } else {
+        String::new()
+    };
+
+    formatdoc! {
+        r#"
+            import {{ NextResponse }} from 'next/server'
+            import * as _sitemapModule from {import_path}
+            import {{ resolveRouteData }} from 'next/dist/build/webpack/loaders/metadata/resolve-route-data'
+
+            const sitemapModule = {{ ..._sitemapModule }}
+            const handler = sitemapModule.default
+            const generateSitemaps = sitemapModule.generateSitemaps
+
+            export async function GET(_, ctx) {{
+                const targetId = ctx.params?.{segment}?.replace(/\.xml$/, '')
+                let id = undefined
+
+                if (generateSitemaps) {{
+                    const sitemaps = await generateSitemaps()
+                    id = sitemaps.find((item) => {{
+                        if (item?.id == null) {{
+                            throw new Error('id property is required for every item returned from generateSitemaps')
+                        }}
+                        return item.id.toString() === targetId
+                    }})?.id
+                    if (id == null) {{
+                        return new NextResponse('Not Found', {{
+                            status: 404,
+                        }})
+                    }}
+                }}
+
+                const data = await handler({{ id }})
+                const content = resolveRouteData(data, 'sitemap')
+
+                return new NextResponse(content, {{
+                    headers: {{
+                        'Content-Type': 'application/xml',
+                        'Cache-Control': 'public, max-age=0, must-revalidate',
+                    }},
+                }})
+            }}
+
+            {static_generation_code}
+        "#,
+        import_path = StringifyJs(&import_path),
+        segment = METADATA_ID_SEGMENT,
+        static_generation_code = static_generation_code,
+    }
+}
+
+/// Whether the module exports a `generateSitemaps` function.
+#[turbo_tasks::function]
+async fn exports_generate_sitemaps(module: Vc<Box<dyn Module>>) -> Result<Vc<bool>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(false));
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        return Ok(Vc::cell(false));
+    };
+
+    Ok(Vc::cell(
+        module_ast.body.iter().any(exports_generate_sitemaps_item),
+    ))
+}
+
+fn exports_generate_sitemaps_item(item: &ModuleItem) -> bool {
+    const EXPORT_NAME: &str = "generateSitemaps";
+
+    match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => match &export_decl.decl {
+            Decl::Fn(fn_decl) => &*fn_decl.ident.sym == EXPORT_NAME,
+            Decl::Var(var_decl) => var_decl.decls.iter().any(|decl| {
+                decl.name
+                    .as_ident()
+                    .map_or(false, |ident| &*ident.sym == EXPORT_NAME)
+            }),
+            _ => false,
+        },
+        // `export { generateSitemaps }` and `export { fn as generateSitemaps }`
+        ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named_export)) => {
+            named_export.specifiers.iter().any(|specifier| {
+                let Some(named) = specifier.as_named() else {
+                    return false;
+                };
+                let exported = named.exported.as_ref().unwrap_or(&named.orig);
+                matches!(exported, ModuleExportName::Ident(ident) if &*ident.sym == EXPORT_NAME)
+            })
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::{EsVersion, Module},
+            parser::{parse_file_as_module, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn parse(code: &str) -> Module {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        parse_file_as_module(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap()
+    }
+
+    fn exports_generate_sitemaps(code: &str) -> bool {
+        parse(code).body.iter().any(exports_generate_sitemaps_item)
+    }
+
+    #[test]
+    fn test_exports_generate_sitemaps_item() {
+        assert!(exports_generate_sitemaps(
+            "export async function generateSitemaps() { return [{ id: 0 }] }"
+        ));
+        assert!(exports_generate_sitemaps(
+            "export const generateSitemaps = () => [{ id: 0 }]"
+        ));
+        assert!(exports_generate_sitemaps(
+            "function generateSitemaps() {}\nexport { generateSitemaps }"
+        ));
+        assert!(exports_generate_sitemaps(
+            "function sitemaps() {}\nexport { sitemaps as generateSitemaps }"
+        ));
+        assert!(!exports_generate_sitemaps(
+            "export default function sitemap() { return [] }"
+        ));
+        assert!(!exports_generate_sitemaps(
+            "export function generateSitemaps2() {}\nfunction generateSitemaps() {}"
+        ));
+        assert!(!exports_generate_sitemaps(
+            "function generateSitemaps() {}\nexport { generateSitemaps as sitemaps }"
+        ));
+    }
+
+    #[test]
+    fn test_sitemap_page() {
+        let directory = AppPage::parse("/(marketing)/blog").unwrap();
+        assert_eq!(
+            sitemap_page(&directory, false).unwrap().to_string(),
+            "/(marketing)/blog/sitemap.xml"
+        );
+        assert_eq!(
+            sitemap_page(&directory, true).unwrap().to_string(),
+            "/(marketing)/blog/sitemap/[__metadata_id__]"
+        );
+        assert_eq!(
+            sitemap_page(&AppPage::new(), false).unwrap().to_string(),
+            "/sitemap.xml"
+        );
+    }
+
+    #[test]
+    fn test_sitemap_route_code() {
+        let exports = |code: &str| {
+            parse(code)
+                .body
+                .iter()
+                .filter_map(|item| match item {
+                    ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                        match &export_decl.decl {
+                            Decl::Fn(fn_decl) => Some(fn_decl.ident.sym.to_string()),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let code = sitemap_route_code("./sitemap.ts", false);
+        assert!(code.contains("import * as _sitemapModule from \"./sitemap.ts\""));
+        assert_eq!(exports(&code), vec!["GET"]);
+
+        let code = sitemap_route_code("./sitemap.ts", true);
+        assert!(code.contains("__metadata_id__: item.id.toString() + '.xml'"));
+        assert_eq!(exports(&code), vec!["GET", "generateStaticParams"]);
+    }
+}
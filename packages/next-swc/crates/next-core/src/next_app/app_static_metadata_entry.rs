@@ -0,0 +1,114 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use indoc::writedoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{rope::RopeBuilder, File, FileContent, FileSystemPath},
+    turbopack::{
+        core::{asset::AssetContent, virtual_source::VirtualSource},
+        ecmascript::utils::StringifyJs,
+        turbopack::ModuleAssetContext,
+    },
+};
+
+use super::{
+    app_route_entry::get_app_route_entry, metadata_file_validation::validate_static_metadata_file,
+};
+use crate::{
+    app_structure::MetadataItem,
+    next_app::{AppEntry, AppPage, PageSegment},
+};
+
+/// The content type of a static metadata file, the same as the one of the
+/// webpack `next-metadata-route-loader`.
+fn content_type(file_stem: &str, extension: &str) -> String {
+    match file_stem {
+        "sitemap" => "application/xml".to_string(),
+        "robots" => "text/plain".to_string(),
+        "manifest" => "application/manifest+json".to_string(),
+        _ => mime_guess::from_ext(extension)
+            .first_or_text_plain()
+            .to_string(),
+    }
+}
+
+/// Computes the entry for a static metadata file in the given directory of
+/// the app directory, like a `robots.txt` in the root or a `sitemap.xml`. The
+/// file is validated and served with its content type at
+/// `<directory>/<file name>`.
+#[turbo_tasks::function]
+pub async fn get_app_route_static_metadata_entry(
+    nodejs_context: Vc<ModuleAssetContext>,
+    edge_context: Vc<ModuleAssetContext>,
+    item: MetadataItem,
+    directory: AppPage,
+    project_root: Vc<FileSystemPath>,
+) -> Result<Vc<AppEntry>> {
+    let path = match item {
+        MetadataItem::Static { path } => path,
+        MetadataItem::Dynamic { path } => {
+            bail!(
+                "dynamic metadata has its own route entry: {}",
+                path.to_string().await?
+            )
+        }
+    };
+
+    validate_static_metadata_file(path).await?;
+
+    let path_value = path.await?;
+    let file_name = path_value.file_name().to_string();
+    let file_stem = path.file_stem().await?;
+    let extension = path.extension().await?;
+    let content_type = content_type(file_stem.as_deref().unwrap_or_default(), &extension);
+
+    let FileContent::Content(file_content) = &*path.read().await? else {
+        bail!("metadata file not found: {}", &path.to_string().await?);
+    };
+    let file_content_b64 =
+        Base64Display::new(&file_content.content().to_bytes()?, &STANDARD).to_string();
+    // The files are part of the build, so they only change with a deployment.
+    let cache_control = "public, immutable, no-transform, max-age=31536000";
+
+    let mut code = RopeBuilder::default();
+    writedoc! {
+        code,
+        r#"
+            import {{ NextResponse }} from 'next/server'
+
+            const contentType = {content_type}
+            const cacheControl = {cache_control}
+            const buffer = Buffer.from({file_content_b64}, 'base64')
+
+            export function GET() {{
+                return new NextResponse(buffer, {{
+                    headers: {{
+                        'Content-Type': contentType,
+                        'Cache-Control': cacheControl,
+                    }},
+                }})
+            }}
+
+            export const dynamic = 'force-static'
+        "#,
+        content_type = StringifyJs(&content_type),
+        cache_control = StringifyJs(&cache_control),
+        file_content_b64 = StringifyJs(&file_content_b64),
+    }?;
+
+    let file = File::from(code.build());
+    let source = VirtualSource::new(
+        path.parent().join(format!("{file_name}-entry.tsx")),
+        AssetContent::file(file.into()),
+    );
+
+    Ok(get_app_route_entry(
+        nodejs_context,
+        edge_context,
+        Vc::upcast(source),
+        directory.clone_push(PageSegment::Static(file_name))?,
+        project_root,
+    ))
+}
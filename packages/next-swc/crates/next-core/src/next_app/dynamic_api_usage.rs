@@ -0,0 +1,518 @@
+use anyhow::Result;
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{source_map::Pos, Span},
+    ecma::{
+        ast::{
+            ArrowExpr, CallExpr, Callee, Decl, DefaultDecl, ExportDecl, ExportDefaultDecl,
+            ExportDefaultExpr, ExportSpecifier, Expr, Function, ImportDecl, ImportSpecifier,
+            MemberExpr, MemberProp, Module, ModuleDecl, ModuleExportName, ModuleItem, NamedExport,
+            ObjectPatProp, Pat, PropName, Stmt,
+        },
+        atoms::JsWord,
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
+};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{
+        file_source::FileSource,
+        issue::{Issue, IssueSeverity, IssueSource, OptionIssueSource},
+        module::Module,
+    },
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+use super::server_modules::app_server_modules;
+
+/// An API which makes a route render at request time.
+#[derive(
+    PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord, TraceRawVcs, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum DynamicApi {
+    /// `headers()` from `next/headers`.
+    Headers,
+    /// `cookies()` from `next/headers`.
+    Cookies,
+    /// `draftMode()` from `next/headers`.
+    DraftMode,
+    /// The `searchParams` prop of a page.
+    SearchParams,
+}
+
+impl DynamicApi {
+    fn name(&self) -> &'static str {
+        match self {
+            DynamicApi::Headers => "headers()",
+            DynamicApi::Cookies => "cookies()",
+            DynamicApi::DraftMode => "draftMode()",
+            DynamicApi::SearchParams => "searchParams",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct DynamicApiUsage {
+    pub api: DynamicApi,
+    pub file_path: Vc<FileSystemPath>,
+    pub source: Vc<IssueSource>,
+}
+
+/// The dynamic APIs used by the server modules of a route, in the order they
+/// were found.
+#[turbo_tasks::value(transparent)]
+pub struct DynamicApiUsages(Vec<DynamicApiUsage>);
+
+#[turbo_tasks::value_impl]
+impl DynamicApiUsages {
+    /// The distinct dynamic APIs used by the route, sorted.
+    #[turbo_tasks::function]
+    pub async fn apis(self: Vc<Self>) -> Result<Vc<DynamicApis>> {
+        let mut apis: Vec<_> = self
+            .await?
+            .iter()
+            .map(|usage| usage.api)
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect();
+        apis.sort();
+        Ok(Vc::cell(apis))
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct DynamicApis(Vec<DynamicApi>);
+
+/// Walks the server modules reachable from an app entry and collects their
+/// uses of dynamic APIs. Client components aren't followed, they can't call
+/// these APIs. `searchParams` is only looked for in the given page files, as
+/// project-relative paths.
+///
+/// The analysis is syntactic: a route can still be dynamic because of APIs
+/// called by packages in node_modules, or uncached `fetch` requests.
+#[turbo_tasks::function]
+pub async fn find_dynamic_api_usages(
+    entry: Vc<Box<dyn Module>>,
+    pages: Vc<Vec<String>>,
+) -> Result<Vc<DynamicApiUsages>> {
+    let pages = pages.await?;
+    let usages = app_server_modules(entry)
+        .await?
+        .iter()
+        .map(|&module| {
+            let pages = &pages;
+            async move {
+                let is_page = pages.contains(&module.ident().path().await?.path);
+                Ok(module_dynamic_api_usages(module, is_page)
+                    .await?
+                    .clone_value())
+            }
+        })
+        .try_join()
+        .await?;
+
+    Ok(Vc::cell(usages.into_iter().flatten().collect()))
+}
+
+#[turbo_tasks::function]
+async fn module_dynamic_api_usages(
+    module: Vc<Box<dyn Module>>,
+    is_page: bool,
+) -> Result<Vc<DynamicApiUsages>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Vc::cell(vec![]));
+    };
+
+    let path = module.ident().path();
+    let mut visitor = DynamicApiVisitor {
+        is_page,
+        ..Default::default()
+    };
+    program.visit_with(&mut visitor);
+
+    let source = Vc::upcast(FileSource::new(path));
+    Ok(Vc::cell(
+        visitor
+            .usages
+            .into_iter()
+            .map(|(api, span)| DynamicApiUsage {
+                api,
+                file_path: path,
+                source: IssueSource::from_byte_offset(
+                    source,
+                    span.lo.to_usize(),
+                    span.hi.to_usize(),
+                ),
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Default)]
+struct DynamicApiVisitor {
+    is_page: bool,
+    /// The local names of the functions imported from `next/headers`.
+    imports: Vec<(JsWord, DynamicApi)>,
+    usages: Vec<(DynamicApi, Span)>,
+}
+
+impl DynamicApiVisitor {
+    /// Records the `searchParams` read from the props of a page component.
+    fn check_page_component(&mut self, component: PageComponent) {
+        match component {
+            PageComponent::Fn(function) => {
+                if let Some(props) = function.params.first() {
+                    self.check_page_props(&props.pat, &function.body);
+                }
+            }
+            PageComponent::Arrow(arrow) => {
+                if let Some(props) = arrow.params.first() {
+                    self.check_page_props(props, &arrow.body);
+                }
+            }
+        }
+    }
+
+    fn check_page_props<N: VisitWith<PropsMemberVisitor>>(&mut self, props: &Pat, body: &N) {
+        match props {
+            // `function Page({ searchParams })`
+            Pat::Object(pat) => {
+                for prop in &pat.props {
+                    let span = match prop {
+                        ObjectPatProp::KeyValue(key_value) => match &key_value.key {
+                            PropName::Ident(key) if &*key.sym == "searchParams" => key.span,
+                            _ => continue,
+                        },
+                        ObjectPatProp::Assign(assign) if &*assign.key.sym == "searchParams" => {
+                            assign.span
+                        }
+                        _ => continue,
+                    };
+                    self.usages.push((DynamicApi::SearchParams, span));
+                }
+            }
+            // `function Page(props) { props.searchParams }`
+            Pat::Ident(props) => {
+                let mut visitor = PropsMemberVisitor {
+                    props: props.id.sym.clone(),
+                    spans: vec![],
+                };
+                body.visit_with(&mut visitor);
+                self.usages.extend(
+                    visitor
+                        .spans
+                        .into_iter()
+                        .map(|span| (DynamicApi::SearchParams, span)),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit for DynamicApiVisitor {
+    noop_visit_type!();
+
+    fn visit_import_decl(&mut self, import: &ImportDecl) {
+        if import.type_only || &*import.src.value != "next/headers" {
+            return;
+        }
+        for specifier in &import.specifiers {
+            let ImportSpecifier::Named(named) = specifier else {
+                continue;
+            };
+            let imported = match &named.imported {
+                Some(ModuleExportName::Ident(ident)) => &ident.sym,
+                Some(ModuleExportName::Str(str)) => &str.value,
+                None => &named.local.sym,
+            };
+            let api = match &**imported {
+                "headers" => DynamicApi::Headers,
+                "cookies" => DynamicApi::Cookies,
+                "draftMode" => DynamicApi::DraftMode,
+                _ => continue,
+            };
+            self.imports.push((named.local.sym.clone(), api));
+        }
+    }
+
+    fn visit_module(&mut self, module: &Module) {
+        if self.is_page {
+            if let Some(component) = page_component(&module.body) {
+                self.check_page_component(component);
+            }
+        }
+        module.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            if let Expr::Ident(ident) = &**callee {
+                if let Some((_, api)) = self.imports.iter().find(|(local, _)| *local == ident.sym) {
+                    self.usages.push((*api, call.span));
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// The function of a page component, which gets `searchParams` as a prop.
+#[derive(Clone, Copy)]
+enum PageComponent<'a> {
+    Fn(&'a Function),
+    Arrow(&'a ArrowExpr),
+}
+
+impl<'a> PageComponent<'a> {
+    fn from_expr(expr: &'a Expr) -> Option<Self> {
+        match expr {
+            Expr::Paren(paren) => Self::from_expr(&paren.expr),
+            Expr::Fn(function) => Some(PageComponent::Fn(&function.function)),
+            Expr::Arrow(arrow) => Some(PageComponent::Arrow(arrow)),
+            _ => None,
+        }
+    }
+}
+
+/// The default exported component of a page: `export default function`, an
+/// exported function expression or arrow function, or a function of the module
+/// which is exported by name, with `export default Page` or
+/// `export { Page as default }`.
+fn page_component(items: &[ModuleItem]) -> Option<PageComponent<'_>> {
+    let mut default_export = None;
+    for item in items {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::Fn(function),
+                ..
+            })) => return Some(PageComponent::Fn(&function.function)),
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                expr,
+                ..
+            })) => match &**expr {
+                Expr::Ident(ident) => default_export = Some(&ident.sym),
+                expr => return PageComponent::from_expr(expr),
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src: None,
+                ..
+            })) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(named) = specifier {
+                        if let (
+                            ModuleExportName::Ident(orig),
+                            Some(ModuleExportName::Ident(exported)),
+                        ) = (&named.orig, &named.exported)
+                        {
+                            if &*exported.sym == "default" {
+                                default_export = Some(&orig.sym);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let name = default_export?;
+    items.iter().find_map(|item| {
+        let decl = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => decl,
+            _ => return None,
+        };
+        match decl {
+            Decl::Fn(fn_decl) if fn_decl.ident.sym == *name => {
+                Some(PageComponent::Fn(&fn_decl.function))
+            }
+            Decl::Var(var_decl) => var_decl.decls.iter().find_map(|declarator| {
+                match (&declarator.name, &declarator.init) {
+                    (Pat::Ident(ident), Some(init)) if ident.id.sym == *name => {
+                        PageComponent::from_expr(init)
+                    }
+                    _ => None,
+                }
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// Collects the spans of `props.searchParams`.
+struct PropsMemberVisitor {
+    props: JsWord,
+    spans: Vec<Span>,
+}
+
+impl Visit for PropsMemberVisitor {
+    noop_visit_type!();
+
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        if matches!(&*member.obj, Expr::Ident(obj) if obj.sym == self.props)
+            && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "searchParams")
+        {
+            self.spans.push(member.span);
+        }
+        member.visit_children_with(self);
+    }
+}
+
+/// Explains why a route is rendered at request time, pointing at the first
+/// use of a dynamic API.
+#[turbo_tasks::value(shared)]
+pub struct DynamicRouteIssue {
+    pub pathname: String,
+    pub usages: Vec<DynamicApiUsage>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DynamicRouteIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Info.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("rendering".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.usages[0].file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Route {} is rendered at request time",
+            self.pathname
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<String>> {
+        let mut description =
+            "The route uses dynamic APIs, so it can't be prerendered at build time:".to_string();
+        for usage in &self.usages {
+            description.push_str(&format!(
+                "\n  {} in {}",
+                usage.api.name(),
+                usage.file_path.await?.path
+            ));
+        }
+        Ok(Vc::cell(description))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            "https://nextjs.org/docs/app/building-your-application/rendering/server-components#dynamic-functions"
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.usages[0].source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn dynamic_apis(code: &str, is_page: bool) -> Vec<DynamicApi> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let mut visitor = DynamicApiVisitor {
+            is_page,
+            ..Default::default()
+        };
+        program.visit_with(&mut visitor);
+        visitor.usages.into_iter().map(|(api, _)| api).collect()
+    }
+
+    #[test]
+    fn test_next_headers() {
+        assert_eq!(
+            dynamic_apis(
+                r#"
+                import { headers, cookies as getCookies } from "next/headers";
+                import { draftMode } from "./local";
+                export function read() {
+                    return [headers(), getCookies(), draftMode()];
+                }
+                "#,
+                false
+            ),
+            vec![DynamicApi::Headers, DynamicApi::Cookies]
+        );
+    }
+
+    #[test]
+    fn test_search_params() {
+        let search_params = vec![DynamicApi::SearchParams];
+        for code in [
+            "export default function Page({ searchParams }) { return null; }",
+            "export default function Page(props) { return props.searchParams.q; }",
+            "export default ({ searchParams }) => searchParams.q;",
+            "export default (async (props) => props.searchParams);",
+            "function Page({ searchParams }) { return null; }\nexport default Page;",
+            "const Page = (props) => props.searchParams;\nexport { Page as default };",
+            "export const Page = function ({ searchParams: params }) {};\nexport default Page;",
+        ] {
+            assert_eq!(dynamic_apis(code, true), search_params, "{code}");
+        }
+    }
+
+    #[test]
+    fn test_search_params_outside_of_pages() {
+        for (code, is_page) in [
+            // Only the page file gets `searchParams`.
+            ("export default function Layout({ searchParams }) {}", false),
+            // Only the default exported component's props are page props.
+            (
+                "export function Card({ searchParams }) {}\nexport default function Page() {}",
+                true,
+            ),
+            (
+                "const Page = ({ params }) => null;\nconst Other = ({ searchParams }) => \
+                 null;\nexport default Page;",
+                true,
+            ),
+        ] {
+            assert_eq!(dynamic_apis(code, is_page), vec![], "{code}");
+        }
+    }
+}
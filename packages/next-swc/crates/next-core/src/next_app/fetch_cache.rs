@@ -0,0 +1,399 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use swc_core::ecma::{
+    ast::{
+        ArrayLit, CallExpr, Callee, Expr, ImportDecl, ImportSpecifier, Lit, ModuleExportName,
+        ObjectLit, Prop, PropName, PropOrSpread,
+    },
+    atoms::JsWord,
+    visit::{noop_visit_type, Visit, VisitWith},
+};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, ValueToString, Vc};
+use turbopack_binding::turbopack::{
+    core::module::Module,
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+use super::server_modules::app_server_modules;
+
+/// The cache options passed to a `fetch` or `unstable_cache` call, as far as
+/// they are static.
+#[derive(PartialEq, Eq, Clone, Debug, Default, TraceRawVcs, Serialize, Deserialize)]
+pub struct FetchCacheOptions {
+    /// The `cache` option, e.g. `no-store`.
+    pub cache: Option<String>,
+    /// `next.revalidate` in seconds, `Some(0)` for `revalidate: 0` and `None`
+    /// for `revalidate: false` or when it isn't set.
+    pub revalidate: Option<u32>,
+    /// `next.tags`, or `tags` for `unstable_cache`.
+    pub tags: Vec<String>,
+}
+
+impl FetchCacheOptions {
+    /// Whether the response bypasses the data cache.
+    fn is_uncached(&self) -> bool {
+        matches!(self.cache.as_deref(), Some("no-store" | "no-cache")) || self.revalidate == Some(0)
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+struct ModuleFetchCacheOptions(Vec<FetchCacheOptions>);
+
+/// The data cache options of the `fetch` and `unstable_cache` calls of an app
+/// route.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteFetchCache {
+    /// The cache tags of the cached data, sorted.
+    pub tags: Vec<String>,
+    /// The shortest revalidation time of the fetches in seconds, if any sets
+    /// one.
+    pub revalidate: Option<u32>,
+    /// The modules with fetches which opt out of the data cache, which makes
+    /// the route dynamic.
+    pub uncached_fetches: Vec<String>,
+}
+
+/// The fetch cache options of the app routes, keyed by the original name of
+/// the route.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchCacheManifest {
+    pub version: u32,
+    pub routes: IndexMap<String, RouteFetchCache>,
+}
+
+/// The app routes which use each cache tag, so `revalidateTag` can be fanned
+/// out to the affected routes without tracking tags at runtime.
+#[derive(Serialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheTagsManifest {
+    pub version: u32,
+    /// The original names of the routes using a tag, sorted.
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+impl CacheTagsManifest {
+    pub fn new(fetch_cache: &FetchCacheManifest) -> Self {
+        let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (route, route_fetch_cache) in &fetch_cache.routes {
+            for tag in &route_fetch_cache.tags {
+                tags.entry(tag.clone()).or_default().push(route.clone());
+            }
+        }
+        for routes in tags.values_mut() {
+            routes.sort();
+            routes.dedup();
+        }
+        CacheTagsManifest { version: 1, tags }
+    }
+}
+
+/// Scans the server modules of an app entry for `fetch(url, { cache, next: {
+/// revalidate, tags } })` and `unstable_cache(fn, keys, { revalidate, tags })`
+/// calls and aggregates their options.
+///
+/// Only options written as literals at the call site are found, fetches made
+/// by packages in node_modules aren't scanned.
+#[turbo_tasks::function]
+pub async fn route_fetch_cache(entry: Vc<Box<dyn Module>>) -> Result<Vc<RouteFetchCache>> {
+    let modules = app_server_modules(entry)
+        .await?
+        .iter()
+        .map(|&module| async move { Ok((module, module_fetch_cache_options(module).await?)) })
+        .try_join()
+        .await?;
+
+    let mut tags = BTreeSet::new();
+    let mut revalidate: Option<u32> = None;
+    let mut uncached_fetches = vec![];
+    for (module, fetches) in modules {
+        let mut has_uncached = false;
+        for fetch in fetches.iter() {
+            tags.extend(fetch.tags.iter().cloned());
+            if fetch.is_uncached() {
+                has_uncached = true;
+            } else if let Some(seconds) = fetch.revalidate {
+                revalidate = Some(revalidate.map_or(seconds, |current| current.min(seconds)));
+            }
+        }
+        if has_uncached {
+            uncached_fetches.push(module.ident().path().to_string().await?.clone_value());
+        }
+    }
+
+    Ok(RouteFetchCache {
+        tags: tags.into_iter().collect(),
+        revalidate,
+        uncached_fetches,
+    }
+    .cell())
+}
+
+#[turbo_tasks::function]
+async fn module_fetch_cache_options(
+    module: Vc<Box<dyn Module>>,
+) -> Result<Vc<ModuleFetchCacheOptions>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Vc::cell(vec![]));
+    };
+
+    let mut visitor = FetchVisitor {
+        unstable_cache_idents: vec![],
+        fetches: vec![],
+    };
+    program.visit_with(&mut visitor);
+    Ok(Vc::cell(visitor.fetches))
+}
+
+struct FetchVisitor {
+    /// The local names of `unstable_cache` imported from `next/cache`.
+    unstable_cache_idents: Vec<JsWord>,
+    fetches: Vec<FetchCacheOptions>,
+}
+
+/// Iterates over the statically named properties of an object literal.
+fn object_props(object: &ObjectLit) -> impl Iterator<Item = (&str, &Expr)> {
+    object.props.iter().filter_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+        let Prop::KeyValue(key_value) = &**prop else {
+            return None;
+        };
+        let key = match &key_value.key {
+            PropName::Ident(ident) => &*ident.sym,
+            PropName::Str(str) => &*str.value,
+            _ => return None,
+        };
+        Some((key, &*key_value.value))
+    })
+}
+
+fn string_array(array: &ArrayLit) -> Vec<String> {
+    array
+        .elems
+        .iter()
+        .flatten()
+        .filter_map(|elem| match &*elem.expr {
+            Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_revalidate(value: &Expr) -> Option<u32> {
+    match value {
+        Expr::Lit(Lit::Num(seconds)) if seconds.value >= 0.0 => Some(seconds.value as u32),
+        _ => None,
+    }
+}
+
+fn parse_unstable_cache_options(options: &ObjectLit) -> FetchCacheOptions {
+    let mut cache = FetchCacheOptions::default();
+    for (key, value) in object_props(options) {
+        match (key, value) {
+            ("revalidate", value) => cache.revalidate = parse_revalidate(value),
+            ("tags", Expr::Array(tags)) => cache.tags = string_array(tags),
+            _ => {}
+        }
+    }
+    cache
+}
+
+fn parse_fetch_options(options: &ObjectLit) -> FetchCacheOptions {
+    let mut fetch = FetchCacheOptions::default();
+    for (key, value) in object_props(options) {
+        match (key, value) {
+            ("cache", Expr::Lit(Lit::Str(cache))) => fetch.cache = Some(cache.value.to_string()),
+            ("next", Expr::Object(next)) => {
+                for (key, value) in object_props(next) {
+                    match (key, value) {
+                        ("revalidate", value) => fetch.revalidate = parse_revalidate(value),
+                        ("tags", Expr::Array(tags)) => fetch.tags = string_array(tags),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fetch
+}
+
+impl Visit for FetchVisitor {
+    noop_visit_type!();
+
+    fn visit_import_decl(&mut self, import: &ImportDecl) {
+        if import.type_only || &*import.src.value != "next/cache" {
+            return;
+        }
+        for specifier in &import.specifiers {
+            let ImportSpecifier::Named(named) = specifier else {
+                continue;
+            };
+            let imported = match &named.imported {
+                Some(ModuleExportName::Ident(ident)) => &ident.sym,
+                Some(ModuleExportName::Str(str)) => &str.value,
+                None => &named.local.sym,
+            };
+            if &**imported == "unstable_cache" {
+                self.unstable_cache_idents.push(named.local.sym.clone());
+            }
+        }
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            if let Expr::Ident(ident) = &**callee {
+                if &*ident.sym == "fetch" {
+                    if let Some(Expr::Object(options)) = call.args.get(1).map(|arg| &*arg.expr) {
+                        self.fetches.push(parse_fetch_options(options));
+                    }
+                } else if self.unstable_cache_idents.contains(&ident.sym) {
+                    if let Some(Expr::Object(options)) = call.args.get(2).map(|arg| &*arg.expr) {
+                        self.fetches.push(parse_unstable_cache_options(options));
+                    }
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn fetches(code: &str) -> Vec<FetchCacheOptions> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Es(EsConfig::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let mut visitor = FetchVisitor {
+            unstable_cache_idents: vec![],
+            fetches: vec![],
+        };
+        program.visit_with(&mut visitor);
+        visitor.fetches
+    }
+
+    #[test]
+    fn test_fetch_options() {
+        assert_eq!(
+            fetches(
+                r#"
+                fetch("https://example.com/posts", { next: { revalidate: 60, tags: ["posts"] } });
+                fetch("https://example.com/user", { cache: "no-store" });
+                fetch(url, { next: { revalidate: false } });
+                fetch(url, options);
+                fetch(url);
+                "#
+            ),
+            vec![
+                FetchCacheOptions {
+                    cache: None,
+                    revalidate: Some(60),
+                    tags: vec!["posts".to_string()],
+                },
+                FetchCacheOptions {
+                    cache: Some("no-store".to_string()),
+                    ..Default::default()
+                },
+                FetchCacheOptions::default(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unstable_cache_options() {
+        assert_eq!(
+            fetches(
+                r#"
+                import { unstable_cache as cache } from "next/cache";
+                import { unstable_cache } from "./cache";
+                const getPosts = cache(load, ["posts"], { revalidate: 0, tags: ["posts", id] });
+                const getUsers = unstable_cache(load, ["users"], { tags: ["users"] });
+                "#
+            ),
+            vec![FetchCacheOptions {
+                cache: None,
+                revalidate: Some(0),
+                tags: vec!["posts".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_uncached() {
+        let options = |cache: Option<&str>, revalidate| FetchCacheOptions {
+            cache: cache.map(|cache| cache.to_string()),
+            revalidate,
+            tags: vec![],
+        };
+        assert!(options(Some("no-store"), None).is_uncached());
+        assert!(options(Some("no-cache"), Some(60)).is_uncached());
+        assert!(options(None, Some(0)).is_uncached());
+        assert!(!options(Some("force-cache"), Some(60)).is_uncached());
+        assert!(!options(None, None).is_uncached());
+    }
+
+    fn route(tags: &[&str]) -> RouteFetchCache {
+        RouteFetchCache {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_tags_manifest() {
+        let fetch_cache = FetchCacheManifest {
+            version: 1,
+            routes: [
+                (
+                    "/blog/[slug]/page".to_string(),
+                    route(&["posts", "authors"]),
+                ),
+                ("/blog/page".to_string(), route(&["posts"])),
+                ("/about/page".to_string(), route(&[])),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        assert_eq!(
+            CacheTagsManifest::new(&fetch_cache).tags,
+            [
+                ("authors".to_string(), vec!["/blog/[slug]/page".to_string()]),
+                (
+                    "posts".to_string(),
+                    vec!["/blog/[slug]/page".to_string(), "/blog/page".to_string()]
+                ),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use turbo_tasks::{trace::TraceRawVcs, Completion, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::issue::{Issue, IssueExt, IssueSeverity},
+};
+
+/// The static metadata files which are checked at build time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+enum MetadataFileKind {
+    Robots,
+    Manifest,
+}
+
+impl MetadataFileKind {
+    fn from_file_stem(stem: &str) -> Option<Self> {
+        match stem {
+            "robots" => Some(Self::Robots),
+            "manifest" => Some(Self::Manifest),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the syntax of a static `robots.txt` or `manifest.(json|webmanifest)`
+/// file and emits a warning listing the problems found. Other files are
+/// ignored.
+#[turbo_tasks::function]
+pub async fn validate_static_metadata_file(path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+    let Some(kind) = path
+        .file_stem()
+        .await?
+        .as_deref()
+        .and_then(MetadataFileKind::from_file_stem)
+    else {
+        return Ok(Completion::new());
+    };
+    let FileContent::Content(file) = &*path.read().await? else {
+        return Ok(Completion::new());
+    };
+    let content = file.content().to_str()?;
+
+    let problems = match kind {
+        MetadataFileKind::Robots => validate_robots_txt(&content),
+        MetadataFileKind::Manifest => validate_web_manifest(&content),
+    };
+    if !problems.is_empty() {
+        InvalidMetadataFileIssue {
+            path,
+            kind,
+            problems,
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Completion::new())
+}
+
+const ROBOTS_FIELDS: [&str; 7] = [
+    "user-agent",
+    "allow",
+    "disallow",
+    "sitemap",
+    "crawl-delay",
+    "host",
+    "clean-param",
+];
+
+fn validate_robots_txt(content: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut has_user_agent = false;
+
+    for (i, line) in content.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((field, value)) = line.split_once(':') else {
+            problems.push(format!(
+                "line {line_number}: expected a `field: value` directive"
+            ));
+            continue;
+        };
+        let field = field.trim().to_ascii_lowercase();
+        let value = value.trim();
+
+        match field.as_str() {
+            "user-agent" => {
+                has_user_agent = true;
+                if value.is_empty() {
+                    problems.push(format!("line {line_number}: `User-agent` has no value"));
+                }
+            }
+            "allow" | "disallow" => {
+                if !has_user_agent {
+                    problems.push(format!(
+                        "line {line_number}: `{field}` rules must follow a `User-agent` line"
+                    ));
+                }
+                if !value.is_empty() && !value.starts_with('/') && !value.starts_with('*') {
+                    problems.push(format!(
+                        "line {line_number}: `{field}` paths must start with `/` or `*`"
+                    ));
+                }
+            }
+            "sitemap" => {
+                if !value.starts_with("http://") && !value.starts_with("https://") {
+                    problems.push(format!(
+                        "line {line_number}: `Sitemap` must be an absolute URL"
+                    ));
+                }
+            }
+            "crawl-delay" => {
+                if value.parse::<f64>().is_err() {
+                    problems.push(format!(
+                        "line {line_number}: `Crawl-delay` must be a number"
+                    ));
+                }
+            }
+            _ if ROBOTS_FIELDS.contains(&field.as_str()) => {}
+            _ => problems.push(format!("line {line_number}: unknown directive `{field}`")),
+        }
+    }
+
+    problems
+}
+
+const MANIFEST_STRING_FIELDS: [&str; 9] = [
+    "name",
+    "short_name",
+    "description",
+    "start_url",
+    "scope",
+    "id",
+    "lang",
+    "theme_color",
+    "background_color",
+];
+
+const MANIFEST_DISPLAY_MODES: [&str; 4] = ["fullscreen", "standalone", "minimal-ui", "browser"];
+
+fn validate_web_manifest(content: &str) -> Vec<String> {
+    let manifest: JsonValue = match serde_json::from_str(content) {
+        Ok(manifest) => manifest,
+        Err(err) => return vec![format!("the manifest isn't valid JSON: {err}")],
+    };
+    let Some(manifest) = manifest.as_object() else {
+        return vec!["the manifest must be a JSON object".to_string()];
+    };
+
+    let mut problems = Vec::new();
+    if !manifest.contains_key("name") && !manifest.contains_key("short_name") {
+        problems.push("the manifest should have a `name` or a `short_name`".to_string());
+    }
+    for field in MANIFEST_STRING_FIELDS {
+        if manifest
+            .get(field)
+            .map_or(false, |value| !value.is_string())
+        {
+            problems.push(format!("`{field}` must be a string"));
+        }
+    }
+    if let Some(display) = manifest.get("display") {
+        if !display
+            .as_str()
+            .map_or(false, |display| MANIFEST_DISPLAY_MODES.contains(&display))
+        {
+            problems.push(format!(
+                "`display` must be one of {}",
+                MANIFEST_DISPLAY_MODES
+                    .map(|mode| format!("\"{mode}\""))
+                    .join(", ")
+            ));
+        }
+    }
+    if let Some(icons) = manifest.get("icons") {
+        match icons.as_array() {
+            Some(icons) => {
+                for (i, icon) in icons.iter().enumerate() {
+                    if !icon.get("src").map_or(false, JsonValue::is_string) {
+                        problems.push(format!("`icons[{i}]` must have a `src` string"));
+                    }
+                }
+            }
+            None => problems.push("`icons` must be an array".to_string()),
+        }
+    }
+
+    problems
+}
+
+#[turbo_tasks::value(shared)]
+struct InvalidMetadataFileIssue {
+    path: Vc<FileSystemPath>,
+    kind: MetadataFileKind,
+    problems: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for InvalidMetadataFileIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(
+            match self.kind {
+                MetadataFileKind::Robots => "Invalid robots.txt",
+                MetadataFileKind::Manifest => "Invalid web app manifest",
+            }
+            .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("metadata".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            self.problems
+                .iter()
+                .map(|problem| format!("- {problem}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            match self.kind {
+                MetadataFileKind::Robots => {
+                    "https://nextjs.org/docs/app/api-reference/file-conventions/metadata/robots"
+                }
+                MetadataFileKind::Manifest => {
+                    "https://nextjs.org/docs/app/api-reference/file-conventions/metadata/manifest"
+                }
+            }
+            .to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_robots_txt() {
+        assert!(validate_robots_txt(
+            "# comment\nUser-Agent: *\nAllow: /\nDisallow: /private # no\n\nSitemap: \
+             https://example.com/sitemap.xml\n"
+        )
+        .is_empty());
+        assert_eq!(
+            validate_robots_txt(
+                "Disallow: /\nUser-agent: *\nAllow: private\nNoindex\nCrawl-delay: soon\nSitemap: \
+                 /sitemap.xml\nFoo: bar"
+            ),
+            vec![
+                "line 1: `disallow` rules must follow a `User-agent` line",
+                "line 3: `allow` paths must start with `/` or `*`",
+                "line 4: expected a `field: value` directive",
+                "line 5: `Crawl-delay` must be a number",
+                "line 6: `Sitemap` must be an absolute URL",
+                "line 7: unknown directive `foo`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_web_manifest() {
+        assert!(validate_web_manifest(
+            r#"{"name": "App", "display": "standalone", "icons": [{"src": "/icon.png"}]}"#
+        )
+        .is_empty());
+        assert_eq!(
+            validate_web_manifest(r#"{"short_name": 1, "display": "window", "icons": [{}]}"#),
+            vec![
+                "`short_name` must be a string",
+                "`display` must be one of \"fullscreen\", \"standalone\", \"minimal-ui\", \
+                 \"browser\"",
+                "`icons[0]` must have a `src` string",
+            ]
+        );
+        assert_eq!(
+            validate_web_manifest("[]"),
+            vec!["the manifest must be a JSON object"]
+        );
+        assert_eq!(validate_web_manifest("{").len(), 1);
+    }
+}
@@ -4,6 +4,16 @@ pub(crate) mod app_entry;
 pub(crate) mod app_favicon_entry;
 pub(crate) mod app_page_entry;
 pub(crate) mod app_route_entry;
+pub(crate) mod app_router_trie;
+pub(crate) mod app_sitemap_entry;
+pub(crate) mod app_static_metadata_entry;
+pub(crate) mod dynamic_api_usage;
+pub(crate) mod fetch_cache;
+pub(crate) mod metadata_file_validation;
+pub(crate) mod route_handler_methods;
+pub(crate) mod route_handler_streaming;
+pub(crate) mod segment_boundaries;
+pub(crate) mod server_modules;
 pub(crate) mod unsupported_dynamic_metadata_issue;
 
 use std::{
@@ -13,6 +23,7 @@ use std::{
 
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use turbo_tasks::{trace::TraceRawVcs, TaskInput};
 
 pub use crate::next_app::{
@@ -21,9 +32,21 @@ pub use crate::next_app::{
     },
     app_client_shared_chunks::get_app_client_shared_chunks,
     app_entry::AppEntry,
-    app_favicon_entry::get_app_route_favicon_entry,
+    app_favicon_entry::{get_app_route_favicon_entry, get_favicon_ico_content},
     app_page_entry::get_app_page_entry,
     app_route_entry::get_app_route_entry,
+    app_router_trie::{AppRouterMatch, AppRouterTrie, AppRouterTrieError},
+    app_sitemap_entry::get_app_route_sitemap_entry,
+    app_static_metadata_entry::get_app_route_static_metadata_entry,
+    dynamic_api_usage::{
+        DynamicApi, DynamicApiUsage, DynamicApiUsages, DynamicApis, DynamicRouteIssue,
+    },
+    fetch_cache::{
+        route_fetch_cache, CacheTagsManifest, FetchCacheManifest, FetchCacheOptions,
+        RouteFetchCache,
+    },
+    route_handler_methods::{route_handler_methods, RouteHandlerMethods, HTTP_METHODS},
+    segment_boundaries::{segment_boundaries, SegmentBoundaries, SegmentBoundary},
     unsupported_dynamic_metadata_issue::UnsupportedDynamicMetadataIssue,
 };
 
@@ -38,43 +61,93 @@ pub enum PageSegment {
     PageType(PageType),
 }
 
+/// Why a directory name isn't a valid [PageSegment].
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParsedSegmentError {
+    #[error("empty segments are not allowed")]
+    Empty,
+    #[error("slashes are not allowed in segments ({0})")]
+    Slash(String),
+    #[error("The route group () has no name.")]
+    EmptyGroupName,
+    #[error("The parallel route @ has no name.")]
+    EmptyParallelRouteName,
+    #[error("The dynamic segment {0} has no parameter name.")]
+    EmptyParamName(String),
+    #[error("The segment {0} has unbalanced brackets.")]
+    UnbalancedBrackets(String),
+    #[error(
+        "The segment {0} isn't a valid dynamic segment, brackets must wrap the whole segment as \
+         [name], [...name] or [[...name]]."
+    )]
+    InvalidDynamicSegment(String),
+    #[error("The parameter name of the dynamic segment {0} can't start with a dot.")]
+    InvalidParamName(String),
+}
+
 impl PageSegment {
-    pub fn parse(segment: &str) -> Result<Self> {
+    pub fn parse(segment: &str) -> Result<Self, ParsedSegmentError> {
         if segment.is_empty() {
-            bail!("empty segments are not allowed");
+            return Err(ParsedSegmentError::Empty);
         }
 
         if segment.contains('/') {
-            bail!("slashes are not allowed in segments");
+            return Err(ParsedSegmentError::Slash(segment.to_string()));
         }
 
         if let Some(s) = segment.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            if s.is_empty() {
+                return Err(ParsedSegmentError::EmptyGroupName);
+            }
             return Ok(PageSegment::Group(s.to_string()));
         }
 
         if let Some(s) = segment.strip_prefix('@') {
+            if s.is_empty() {
+                return Err(ParsedSegmentError::EmptyParallelRouteName);
+            }
             return Ok(PageSegment::Parallel(s.to_string()));
         }
 
-        if let Some(s) = segment
+        if !segment.contains(['[', ']']) {
+            return Ok(PageSegment::Static(segment.to_string()));
+        }
+
+        let (name, make_segment): (_, fn(String) -> Self) = if let Some(name) = segment
             .strip_prefix("[[...")
             .and_then(|s| s.strip_suffix("]]"))
         {
-            return Ok(PageSegment::OptionalCatchAll(s.to_string()));
-        }
-
-        if let Some(s) = segment
+            (name, PageSegment::OptionalCatchAll)
+        } else if let Some(name) = segment
             .strip_prefix("[...")
             .and_then(|s| s.strip_suffix(']'))
         {
-            return Ok(PageSegment::CatchAll(s.to_string()));
+            (name, PageSegment::CatchAll)
+        } else if let Some(name) = segment.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            (name, PageSegment::Dynamic)
+        } else {
+            return Err(invalid_brackets(segment));
+        };
+
+        if name.contains(['[', ']']) {
+            return Err(invalid_brackets(segment));
         }
-
-        if let Some(s) = segment.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
-            return Ok(PageSegment::Dynamic(s.to_string()));
+        if name.is_empty() {
+            return Err(ParsedSegmentError::EmptyParamName(segment.to_string()));
+        }
+        if name.starts_with('.') {
+            return Err(ParsedSegmentError::InvalidParamName(segment.to_string()));
         }
 
-        Ok(PageSegment::Static(segment.to_string()))
+        Ok(make_segment(name.to_string()))
+    }
+}
+
+fn invalid_brackets(segment: &str) -> ParsedSegmentError {
+    if segment.matches('[').count() != segment.matches(']').count() {
+        ParsedSegmentError::UnbalancedBrackets(segment.to_string())
+    } else {
+        ParsedSegmentError::InvalidDynamicSegment(segment.to_string())
     }
 }
 
@@ -184,6 +257,43 @@ impl AppPage {
 
         Ok(app_page)
     }
+
+    /// Formats the page in the denormalized page path format used by Next.js,
+    /// which ends with the page type, e.g. `/blog/[slug]/page` or
+    /// `/api/route`. The not-found pages are the exception, they are named
+    /// `/not-found` and `/_not-found`.
+    pub fn to_page_path(&self, page_type: PageType) -> String {
+        if matches!(self.0.last(), Some(PageSegment::PageType(_))) || self.is_not_found() {
+            return self.to_string();
+        }
+
+        let mut segments = self.0.clone();
+        segments.push(PageSegment::PageType(page_type));
+        AppPage(segments).to_string()
+    }
+
+    /// Parses a page path in the denormalized format, the inverse of
+    /// [AppPage::to_page_path]. The returned page doesn't include the page
+    /// type.
+    pub fn from_page_path(page_path: &str) -> Result<(Self, PageType)> {
+        if matches!(page_path, "/not-found" | "/_not-found") {
+            return Ok((Self::parse(page_path)?, PageType::Page));
+        }
+
+        let Some((page, page_type)) = page_path.rsplit_once('/') else {
+            bail!("Invalid page path {page_path}, it must start with a slash");
+        };
+        let page_type = match page_type {
+            "page" => PageType::Page,
+            "route" => PageType::Route,
+            _ => bail!("Invalid page path {page_path}, it must end with /page or /route"),
+        };
+        Ok((Self::parse(page)?, page_type))
+    }
+
+    fn is_not_found(&self) -> bool {
+        matches!(&self.0[..], [PageSegment::Static(segment)] if segment == "not-found" || segment == "_not-found")
+    }
 }
 
 impl Display for AppPage {
@@ -286,3 +396,123 @@ impl From<AppPage> for AppPath {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_segment() {
+        for (segment, expected) in [
+            ("blog", Ok(PageSegment::Static("blog".to_string()))),
+            ("(.)photo", Ok(PageSegment::Static("(.)photo".to_string()))),
+            ("(shop)", Ok(PageSegment::Group("shop".to_string()))),
+            ("@modal", Ok(PageSegment::Parallel("modal".to_string()))),
+            ("[slug]", Ok(PageSegment::Dynamic("slug".to_string()))),
+            ("[...slug]", Ok(PageSegment::CatchAll("slug".to_string()))),
+            (
+                "[[...slug]]",
+                Ok(PageSegment::OptionalCatchAll("slug".to_string())),
+            ),
+            ("", Err(ParsedSegmentError::Empty)),
+            ("a/b", Err(ParsedSegmentError::Slash("a/b".to_string()))),
+            ("()", Err(ParsedSegmentError::EmptyGroupName)),
+            ("@", Err(ParsedSegmentError::EmptyParallelRouteName)),
+            (
+                "[]",
+                Err(ParsedSegmentError::EmptyParamName("[]".to_string())),
+            ),
+            (
+                "[...]",
+                Err(ParsedSegmentError::EmptyParamName("[...]".to_string())),
+            ),
+            (
+                "[[...]]",
+                Err(ParsedSegmentError::EmptyParamName("[[...]]".to_string())),
+            ),
+            (
+                "[slug",
+                Err(ParsedSegmentError::UnbalancedBrackets("[slug".to_string())),
+            ),
+            (
+                "[[...slug]",
+                Err(ParsedSegmentError::UnbalancedBrackets(
+                    "[[...slug]".to_string(),
+                )),
+            ),
+            (
+                "[[slug]]",
+                Err(ParsedSegmentError::InvalidDynamicSegment(
+                    "[[slug]]".to_string(),
+                )),
+            ),
+            (
+                "post-[id]",
+                Err(ParsedSegmentError::InvalidDynamicSegment(
+                    "post-[id]".to_string(),
+                )),
+            ),
+            (
+                "[..slug]",
+                Err(ParsedSegmentError::InvalidParamName("[..slug]".to_string())),
+            ),
+        ] {
+            assert_eq!(PageSegment::parse(segment), expected, "{segment}");
+        }
+    }
+
+    /// Parses every segment of up to four characters from an alphabet of the
+    /// characters with a meaning, every accepted segment must display as the
+    /// input again.
+    #[test]
+    fn test_parse_segment_exhaustive() {
+        const ALPHABET: [char; 7] = ['[', ']', '.', '(', ')', '@', 'a'];
+        let mut segments = vec![String::new()];
+        for _ in 0..4 {
+            segments = segments
+                .iter()
+                .flat_map(|segment| {
+                    ALPHABET.iter().map(move |c| {
+                        let mut segment = segment.clone();
+                        segment.push(*c);
+                        segment
+                    })
+                })
+                .collect();
+            for segment in &segments {
+                if let Ok(parsed) = PageSegment::parse(segment) {
+                    assert_eq!(parsed.to_string(), *segment);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_page_path_round_trip() {
+        for (page_path, page_type) in [
+            ("/page", PageType::Page),
+            ("/blog/[slug]/page", PageType::Page),
+            ("/(marketing)/@modal/(.)photo/[id]/page", PageType::Page),
+            ("/docs/[[...slug]]/page", PageType::Page),
+            ("/api/[...path]/route", PageType::Route),
+            ("/page/page", PageType::Page),
+            ("/_not-found", PageType::Page),
+        ] {
+            let (page, parsed_page_type) = AppPage::from_page_path(page_path).unwrap();
+            assert_eq!(parsed_page_type, page_type);
+            assert_eq!(page.to_page_path(page_type), page_path);
+        }
+    }
+
+    #[test]
+    fn test_page_path() {
+        let page = AppPage::parse("/blog/[slug]").unwrap();
+        assert_eq!(page.to_page_path(PageType::Page), "/blog/[slug]/page");
+        assert_eq!(
+            AppPage::from_page_path("/blog/[slug]/page").unwrap().0,
+            page
+        );
+        assert!(AppPage::from_page_path("/blog/[slug]").is_err());
+        assert!(AppPage::from_page_path("page").is_err());
+    }
+}
@@ -0,0 +1,526 @@
+use std::{collections::HashSet, future::Future, hash::Hash};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{source_map::Pos, Span, Spanned},
+    ecma::ast::{Decl, ExportSpecifier, ModuleDecl, ModuleExportName, Program},
+};
+use turbo_tasks::{trace::TraceRawVcs, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+            module::Module,
+            reference_type::EcmaScriptModulesReferenceSubType,
+            resolve::{parse::Request, pattern::Pattern},
+            source::Source,
+        },
+        ecmascript::{parse::ParseResult, resolve::esm_resolve, EcmascriptModuleAsset},
+    },
+};
+
+/// The HTTP methods a route handler can export a handler for.
+pub const HTTP_METHODS: [&str; 7] = ["GET", "HEAD", "OPTIONS", "POST", "PUT", "DELETE", "PATCH"];
+
+/// The HTTP methods a route handler exports, including the ones re-exported
+/// from other modules.
+#[turbo_tasks::value(shared)]
+pub struct RouteHandlerMethods {
+    pub methods: Vec<String>,
+    /// The route handler module and the modules its methods are re-exported
+    /// from, which define the handlers.
+    pub modules: Vec<Vc<Box<dyn Module>>>,
+}
+
+/// `export { orig as exported } from "request"`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct ReExport {
+    exported: String,
+    orig: String,
+    request: String,
+    /// `None` when the request can't be resolved.
+    module: Option<Vc<Box<dyn Module>>>,
+    span: (usize, usize),
+}
+
+/// The exports of a single module, without following its re-exports.
+#[turbo_tasks::value(shared)]
+struct ModuleExports {
+    /// Whether the exports could be analyzed. They can't for CommonJS modules,
+    /// for example.
+    analyzable: bool,
+    /// The names the module exports itself.
+    names: Vec<String>,
+    re_exports: Vec<ReExport>,
+    /// The modules of `export * from "..."`. `None` when a request can't be
+    /// resolved.
+    star_exports: Vec<Option<Vc<Box<dyn Module>>>>,
+    /// The span of `export default`, if any.
+    default_export: Option<(usize, usize)>,
+}
+
+fn span_offsets(span: Span) -> (usize, usize) {
+    (span.lo.to_usize(), span.hi.to_usize())
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+#[turbo_tasks::function]
+async fn module_exports(module: Vc<Box<dyn Module>>) -> Result<Vc<ModuleExports>> {
+    let mut exports = ModuleExports {
+        analyzable: false,
+        names: vec![],
+        re_exports: vec![],
+        star_exports: vec![],
+        default_export: None,
+    };
+
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(exports.cell());
+    };
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        return Ok(exports.cell());
+    };
+    exports.analyzable = true;
+
+    let resolve = |request: &str| {
+        esm_resolve(
+            Vc::upcast(ecmascript_asset),
+            Request::parse(Value::new(Pattern::Constant(request.to_string()))),
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            OptionIssueSource::none(),
+            IssueSeverity::Error.cell(),
+        )
+        .first_module()
+    };
+
+    for item in &module_ast.body {
+        let Some(module_decl) = item.as_module_decl() else {
+            continue;
+        };
+        match module_decl {
+            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
+                Decl::Fn(fn_decl) => exports.names.push(fn_decl.ident.sym.to_string()),
+                Decl::Class(class_decl) => exports.names.push(class_decl.ident.sym.to_string()),
+                Decl::Var(var_decl) => {
+                    for decl in &var_decl.decls {
+                        if let Some(ident) = decl.name.as_ident() {
+                            exports.names.push(ident.sym.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+                exports.names.push("default".to_string());
+                exports.default_export = Some(span_offsets(module_decl.span()));
+            }
+            ModuleDecl::ExportNamed(named_export) => {
+                let src_module = match &named_export.src {
+                    Some(src) => Some(*resolve(&src.value).await?),
+                    None => None,
+                };
+                for specifier in &named_export.specifiers {
+                    let (exported, orig) = match specifier {
+                        ExportSpecifier::Named(named) => (
+                            export_name(named.exported.as_ref().unwrap_or(&named.orig)),
+                            export_name(&named.orig),
+                        ),
+                        // `export * as ns from "..."`
+                        ExportSpecifier::Namespace(namespace) => {
+                            exports.names.push(export_name(&namespace.name));
+                            continue;
+                        }
+                        ExportSpecifier::Default(default) => {
+                            (default.exported.sym.to_string(), "default".to_string())
+                        }
+                    };
+                    if exported == "default" {
+                        exports.default_export = Some(span_offsets(specifier.span()));
+                    }
+                    match &named_export.src {
+                        Some(src) => exports.re_exports.push(ReExport {
+                            exported,
+                            orig,
+                            request: src.value.to_string(),
+                            module: src_module,
+                            span: span_offsets(specifier.span()),
+                        }),
+                        None => exports.names.push(exported),
+                    }
+                }
+            }
+            ModuleDecl::ExportAll(export_all) => {
+                exports
+                    .star_exports
+                    .push(*resolve(&export_all.src.value).await?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(exports.cell())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportResolution<M> {
+    /// The export is defined in the module.
+    Found(M),
+    Missing,
+    /// The re-export chain goes through a module whose exports can't be
+    /// analyzed.
+    Unknown,
+}
+
+/// What [resolve_export] needs to know about the exports of a module. It's
+/// generic over the modules, so export chains can be followed in tests
+/// without turbo tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExportLinks<M> {
+    names: Vec<String>,
+    /// `(exported, orig, module)` of `export { orig as exported } from "..."`.
+    re_exports: Vec<(String, String, Option<M>)>,
+    star_exports: Vec<Option<M>>,
+}
+
+impl ModuleExports {
+    fn links(&self) -> Option<ExportLinks<Vc<Box<dyn Module>>>> {
+        self.analyzable.then(|| ExportLinks {
+            names: self.names.clone(),
+            re_exports: self
+                .re_exports
+                .iter()
+                .map(|r| (r.exported.clone(), r.orig.clone(), r.module))
+                .collect(),
+            star_exports: self.star_exports.clone(),
+        })
+    }
+}
+
+/// Follows the re-exports of the module to find out whether it exports
+/// `name`, and which module defines it. `links` returns `None` for modules
+/// whose exports can't be analyzed.
+async fn resolve_export<M, F, Fut>(
+    module: M,
+    name: &str,
+    mut links: F,
+) -> Result<ExportResolution<M>>
+where
+    M: Copy + Eq + Hash,
+    F: FnMut(M) -> Fut,
+    Fut: Future<Output = Result<Option<ExportLinks<M>>>>,
+{
+    let mut visited = HashSet::new();
+    let mut queue = vec![(module, name.to_string())];
+    let mut unknown = false;
+
+    while let Some((module, name)) = queue.pop() {
+        if !visited.insert((module, name.clone())) {
+            continue;
+        }
+        let Some(exports) = links(module).await? else {
+            unknown = true;
+            continue;
+        };
+        if exports.names.contains(&name) {
+            return Ok(ExportResolution::Found(module));
+        }
+        if let Some((_, orig, re_export_module)) = exports
+            .re_exports
+            .iter()
+            .find(|(exported, ..)| *exported == name)
+        {
+            match re_export_module {
+                Some(module) => queue.push((*module, orig.clone())),
+                None => unknown = true,
+            }
+            continue;
+        }
+        // Star exports never include the default export.
+        if name != "default" {
+            for star_export in &exports.star_exports {
+                match star_export {
+                    Some(module) => queue.push((*module, name.clone())),
+                    None => unknown = true,
+                }
+            }
+        }
+    }
+
+    Ok(if unknown {
+        ExportResolution::Unknown
+    } else {
+        ExportResolution::Missing
+    })
+}
+
+/// Finds the HTTP methods a route handler exports, following
+/// `export { GET } from "./handlers"` and `export * from "./handlers"`
+/// re-exports. Warns about route handlers which don't export any HTTP method
+/// and about re-exported methods which don't exist.
+#[turbo_tasks::function]
+pub async fn route_handler_methods(
+    module: Vc<Box<dyn Module>>,
+    source: Vc<Box<dyn Source>>,
+) -> Result<Vc<RouteHandlerMethods>> {
+    let exports = module_exports(module).await?;
+    if !exports.analyzable {
+        return Ok(RouteHandlerMethods {
+            methods: vec![],
+            modules: vec![module],
+        }
+        .cell());
+    }
+
+    let issue = |severity: IssueSeverity, title: String, span: Option<(usize, usize)>| {
+        RouteHandlerExportIssue {
+            ident: module.ident().path(),
+            severity: severity.cell(),
+            title: Vc::cell(title),
+            source: span.map(|(start, end)| IssueSource::from_byte_offset(source, start, end)),
+        }
+        .cell()
+        .emit()
+    };
+
+    let mut methods = vec![];
+    let mut modules = vec![module];
+    let mut unknown = false;
+    for method in HTTP_METHODS {
+        let resolution = resolve_export(module, method, |module| async move {
+            Ok::<_, anyhow::Error>(module_exports(module).await?.links())
+        })
+        .await?;
+        match resolution {
+            ExportResolution::Found(defining_module) => {
+                methods.push(method.to_string());
+                if !modules.contains(&defining_module) {
+                    modules.push(defining_module);
+                }
+            }
+            ExportResolution::Missing => {
+                if let Some(re_export) = exports.re_exports.iter().find(|r| r.exported == method) {
+                    issue(
+                        IssueSeverity::Error,
+                        format!(
+                            "`{method}` is re-exported from \"{}\", which doesn't export `{}`",
+                            re_export.request, re_export.orig
+                        ),
+                        Some(re_export.span),
+                    );
+                }
+            }
+            ExportResolution::Unknown => unknown = true,
+        }
+    }
+
+    if let Some(span) = exports.default_export {
+        issue(
+            IssueSeverity::Warning,
+            "Detected a default export in a route handler, export a named export for each HTTP \
+             method instead"
+                .to_string(),
+            Some(span),
+        );
+    }
+    let exported_names = exports
+        .names
+        .iter()
+        .chain(exports.re_exports.iter().map(|r| &r.exported));
+    for name in exported_names {
+        let uppercase = name.to_ascii_uppercase();
+        if *name != uppercase && HTTP_METHODS.contains(&uppercase.as_str()) {
+            issue(
+                IssueSeverity::Warning,
+                format!("Detected the lowercase method `{name}`, export `{uppercase}` instead"),
+                None,
+            );
+        }
+    }
+    if methods.is_empty() && !unknown {
+        issue(
+            IssueSeverity::Warning,
+            "No HTTP methods are exported by the route handler, export a named export for each \
+             HTTP method"
+                .to_string(),
+            None,
+        );
+    }
+
+    Ok(RouteHandlerMethods { methods, modules }.cell())
+}
+
+#[turbo_tasks::value(shared)]
+struct RouteHandlerExportIssue {
+    ident: Vc<FileSystemPath>,
+    severity: Vc<IssueSeverity>,
+    title: Vc<String>,
+    source: Option<Vc<IssueSource>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RouteHandlerExportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        self.title
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("route handler".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Route handlers handle the HTTP methods they export a function for: {}.",
+            HTTP_METHODS.join(", ")
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(
+            "https://nextjs.org/docs/app/building-your-application/routing/route-handlers"
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        match self.source {
+            Some(source) => OptionIssueSource::some(source),
+            None => OptionIssueSource::none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn links(
+        names: &[&str],
+        re_exports: &[(&str, &str, Option<&'static str>)],
+        star_exports: &[Option<&'static str>],
+    ) -> Option<ExportLinks<&'static str>> {
+        Some(ExportLinks {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            re_exports: re_exports
+                .iter()
+                .map(|&(exported, orig, module)| (exported.to_string(), orig.to_string(), module))
+                .collect(),
+            star_exports: star_exports.to_vec(),
+        })
+    }
+
+    /// Resolves `name` from the `route` module of the graph. Modules mapped
+    /// to `None` can't be analyzed, like CommonJS modules.
+    fn resolve(
+        graph: &HashMap<&'static str, Option<ExportLinks<&'static str>>>,
+        name: &str,
+    ) -> ExportResolution<&'static str> {
+        block_on(resolve_export("route", name, |module| {
+            let links = graph[module].clone();
+            async move { Ok::<_, anyhow::Error>(links) }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_own_export() {
+        let graph = HashMap::from([("route", links(&["GET"], &[], &[]))]);
+        assert_eq!(resolve(&graph, "GET"), ExportResolution::Found("route"));
+        assert_eq!(resolve(&graph, "POST"), ExportResolution::Missing);
+    }
+
+    #[test]
+    fn test_resolve_renamed_re_export() {
+        let graph = HashMap::from([
+            (
+                "route",
+                links(
+                    &[],
+                    &[
+                        ("GET", "handler", Some("handlers")),
+                        ("POST", "post", Some("handlers")),
+                    ],
+                    &[],
+                ),
+            ),
+            ("handlers", links(&["handler"], &[], &[])),
+        ]);
+        assert_eq!(resolve(&graph, "GET"), ExportResolution::Found("handlers"));
+        // `handlers` doesn't export `post`.
+        assert_eq!(resolve(&graph, "POST"), ExportResolution::Missing);
+    }
+
+    #[test]
+    fn test_resolve_star_export() {
+        let graph = HashMap::from([
+            ("route", links(&[], &[], &[Some("a")])),
+            ("a", links(&["helper"], &[], &[Some("b")])),
+            ("b", links(&["GET", "default"], &[], &[])),
+        ]);
+        assert_eq!(resolve(&graph, "GET"), ExportResolution::Found("b"));
+        // Star exports don't include the default export.
+        assert_eq!(resolve(&graph, "default"), ExportResolution::Missing);
+    }
+
+    #[test]
+    fn test_resolve_export_cycle() {
+        let graph = HashMap::from([
+            ("route", links(&[], &[], &[Some("a")])),
+            ("a", links(&[], &[("GET", "GET", Some("b"))], &[])),
+            ("b", links(&[], &[], &[Some("route"), Some("a")])),
+        ]);
+        assert_eq!(resolve(&graph, "GET"), ExportResolution::Missing);
+    }
+
+    #[test]
+    fn test_resolve_unanalyzable_export() {
+        let graph = HashMap::from([
+            (
+                "route",
+                links(
+                    &[],
+                    &[("GET", "GET", Some("cjs")), ("POST", "POST", None)],
+                    &[],
+                ),
+            ),
+            ("cjs", None),
+        ]);
+        // Through a CommonJS module.
+        assert_eq!(resolve(&graph, "GET"), ExportResolution::Unknown);
+        // From a request which can't be resolved.
+        assert_eq!(resolve(&graph, "POST"), ExportResolution::Unknown);
+        assert_eq!(resolve(&graph, "PUT"), ExportResolution::Missing);
+    }
+}
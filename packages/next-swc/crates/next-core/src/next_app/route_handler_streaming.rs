@@ -0,0 +1,156 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{CallExpr, Callee, Expr, Ident, MemberExpr, MemberProp, NewExpr, Program},
+    visit::{noop_visit_type, Visit, VisitWith},
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::{
+    core::module::Module,
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+use super::route_handler_methods::RouteHandlerMethods;
+
+/// Whether a route handler streams its responses, i.e. it constructs a
+/// `ReadableStream` or `TransformStream`, or defers work with `waitUntil`.
+/// Hosting adapters shouldn't buffer the responses of such routes.
+///
+/// The route handler module and the modules it re-exports its methods from
+/// are analyzed, streams created by other imported modules aren't detected.
+#[turbo_tasks::function]
+pub async fn is_streaming_route_handler(methods: Vc<RouteHandlerMethods>) -> Result<Vc<bool>> {
+    for &module in methods.await?.modules.iter() {
+        if *has_streaming_module(module).await? {
+            return Ok(Vc::cell(true));
+        }
+    }
+    Ok(Vc::cell(false))
+}
+
+#[turbo_tasks::function]
+async fn has_streaming_module(module: Vc<Box<dyn Module>>) -> Result<Vc<bool>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(false));
+    };
+
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Vc::cell(false));
+    };
+
+    Ok(Vc::cell(has_streaming_constructs(program)))
+}
+
+fn has_streaming_constructs(program: &Program) -> bool {
+    let mut visitor = StreamingVisitor { streaming: false };
+    program.visit_with(&mut visitor);
+    visitor.streaming
+}
+
+struct StreamingVisitor {
+    streaming: bool,
+}
+
+fn is_stream_constructor(ident: &Ident) -> bool {
+    matches!(&*ident.sym, "ReadableStream" | "TransformStream")
+}
+
+impl Visit for StreamingVisitor {
+    noop_visit_type!();
+
+    fn visit_new_expr(&mut self, new_expr: &NewExpr) {
+        if matches!(&*new_expr.callee, Expr::Ident(ident) if is_stream_constructor(ident)) {
+            self.streaming = true;
+            return;
+        }
+        new_expr.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        // `ReadableStream.from(...)`
+        if let Callee::Expr(callee) = &call_expr.callee {
+            if let Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) = &**callee
+            {
+                if &*prop.sym == "from"
+                    && matches!(&**obj, Expr::Ident(ident) if is_stream_constructor(ident))
+                {
+                    self.streaming = true;
+                    return;
+                }
+            }
+        }
+        call_expr.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        // `waitUntil(...)`, `ctx.waitUntil(...)` or passing it along.
+        if &*ident.sym == "waitUntil" {
+            self.streaming = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn is_streaming(code: &str) -> bool {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        has_streaming_constructs(&program)
+    }
+
+    #[test]
+    fn test_streaming_constructs() {
+        assert!(is_streaming(
+            "export function GET() { return new Response(new ReadableStream({})); }"
+        ));
+        assert!(is_streaming(
+            "export async function POST(req) { const { readable } = new TransformStream(); return \
+             new Response(readable); }"
+        ));
+        assert!(is_streaming(
+            "export function GET() { return new Response(ReadableStream.from(items())); }"
+        ));
+        assert!(is_streaming(
+            "export function GET(req, ctx) { ctx.waitUntil(log()); return Response.json({}); }"
+        ));
+        assert!(is_streaming(
+            "import { waitUntil } from './utils'; export function GET() { waitUntil(log()); }"
+        ));
+    }
+
+    #[test]
+    fn test_buffered_route_handlers() {
+        assert!(!is_streaming(
+            "export async function GET() { return Response.json(await getData()); }"
+        ));
+        assert!(!is_streaming(
+            "export function GET() { return new Response(ReadableStreamLike); }"
+        ));
+        assert!(!is_streaming(
+            "export function GET() { return new Response(streams.ReadableStream); }"
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+
+use crate::app_structure::{
+    visit_loader_tree, Components, LoaderTree, LoaderTreeVisitor, VisitControlFlow,
+};
+
+/// The error, loading and not found boundaries of a segment of a page.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentBoundary {
+    /// The path of the segment in the loader tree, e.g.
+    /// `/(shop)/dashboard/@modal`. Parallel routes other than `children` are
+    /// prefixed with `@`.
+    pub segment: String,
+    pub error: bool,
+    pub loading: bool,
+    pub not_found: bool,
+}
+
+/// The segments of a page which have an `error`, `loading` or `not-found`
+/// file, from the root layout down to the page.
+#[turbo_tasks::value(transparent)]
+pub struct SegmentBoundaries(Vec<SegmentBoundary>);
+
+#[turbo_tasks::function]
+pub async fn segment_boundaries(loader_tree: Vc<LoaderTree>) -> Result<Vc<SegmentBoundaries>> {
+    let mut visitor = SegmentBoundariesVisitor::default();
+    visit_loader_tree(loader_tree, &mut visitor).await?;
+    Ok(Vc::cell(visitor.boundaries))
+}
+
+#[derive(Default)]
+struct SegmentBoundariesVisitor {
+    /// The segments and parallel routes entered so far. `None` for the ones
+    /// which don't appear in the path, like the root segment and `children`.
+    path: Vec<Option<String>>,
+    boundaries: Vec<SegmentBoundary>,
+}
+
+impl SegmentBoundariesVisitor {
+    fn segment_path(&self) -> String {
+        let path = self.path.iter().flatten().cloned().collect::<Vec<_>>();
+        format!("/{}", path.join("/"))
+    }
+}
+
+#[async_trait]
+impl LoaderTreeVisitor for SegmentBoundariesVisitor {
+    async fn enter_segment(
+        &mut self,
+        tree: &LoaderTree,
+        components: &Components,
+    ) -> Result<VisitControlFlow> {
+        self.path
+            .push((!tree.segment.is_empty()).then(|| tree.segment.clone()));
+
+        let boundary = SegmentBoundary {
+            segment: self.segment_path(),
+            error: components.error.is_some(),
+            loading: components.loading.is_some(),
+            not_found: components.not_found.is_some(),
+        };
+        if boundary.error || boundary.loading || boundary.not_found {
+            self.boundaries.push(boundary);
+        }
+        Ok(VisitControlFlow::Continue)
+    }
+
+    async fn enter_parallel_route(&mut self, key: &str) -> Result<()> {
+        self.path
+            .push((key != "children").then(|| format!("@{key}")));
+        Ok(())
+    }
+
+    async fn leave_parallel_route(&mut self, _key: &str) -> Result<()> {
+        self.path.pop();
+        Ok(())
+    }
+
+    async fn leave_segment(&mut self, _tree: &LoaderTree, _components: &Components) -> Result<()> {
+        self.path.pop();
+        Ok(())
+    }
+}
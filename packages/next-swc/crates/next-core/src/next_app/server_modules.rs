@@ -0,0 +1,33 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::core::module::{Module, Modules};
+
+use crate::{
+    next_client_reference::{CssClientReferenceModule, EcmascriptClientReferenceModule},
+    util::ModuleWalk,
+};
+
+/// The server modules reachable from an app entry, in breadth-first order.
+/// Client components aren't followed, and neither are packages in
+/// node_modules.
+#[turbo_tasks::function]
+pub(crate) async fn app_server_modules(entry: Vc<Box<dyn Module>>) -> Result<Vc<Modules>> {
+    let mut walk = ModuleWalk::new([entry]);
+    let mut modules = vec![];
+
+    while let Some(module) = walk.next().await? {
+        if Vc::try_resolve_downcast_type::<EcmascriptClientReferenceModule>(module)
+            .await?
+            .is_some()
+            || Vc::try_resolve_downcast_type::<CssClientReferenceModule>(module)
+                .await?
+                .is_some()
+        {
+            continue;
+        }
+        modules.push(module);
+        walk.visit_references(module).await?;
+    }
+
+    Ok(Vc::cell(modules))
+}
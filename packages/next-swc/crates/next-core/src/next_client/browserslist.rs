@@ -0,0 +1,167 @@
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use turbo_tasks::Vc;
+use turbopack_binding::turbo::tasks_fs::{FileContent, FileJsonContent, FileSystemPath};
+
+use crate::{mode::NextMode, next_config::NextConfig};
+
+/// The browsers targeted when a project doesn't configure `browserslist`.
+///
+/// https://github.com/vercel/next.js/blob/bf52c254973d99fed9d71507a2e818af80b8ade7/packages/next/src/shared/lib/constants.ts#L128-L134
+pub const MODERN_BROWSERSLIST_TARGET: &str = "chrome 64, edge 79, firefox 67, opera 51, safari 12";
+
+/// The browsers targeted when `experimental.legacyBrowsers` is enabled and
+/// the project doesn't configure `browserslist`.
+pub const LEGACY_BROWSERSLIST_TARGET: &str = "defaults";
+
+/// Development builds don't need to support older browsers, transpiling less
+/// keeps them fast.
+const DEVELOPMENT_BROWSERSLIST_TARGET: &str =
+    "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari versions, last 1 Edge versions";
+
+/// Computes the browserslist query used for client chunks, following the
+/// lookup order of browserslist itself: the `browserslist` key in
+/// package.json, then a `.browserslistrc` file in the project directory.
+#[turbo_tasks::function]
+pub async fn get_browserslist_query(
+    project_path: Vc<FileSystemPath>,
+    mode: NextMode,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<String>> {
+    let env = mode.node_env();
+
+    if let FileJsonContent::Content(package) = &*project_path
+        .join("package.json".to_string())
+        .read_json()
+        .await?
+    {
+        if let Some(query) = browserslist_from_package_json(&package["browserslist"], env) {
+            return Ok(Vc::cell(query));
+        }
+    }
+
+    if let FileContent::Content(file) = &*project_path
+        .join(".browserslistrc".to_string())
+        .read()
+        .await?
+    {
+        if let Some(query) = browserslist_from_rc(&file.content().to_str()?, env) {
+            return Ok(Vc::cell(query));
+        }
+    }
+
+    let query = if *next_config.legacy_browsers().await? {
+        LEGACY_BROWSERSLIST_TARGET
+    } else if mode == NextMode::Build {
+        MODERN_BROWSERSLIST_TARGET
+    } else {
+        DEVELOPMENT_BROWSERSLIST_TARGET
+    };
+    Ok(Vc::cell(query.to_string()))
+}
+
+/// Reads the `browserslist` key of a package.json, which is either a query, a
+/// list of queries, or an object of queries keyed by environment.
+fn browserslist_from_package_json(value: &JsonValue, env: &str) -> Option<String> {
+    match value {
+        JsonValue::String(query) => Some(query.clone()),
+        JsonValue::Array(queries) => Some(
+            queries
+                .iter()
+                .filter_map(|query| query.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        JsonValue::Object(envs) => envs
+            .get(env)
+            .or_else(|| envs.get("defaults"))
+            .and_then(|queries| browserslist_from_package_json(queries, env)),
+        _ => None,
+    }
+    .filter(|query| !query.is_empty())
+}
+
+/// Parses a `.browserslistrc` file. Queries listed in an `[env]` section only
+/// apply to that environment and replace the ones listed without a section.
+fn browserslist_from_rc(content: &str, env: &str) -> Option<String> {
+    let mut defaults = Vec::new();
+    let mut env_queries: Option<Vec<&str>> = None;
+    let mut section: Option<bool> = None;
+
+    for line in content.lines() {
+        let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(names) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(names.split_whitespace().any(|name| name == env));
+            continue;
+        }
+        match section {
+            None => defaults.push(line),
+            Some(true) => env_queries.get_or_insert_with(Vec::new).push(line),
+            Some(false) => {}
+        }
+    }
+
+    let queries = env_queries.unwrap_or(defaults);
+    (!queries.is_empty()).then(|| queries.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_browserslist_from_package_json() {
+        assert_eq!(
+            browserslist_from_package_json(&json!(["chrome 90", "safari 14"]), "production"),
+            Some("chrome 90, safari 14".to_string())
+        );
+        assert_eq!(
+            browserslist_from_package_json(
+                &json!({ "production": [">0.2%"], "development": ["last 1 chrome version"] }),
+                "development"
+            ),
+            Some("last 1 chrome version".to_string())
+        );
+        assert_eq!(
+            browserslist_from_package_json(&json!({ "development": ["chrome 90"] }), "production"),
+            None
+        );
+        assert_eq!(
+            browserslist_from_package_json(&JsonValue::Null, "production"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_browserslist_from_rc() {
+        let rc = r#"
+            # supported browsers
+            defaults
+            not IE 11
+
+            [production staging]
+            > 0.5%
+
+            [development]
+            last 1 chrome version # fast builds
+        "#;
+        assert_eq!(
+            browserslist_from_rc(rc, "production"),
+            Some("> 0.5%".to_string())
+        );
+        assert_eq!(
+            browserslist_from_rc(rc, "development"),
+            Some("last 1 chrome version".to_string())
+        );
+        assert_eq!(
+            browserslist_from_rc(rc, "test"),
+            Some("defaults, not IE 11".to_string())
+        );
+        assert_eq!(browserslist_from_rc("# nothing\n", "production"), None);
+    }
+}
@@ -25,7 +25,7 @@ use turbopack_binding::{
             module_options::{
                 module_options_context::ModuleOptionsContext, CustomEcmascriptTransformPlugins,
                 JsxTransformOptions, MdxTransformModuleOptions, PostCssTransformOptions,
-                TypescriptTransformOptions, WebpackLoadersOptions,
+                TypescriptTransformOptions, WebpackLoadersOptions, WebpackRules,
             },
             resolve_options_context::ResolveOptionsContext,
         },
@@ -34,7 +34,10 @@ use turbopack_binding::{
 
 use super::transforms::get_next_client_transforms_rules;
 use crate::{
-    babel::maybe_add_babel_loader,
+    babel::{
+        exclude_react_compiler_from_node_modules, maybe_add_babel_loader,
+        maybe_add_react_compiler_loader,
+    },
     embed_js::next_js_fs,
     env::env_for_js,
     mode::NextMode,
@@ -46,9 +49,15 @@ use crate::{
         get_next_client_resolved_map, mdx_import_source_file,
     },
     next_shared::{
-        resolve::{ModuleFeatureReportResolvePlugin, UnsupportedModulesResolvePlugin},
+        asset_query::AssetQueryResolvePlugin,
+        resolve::{
+            ImportCasingResolvePlugin, ModuleFeatureReportResolvePlugin, PackageExportsReplacer,
+            UnsupportedModulesResolvePlugin,
+        },
         transforms::{
             emotion::get_emotion_transform_plugin, get_relay_transform_plugin,
+            keep_component_names::get_keep_component_names_transform_plugin,
+            react_remove_properties::get_react_remove_properties_transform_plugin,
             styled_components::get_styled_components_transform_plugin,
             styled_jsx::get_styled_jsx_transform_plugin,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin,
@@ -56,33 +65,72 @@ use crate::{
     },
     sass::maybe_add_sass_loader,
     transform_options::{
-        get_decorators_transform_options, get_jsx_transform_options,
+        add_package_tsconfig_rules, get_decorators_transform_options, get_jsx_transform_options,
         get_typescript_transform_options,
     },
     util::foreign_code_context_condition,
 };
 
-fn defines(mode: NextMode) -> CompileTimeDefines {
-    compile_time_defines!(
+fn defines(
+    mode: NextMode,
+    cross_origin: Option<&str>,
+    dev_server_origin: Option<&str>,
+) -> CompileTimeDefines {
+    let mut defines = compile_time_defines!(
         process.turbopack = true,
         process.env.NODE_ENV = mode.node_env(),
         process.env.__NEXT_CLIENT_ROUTER_FILTER_ENABLED = false,
         process.env.__NEXT_HAS_REWRITES = true,
         process.env.__NEXT_I18N_SUPPORT = false,
-    )
+    );
+    // Read by the route loader for the `crossorigin` attribute of the chunk
+    // script and preload tags. It stays undefined when not configured.
+    if let Some(cross_origin) = cross_origin {
+        defines.0.insert(
+            vec![
+                "process".to_string(),
+                "env".to_string(),
+                "__NEXT_CROSS_ORIGIN".to_string(),
+            ],
+            cross_origin.to_string().into(),
+        );
+    }
+    // Read for the protocol of the HMR websocket URL, e.g. with
+    // `next dev --experimental-https`.
+    if let Some(dev_server_origin) = dev_server_origin {
+        defines.0.insert(
+            vec![
+                "process".to_string(),
+                "env".to_string(),
+                "__NEXT_DEV_SERVER_ORIGIN".to_string(),
+            ],
+            dev_server_origin.to_string().into(),
+        );
+    }
     // TODO(WEB-937) there are more defines needed, see
     // packages/next/src/build/webpack-config.ts
+    defines
 }
 
 #[turbo_tasks::function]
-fn next_client_defines(mode: NextMode) -> Vc<CompileTimeDefines> {
-    defines(mode).cell()
+async fn next_client_defines(
+    mode: NextMode,
+    next_config: Vc<NextConfig>,
+    dev_server_origin: Option<String>,
+) -> Result<Vc<CompileTimeDefines>> {
+    let cross_origin = next_config.cross_origin().await?;
+    Ok(defines(mode, cross_origin.as_deref(), dev_server_origin.as_deref()).cell())
 }
 
 #[turbo_tasks::function]
-async fn next_client_free_vars(mode: NextMode) -> Result<Vc<FreeVarReferences>> {
+async fn next_client_free_vars(
+    mode: NextMode,
+    next_config: Vc<NextConfig>,
+    dev_server_origin: Option<String>,
+) -> Result<Vc<FreeVarReferences>> {
+    let cross_origin = next_config.cross_origin().await?;
     Ok(free_var_references!(
-        ..defines(mode).into_iter(),
+        ..defines(mode, cross_origin.as_deref(), dev_server_origin.as_deref()).into_iter(),
         Buffer = FreeVarReference::EcmaScriptModule {
             request: "node:buffer".to_string(),
             lookup_path: None,
@@ -101,6 +149,8 @@ async fn next_client_free_vars(mode: NextMode) -> Result<Vc<FreeVarReferences>>
 pub fn get_client_compile_time_info(
     mode: NextMode,
     browserslist_query: String,
+    next_config: Vc<NextConfig>,
+    dev_server_origin: Option<String>,
 ) -> Vc<CompileTimeInfo> {
     CompileTimeInfo::builder(Environment::new(Value::new(ExecutionEnvironment::Browser(
         BrowserEnvironment {
@@ -111,8 +161,12 @@ pub fn get_client_compile_time_info(
         }
         .into(),
     ))))
-    .defines(next_client_defines(mode))
-    .free_var_references(next_client_free_vars(mode))
+    .defines(next_client_defines(
+        mode,
+        next_config,
+        dev_server_origin.clone(),
+    ))
+    .free_var_references(next_client_free_vars(mode, next_config, dev_server_origin))
     .cell()
 }
 
@@ -135,19 +189,39 @@ pub async fn get_client_resolve_options_context(
 ) -> Result<Vc<ResolveOptionsContext>> {
     let next_client_import_map =
         get_next_client_import_map(project_path, ty, mode, next_config, execution_context);
-    let next_client_fallback_import_map = get_next_client_fallback_import_map(ty);
     let next_client_resolved_map = get_next_client_resolved_map(project_path, project_path, mode);
+    let mut custom_conditions = vec![mode.node_env().to_string()];
+    custom_conditions.extend(
+        next_config
+            .export_conditions()
+            .await?
+            .client
+            .iter()
+            .cloned(),
+    );
+    let package_exports_replacer = PackageExportsReplacer::new(
+        "client".to_string(),
+        custom_conditions
+            .iter()
+            .cloned()
+            .chain(["browser".to_string(), "module".to_string()])
+            .collect(),
+    );
+    let next_client_fallback_import_map =
+        get_next_client_fallback_import_map(ty, next_config, package_exports_replacer);
     let module_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().resolve().await?),
-        custom_conditions: vec![mode.node_env().to_string()],
+        custom_conditions,
         import_map: Some(next_client_import_map),
         fallback_import_map: Some(next_client_fallback_import_map),
         resolved_map: Some(next_client_resolved_map),
         browser: true,
         module: true,
         plugins: vec![
+            Vc::upcast(AssetQueryResolvePlugin::new(project_path)),
             Vc::upcast(ModuleFeatureReportResolvePlugin::new(project_path)),
             Vc::upcast(UnsupportedModulesResolvePlugin::new(project_path)),
+            Vc::upcast(ImportCasingResolvePlugin::new(project_path)),
         ],
         ..Default::default()
     };
@@ -172,7 +246,8 @@ pub async fn get_client_module_options_context(
     mode: NextMode,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<ModuleOptionsContext>> {
-    let custom_rules = get_next_client_transforms_rules(next_config, ty.into_value(), mode).await?;
+    let custom_rules =
+        get_next_client_transforms_rules(project_path, next_config, ty.into_value(), mode).await?;
     let resolve_options_context =
         get_client_resolve_options_context(project_path, ty, mode, next_config, execution_context);
 
@@ -190,23 +265,41 @@ pub async fn get_client_module_options_context(
     };
     let jsx_runtime_options =
         get_jsx_transform_options(project_path, mode, Some(resolve_options_context));
+    let webpack_rules = *maybe_add_babel_loader(
+        project_path,
+        next_config.babel_rules(),
+        *next_config.webpack_rules().await?,
+    )
+    .await?;
+    let webpack_rules = if *next_config.use_grass().await? {
+        // Sass files are compiled by grass, see `get_grass_rules`.
+        webpack_rules
+    } else {
+        *maybe_add_sass_loader(next_config.sass_config(), webpack_rules).await?
+    };
+    let node_modules_webpack_rules = webpack_rules;
     let webpack_rules =
-        *maybe_add_babel_loader(project_path, *next_config.webpack_rules().await?).await?;
-    let webpack_rules = maybe_add_sass_loader(next_config.sass_config(), webpack_rules).await?;
-    let enable_webpack_loaders = webpack_rules.map(|rules| {
-        WebpackLoadersOptions {
-            rules,
-            loader_runner_package: Some(get_external_next_compiled_package_mapping(Vc::cell(
-                "loader-runner".to_owned(),
-            ))),
-        }
-        .cell()
-    });
+        *maybe_add_react_compiler_loader(project_path, next_config.react_compiler(), webpack_rules)
+            .await?;
+    let webpack_loaders = |rules: Option<Vc<WebpackRules>>| {
+        rules.map(|rules| {
+            WebpackLoadersOptions {
+                rules,
+                loader_runner_package: Some(get_external_next_compiled_package_mapping(Vc::cell(
+                    "loader-runner".to_owned(),
+                ))),
+            }
+            .cell()
+        })
+    };
+    let enable_webpack_loaders = webpack_loaders(webpack_rules);
 
     let source_transforms = vec![
         *get_swc_ecma_transform_plugin(project_path, next_config).await?,
         *get_relay_transform_plugin(next_config).await?,
         *get_emotion_transform_plugin(next_config).await?,
+        *get_react_remove_properties_transform_plugin(next_config).await?,
+        *get_keep_component_names_transform_plugin(next_config, mode).await?,
         *get_styled_components_transform_plugin(next_config).await?,
         *get_styled_jsx_transform_plugin().await?,
         Some(Vc::cell(Box::new(ServerDirectiveTransformer::new(
@@ -269,7 +362,23 @@ pub async fn get_client_module_options_context(
         ],
         custom_rules,
         ..module_options_context
-    }
+    };
+    let module_options_context = if webpack_rules != node_modules_webpack_rules {
+        exclude_react_compiler_from_node_modules(
+            module_options_context,
+            webpack_loaders(node_modules_webpack_rules),
+        )
+    } else {
+        module_options_context
+    };
+    let module_options_context = add_package_tsconfig_rules(
+        module_options_context,
+        project_path,
+        next_config,
+        mode,
+        Some(resolve_options_context),
+    )
+    .await?
     .cell();
 
     Ok(module_options_context)
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::AssetContent,
+        issue::{Issue, IssueExt, IssueSeverity},
+        resolve::{
+            options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+            parse::Request,
+            pattern::Pattern,
+            ResolveResult,
+        },
+        virtual_source::VirtualSource,
+    },
+};
+
+use crate::embed_js::next_js_file_path;
+
+/// Resolves a Node.js built-in imported from client code to its fallback,
+/// either a polyfill or an empty module, and reports that it did so.
+#[turbo_tasks::value(shared)]
+pub(crate) struct NodeFallbackReplacer {
+    context_dir: Vc<FileSystemPath>,
+    builtin: String,
+    /// The request of the polyfill, or `None` for an empty module.
+    target: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl NodeFallbackReplacer {
+    #[turbo_tasks::function]
+    pub fn new(
+        context_dir: Vc<FileSystemPath>,
+        builtin: String,
+        target: Option<String>,
+    ) -> Vc<Self> {
+        Self::cell(NodeFallbackReplacer {
+            context_dir,
+            builtin,
+            target,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for NodeFallbackReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    #[turbo_tasks::function]
+    fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        _request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        NodeFallbackIssue {
+            context,
+            builtin: self.builtin.clone(),
+            target: self.target.clone(),
+        }
+        .cell()
+        .emit();
+
+        Ok(match &self.target {
+            Some(target) => ImportMapResult::Alias(
+                Request::parse(Value::new(Pattern::Constant(target.clone()))),
+                Some(self.context_dir),
+            ),
+            None => {
+                let empty_module = VirtualSource::new(
+                    next_js_file_path(format!("internal/node-fallback/{}.js", self.builtin)),
+                    AssetContent::file(
+                        FileContent::Content("module.exports = {};\n".into()).cell(),
+                    ),
+                );
+                ImportMapResult::Result(ResolveResult::source(Vc::upcast(empty_module)).into())
+            }
+        }
+        .into())
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct NodeFallbackIssue {
+    context: Vc<FileSystemPath>,
+    builtin: String,
+    target: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for NodeFallbackIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Info.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(match &self.target {
+            Some(target) => format!(
+                "Node.js built-in \"{}\" was replaced by \"{}\" for the browser",
+                self.builtin, target
+            ),
+            None => format!(
+                "Node.js built-in \"{}\" was replaced by an empty module for the browser",
+                self.builtin
+            ),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "Node.js built-ins are not available in the browser. The fallback can be configured \
+             with `experimental.turbo.resolveFallback` in next.config.js."
+                .to_string(),
+        )
+    }
+}
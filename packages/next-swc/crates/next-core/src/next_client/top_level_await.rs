@@ -0,0 +1,233 @@
+use anyhow::Result;
+use lightningcss::targets::Browsers;
+use swc_core::{
+    common::{source_map::Pos, Span},
+    ecma::{
+        ast::{ArrowExpr, AwaitExpr, Class, ForOfStmt, Function, GetterProp, Program, SetterProp},
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{
+        file_source::FileSource,
+        issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+        module::Module,
+    },
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+use super::get_browserslist_query;
+use crate::{mode::NextMode, next_config::NextConfig, util::ModuleWalk};
+
+/// Returns the targeted browsers which don't support top-level await.
+fn browsers_without_top_level_await(browsers: &Browsers) -> Vec<String> {
+    // The first versions supporting it, encoded like lightningcss encodes
+    // browser versions.
+    [
+        ("Android", browsers.android, 89 << 16),
+        ("Chrome", browsers.chrome, 89 << 16),
+        ("Edge", browsers.edge, 89 << 16),
+        ("Firefox", browsers.firefox, 89 << 16),
+        ("Internet Explorer", browsers.ie, u32::MAX),
+        ("iOS Safari", browsers.ios_saf, 15 << 16),
+        ("Opera", browsers.opera, 75 << 16),
+        ("Safari", browsers.safari, 15 << 16),
+        ("Samsung Internet", browsers.samsung, 15 << 16),
+    ]
+    .into_iter()
+    .filter_map(|(name, version, supported)| {
+        let version = version?;
+        (version < supported).then(|| format!("{name} {}.{}", version >> 16, (version >> 8) & 0xff))
+    })
+    .collect()
+}
+
+#[turbo_tasks::function]
+async fn unsupported_browsers(browserslist_query: Vc<String>) -> Result<Vc<Vec<String>>> {
+    // An invalid query is already reported when it's used to transform CSS.
+    let Ok(Some(browsers)) = Browsers::from_browserslist([browserslist_query.await?.as_str()])
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+    Ok(Vc::cell(browsers_without_top_level_await(&browsers)))
+}
+
+/// Warns about modules in the client module graph of an entry which use
+/// top-level await when some of the targeted browsers don't support it. Server
+/// and edge modules can always use it, and packages in node_modules are not
+/// checked.
+///
+/// The production targets are checked in every mode, so development reports
+/// the same problems as a build.
+#[turbo_tasks::function]
+pub async fn check_client_top_level_await(
+    entry: Vc<Box<dyn Module>>,
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    chunk_group: String,
+) -> Result<Vc<()>> {
+    let browsers = unsupported_browsers(get_browserslist_query(
+        project_path,
+        NextMode::Build,
+        next_config,
+    ));
+    if browsers.await?.is_empty() {
+        return Ok(Default::default());
+    }
+
+    let mut walk = ModuleWalk::new([entry]);
+    while let Some(module) = walk.next().await? {
+        check_module_top_level_await(module, browsers, chunk_group.clone()).await?;
+        walk.visit_references(module).await?;
+    }
+
+    Ok(Default::default())
+}
+
+#[turbo_tasks::function]
+async fn check_module_top_level_await(
+    module: Vc<Box<dyn Module>>,
+    browsers: Vc<Vec<String>>,
+    chunk_group: String,
+) -> Result<Vc<()>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Default::default());
+    };
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Default::default());
+    };
+    // Scripts can't use top-level await.
+    let Program::Module(program) = program else {
+        return Ok(Default::default());
+    };
+
+    let mut visitor = TopLevelAwaitVisitor::default();
+    program.visit_with(&mut visitor);
+    let Some(span) = visitor.span else {
+        return Ok(Default::default());
+    };
+
+    let path = module.ident().path();
+    TopLevelAwaitIssue {
+        file_path: path,
+        chunk_group,
+        browsers: browsers.await?.clone_value(),
+        source: IssueSource::from_byte_offset(
+            Vc::upcast(FileSource::new(path)),
+            span.lo.to_usize(),
+            span.hi.to_usize(),
+        ),
+    }
+    .cell()
+    .emit();
+
+    Ok(Default::default())
+}
+
+/// Finds the first `await` outside of a function.
+#[derive(Default)]
+struct TopLevelAwaitVisitor {
+    span: Option<Span>,
+}
+
+impl Visit for TopLevelAwaitVisitor {
+    noop_visit_type!();
+
+    fn visit_await_expr(&mut self, await_expr: &AwaitExpr) {
+        self.span.get_or_insert(await_expr.span);
+    }
+
+    fn visit_for_of_stmt(&mut self, for_of: &ForOfStmt) {
+        if for_of.is_await {
+            self.span.get_or_insert(for_of.span);
+        }
+        for_of.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+
+    fn visit_getter_prop(&mut self, _: &GetterProp) {}
+
+    fn visit_setter_prop(&mut self, _: &SetterProp) {}
+}
+
+#[turbo_tasks::value(shared)]
+struct TopLevelAwaitIssue {
+    file_path: Vc<FileSystemPath>,
+    chunk_group: String,
+    browsers: Vec<String>,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TopLevelAwaitIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("code generation".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Top-level await is not supported by the browsers targeted by the client chunks of {}",
+            self.chunk_group
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "This module is bundled for the browser and uses top-level await, which isn't \
+             available in {}. Move the await into an async function, or update the browserslist \
+             configuration of the project.",
+            self.browsers.join(", ")
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browsers_without_top_level_await() {
+        let browsers = Browsers {
+            chrome: Some(64 << 16),
+            firefox: Some(100 << 16),
+            safari: Some((14 << 16) | (1 << 8)),
+            ..Default::default()
+        };
+        assert_eq!(
+            browsers_without_top_level_await(&browsers),
+            vec!["Chrome 64.0".to_string(), "Safari 14.1".to_string()]
+        );
+        assert!(browsers_without_top_level_await(&Browsers {
+            chrome: Some(110 << 16),
+            ..Default::default()
+        })
+        .is_empty());
+    }
+}
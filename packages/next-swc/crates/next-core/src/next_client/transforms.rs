@@ -1,21 +1,28 @@
 use anyhow::Result;
 use next_transform_strip_page_exports::ExportFilter;
 use turbo_tasks::Vc;
-use turbopack_binding::turbopack::turbopack::module_options::ModuleRule;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath, turbopack::turbopack::module_options::ModuleRule,
+};
 
 use crate::{
     mode::NextMode,
     next_client::context::ClientContextType,
     next_config::NextConfig,
     next_shared::transforms::{
-        get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
+        get_next_context_module_transform_rule, get_next_dynamic_transform_rule,
+        get_next_font_transform_rule, get_next_image_remote_patterns_rule, get_next_image_rule,
         get_next_modularize_imports_rule, get_next_pages_transforms_rule,
     },
+    sass::get_grass_rules,
+    structured_data::get_structured_data_rules,
+    svg_component::get_svg_component_rule,
 };
 
 /// Returns a list of module rules which apply client-side, Next.js-specific
 /// transforms.
 pub async fn get_next_client_transforms_rules(
+    project_path: Vc<FileSystemPath>,
     next_config: Vc<NextConfig>,
     context_ty: ClientContextType,
     mode: NextMode,
@@ -43,7 +50,31 @@ pub async fn get_next_client_transforms_rules(
 
     rules.push(get_next_dynamic_transform_rule(false, false, pages_dir, mode).await?);
 
+    rules.push(get_next_context_module_transform_rule(
+        project_path,
+        *next_config.import_meta_glob().await?,
+    ));
+
     rules.push(get_next_image_rule());
 
+    if *next_config.validate_remote_images().await? {
+        rules.push(get_next_image_remote_patterns_rule(
+            project_path,
+            next_config.image_config(),
+        ));
+    }
+
+    if *next_config.svgr().await? {
+        rules.push(get_svg_component_rule());
+    }
+
+    if *next_config.use_grass().await? {
+        rules.extend(get_grass_rules(next_config.sass_config()));
+    }
+
+    if *next_config.structured_data().await? {
+        rules.extend(get_structured_data_rules(next_config.webpack_rules()).await?);
+    }
+
     Ok(rules)
 }
@@ -1,5 +1,6 @@
 pub(crate) mod css_client_reference;
 pub(crate) mod ecmascript_client_reference;
+pub(crate) mod unused_client_components;
 pub(crate) mod visit_client_reference;
 
 pub use css_client_reference::css_client_reference_module::CssClientReferenceModule;
@@ -7,6 +8,7 @@ pub use ecmascript_client_reference::{
     ecmascript_client_reference_module::EcmascriptClientReferenceModule,
     ecmascript_client_reference_transition::NextEcmascriptClientReferenceTransition,
 };
+pub use unused_client_components::{unused_client_components, UnusedClientComponents};
 pub use visit_client_reference::{
     ClientReference, ClientReferenceGraph, ClientReferenceType, ClientReferenceTypes,
     ClientReferences,
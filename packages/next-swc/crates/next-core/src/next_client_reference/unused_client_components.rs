@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{
+    DirectoryContent, DirectoryEntry, FileContent, FileSystemEntryType, FileSystemPath,
+};
+use turbopack_binding::turbopack::core::module::Module;
+
+use super::{ClientReferenceType, ClientReferenceTypes};
+use crate::util::ModuleWalk;
+
+const SOURCE_EXTENSIONS: &[&str] = &["js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts"];
+
+/// The directories of the project which are searched for client components.
+/// Build outputs like `out/` and `dist/` live next to them.
+const SOURCE_DIRECTORIES: &[&str] = &["app", "src"];
+
+/// The project-relative paths of `"use client"` modules, sorted.
+#[turbo_tasks::value(transparent)]
+pub struct UnusedClientComponents(Vec<String>);
+
+/// Finds the `"use client"` modules in the `app` and `src` directories of the
+/// project which aren't part of the client module graph of the given client
+/// references, i.e. which neither a server entry renders nor another client
+/// component imports. Dot directories and `node_modules` are skipped.
+#[turbo_tasks::function]
+pub async fn unused_client_components(
+    project_path: Vc<FileSystemPath>,
+    client_reference_types: Vc<ClientReferenceTypes>,
+) -> Result<Vc<UnusedClientComponents>> {
+    let project_path_ref = project_path.await?;
+
+    let mut client_modules = vec![];
+    for ty in client_reference_types.await?.iter() {
+        if let ClientReferenceType::EcmascriptClientReference(reference) = ty {
+            client_modules.push(Vc::upcast::<Box<dyn Module>>(
+                reference.await?.client_module,
+            ));
+        }
+    }
+    let mut walk = ModuleWalk::new(client_modules);
+    let mut used = HashSet::new();
+    while let Some(module) = walk.next().await? {
+        let path = module.ident().path().await?;
+        if let Some(path) = project_path_ref.get_path_to(&path) {
+            used.insert(path.to_string());
+        }
+        walk.visit_references(module).await?;
+    }
+
+    let mut unused = vec![];
+    let mut queue = vec![];
+    for dir in SOURCE_DIRECTORIES {
+        let dir = project_path.join(dir.to_string());
+        if *dir.get_type().await? == FileSystemEntryType::Directory {
+            queue.push(dir);
+        }
+    }
+    while let Some(dir) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+            continue;
+        };
+        for (name, entry) in entries.iter() {
+            match entry {
+                DirectoryEntry::Directory(dir) => {
+                    if !name.starts_with('.') && name != "node_modules" {
+                        queue.push(*dir);
+                    }
+                }
+                DirectoryEntry::File(file) => {
+                    if !is_source_file(name) {
+                        continue;
+                    }
+                    let file_ref = file.await?;
+                    let Some(path) = project_path_ref.get_path_to(&file_ref) else {
+                        continue;
+                    };
+                    if used.contains(path) {
+                        continue;
+                    }
+                    let FileContent::Content(content) = &*file.read().await? else {
+                        continue;
+                    };
+                    if has_use_client_directive(&content.content().to_str()?) {
+                        unused.push(path.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    unused.sort();
+
+    Ok(Vc::cell(unused))
+}
+
+fn is_source_file(name: &str) -> bool {
+    !name.ends_with(".d.ts")
+        && name
+            .rsplit_once('.')
+            .map_or(false, |(_, ext)| SOURCE_EXTENSIONS.contains(&ext))
+}
+
+/// Whether the directive prologue of a module contains `"use client"`,
+/// without parsing it.
+fn has_use_client_directive(source: &str) -> bool {
+    let mut rest = source.strip_prefix('\u{feff}').unwrap_or(source);
+    if rest.starts_with("#!") {
+        rest = rest.find('\n').map_or("", |index| &rest[index..]);
+    }
+    loop {
+        rest = skip_trivia(rest);
+        let Some(quote) = rest.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+            return false;
+        };
+        let Some(end) = rest[1..].find(quote) else {
+            return false;
+        };
+        if &rest[1..end + 1] == "use client" {
+            return true;
+        }
+        let after_string = &rest[end + 2..];
+        rest = skip_trivia(after_string);
+        // Directives are terminated by a semicolon or a line break, anything
+        // else means the string was part of an expression.
+        if let Some(after) = rest.strip_prefix(';') {
+            rest = after;
+        } else if !after_string[..after_string.len() - rest.len()].contains(['\n', '\r']) {
+            return false;
+        }
+    }
+}
+
+/// Skips whitespace and comments.
+fn skip_trivia(mut source: &str) -> &str {
+    loop {
+        source = source.trim_start();
+        if let Some(comment) = source.strip_prefix("//") {
+            source = comment.find('\n').map_or("", |index| &comment[index..]);
+        } else if let Some(comment) = source.strip_prefix("/*") {
+            source = comment.find("*/").map_or("", |index| &comment[index + 2..]);
+        } else {
+            return source;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_use_client_directive() {
+        assert!(has_use_client_directive("'use client'\nexport default 1"));
+        assert!(has_use_client_directive(
+            "#!/usr/bin/env node\n// Button\n/* shared */ \"use strict\";\n\"use client\";"
+        ));
+        assert!(has_use_client_directive("\"use strict\"\n'use client'"));
+        assert!(has_use_client_directive(
+            "  'use strict'\n  'use client'\n  export default 1"
+        ));
+        assert!(has_use_client_directive(
+            "'use strict' /* a\n */ 'use client'"
+        ));
+        assert!(!has_use_client_directive(
+            "'use server'\nexport async function a() {}"
+        ));
+        assert!(!has_use_client_directive("import a from 'a'\n'use client'"));
+        assert!(!has_use_client_directive("'use strict' + 'use client'"));
+        assert!(!has_use_client_directive(
+            "// 'use client'\nexport default 1"
+        ));
+        assert!(!has_use_client_directive(
+            "'use strict' /* a */ 'use client'"
+        ));
+    }
+
+    #[test]
+    fn test_is_source_file() {
+        assert!(is_source_file("button.tsx"));
+        assert!(is_source_file("page.mjs"));
+        assert!(!is_source_file("types.d.ts"));
+        assert!(!is_source_file("styles.css"));
+        assert!(!is_source_file("README"));
+    }
+}
@@ -39,7 +39,10 @@ use turbopack_binding::{
     },
 };
 
-use crate::{embed_js::next_asset, next_shared::transforms::ModularizeImportPackageConfig};
+use crate::{
+    embed_js::next_asset, minify::MinifyOptions,
+    next_shared::transforms::ModularizeImportPackageConfig,
+};
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,15 +61,16 @@ struct NextConfigAndCustomRoutes {
 #[serde(rename_all = "camelCase")]
 struct CustomRoutesRaw {
     rewrites: Rewrites,
+    headers: Vec<Header>,
 
     // unsupported
-    headers: Vec<Header>,
     redirects: Vec<Redirect>,
 }
 
 #[turbo_tasks::value]
 struct CustomRoutes {
     rewrites: Vc<Rewrites>,
+    headers: Vc<Headers>,
 }
 
 #[turbo_tasks::value(serialization = "custom", eq = "manual")]
@@ -81,6 +85,7 @@ pub struct NextConfig {
     pub images: ImageConfig,
     pub page_extensions: Vec<String>,
     pub react_strict_mode: Option<bool>,
+    react_production_profiling: Option<bool>,
     pub transpile_packages: Option<Vec<String>>,
     pub modularize_imports: Option<IndexMap<String, ModularizeImportPackageConfig>>,
     sass_options: Option<serde_json::Value>,
@@ -89,9 +94,9 @@ pub struct NextConfig {
     pub compiler: Option<CompilerConfig>,
 
     pub output: Option<OutputType>,
+    pub cross_origin: Option<String>,
 
     // unsupported
-    cross_origin: Option<String>,
     amp: AmpConfig,
     analytics_id: String,
     asset_prefix: String,
@@ -157,11 +162,18 @@ struct DevIndicatorsConfig {
     build_activity_position: BuildActivityPositions,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[turbo_tasks::value(eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct OnDemandEntriesConfig {
-    max_inactive_age: f64,
-    pages_buffer_length: f64,
+pub struct OnDemandEntriesConfig {
+    /// The time in milliseconds after which an inactive entry is disposed.
+    pub max_inactive_age: f64,
+    /// The number of most recently used entries which are never disposed.
+    pub pages_buffer_length: f64,
+    /// The number of entries compiled concurrently in dev, unlimited by
+    /// default.
+    #[serde(default)]
+    pub max_concurrent_compilations: Option<f64>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
@@ -240,6 +252,17 @@ pub struct Header {
     pub missing: Option<Vec<RouteHas>>,
 }
 
+#[turbo_tasks::value(transparent)]
+pub struct Headers(Vec<Header>);
+
+#[turbo_tasks::value_impl]
+impl Headers {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(vec![])
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub enum RedirectStatus {
@@ -380,6 +403,144 @@ pub struct ExperimentalTurboConfig {
     pub loaders: Option<JsonValue>,
     pub rules: Option<IndexMap<String, RuleConfigItem>>,
     pub resolve_alias: Option<IndexMap<String, JsonValue>>,
+    pub conditions: Option<ExportConditions>,
+    pub use_grass: Option<bool>,
+    pub svgr: Option<bool>,
+    pub structured_data: Option<bool>,
+    pub import_meta_glob: Option<bool>,
+    pub babel_rules: Option<Vec<String>>,
+    pub resolve_fallback: Option<IndexMap<String, ResolveFallbackTarget>>,
+    /// Globs matching the pathnames of the routes which are compiled when the
+    /// dev server starts.
+    pub warm_up: Option<Vec<String>>,
+}
+
+/// What a Node.js built-in imported from client code resolves to, like
+/// webpack's `resolve.fallback`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum ResolveFallbackTarget {
+    /// A module request, e. g. `"crypto-browserify"`.
+    Module(String),
+    /// `false` resolves to an empty module, `true` keeps the default.
+    Enabled(bool),
+}
+
+/// The configured fallbacks for Node.js built-ins in client code, keyed by
+/// built-in. `None` maps a built-in to an empty module.
+#[turbo_tasks::value(transparent)]
+pub struct ResolveFallbackMap(IndexMap<String, Option<String>>);
+
+/// Another Next.js app serving a part of the routes of a multi-zone setup,
+/// see `experimental.zones`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneConfig {
+    pub name: String,
+    /// The pathname prefixes of the routes served by the zone.
+    pub path_prefixes: Vec<String>,
+    /// The origin the routes are delegated to, e. g. `https://blog.example.com`.
+    pub destination: String,
+    /// The `assetPrefix` of the zone, which its `_next` assets are served
+    /// under.
+    pub asset_prefix: Option<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct Zones(Vec<ZoneConfig>);
+
+/// `experimental.appI18n`, the locales served under a `/[locale]` prefix by
+/// the app router pages.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct AppI18nConfig {
+    pub locales: Vec<String>,
+    /// The locale of the pages which are served without a prefix.
+    pub default_locale: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionAppI18nConfig(Option<AppI18nConfig>);
+
+/// An entry of `experimental.bundleBudgets`, the maximum First Load JS of the
+/// app routes matching a glob.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleBudget {
+    /// A glob matched against the pathname of the route, e.g. `/dashboard/**`.
+    pub path: String,
+    /// The maximum size of the JavaScript loaded by the route on the first
+    /// visit, in bytes.
+    pub max_first_load_js: u64,
+    /// Whether exceeding the budget fails the build.
+    #[serde(default)]
+    pub level: BundleBudgetLevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleBudgetLevel {
+    #[default]
+    Warning,
+    Error,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct BundleBudgets(Vec<BundleBudget>);
+
+/// `experimental.sri`, the subresource integrity hashes of the client scripts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+pub struct SubresourceIntegrityConfig {
+    pub algorithm: Option<SubresourceIntegrityAlgorithm>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum SubresourceIntegrityAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SubresourceIntegrityAlgorithm {
+    /// The prefix of an `integrity` attribute value using the algorithm.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubresourceIntegrityAlgorithm::Sha256 => "sha256",
+            SubresourceIntegrityAlgorithm::Sha384 => "sha384",
+            SubresourceIntegrityAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionSubresourceIntegrityAlgorithm(Option<SubresourceIntegrityAlgorithm>);
+
+/// Globs of files to add to or remove from the output file traces, keyed by a
+/// glob matching the routes they apply to.
+#[turbo_tasks::value(transparent)]
+pub struct OutputFileTracingGlobs(IndexMap<String, Vec<String>>);
+
+/// Additional conditions used when resolving the `exports` and `imports`
+/// fields of packages, on top of the ones Next.js sets for each layer.
+#[turbo_tasks::value(eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConditions {
+    /// Conditions for the browser bundles.
+    #[serde(default)]
+    pub client: Vec<String>,
+    /// Conditions for everything bundled for the Node.js runtime.
+    #[serde(default)]
+    pub server: Vec<String>,
+    /// Conditions for everything bundled for the Edge runtime, e. g.
+    /// `"workerd"`.
+    #[serde(default)]
+    pub edge: Vec<String>,
+    /// Conditions for the server component and route handler layers only.
+    /// They are added in addition to the `server` or `edge` conditions.
+    #[serde(default)]
+    pub react_server: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
@@ -409,8 +570,24 @@ pub struct ExperimentalConfig {
     pub fetch_cache_key_prefix: Option<String>,
     pub isr_memory_cache_size: Option<f64>,
     pub isr_flush_to_disk: Option<bool>,
+    legacy_browsers: Option<bool>,
+    use_lightningcss: Option<bool>,
     mdx_rs: Option<bool>,
     pub swc_plugins: Option<Vec<(String, serde_json::Value)>>,
+    swc_minify_debug_options: Option<SwcMinifyDebugOptions>,
+    keep_component_names: Option<bool>,
+    fallback_node_polyfills: Option<bool>,
+    react_compiler: Option<ReactCompilerConfig>,
+    output_file_tracing_includes: Option<IndexMap<String, Vec<String>>>,
+    output_file_tracing_excludes: Option<IndexMap<String, Vec<String>>>,
+    zones: Option<Vec<ZoneConfig>>,
+    app_i18n: Option<AppI18nConfig>,
+    bundle_budgets: Option<Vec<BundleBudget>>,
+    server_actions: Option<bool>,
+    pub taint: Option<bool>,
+    use_cache: Option<bool>,
+    app_dir_path: Option<String>,
+    validate_remote_images: Option<bool>,
 
     // unsupported
     adjust_font_fallbacks: Option<bool>,
@@ -423,7 +600,6 @@ pub struct ExperimentalConfig {
     disable_postcss_preset_env: Option<bool>,
     esm_externals: Option<serde_json::Value>,
     external_dir: Option<bool>,
-    fallback_node_polyfills: Option<bool>,
     font_loaders: Option<serde_json::Value>,
     force_swc_transforms: Option<bool>,
     fully_specified: Option<bool>,
@@ -440,8 +616,7 @@ pub struct ExperimentalConfig {
     proxy_timeout: Option<f64>,
     scroll_restoration: Option<bool>,
     shared_pool: Option<bool>,
-    sri: Option<serde_json::Value>,
-    swc_minify_debug_options: Option<serde_json::Value>,
+    pub sri: Option<SubresourceIntegrityConfig>,
     swc_trace_profiling: Option<bool>,
     transpile_packages: Option<Vec<String>>,
     pub turbotrace: Option<serde_json::Value>,
@@ -450,6 +625,69 @@ pub struct ExperimentalConfig {
     worker_threads: Option<bool>,
 }
 
+/// `experimental.reactCompiler`, either a boolean or the options passed to
+/// babel-plugin-react-compiler.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+enum ReactCompilerConfig {
+    Enabled(bool),
+    Options(ReactCompilerOptions),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactCompilerOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compilation_mode: Option<ReactCompilerCompilationMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub panic_threshold: Option<ReactCompilerPanicThreshold>,
+}
+
+/// Which components and hooks are compiled.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactCompilerCompilationMode {
+    /// Components and hooks detected by their name and usage.
+    Infer,
+    /// Only functions annotated with `"use memo"`.
+    Annotation,
+    /// All top-level functions.
+    All,
+}
+
+/// Which errors fail the build instead of skipping the function.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReactCompilerPanicThreshold {
+    AllErrors,
+    CriticalErrors,
+    None,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionReactCompilerOptions(Option<ReactCompilerOptions>);
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+struct SwcMinifyDebugOptions {
+    compress: Option<SwcMinifyCompressOptions>,
+    mangle: Option<SwcMinifyMangleOptions>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+struct SwcMinifyCompressOptions {
+    keep_fnames: Option<bool>,
+    keep_classnames: Option<bool>,
+    pure_funcs: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+struct SwcMinifyMangleOptions {
+    keep_fnames: Option<bool>,
+    keep_classnames: Option<bool>,
+    reserved: Option<Vec<String>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "kebab-case")]
 enum MiddlewarePrefetchType {
@@ -474,7 +712,7 @@ pub enum StyledComponentsTransformOptionsOrBoolean {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct CompilerConfig {
-    pub react_remove_properties: Option<bool>,
+    pub react_remove_properties: Option<ReactRemoveProperties>,
     pub relay: Option<RelayConfig>,
     pub emotion: Option<EmotionTransformOptionsOrBoolean>,
     pub remove_console: Option<RemoveConsoleConfig>,
@@ -547,6 +785,11 @@ impl NextConfig {
         Ok(self.await?.images.clone().cell())
     }
 
+    #[turbo_tasks::function]
+    pub async fn on_demand_entries(self: Vc<Self>) -> Result<Vc<OnDemandEntriesConfig>> {
+        Ok(self.await?.on_demand_entries.clone().cell())
+    }
+
     #[turbo_tasks::function]
     pub async fn page_extensions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         Ok(Vc::cell(self.await?.page_extensions.clone()))
@@ -620,6 +863,32 @@ impl NextConfig {
         Ok(alias_map.cell())
     }
 
+    #[turbo_tasks::function]
+    pub async fn export_conditions(self: Vc<Self>) -> Result<Vc<ExportConditions>> {
+        Ok(self
+            .await?
+            .experimental
+            .turbo
+            .as_ref()
+            .and_then(|t| t.conditions.clone())
+            .unwrap_or_default()
+            .cell())
+    }
+
+    #[turbo_tasks::function]
+    pub async fn legacy_browsers(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.legacy_browsers.unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn use_lightningcss(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.use_lightningcss.unwrap_or(false),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn mdx_rs(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(self.await?.experimental.mdx_rs.unwrap_or(false)))
@@ -632,11 +901,290 @@ impl NextConfig {
         ))
     }
 
+    #[turbo_tasks::function]
+    pub async fn use_grass(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.use_grass)
+                .unwrap_or(false),
+        ))
+    }
+
+    /// Whether Node.js built-ins imported from client code are polyfilled by
+    /// default. `experimental.fallbackNodePolyfills: false` turns them into
+    /// empty modules instead.
+    #[turbo_tasks::function]
+    pub async fn fallback_node_polyfills(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .fallback_node_polyfills
+                .unwrap_or(true),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn resolve_fallback(self: Vc<Self>) -> Result<Vc<ResolveFallbackMap>> {
+        let this = self.await?;
+        let fallbacks = this
+            .experimental
+            .turbo
+            .as_ref()
+            .and_then(|t| t.resolve_fallback.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|(builtin, target)| match target {
+                ResolveFallbackTarget::Module(request) => {
+                    Some((builtin.clone(), Some(request.clone())))
+                }
+                ResolveFallbackTarget::Enabled(false) => Some((builtin.clone(), None)),
+                ResolveFallbackTarget::Enabled(true) => None,
+            })
+            .collect();
+        Ok(Vc::cell(fallbacks))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn svgr(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.svgr)
+                .unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn structured_data(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.structured_data)
+                .unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn react_compiler(self: Vc<Self>) -> Result<Vc<OptionReactCompilerOptions>> {
+        Ok(Vc::cell(
+            match self.await?.experimental.react_compiler.as_ref() {
+                Some(ReactCompilerConfig::Options(options)) => Some(options.clone()),
+                Some(ReactCompilerConfig::Enabled(true)) => Some(Default::default()),
+                Some(ReactCompilerConfig::Enabled(false)) | None => None,
+            },
+        ))
+    }
+
+    /// The rules babel-loader is added to when the project has a babel config.
+    #[turbo_tasks::function]
+    pub async fn babel_rules(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.babel_rules.clone())
+                .unwrap_or_else(|| {
+                    ["*.js", "*.jsx", "*.ts", "*.tsx", "*.cjs", "*.mjs"]
+                        .into_iter()
+                        .map(|pattern| pattern.to_string())
+                        .collect()
+                }),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn warm_up(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.warm_up.clone())
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn zones(self: Vc<Self>) -> Result<Vc<Zones>> {
+        Ok(Vc::cell(
+            self.await?.experimental.zones.clone().unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn app_i18n(self: Vc<Self>) -> Result<Vc<OptionAppI18nConfig>> {
+        Ok(Vc::cell(self.await?.experimental.app_i18n.clone()))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn bundle_budgets(self: Vc<Self>) -> Result<Vc<BundleBudgets>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .bundle_budgets
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn asset_prefix(self: Vc<Self>) -> Result<Vc<String>> {
+        Ok(Vc::cell(self.await?.asset_prefix.clone()))
+    }
+
+    /// The algorithm of the subresource integrity hashes, `None` when they are
+    /// disabled.
+    #[turbo_tasks::function]
+    pub async fn sri_algorithm(self: Vc<Self>) -> Result<Vc<OptionSubresourceIntegrityAlgorithm>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .sri
+                .as_ref()
+                .and_then(|sri| sri.algorithm),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn cross_origin(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        Ok(Vc::cell(self.await?.cross_origin.clone()))
+    }
+
+    /// `experimental.appDirPath`, the project-relative location of the app
+    /// directory when it isn't `app` or `src/app`.
+    #[turbo_tasks::function]
+    pub async fn app_dir_path(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        Ok(Vc::cell(self.await?.experimental.app_dir_path.clone()))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn import_meta_glob(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|t| t.import_meta_glob)
+                .unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn output_file_tracing(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(self.await?.output_file_tracing))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn output_file_tracing_includes(
+        self: Vc<Self>,
+    ) -> Result<Vc<OutputFileTracingGlobs>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .output_file_tracing_includes
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn output_file_tracing_excludes(
+        self: Vc<Self>,
+    ) -> Result<Vc<OutputFileTracingGlobs>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .output_file_tracing_excludes
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn swc_minify(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(self.await?.swc_minify.unwrap_or(false)))
     }
 
+    #[turbo_tasks::function]
+    pub async fn minify_options(self: Vc<Self>) -> Result<Vc<MinifyOptions>> {
+        let this = self.await?;
+        let debug_options = this
+            .experimental
+            .swc_minify_debug_options
+            .clone()
+            .unwrap_or_default();
+        let compress = debug_options.compress.unwrap_or_default();
+        let mangle = debug_options.mangle.unwrap_or_default();
+
+        // Component names must survive minification to show up in profiles.
+        let profiling = this.react_production_profiling.unwrap_or(false);
+
+        Ok(MinifyOptions {
+            keep_fnames: profiling || compress.keep_fnames.or(mangle.keep_fnames).unwrap_or(false),
+            keep_classnames: profiling
+                || compress
+                    .keep_classnames
+                    .or(mangle.keep_classnames)
+                    .unwrap_or(false),
+            mangle_reserved: mangle.reserved.unwrap_or_default(),
+            pure_funcs: compress.pure_funcs.unwrap_or_default(),
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    pub async fn keep_component_names(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .keep_component_names
+                .unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn react_production_profiling(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.react_production_profiling.unwrap_or(false),
+        ))
+    }
+
+    /// Whether the app directory uses the `react@experimental` builds bundled
+    /// with Next.js, like `needsExperimentalReact`. Server Actions and the
+    /// taint APIs are only available there.
+    #[turbo_tasks::function]
+    pub async fn needs_experimental_react(self: Vc<Self>) -> Result<Vc<bool>> {
+        let experimental = &self.await?.experimental;
+        Ok(Vc::cell(
+            experimental.server_actions.unwrap_or(false) || experimental.taint.unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn enable_use_cache(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.use_cache.unwrap_or(false),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn validate_remote_images(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .validate_remote_images
+                .unwrap_or(false),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn skip_middleware_url_normalize(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(
@@ -677,6 +1225,17 @@ pub async fn load_rewrites(execution_context: Vc<ExecutionContext>) -> Result<Vc
         .rewrites)
 }
 
+/// The `headers()` of next.config.js, after the basePath has been applied by
+/// `loadCustomRoutes`.
+#[turbo_tasks::function]
+pub async fn load_headers(execution_context: Vc<ExecutionContext>) -> Result<Vc<Headers>> {
+    Ok(load_config_and_custom_routes(execution_context)
+        .await?
+        .custom_routes
+        .await?
+        .headers)
+}
+
 #[turbo_tasks::function]
 async fn load_config_and_custom_routes(
     execution_context: Vc<ExecutionContext>,
@@ -749,6 +1308,7 @@ async fn load_next_config_and_custom_routes_internal(
             config: NextConfig::default().cell(),
             custom_routes: CustomRoutes {
                 rewrites: Rewrites::default().cell(),
+                headers: Headers::empty(),
             }
             .cell(),
         }
@@ -782,6 +1342,7 @@ async fn load_next_config_and_custom_routes_internal(
         config: next_config_and_custom_routes.config.cell(),
         custom_routes: CustomRoutes {
             rewrites: next_config_and_custom_routes.custom_routes.rewrites.cell(),
+            headers: Vc::cell(next_config_and_custom_routes.custom_routes.headers),
         }
         .cell(),
     }
@@ -22,9 +22,15 @@ use crate::{
     mode::NextMode,
     next_client::context::get_client_assets_path,
     next_config::NextConfig,
-    next_import_map::get_next_edge_import_map,
+    next_import_map::{get_next_edge_import_map, get_next_server_fallback_import_map},
     next_server::context::ServerContextType,
-    next_shared::resolve::{ModuleFeatureReportResolvePlugin, UnsupportedModulesResolvePlugin},
+    next_shared::{
+        asset_query::AssetQueryResolvePlugin,
+        resolve::{
+            ImportCasingResolvePlugin, ModuleFeatureReportResolvePlugin, PackageExportsReplacer,
+            UnsupportedModulesResolvePlugin,
+        },
+    },
     util::foreign_code_context_condition,
 };
 
@@ -88,16 +94,20 @@ pub async fn get_edge_resolve_options_context(
 
     let ty = ty.into_value();
 
+    let export_conditions = next_config.export_conditions().await?;
+
     // https://github.com/vercel/next.js/blob/bf52c254973d99fed9d71507a2e818af80b8ade7/packages/next/src/build/webpack-config.ts#L96-L102
     let mut custom_conditions = vec![
         mode.node_env().to_string(),
         "edge-light".to_string(),
         "worker".to_string(),
     ];
+    custom_conditions.extend(export_conditions.edge.iter().cloned());
 
     match ty {
         ServerContextType::AppRSC { .. } | ServerContextType::AppRoute { .. } => {
-            custom_conditions.push("react-server".to_string())
+            custom_conditions.push("react-server".to_string());
+            custom_conditions.extend(export_conditions.react_server.iter().cloned());
         }
         ServerContextType::Pages { .. }
         | ServerContextType::PagesData { .. }
@@ -105,15 +115,29 @@ pub async fn get_edge_resolve_options_context(
         | ServerContextType::Middleware { .. } => {}
     };
 
+    let package_exports_replacer = PackageExportsReplacer::new(
+        "edge".to_string(),
+        custom_conditions
+            .iter()
+            .cloned()
+            .chain(["browser".to_string(), "module".to_string()])
+            .collect(),
+    );
+
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().resolve().await?),
         custom_conditions,
         import_map: Some(next_edge_import_map),
+        fallback_import_map: Some(get_next_server_fallback_import_map(
+            package_exports_replacer,
+        )),
         module: true,
         browser: true,
         plugins: vec![
+            Vc::upcast(AssetQueryResolvePlugin::new(project_path)),
             Vc::upcast(ModuleFeatureReportResolvePlugin::new(project_path)),
             Vc::upcast(UnsupportedModulesResolvePlugin::new(project_path)),
+            Vc::upcast(ImportCasingResolvePlugin::new(project_path)),
         ],
         ..Default::default()
     };
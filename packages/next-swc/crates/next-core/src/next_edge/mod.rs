@@ -3,3 +3,4 @@ pub mod entry;
 pub mod page_transition;
 pub mod route_regex;
 pub mod route_transition;
+pub mod unsupported_apis;
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{source_map::Pos, Span},
+    ecma::{
+        ast::{CallExpr, Callee, ExportAll, Expr, ImportDecl, Lit, NamedExport, NewExpr},
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
+};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{
+        file_source::FileSource,
+        issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+        module::Module,
+    },
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+use crate::util::ModuleWalk;
+
+/// Node.js modules which are not available in the edge runtime.
+const UNSUPPORTED_NODE_MODULES: &[&str] = &[
+    "child_process",
+    "cluster",
+    "dgram",
+    "dns",
+    "fs",
+    "fs/promises",
+    "http2",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "perf_hooks",
+    "readline",
+    "repl",
+    "tls",
+    "tty",
+    "v8",
+    "vm",
+    "worker_threads",
+];
+
+/// Walks the module graph of an edge entry and reports imports of Node.js
+/// modules and dynamic code evaluation, which would otherwise only fail once
+/// the code runs in the edge runtime.
+///
+/// Packages in node_modules are not checked, they commonly guard these usages
+/// behind runtime checks.
+#[turbo_tasks::function]
+pub async fn check_edge_runtime_apis(entry: Vc<Box<dyn Module>>) -> Result<Vc<()>> {
+    let mut walk = ModuleWalk::new([entry]);
+    while let Some(module) = walk.next().await? {
+        check_edge_module_apis(module).await?;
+        walk.visit_references(module).await?;
+    }
+
+    Ok(Default::default())
+}
+
+#[turbo_tasks::function]
+async fn check_edge_module_apis(module: Vc<Box<dyn Module>>) -> Result<Vc<()>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Default::default());
+    };
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Default::default());
+    };
+
+    let mut visitor = EdgeApiVisitor::default();
+    program.visit_with(&mut visitor);
+    if visitor.usages.is_empty() {
+        return Ok(Default::default());
+    }
+
+    let path = module.ident().path();
+    let source = Vc::upcast(FileSource::new(path));
+    for (api, span) in visitor.usages {
+        EdgeRuntimeUnsupportedApiIssue {
+            file_path: path,
+            api,
+            source: IssueSource::from_byte_offset(source, span.lo.to_usize(), span.hi.to_usize()),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, TraceRawVcs, Serialize, Deserialize)]
+enum EdgeUnsupportedApi {
+    /// An import or require of a Node.js module.
+    NodeModule(String),
+    /// `eval(...)` or `new Function(...)`.
+    DynamicCodeEvaluation(String),
+}
+
+#[derive(Default)]
+struct EdgeApiVisitor {
+    usages: Vec<(EdgeUnsupportedApi, Span)>,
+}
+
+impl EdgeApiVisitor {
+    fn check_request(&mut self, request: &str, span: Span) {
+        let request = request.strip_prefix("node:").unwrap_or(request);
+        if UNSUPPORTED_NODE_MODULES.contains(&request) {
+            self.usages
+                .push((EdgeUnsupportedApi::NodeModule(request.to_string()), span));
+        }
+    }
+}
+
+impl Visit for EdgeApiVisitor {
+    noop_visit_type!();
+
+    fn visit_import_decl(&mut self, import: &ImportDecl) {
+        // Type-only imports are erased before the code runs.
+        if !import.type_only {
+            self.check_request(&import.src.value, import.span);
+        }
+    }
+
+    fn visit_named_export(&mut self, export: &NamedExport) {
+        if let Some(src) = &export.src {
+            if !export.type_only {
+                self.check_request(&src.value, export.span);
+            }
+        }
+    }
+
+    fn visit_export_all(&mut self, export: &ExportAll) {
+        if !export.type_only {
+            self.check_request(&export.src.value, export.span);
+        }
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        match &call.callee {
+            Callee::Expr(callee) => match &**callee {
+                Expr::Ident(ident) if &*ident.sym == "eval" => self.usages.push((
+                    EdgeUnsupportedApi::DynamicCodeEvaluation("eval".to_string()),
+                    call.span,
+                )),
+                Expr::Ident(ident) if &*ident.sym == "require" => {
+                    if let Some(Expr::Lit(Lit::Str(request))) =
+                        call.args.first().map(|arg| &*arg.expr)
+                    {
+                        self.check_request(&request.value, call.span);
+                    }
+                }
+                _ => {}
+            },
+            Callee::Import(_) => {
+                if let Some(Expr::Lit(Lit::Str(request))) = call.args.first().map(|arg| &*arg.expr)
+                {
+                    self.check_request(&request.value, call.span);
+                }
+            }
+            Callee::Super(_) => {}
+        }
+        call.visit_children_with(self);
+    }
+
+    fn visit_new_expr(&mut self, new: &NewExpr) {
+        if matches!(&*new.callee, Expr::Ident(ident) if &*ident.sym == "Function") {
+            self.usages.push((
+                EdgeUnsupportedApi::DynamicCodeEvaluation("new Function".to_string()),
+                new.span,
+            ));
+        }
+        new.visit_children_with(self);
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct EdgeRuntimeUnsupportedApiIssue {
+    file_path: Vc<FileSystemPath>,
+    api: EdgeUnsupportedApi,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for EdgeRuntimeUnsupportedApiIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("edge runtime".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(match &self.api {
+            EdgeUnsupportedApi::NodeModule(name) => format!(
+                "The edge runtime does not support Node.js '{}' module",
+                name
+            ),
+            EdgeUnsupportedApi::DynamicCodeEvaluation(api) => format!(
+                "Dynamic code evaluation ('{}') is not allowed in the edge runtime",
+                api
+            ),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(match &self.api {
+            EdgeUnsupportedApi::NodeModule(_) => {
+                "Only a subset of Node.js APIs is available in the edge runtime. Use the Node.js \
+                 runtime for this route, or guard the code so it only runs on the server."
+                    .to_string()
+            }
+            EdgeUnsupportedApi::DynamicCodeEvaluation(_) => {
+                "The edge runtime doesn't allow compiling code from strings at runtime.".to_string()
+            }
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(match &self.api {
+            EdgeUnsupportedApi::NodeModule(_) => {
+                "https://nextjs.org/docs/messages/node-module-in-edge-runtime".to_string()
+            }
+            EdgeUnsupportedApi::DynamicCodeEvaluation(_) => {
+                "https://nextjs.org/docs/messages/edge-dynamic-code-evaluation".to_string()
+            }
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.source)
+    }
+}
@@ -0,0 +1,221 @@
+//! Reads flags from the headers of image files without decoding them, like
+//! the `is-animated` and `get-orientation` packages used by the image
+//! optimizer.
+
+/// Whether the image is an animated GIF, PNG, WebP or AVIF.
+pub fn is_animated(bytes: &[u8]) -> bool {
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        gif_frame_count(bytes) > 1
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        is_animated_png(bytes)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP".as_slice()) {
+        // Only the extended format can be animated, its flags have the
+        // animation bit set.
+        bytes.get(12..16) == Some(b"VP8X".as_slice())
+            && bytes.get(20).map_or(false, |flags| flags & 0x02 != 0)
+    } else {
+        is_animated_avif(bytes)
+    }
+}
+
+fn gif_frame_count(bytes: &[u8]) -> usize {
+    // The header and the logical screen descriptor.
+    let mut offset = 13;
+    let Some(&flags) = bytes.get(10) else {
+        return 0;
+    };
+    if flags & 0x80 != 0 {
+        offset += 3 << ((flags & 0x07) + 1);
+    }
+
+    let mut frames = 0;
+    while let Some(&block) = bytes.get(offset) {
+        match block {
+            // An extension, the label followed by data sub-blocks.
+            0x21 => offset = skip_gif_sub_blocks(bytes, offset + 2),
+            // An image descriptor, followed by an optional local color table,
+            // the minimum LZW code size and the image data sub-blocks.
+            0x2c => {
+                frames += 1;
+                if frames > 1 {
+                    break;
+                }
+                let Some(&flags) = bytes.get(offset + 9) else {
+                    break;
+                };
+                offset += 10;
+                if flags & 0x80 != 0 {
+                    offset += 3 << ((flags & 0x07) + 1);
+                }
+                offset = skip_gif_sub_blocks(bytes, offset + 1);
+            }
+            _ => break,
+        }
+    }
+    frames
+}
+
+fn skip_gif_sub_blocks(bytes: &[u8], mut offset: usize) -> usize {
+    while let Some(&size) = bytes.get(offset) {
+        offset += 1;
+        if size == 0 {
+            return offset;
+        }
+        offset += size as usize;
+    }
+    offset
+}
+
+/// APNGs have an `acTL` chunk before the image data.
+fn is_animated_png(bytes: &[u8]) -> bool {
+    let mut offset = 8;
+    while let (Some(length), Some(chunk_type)) = (
+        read_u32(bytes, offset, false),
+        bytes.get(offset + 4..offset + 8),
+    ) {
+        match chunk_type {
+            b"acTL" => return true,
+            b"IDAT" => return false,
+            _ => offset += 12 + length as usize,
+        }
+    }
+    false
+}
+
+/// Animated AVIF images are image sequences, which use the `avis` brand.
+fn is_animated_avif(bytes: &[u8]) -> bool {
+    let Some(size) = read_u32(bytes, 0, false) else {
+        return false;
+    };
+    if bytes.get(4..8) != Some(b"ftyp".as_slice()) {
+        return false;
+    }
+    let Some(brands) = bytes.get(8..(size as usize).min(bytes.len())) else {
+        return false;
+    };
+    // The major brand, the minor version and the compatible brands.
+    brands
+        .chunks_exact(4)
+        .enumerate()
+        .any(|(i, brand)| i != 1 && brand == b"avis")
+}
+
+/// The EXIF orientation of a JPEG image, between 1 and 8.
+pub fn exif_orientation(bytes: &[u8]) -> Option<u8> {
+    if !bytes.starts_with(&[0xff, 0xd8]) {
+        return None;
+    }
+    let mut offset = 2;
+    loop {
+        let [0xff, marker] = *bytes.get(offset..offset + 2)? else {
+            return None;
+        };
+        // The start of the image data, EXIF data comes before it.
+        if marker == 0xda {
+            return None;
+        }
+        let length = read_u16(bytes, offset + 2, false)? as usize;
+        let segment = bytes.get(offset + 4..offset + 2 + length)?;
+        if marker == 0xe1 {
+            if let Some(tiff) = segment.strip_prefix(b"Exif\0\0") {
+                return tiff_orientation(tiff);
+            }
+        }
+        offset += 2 + length;
+    }
+}
+
+fn tiff_orientation(tiff: &[u8]) -> Option<u8> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let ifd = read_u32(tiff, 4, little_endian)? as usize;
+    let entries = read_u16(tiff, ifd, little_endian)? as usize;
+    (0..entries).find_map(|i| {
+        let entry = ifd + 2 + i * 12;
+        if read_u16(tiff, entry, little_endian)? != 0x0112 {
+            return None;
+        }
+        let orientation = read_u16(tiff, entry + 8, little_endian)?;
+        (1..=8).contains(&orientation).then_some(orientation as u8)
+    })
+}
+
+fn read_u16(bytes: &[u8], offset: usize, little_endian: bool) -> Option<u16> {
+    let bytes = bytes.get(offset..offset + 2)?.try_into().ok()?;
+    Some(if little_endian {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    })
+}
+
+fn read_u32(bytes: &[u8], offset: usize, little_endian: bool) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if little_endian {
+        u32::from_le_bytes(bytes)
+    } else {
+        u32::from_be_bytes(bytes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gif(frames: usize) -> Vec<u8> {
+        let mut gif = b"GIF89a\x01\x00\x01\x00\x00\x00\x00".to_vec();
+        // A graphic control extension.
+        gif.extend(b"\x21\xf9\x04\x00\x00\x00\x00\x00");
+        for _ in 0..frames {
+            gif.extend(b"\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x4c\x01\x00");
+        }
+        gif.push(0x3b);
+        gif
+    }
+
+    fn jpeg_with_orientation(orientation: u8, little_endian: bool) -> Vec<u8> {
+        let mut tiff = if little_endian {
+            b"II\x2a\x00\x08\x00\x00\x00\x01\x00\x12\x01\x03\x00\x01\x00\x00\x00".to_vec()
+        } else {
+            b"MM\x00\x2a\x00\x00\x00\x08\x00\x01\x01\x12\x00\x03\x00\x00\x00\x01".to_vec()
+        };
+        if little_endian {
+            tiff.extend([orientation, 0, 0, 0]);
+        } else {
+            tiff.extend([0, orientation, 0, 0]);
+        }
+        let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe1];
+        jpeg.extend((8 + tiff.len() as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(tiff);
+        jpeg.extend([0xff, 0xda, 0x00, 0x02]);
+        jpeg
+    }
+
+    #[test]
+    fn test_is_animated() {
+        assert!(!is_animated(&gif(1)));
+        assert!(is_animated(&gif(2)));
+        assert!(is_animated(b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\x02"));
+        assert!(!is_animated(b"RIFF\0\0\0\0WEBPVP8 \x0a\0\0\0\x02"));
+        assert!(is_animated(b"\0\0\0\x18ftypavis\0\0\0\0avifmif1"));
+        assert!(is_animated(b"\0\0\0\x18ftypavif\0\0\0\0avismif1"));
+        assert!(!is_animated(b"\0\0\0\x18ftypavif\0\0\0\0avifmif1"));
+        assert!(is_animated(
+            b"\x89PNG\r\n\x1a\n\0\0\0\x08acTL\0\0\0\x02\0\0\0\0\0\0\0\0"
+        ));
+        assert!(!is_animated(b"\x89PNG\r\n\x1a\n\0\0\0\0IDAT\0\0\0\0"));
+    }
+
+    #[test]
+    fn test_exif_orientation() {
+        assert_eq!(exif_orientation(&jpeg_with_orientation(6, true)), Some(6));
+        assert_eq!(exif_orientation(&jpeg_with_orientation(8, false)), Some(8));
+        assert_eq!(exif_orientation(&jpeg_with_orientation(9, true)), None);
+        assert_eq!(exif_orientation(&[0xff, 0xd8, 0xff, 0xda, 0, 2]), None);
+        assert_eq!(exif_orientation(b"GIF89a"), None);
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{bail, Result};
+use image::{codecs::png::PngEncoder, imageops::FilterType, DynamicImage, ImageEncoder};
+
+/// The sizes of the images in a generated `favicon.ico`.
+pub const FAVICON_SIZES: [u32; 3] = [16, 32, 48];
+
+/// Encodes an `.ico` file containing the image in each of the sizes. The
+/// images are stored as PNGs, which all browsers support in icons.
+pub fn encode_ico(image: &DynamicImage, sizes: &[u32]) -> Result<Vec<u8>> {
+    if sizes.iter().any(|&size| size == 0 || size > 256) {
+        bail!("icon sizes must be between 1 and 256 pixels");
+    }
+
+    let mut images = Vec::with_capacity(sizes.len());
+    for &size in sizes {
+        // Fits the image into the square, keeping its aspect ratio.
+        let resized = image.resize(size, size, FilterType::Lanczos3).to_rgba8();
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png).write_image(
+            resized.as_raw(),
+            resized.width(),
+            resized.height(),
+            image::ColorType::Rgba8,
+        )?;
+        images.push((resized.width(), resized.height(), png));
+    }
+
+    // The ICONDIR header, followed by an ICONDIRENTRY per image and the image
+    // data.
+    let mut ico = Vec::new();
+    ico.extend(0u16.to_le_bytes());
+    ico.extend(1u16.to_le_bytes());
+    ico.extend((images.len() as u16).to_le_bytes());
+    let mut offset = 6 + 16 * images.len() as u32;
+    for (width, height, png) in &images {
+        // A size of 0 means 256 pixels.
+        ico.push(*width as u8);
+        ico.push(*height as u8);
+        // The number of colors in the palette and a reserved byte.
+        ico.extend([0, 0]);
+        // The color planes and bits per pixel.
+        ico.extend(1u16.to_le_bytes());
+        ico.extend(32u16.to_le_bytes());
+        ico.extend((png.len() as u32).to_le_bytes());
+        ico.extend(offset.to_le_bytes());
+        offset += png.len() as u32;
+    }
+    for (_, _, png) in images {
+        ico.extend(png);
+    }
+    Ok(ico)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    #[test]
+    fn test_encode_ico() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(64, 32, Rgba([0, 0, 255, 255])));
+        let ico = encode_ico(&image, &FAVICON_SIZES).unwrap();
+
+        assert_eq!(&ico[0..6], &[0, 0, 1, 0, 3, 0]);
+        // The first entry is 16x8, as the aspect ratio is kept.
+        assert_eq!(&ico[6..8], &[16, 8]);
+        let size = u32::from_le_bytes(ico[14..18].try_into().unwrap()) as usize;
+        let offset = u32::from_le_bytes(ico[18..22].try_into().unwrap()) as usize;
+        assert_eq!(offset, 6 + 16 * 3);
+        let first = image::load_from_memory(&ico[offset..offset + size]).unwrap();
+        assert_eq!((first.width(), first.height()), (16, 8));
+
+        assert!(encode_ico(&image, &[512]).is_err());
+    }
+}
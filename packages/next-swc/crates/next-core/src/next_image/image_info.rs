@@ -0,0 +1,163 @@
+use anyhow::Result;
+use image::{imageops::FilterType, GenericImageView};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileContent,
+    turbopack::{
+        core::ident::AssetIdent,
+        image::process::{get_meta_data, BlurPlaceholderOptions},
+    },
+};
+
+use super::detect::{exif_orientation, is_animated};
+
+/// The options of the blur placeholders of static image imports, the same as
+/// the ones of the webpack `next-image-loader`.
+#[turbo_tasks::function]
+pub fn blur_placeholder_options() -> Vc<BlurPlaceholderOptions> {
+    BlurPlaceholderOptions {
+        quality: 70,
+        size: 8,
+    }
+    .cell()
+}
+
+/// A tiny version of an image, shown while the image itself is loading.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ImageBlurPlaceholder {
+    pub data_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The information about an image which is known at build time.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ImageInfo {
+    /// The size the image is displayed at, which is swapped for images which
+    /// are rotated by their EXIF orientation.
+    pub width: u32,
+    pub height: u32,
+    /// Animated images are served as they are by the image optimizer.
+    pub animated: bool,
+    /// The EXIF orientation, between 1 and 8.
+    pub orientation: Option<u8>,
+    /// Only computed when requested, as it requires resizing the image.
+    pub blur_placeholder: Option<ImageBlurPlaceholder>,
+}
+
+impl ImageInfo {
+    pub fn aspect_ratio(&self) -> f64 {
+        if self.height == 0 {
+            return 0.0;
+        }
+        self.width as f64 / self.height as f64
+    }
+
+    /// The size of the blur placeholder, where the longer side is `size`
+    /// pixels long.
+    pub fn blur_placeholder_size(&self, size: u32) -> (u32, u32) {
+        let (width, height) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        if width > height {
+            (size, (size as f32 * height / width).ceil() as u32)
+        } else {
+            ((size as f32 * width / height).ceil() as u32, size)
+        }
+    }
+}
+
+/// Reads the size of an image and optionally computes a blur placeholder as a
+/// data URL. This is shared by static image imports, the metadata image
+/// routes and anything else which needs to know about an image.
+#[turbo_tasks::function]
+pub async fn image_info(
+    ident: Vc<AssetIdent>,
+    content: Vc<FileContent>,
+    blur_data_url: bool,
+) -> Result<Vc<ImageInfo>> {
+    let blur_options = blur_data_url.then(blur_placeholder_options);
+    let meta_data = get_meta_data(ident, content, blur_options).await?;
+
+    let (animated, orientation) = match &*content.await? {
+        FileContent::Content(file) => {
+            let bytes = file.content().to_bytes()?;
+            (is_animated(&bytes), exif_orientation(&bytes))
+        }
+        FileContent::NotFound => (false, None),
+    };
+    // Orientations 5 to 8 rotate the image by 90 degrees.
+    let (width, height) = if matches!(orientation, Some(5..=8)) {
+        (meta_data.height, meta_data.width)
+    } else {
+        (meta_data.width, meta_data.height)
+    };
+
+    Ok(ImageInfo {
+        width,
+        height,
+        animated,
+        orientation,
+        blur_placeholder: meta_data
+            .blur_placeholder
+            .map(|placeholder| ImageBlurPlaceholder {
+                data_url: placeholder.data_url,
+                width: placeholder.width,
+                height: placeholder.height,
+            }),
+    }
+    .cell())
+}
+
+/// The average color of an image as a `#rrggbb` hex color, e.g. to be used as
+/// a background while the image is loading. This is `None` for vector images
+/// and formats which can't be decoded.
+#[turbo_tasks::function]
+pub async fn image_dominant_color(content: Vc<FileContent>) -> Result<Vc<Option<String>>> {
+    let FileContent::Content(file) = &*content.await? else {
+        return Ok(Vc::cell(None));
+    };
+    let bytes = file.content().to_bytes()?;
+    Ok(Vc::cell(dominant_color(&bytes)))
+}
+
+fn dominant_color(bytes: &[u8]) -> Option<String> {
+    let image = image::load_from_memory(bytes).ok()?;
+    // Resizing into a single pixel averages the colors of the image.
+    let [r, g, b, _] = image
+        .resize_exact(1, 1, FilterType::Triangle)
+        .get_pixel(0, 0)
+        .0;
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, Rgb, RgbImage};
+
+    use super::*;
+
+    #[test]
+    fn test_image_info() {
+        let info = ImageInfo {
+            width: 400,
+            height: 300,
+            animated: false,
+            orientation: None,
+            blur_placeholder: None,
+        };
+        assert_eq!(info.blur_placeholder_size(8), (8, 6));
+        assert_eq!(info.aspect_ratio(), 400.0 / 300.0);
+
+        let image = RgbImage::from_pixel(4, 4, Rgb([0x12, 0x34, 0x56]));
+        let mut buffer = Cursor::new(Vec::new());
+        image.write_to(&mut buffer, ImageFormat::Png).unwrap();
+        assert_eq!(
+            dominant_color(&buffer.into_inner()).as_deref(),
+            Some("#123456")
+        );
+        assert_eq!(dominant_color(b"<svg></svg>"), None);
+    }
+}
@@ -1,6 +1,11 @@
 pub(crate) mod content_source;
+pub(crate) mod detect;
+pub(crate) mod ico;
+pub mod image_info;
 pub(crate) mod module;
+pub mod optimizer;
 pub(crate) mod source_asset;
 
 pub use content_source::NextImageContentSource;
+pub use image_info::{image_dominant_color, image_info, ImageInfo};
 pub use module::StructuredImageModuleType;
@@ -11,23 +11,26 @@ use turbopack_binding::{
             source::Source,
         },
         ecmascript::utils::StringifyJs,
-        image::process::{get_meta_data, BlurPlaceholderOptions},
     },
 };
 
-use super::module::BlurPlaceholderMode;
+use super::{
+    image_info::{blur_placeholder_options, image_info, ImageInfo},
+    module::BlurPlaceholderMode,
+};
 
 fn modifier() -> Vc<String> {
     Vc::cell("structured image object".to_string())
 }
 
-#[turbo_tasks::function]
-fn blur_options() -> Vc<BlurPlaceholderOptions> {
-    BlurPlaceholderOptions {
-        quality: 70,
-        size: 8,
+/// `next/image` doesn't optimize animated images. The flag is only added when
+/// it's set, to keep the object small.
+fn animated_flag(info: &ImageInfo) -> &'static str {
+    if info.animated {
+        ", animated: true"
+    } else {
+        ""
     }
-    .cell()
 }
 
 /// An source asset that transforms an image into javascript code which exports
@@ -59,44 +62,34 @@ impl Asset for StructuredImageFileSource {
         };
         let mut result = RopeBuilder::from("");
         writeln!(result, "import src from \"IMAGE\";",)?;
-        let blur_options = blur_options();
         match self.blur_placeholder_mode {
             BlurPlaceholderMode::NextImageUrl => {
-                let info = get_meta_data(self.image.ident(), content, None).await?;
-                let width = info.width;
-                let height = info.height;
-                let blur_options = blur_options.await?;
-                let (blur_width, blur_height) = if width > height {
-                    (
-                        blur_options.size,
-                        (blur_options.size as f32 * height as f32 / width as f32).ceil() as u32,
-                    )
-                } else {
-                    (
-                        (blur_options.size as f32 * width as f32 / height as f32).ceil() as u32,
-                        blur_options.size,
-                    )
-                };
+                let info = image_info(self.image.ident(), content, false).await?;
+                let blur_options = blur_placeholder_options().await?;
+                let (blur_width, blur_height) = info.blur_placeholder_size(blur_options.size);
                 writeln!(
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
                      `/_next/image?w={blur_width}&q={quality}&url=${{encodeURIComponent(src)}}`, \
-                     blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     blurWidth: {blur_width}, blurHeight: {blur_height}{animated} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
+                    animated = animated_flag(&info),
                     quality = StringifyJs(&blur_options.quality),
                     blur_width = StringifyJs(&blur_width),
                     blur_height = StringifyJs(&blur_height),
                 )?;
             }
             BlurPlaceholderMode::DataUrl => {
-                let info = get_meta_data(self.image.ident(), content, Some(blur_options)).await?;
+                let info = image_info(self.image.ident(), content, true).await?;
                 writeln!(
                     result,
                     "export default {{ src, width: {width}, height: {height}, blurDataURL: \
-                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: {blur_height} }}",
+                     {blur_data_url}, blurWidth: {blur_width}, blurHeight: \
+                     {blur_height}{animated} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
+                    animated = animated_flag(&info),
                     blur_data_url =
                         StringifyJs(&info.blur_placeholder.as_ref().map(|p| p.data_url.as_str())),
                     blur_width =
@@ -106,12 +99,13 @@ impl Asset for StructuredImageFileSource {
                 )?;
             }
             BlurPlaceholderMode::None => {
-                let info = get_meta_data(self.image.ident(), content, None).await?;
+                let info = image_info(self.image.ident(), content, false).await?;
                 writeln!(
                     result,
-                    "export default {{ src, width: {width}, height: {height} }}",
+                    "export default {{ src, width: {width}, height: {height}{animated} }}",
                     width = StringifyJs(&info.width),
                     height = StringifyJs(&info.height),
+                    animated = animated_flag(&info),
                 )?;
             }
         };
@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{Context, Result};
+use indexmap::IndexMap;
 use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
     turbo::tasks_fs::{glob::Glob, FileSystem, FileSystemPath},
@@ -22,13 +23,17 @@ use turbopack_binding::{
 use crate::{
     embed_js::{next_js_fs, VIRTUAL_PACKAGE_NAME},
     mode::NextMode,
-    next_client::context::ClientContextType,
+    next_client::{context::ClientContextType, node_fallback::NodeFallbackReplacer},
     next_config::NextConfig,
     next_font::{
         google::{NextFontGoogleCssModuleReplacer, NextFontGoogleReplacer},
         local::{NextFontLocalCssModuleReplacer, NextFontLocalReplacer},
     },
     next_server::context::ServerContextType,
+    next_shared::{
+        context_module::{ContextModuleReplacer, CONTEXT_MODULE_REQUEST},
+        resolve::PackageExportsReplacer,
+    },
     util::NextRuntime,
 };
 
@@ -61,6 +66,8 @@ pub async fn get_next_client_import_map(
     )
     .await?;
 
+    let react_channel = react_channel(next_config).await?;
+
     match ty.into_value() {
         ClientContextType::Pages { pages_dir } => {
             insert_alias_to_alternatives(
@@ -91,23 +98,38 @@ pub async fn get_next_client_import_map(
         ClientContextType::App { app_dir } => {
             import_map.insert_exact_alias(
                 "react",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react{react_channel}"),
+                ),
             );
             import_map.insert_wildcard_alias(
                 "react/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react{react_channel}/*"),
+                ),
             );
             import_map.insert_exact_alias(
                 "react-dom",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react-dom"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react-dom{react_channel}"),
+                ),
             );
             import_map.insert_wildcard_alias(
                 "react-dom/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react-dom/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react-dom{react_channel}/*"),
+                ),
             );
             import_map.insert_wildcard_alias(
                 "react-server-dom-webpack/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react-server-dom-webpack/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react-server-dom-webpack{react_channel}/*"),
+                ),
             );
             import_map.insert_exact_alias(
                 "next/dynamic",
@@ -118,6 +140,24 @@ pub async fn get_next_client_import_map(
         ClientContextType::Other => {}
     }
 
+    // The profiling builds of React keep the Profiler API working in production.
+    if matches!(mode, NextMode::Build) && *next_config.react_production_profiling().await? {
+        let react_dom_profiling = match ty.into_value() {
+            ClientContextType::App { .. } => {
+                format!("next/dist/compiled/react-dom{react_channel}/profiling")
+            }
+            _ => "react-dom/profiling".to_string(),
+        };
+        import_map.insert_exact_alias(
+            "react-dom",
+            request_to_import_mapping(project_path, &react_dom_profiling),
+        );
+        import_map.insert_exact_alias(
+            "scheduler/tracing",
+            request_to_import_mapping(project_path, "scheduler/tracing-profiling"),
+        );
+    }
+
     match ty.into_value() {
         ClientContextType::Pages { .. }
         | ClientContextType::App { .. }
@@ -160,8 +200,15 @@ pub fn get_next_build_import_map() -> Vc<ImportMap> {
 
 /// Computes the Next-specific client fallback import map, which provides
 /// polyfills to Node.js externals.
+///
+/// The polyfills can be replaced or disabled per built-in with
+/// `experimental.turbo.resolveFallback`.
 #[turbo_tasks::function]
-pub fn get_next_client_fallback_import_map(ty: Value<ClientContextType>) -> Vc<ImportMap> {
+pub async fn get_next_client_fallback_import_map(
+    ty: Value<ClientContextType>,
+    next_config: Vc<NextConfig>,
+    package_exports_replacer: Vc<PackageExportsReplacer>,
+) -> Result<Vc<ImportMap>> {
     let mut import_map = ImportMap::empty();
 
     match ty.into_value() {
@@ -171,9 +218,25 @@ pub fn get_next_client_fallback_import_map(ty: Value<ClientContextType>) -> Vc<I
         | ClientContextType::App {
             app_dir: context_dir,
         } => {
-            for (original, alias) in NEXT_ALIASES {
-                import_map
-                    .insert_exact_alias(original, request_to_import_mapping(context_dir, alias));
+            let polyfills = *next_config.fallback_node_polyfills().await?;
+            let mut fallbacks: IndexMap<String, Option<String>> = NEXT_ALIASES
+                .iter()
+                .map(|(original, alias)| {
+                    (original.to_string(), polyfills.then(|| alias.to_string()))
+                })
+                .collect();
+            fallbacks.extend(next_config.resolve_fallback().await?.clone_value());
+
+            for (original, target) in fallbacks {
+                import_map.insert_exact_alias(
+                    original.clone(),
+                    ImportMapping::Dynamic(Vc::upcast(NodeFallbackReplacer::new(
+                        context_dir,
+                        original,
+                        target,
+                    )))
+                    .into(),
+                );
             }
         }
         ClientContextType::Fallback => {}
@@ -181,10 +244,35 @@ pub fn get_next_client_fallback_import_map(ty: Value<ClientContextType>) -> Vc<I
     }
 
     insert_turbopack_dev_alias(&mut import_map);
+    insert_package_exports_fallback(&mut import_map, package_exports_replacer);
+
+    Ok(import_map.cell())
+}
 
+/// Computes the Next-specific server and edge fallback import map, which
+/// explains requests that fail because of the `exports` field of a package.
+#[turbo_tasks::function]
+pub fn get_next_server_fallback_import_map(
+    package_exports_replacer: Vc<PackageExportsReplacer>,
+) -> Vc<ImportMap> {
+    let mut import_map = ImportMap::empty();
+    insert_package_exports_fallback(&mut import_map, package_exports_replacer);
     import_map.cell()
 }
 
+/// Registers the replacer for all requests. Exact aliases take precedence, so
+/// it only sees the requests the rest of the fallback import map doesn't
+/// handle.
+fn insert_package_exports_fallback(
+    import_map: &mut ImportMap,
+    package_exports_replacer: Vc<PackageExportsReplacer>,
+) {
+    import_map.insert_wildcard_alias(
+        "",
+        ImportMapping::Dynamic(Vc::upcast(package_exports_replacer)).into(),
+    );
+}
+
 /// Computes the Next-specific server-side import map.
 #[turbo_tasks::function]
 pub async fn get_next_server_import_map(
@@ -215,7 +303,14 @@ pub async fn get_next_server_import_map(
 
     let ty = ty.into_value();
 
-    insert_next_server_special_aliases(&mut import_map, ty, mode, NextRuntime::NodeJs).await?;
+    insert_next_server_special_aliases(
+        &mut import_map,
+        ty,
+        mode,
+        NextRuntime::NodeJs,
+        react_channel(next_config).await?,
+    )
+    .await?;
     let external = ImportMapping::External(None).cell();
 
     match ty {
@@ -251,6 +346,13 @@ pub async fn get_next_server_import_map(
                 "next/dynamic",
                 request_to_import_mapping(project_path, "next/dist/shared/lib/app-dynamic"),
             );
+            import_map.insert_exact_alias(
+                "private-next-rsc-cache-wrapper",
+                request_to_import_mapping(
+                    project_path,
+                    "next/dist/build/webpack/loaders/next-flight-loader/cache-wrapper",
+                ),
+            );
         }
         ServerContextType::Middleware => {}
     }
@@ -288,7 +390,14 @@ pub async fn get_next_edge_import_map(
 
     let ty = ty.into_value();
 
-    insert_next_server_special_aliases(&mut import_map, ty, mode, NextRuntime::Edge).await?;
+    insert_next_server_special_aliases(
+        &mut import_map,
+        ty,
+        mode,
+        NextRuntime::Edge,
+        react_channel(next_config).await?,
+    )
+    .await?;
 
     match ty {
         ServerContextType::Pages { .. } | ServerContextType::PagesData { .. } => {}
@@ -303,6 +412,13 @@ pub async fn get_next_edge_import_map(
                 "next/dynamic",
                 request_to_import_mapping(project_path, "next/dist/shared/lib/app-dynamic"),
             );
+            import_map.insert_exact_alias(
+                "private-next-rsc-cache-wrapper",
+                request_to_import_mapping(
+                    project_path,
+                    "next/dist/build/webpack/loaders/next-flight-loader/cache-wrapper",
+                ),
+            );
         }
         ServerContextType::Middleware => {}
     }
@@ -367,11 +483,22 @@ static NEXT_ALIASES: [(&str, &str); 23] = [
     ("setImmediate", "next/dist/compiled/setimmediate"),
 ];
 
+/// The suffix of the bundled React builds used by the app directory. The taint
+/// APIs are only available in React's experimental channel.
+async fn react_channel(next_config: Vc<NextConfig>) -> Result<&'static str> {
+    Ok(if *next_config.needs_experimental_react().await? {
+        "-experimental"
+    } else {
+        ""
+    })
+}
+
 async fn insert_next_server_special_aliases(
     import_map: &mut ImportMap,
     ty: ServerContextType,
     mode: NextMode,
     runtime: NextRuntime,
+    react_channel: &str,
 ) -> Result<()> {
     let external_if_node = move |context_dir: Vc<FileSystemPath>, request: &str| match runtime {
         NextRuntime::Edge => request_to_import_mapping(context_dir, request),
@@ -427,26 +554,40 @@ async fn insert_next_server_special_aliases(
             );
             import_map.insert_exact_alias(
                 "react",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react{react_channel}"),
+                ),
             );
             import_map.insert_wildcard_alias(
                 "react/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react{react_channel}/*"),
+                ),
             );
             import_map.insert_exact_alias(
                 "react-dom",
                 request_to_import_mapping(
                     app_dir,
-                    "next/dist/compiled/react-dom/server-rendering-stub.js",
+                    &format!(
+                        "next/dist/compiled/react-dom{react_channel}/server-rendering-stub.js"
+                    ),
                 ),
             );
             import_map.insert_wildcard_alias(
                 "react-dom/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react-dom/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react-dom{react_channel}/*"),
+                ),
             );
             import_map.insert_wildcard_alias(
                 "react-server-dom-webpack/",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react-server-dom-webpack/*"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react-server-dom-webpack{react_channel}/*"),
+                ),
             );
         }
         // NOTE(alexkirsz) This logic maps loosely to
@@ -471,21 +612,30 @@ async fn insert_next_server_special_aliases(
             );
             import_map.insert_exact_alias(
                 "react",
-                request_to_import_mapping(app_dir, "next/dist/compiled/react/react.shared-subset"),
+                request_to_import_mapping(
+                    app_dir,
+                    &format!("next/dist/compiled/react{react_channel}/react.shared-subset"),
+                ),
             );
             import_map.insert_exact_alias(
                 "react-dom",
                 request_to_import_mapping(
                     app_dir,
-                    "next/dist/compiled/react-dom/server-rendering-stub",
+                    &format!("next/dist/compiled/react-dom{react_channel}/server-rendering-stub"),
                 ),
             );
             for (wildcard_alias, request) in [
-                ("react/", "next/dist/compiled/react/*"),
-                ("react-dom/", "next/dist/compiled/react-dom/*"),
+                (
+                    "react/",
+                    &format!("next/dist/compiled/react{react_channel}/*"),
+                ),
+                (
+                    "react-dom/",
+                    &format!("next/dist/compiled/react-dom{react_channel}/*"),
+                ),
                 (
                     "react-server-dom-webpack/",
-                    "next/dist/compiled/react-server-dom-webpack/*",
+                    &format!("next/dist/compiled/react-server-dom-webpack{react_channel}/*"),
                 ),
             ] {
                 import_map.insert_wildcard_alias(
@@ -504,22 +654,28 @@ async fn insert_next_server_special_aliases(
         (NextMode::Build | NextMode::Development, ServerContextType::AppSSR { app_dir }) => {
             import_map.insert_exact_alias(
                 "react",
-                external_if_node(app_dir, "next/dist/compiled/react"),
+                external_if_node(app_dir, &format!("next/dist/compiled/react{react_channel}")),
             );
             import_map.insert_exact_alias(
                 "react-dom",
                 external_if_node(
                     app_dir,
-                    "next/dist/compiled/react-dom/server-rendering-stub",
+                    &format!("next/dist/compiled/react-dom{react_channel}/server-rendering-stub"),
                 ),
             );
 
             for (wildcard_alias, request) in [
-                ("react/", "next/dist/compiled/react/*"),
-                ("react-dom/", "next/dist/compiled/react-dom/*"),
+                (
+                    "react/",
+                    &format!("next/dist/compiled/react{react_channel}/*"),
+                ),
+                (
+                    "react-dom/",
+                    &format!("next/dist/compiled/react-dom{react_channel}/*"),
+                ),
                 (
                     "react-server-dom-webpack/",
-                    "next/dist/compiled/react-server-dom-webpack/*",
+                    &format!("next/dist/compiled/react-server-dom-webpack{react_channel}/*"),
                 ),
             ] {
                 let import_mapping = external_if_node(app_dir, request);
@@ -614,6 +770,12 @@ pub async fn insert_next_shared_aliases(
         .into(),
     );
 
+    import_map.insert_alias(
+        // Request path from js via the require.context swc transform
+        AliasPattern::exact(CONTEXT_MODULE_REQUEST),
+        ImportMapping::Dynamic(Vc::upcast(ContextModuleReplacer::new())).into(),
+    );
+
     import_map.insert_singleton_alias("@swc/helpers", get_next_package(project_path));
     import_map.insert_singleton_alias("styled-jsx", get_next_package(project_path));
     import_map.insert_singleton_alias("next", project_path);
@@ -631,7 +793,7 @@ pub async fn insert_next_shared_aliases(
 }
 
 #[turbo_tasks::function]
-async fn package_lookup_resolve_options(
+pub(crate) async fn package_lookup_resolve_options(
     project_path: Vc<FileSystemPath>,
 ) -> Result<Vc<ResolveOptions>> {
     Ok(resolve_options(
@@ -17,6 +17,7 @@ use super::{ClientReferenceManifest, ManifestNode, ManifestNodeEntry, ModuleId};
 use crate::{
     next_app::ClientReferencesChunks,
     next_client_reference::{ClientReferenceType, ClientReferences},
+    util::{encode_uri_path, NextRuntime},
 };
 
 #[turbo_tasks::value_impl]
@@ -30,6 +31,7 @@ impl ClientReferenceManifest {
         client_references_chunks: Vc<ClientReferencesChunks>,
         client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
         ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        runtime: NextRuntime,
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
         let mut entry_manifest: ClientReferenceManifest = Default::default();
         let client_references_chunks = client_references_chunks.await?;
@@ -74,7 +76,7 @@ impl ClientReferenceManifest {
                                 .filter_map(|chunk_path| {
                                     client_relative_path.get_path_to(chunk_path)
                                 })
-                                .map(ToString::to_string),
+                                .map(encode_uri_path),
                         );
                     }
 
@@ -89,7 +91,7 @@ impl ClientReferenceManifest {
                                         None
                                     }
                                 })
-                                .map(ToString::to_string),
+                                .map(encode_uri_path),
                         );
                     }
                 }
@@ -126,6 +128,8 @@ impl ClientReferenceManifest {
                         .map(|chunk| chunk.ident().path())
                         .try_join()
                         .await?;
+                    // These are the keys the client loads the chunks with, not URLs, so
+                    // they aren't encoded like the CSS files above.
                     let client_chunks_paths: Vec<String> = client_chunks_paths
                         .iter()
                         .filter_map(|chunk_path| client_relative_path.get_path_to(chunk_path))
@@ -168,7 +172,7 @@ impl ClientReferenceManifest {
                     );
 
                     entry_manifest
-                        .ssr_module_mapping
+                        .ssr_module_mapping_mut(runtime)
                         .insert((&*client_module_id).into(), ssr_manifest_node);
                 }
             }
@@ -0,0 +1,87 @@
+//! Merging of manifests written by separate builds, e.g. by build shards which
+//! each compile a subset of the app routes.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// Merges JSON manifests into one. Objects are merged key by key, arrays are
+/// concatenated without duplicates, and other values, like the manifest
+/// `version`, must be equal.
+pub fn merge_manifests(manifests: impl IntoIterator<Item = Value>) -> Result<Value> {
+    let mut merged = Value::Null;
+    for manifest in manifests {
+        merge_into(&mut merged, manifest, "")?;
+    }
+    Ok(merged)
+}
+
+fn merge_into(target: &mut Value, value: Value, path: &str) -> Result<()> {
+    match (target, value) {
+        (target @ Value::Null, value) => *target = value,
+        (Value::Object(target), Value::Object(value)) => {
+            for (key, value) in value {
+                let path = format!("{path}/{key}");
+                match target.get_mut(&key) {
+                    Some(existing) => merge_into(existing, value, &path)?,
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(value)) => {
+            for item in value {
+                if !target.contains(&item) {
+                    target.push(item);
+                }
+            }
+        }
+        (target, value) => {
+            if *target != value {
+                bail!(
+                    "conflicting values at {}: {} and {}",
+                    if path.is_empty() { "/" } else { path },
+                    target,
+                    value
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_manifests() {
+        let merged = merge_manifests([
+            json!({
+                "version": 1,
+                "pages": { "/a/page": ["a.js", "shared.js"] },
+                "sortedMiddleware": [],
+            }),
+            json!({
+                "version": 1,
+                "pages": { "/b/page": ["b.js"], "/a/page": ["shared.js", "a2.js"] },
+            }),
+        ])
+        .unwrap();
+        assert_eq!(
+            merged,
+            json!({
+                "version": 1,
+                "pages": {
+                    "/a/page": ["a.js", "shared.js", "a2.js"],
+                    "/b/page": ["b.js"],
+                },
+                "sortedMiddleware": [],
+            })
+        );
+
+        assert!(merge_manifests([json!({ "version": 1 }), json!({ "version": 2 })]).is_err());
+    }
+}
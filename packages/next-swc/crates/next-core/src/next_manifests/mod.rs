@@ -1,12 +1,15 @@
 //! Type definitions for the Next.js manifest formats.
 
 pub(crate) mod client_reference_manifest;
+pub mod merge;
+pub mod preload_hints;
+pub mod subresource_integrity;
 
 use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::next_config::Rewrites;
+use crate::{next_app::SegmentBoundary, next_config::Rewrites, util::NextRuntime};
 
 #[derive(Serialize, Default, Debug)]
 pub struct PagesManifest {
@@ -24,6 +27,40 @@ pub struct BuildManifest {
     pub root_main_files: Vec<String>,
     pub pages: HashMap<String, Vec<String>>,
     pub amp_first_pages: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub preload_hints: HashMap<String, Vec<PreloadHint>>,
+}
+
+/// A `<link rel="preload">` hint for a file which is needed to render a
+/// route.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct PreloadHint {
+    pub href: String,
+    #[serde(rename = "as")]
+    pub ty: PreloadType,
+    /// Fonts are always fetched in CORS mode, so their preloads are only used
+    /// when they are sent with `crossorigin` too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cross_origin: Option<String>,
+}
+
+impl PreloadHint {
+    pub fn new(href: String, ty: PreloadType) -> Self {
+        Self {
+            href,
+            ty,
+            cross_origin: (ty == PreloadType::Font).then(|| "anonymous".to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PreloadType {
+    Script,
+    Style,
+    Font,
 }
 
 #[derive(Serialize, Debug)]
@@ -89,9 +126,8 @@ pub struct EdgeFunctionDefinition {
     pub name: String,
     pub page: String,
     pub matchers: Vec<MiddlewareMatcher>,
-    // TODO: AssetBinding[]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub wasm: Option<Vec<()>>,
+    pub wasm: Option<Vec<AssetBinding>>,
     // TODO: AssetBinding[]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assets: Option<Vec<()>>,
@@ -99,6 +135,47 @@ pub struct EdgeFunctionDefinition {
     pub regions: Option<Regions>,
 }
 
+impl EdgeFunctionDefinition {
+    /// Moves WebAssembly files out of `files` into `wasm` bindings. The edge
+    /// runtime compiles them ahead of time and exposes each module as a global
+    /// named after the binding, instead of evaluating them like chunks.
+    pub fn with_wasm_bindings(mut self) -> Self {
+        let (wasm, files): (Vec<_>, Vec<_>) = std::mem::take(&mut self.files)
+            .into_iter()
+            .partition(|file| file.ends_with(".wasm"));
+        self.files = files;
+        if !wasm.is_empty() {
+            self.wasm = Some(
+                wasm.into_iter()
+                    .map(|file_path| AssetBinding {
+                        name: wasm_binding_name(&file_path),
+                        file_path,
+                    })
+                    .collect(),
+            );
+        }
+        self
+    }
+}
+
+/// Turns `server/edge-chunks/module_1a2b3c.wasm` into `wasm_module_1a2b3c`.
+fn wasm_binding_name(file_path: &str) -> String {
+    let file_name = file_path.rsplit('/').next().unwrap_or(file_path);
+    let stem = file_name.strip_suffix(".wasm").unwrap_or(file_name);
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("wasm_{stem}")
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBinding {
+    pub name: String,
+    pub file_path: String,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum Regions {
@@ -145,6 +222,93 @@ pub struct AppPathsManifest {
     pub node_server_app_paths: PagesManifest,
 }
 
+/// Per-route function settings for hosting adapters, written to
+/// `functions-config-manifest.json`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionsConfigManifest {
+    pub version: u32,
+    pub functions: HashMap<String, FunctionConfig>,
+}
+
+impl Default for FunctionsConfigManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            functions: Default::default(),
+        }
+    }
+}
+
+/// The assets which can be sent in a 103 Early Hints response for each app
+/// path, written to `early-hints-manifest.json` for hosting adapters.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EarlyHintsManifest {
+    pub version: u32,
+    pub pages: HashMap<String, Vec<PreloadHint>>,
+}
+
+impl Default for EarlyHintsManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            pages: Default::default(),
+        }
+    }
+}
+
+/// The metadata of the app pages which only use static `metadata` exports,
+/// resolved at compile time, written to `static-metadata-manifest.json`. This
+/// is meant for hosting adapters and tooling which generate the `<head>` of
+/// fully static pages themselves.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticMetadataManifest {
+    pub version: u32,
+    pub pages: HashMap<String, serde_json::Value>,
+}
+
+impl Default for StaticMetadataManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            pages: Default::default(),
+        }
+    }
+}
+
+/// The segments of the app pages which have error, loading or not found
+/// boundaries, written to `segment-boundaries-manifest.json` for tooling which
+/// shows the structure of the routes.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentBoundariesManifest {
+    pub version: u32,
+    pub pages: HashMap<String, Vec<SegmentBoundary>>,
+}
+
+impl Default for SegmentBoundariesManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            pages: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionConfig {
+    pub runtime: NextRuntime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    /// Whether the responses are streamed and shouldn't be buffered.
+    pub streaming: bool,
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerReferenceManifest {
@@ -192,6 +356,20 @@ pub struct ClientReferenceManifest {
     pub entry_css_files: HashMap<String, Vec<String>>,
 }
 
+impl ClientReferenceManifest {
+    /// The mapping of client module IDs to the modules which server render
+    /// them in the given runtime.
+    pub fn ssr_module_mapping_mut(
+        &mut self,
+        runtime: NextRuntime,
+    ) -> &mut HashMap<ModuleId, ManifestNode> {
+        match runtime {
+            NextRuntime::NodeJs => &mut self.ssr_module_mapping,
+            NextRuntime::Edge => &mut self.edge_ssr_module_mapping,
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ManifestNode {
@@ -236,6 +414,8 @@ pub struct FontManifestEntry {
 #[serde(rename_all = "camelCase")]
 pub struct AppBuildManifest {
     pub pages: HashMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub preload_hints: HashMap<String, Vec<PreloadHint>>,
 }
 
 // TODO(alexkirsz) Unify with the one for dev.
@@ -250,3 +430,71 @@ pub struct ClientBuildManifest<'a> {
     #[serde(flatten)]
     pub pages: HashMap<String, Vec<&'a str>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        wasm_binding_name, ClientReferenceManifest, EdgeFunctionDefinition, ManifestNode, ModuleId,
+    };
+    use crate::util::NextRuntime;
+
+    #[test]
+    fn test_wasm_binding_name() {
+        assert_eq!(
+            wasm_binding_name("server/edge-chunks/module_1a2b3c.wasm"),
+            "wasm_module_1a2b3c"
+        );
+        assert_eq!(
+            wasm_binding_name("server/edge-chunks/add.wasm-4f3e.wasm"),
+            "wasm_add_wasm_4f3e"
+        );
+    }
+
+    #[test]
+    fn test_with_wasm_bindings() {
+        let definition = EdgeFunctionDefinition {
+            files: vec![
+                "server/edge-chunks/page.js".to_string(),
+                "server/edge-chunks/add_1a2b.wasm".to_string(),
+            ],
+            ..Default::default()
+        }
+        .with_wasm_bindings();
+        assert_eq!(definition.files, vec!["server/edge-chunks/page.js"]);
+        assert_eq!(
+            serde_json::to_value(&definition.wasm).unwrap(),
+            serde_json::json!([{
+                "name": "wasm_add_1a2b",
+                "filePath": "server/edge-chunks/add_1a2b.wasm",
+            }])
+        );
+
+        let definition = EdgeFunctionDefinition {
+            files: vec!["server/edge-chunks/page.js".to_string()],
+            ..Default::default()
+        }
+        .with_wasm_bindings();
+        assert!(definition.wasm.is_none());
+    }
+
+    #[test]
+    fn test_ssr_module_mapping() {
+        let mut manifest = ClientReferenceManifest::default();
+        manifest
+            .ssr_module_mapping_mut(NextRuntime::NodeJs)
+            .insert(ModuleId::Number(1), ManifestNode::default());
+        manifest.ssr_module_mapping_mut(NextRuntime::Edge).insert(
+            ModuleId::String("[project]/app/button.js".to_string()),
+            ManifestNode::default(),
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            serde_json::json!({
+                "clientModules": {},
+                "ssrModuleMapping": { "1": {} },
+                "edgeSSRModuleMapping": { "[project]/app/button.js": {} },
+                "entryCSSFiles": {},
+            })
+        );
+    }
+}
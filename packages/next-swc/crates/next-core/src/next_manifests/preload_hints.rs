@@ -0,0 +1,52 @@
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::core::output::OutputAsset;
+
+use super::{PreloadHint, PreloadType};
+use crate::util::encode_uri_path;
+
+/// Computes the preload hints of a route from its client chunks: the chunks
+/// themselves and the fonts referenced by its CSS chunks. Paths are relative
+/// to the client root, like the other build manifest entries, and
+/// percent-encoded since they end up in `Link` headers.
+pub async fn get_preload_hints(
+    client_chunks: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+) -> Result<Vec<PreloadHint>> {
+    let mut hints = IndexSet::new();
+    for chunk in client_chunks.iter().copied() {
+        let chunk_path = chunk.ident().path().await?;
+        let Some(ty) = preload_type(chunk_path.extension_ref()) else {
+            continue;
+        };
+        let Some(href) = client_relative_path.get_path_to(&chunk_path) else {
+            continue;
+        };
+        hints.insert(PreloadHint::new(encode_uri_path(href), ty));
+
+        if ty != PreloadType::Style {
+            continue;
+        }
+        for asset in chunk.references().await?.iter() {
+            let asset_path = asset.ident().path().await?;
+            if preload_type(asset_path.extension_ref()) != Some(PreloadType::Font) {
+                continue;
+            }
+            if let Some(href) = client_relative_path.get_path_to(&asset_path) {
+                hints.insert(PreloadHint::new(encode_uri_path(href), PreloadType::Font));
+            }
+        }
+    }
+    Ok(hints.into_iter().collect())
+}
+
+fn preload_type(extension: Option<&str>) -> Option<PreloadType> {
+    match extension? {
+        "js" => Some(PreloadType::Script),
+        "css" => Some(PreloadType::Style),
+        "woff" | "woff2" | "ttf" | "otf" | "eot" => Some(PreloadType::Font),
+        _ => None,
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack_binding::turbopack::core::{
+    asset::{Asset, AssetContent},
+    output::OutputAsset,
+};
+
+use crate::next_config::SubresourceIntegrityAlgorithm;
+
+/// The name of the manifest, in the format of the webpack
+/// `SubresourceIntegrityPlugin`: the hashes keyed by the path of the script
+/// relative to the client root.
+pub const SUBRESOURCE_INTEGRITY_MANIFEST: &str = "subresource-integrity-manifest";
+
+/// Computes the subresource integrity hashes of the client scripts among the
+/// given assets, from their content as it is written to disk.
+pub async fn get_subresource_integrity_hashes(
+    client_assets: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+    algorithm: SubresourceIntegrityAlgorithm,
+) -> Result<BTreeMap<String, String>> {
+    let hashes = client_assets
+        .iter()
+        .map(|&asset| async move {
+            let path = asset.ident().path().await?;
+            let Some(path) = client_relative_path.get_path_to(&path) else {
+                return Ok(None);
+            };
+            if !path.ends_with(".js") {
+                return Ok(None);
+            }
+            let path = path.to_string();
+            let AssetContent::File(file) = &*asset.content().await? else {
+                return Ok(None);
+            };
+            let FileContent::Content(file) = &*file.await? else {
+                return Ok(None);
+            };
+            let bytes = file.content().to_bytes()?;
+            Ok(Some((path, integrity(algorithm, &bytes))))
+        })
+        .try_join()
+        .await?;
+    Ok(hashes.into_iter().flatten().collect())
+}
+
+/// The value of an `integrity` attribute for the given content.
+pub fn integrity(algorithm: SubresourceIntegrityAlgorithm, content: &[u8]) -> String {
+    let digest = match algorithm {
+        SubresourceIntegrityAlgorithm::Sha256 => Sha256::digest(content).to_vec(),
+        SubresourceIntegrityAlgorithm::Sha384 => Sha384::digest(content).to_vec(),
+        SubresourceIntegrityAlgorithm::Sha512 => Sha512::digest(content).to_vec(),
+    };
+    format!("{}-{}", algorithm.as_str(), STANDARD.encode(digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity() {
+        let content = b"console.log(1)";
+        assert_eq!(
+            integrity(SubresourceIntegrityAlgorithm::Sha256, content),
+            "sha256-CihokcEcBW4atb/CW/XWsvWwbTjqwQlE9nj9ii5ww5M="
+        );
+        assert_eq!(
+            integrity(SubresourceIntegrityAlgorithm::Sha384, content),
+            "sha384-vuz+yO71bcb30P4dMUNzy6/D2y+6d/n0KcOnt5clJtTBxEDoKAqGay0stFlC8Dpr"
+        );
+        assert_eq!(
+            integrity(SubresourceIntegrityAlgorithm::Sha512, content),
+            "sha512-8p0Ggfr7AZyLMR3CqtGVLlTyiFmgkUsoB2B8gxzC7Rg0Zmu1NeXok8f8DniPxckSUHZsngULWJIVSLCFcNvh+Q=="
+        );
+    }
+
+    #[test]
+    fn test_algorithm_config() {
+        let algorithm: SubresourceIntegrityAlgorithm = serde_json::from_str("\"sha384\"").unwrap();
+        assert_eq!(algorithm, SubresourceIntegrityAlgorithm::Sha384);
+        assert!(serde_json::from_str::<SubresourceIntegrityAlgorithm>("\"md5\"").is_err());
+    }
+}
@@ -23,7 +23,7 @@ use turbopack_binding::{
             module_options::{
                 CustomEcmascriptTransformPlugins, JsxTransformOptions, MdxTransformModuleOptions,
                 ModuleOptionsContext, PostCssTransformOptions, TypescriptTransformOptions,
-                WebpackLoadersOptions,
+                WebpackLoadersOptions, WebpackRules,
             },
             resolve_options_context::ResolveOptionsContext,
             transition::Transition,
@@ -36,27 +36,39 @@ use super::{
     transforms::{get_next_server_internal_transforms_rules, get_next_server_transforms_rules},
 };
 use crate::{
-    babel::maybe_add_babel_loader,
+    babel::{
+        exclude_react_compiler_from_node_modules, maybe_add_babel_loader,
+        maybe_add_react_compiler_loader,
+    },
     embed_js::next_js_fs,
     env::env_for_js,
     mode::NextMode,
     next_build::{get_external_next_compiled_package_mapping, get_postcss_package_mapping},
     next_client::{RuntimeEntries, RuntimeEntry},
     next_config::NextConfig,
-    next_import_map::{get_next_server_import_map, mdx_import_source_file},
+    next_import_map::{
+        get_next_server_fallback_import_map, get_next_server_import_map, mdx_import_source_file,
+    },
     next_server::resolve::ExternalPredicate,
     next_shared::{
-        resolve::{ModuleFeatureReportResolvePlugin, UnsupportedModulesResolvePlugin},
+        asset_query::AssetQueryResolvePlugin,
+        resolve::{
+            ImportCasingResolvePlugin, ModuleFeatureReportResolvePlugin, PackageExportsReplacer,
+            UnsupportedModulesResolvePlugin,
+        },
         transforms::{
             emotion::get_emotion_transform_plugin, get_relay_transform_plugin,
+            keep_component_names::get_keep_component_names_transform_plugin,
+            react_remove_properties::get_react_remove_properties_transform_plugin,
             styled_components::get_styled_components_transform_plugin,
             styled_jsx::get_styled_jsx_transform_plugin,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin,
+            use_cache::get_use_cache_transform_plugin,
         },
     },
     sass::maybe_add_sass_loader,
     transform_options::{
-        get_decorators_transform_options, get_jsx_transform_options,
+        add_package_tsconfig_rules, get_decorators_transform_options, get_jsx_transform_options,
         get_typescript_transform_options,
     },
     util::foreign_code_context_condition,
@@ -105,28 +117,44 @@ pub async fn get_server_resolve_options_context(
     );
     let ty = ty.into_value();
 
+    let export_conditions = next_config.export_conditions().await?;
     let mut custom_conditions = vec![mode.node_env().to_string(), "node".to_string()];
+    custom_conditions.extend(export_conditions.server.iter().cloned());
 
     match ty {
         ServerContextType::AppRSC { .. } | ServerContextType::AppRoute { .. } => {
-            custom_conditions.push("react-server".to_string())
+            custom_conditions.push("react-server".to_string());
+            custom_conditions.extend(export_conditions.react_server.iter().cloned());
         }
         ServerContextType::Pages { .. }
         | ServerContextType::PagesData { .. }
         | ServerContextType::AppSSR { .. }
         | ServerContextType::Middleware { .. } => {}
     };
+    let package_exports_replacer = PackageExportsReplacer::new(
+        "server".to_string(),
+        custom_conditions
+            .iter()
+            .cloned()
+            .chain(["module".to_string()])
+            .collect(),
+    );
     let external_cjs_modules_plugin = ExternalCjsModulesResolvePlugin::new(
         project_path,
         ExternalPredicate::AllExcept(next_config.transpile_packages()).cell(),
     );
 
+    let asset_query_resolve_plugin = AssetQueryResolvePlugin::new(project_path);
+    let import_casing_resolve_plugin = ImportCasingResolvePlugin::new(project_path);
+
     let plugins = match ty {
         ServerContextType::Pages { .. } | ServerContextType::PagesData { .. } => {
             vec![
+                Vc::upcast(asset_query_resolve_plugin),
                 Vc::upcast(module_feature_report_resolve_plugin),
                 Vc::upcast(external_cjs_modules_plugin),
                 Vc::upcast(unsupported_modules_resolve_plugin),
+                Vc::upcast(import_casing_resolve_plugin),
             ]
         }
         ServerContextType::AppSSR { .. }
@@ -134,9 +162,11 @@ pub async fn get_server_resolve_options_context(
         | ServerContextType::AppRoute { .. }
         | ServerContextType::Middleware { .. } => {
             vec![
+                Vc::upcast(asset_query_resolve_plugin),
                 Vc::upcast(module_feature_report_resolve_plugin),
                 Vc::upcast(server_component_externals_plugin),
                 Vc::upcast(unsupported_modules_resolve_plugin),
+                Vc::upcast(import_casing_resolve_plugin),
             ]
         }
     };
@@ -147,6 +177,9 @@ pub async fn get_server_resolve_options_context(
         module: true,
         custom_conditions,
         import_map: Some(next_server_import_map),
+        fallback_import_map: Some(get_next_server_fallback_import_map(
+            package_exports_replacer,
+        )),
         plugins,
         ..Default::default()
     };
@@ -206,7 +239,8 @@ pub async fn get_server_module_options_context(
     mode: NextMode,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<ModuleOptionsContext>> {
-    let custom_rules = get_next_server_transforms_rules(next_config, ty.into_value(), mode).await?;
+    let custom_rules =
+        get_next_server_transforms_rules(project_path, next_config, ty.into_value(), mode).await?;
     let internal_custom_rules = get_next_server_internal_transforms_rules(ty.into_value()).await?;
 
     let foreign_code_context_condition = foreign_code_context_condition(next_config).await?;
@@ -215,18 +249,44 @@ pub async fn get_server_module_options_context(
         ..Default::default()
     });
 
-    let webpack_rules =
-        *maybe_add_babel_loader(project_path, *next_config.webpack_rules().await?).await?;
-    let webpack_rules = maybe_add_sass_loader(next_config.sass_config(), webpack_rules).await?;
-    let enable_webpack_loaders = webpack_rules.map(|rules| {
-        WebpackLoadersOptions {
-            rules,
-            loader_runner_package: Some(get_external_next_compiled_package_mapping(Vc::cell(
-                "loader-runner".to_owned(),
-            ))),
+    let webpack_rules = *maybe_add_babel_loader(
+        project_path,
+        next_config.babel_rules(),
+        *next_config.webpack_rules().await?,
+    )
+    .await?;
+    let webpack_rules = if *next_config.use_grass().await? {
+        // Sass files are compiled by grass, see `get_grass_rules`.
+        webpack_rules
+    } else {
+        *maybe_add_sass_loader(next_config.sass_config(), webpack_rules).await?
+    };
+    let node_modules_webpack_rules = webpack_rules;
+    let webpack_rules = match ty.into_value() {
+        // The compiler only optimizes rendering, RSC and route handler code doesn't benefit
+        // from it.
+        ServerContextType::Pages { .. } | ServerContextType::AppSSR { .. } => {
+            *maybe_add_react_compiler_loader(
+                project_path,
+                next_config.react_compiler(),
+                webpack_rules,
+            )
+            .await?
         }
-        .cell()
-    });
+        _ => webpack_rules,
+    };
+    let webpack_loaders = |rules: Option<Vc<WebpackRules>>| {
+        rules.map(|rules| {
+            WebpackLoadersOptions {
+                rules,
+                loader_runner_package: Some(get_external_next_compiled_package_mapping(Vc::cell(
+                    "loader-runner".to_owned(),
+                ))),
+            }
+            .cell()
+        })
+    };
+    let enable_webpack_loaders = webpack_loaders(webpack_rules);
 
     // EcmascriptTransformPlugins for custom transforms
     let styled_components_transform_plugin =
@@ -238,6 +298,8 @@ pub async fn get_server_module_options_context(
             // We don't have to pass a valid transition name yet, but the API is prepared.
             &Vc::cell("TODO".to_string()),
         )) as _));
+    let use_cache_transform_plugin =
+        *get_use_cache_transform_plugin(project_path, next_config).await?;
 
     // ModuleOptionsContext related options
     let tsconfig = get_typescript_transform_options(project_path);
@@ -258,6 +320,8 @@ pub async fn get_server_module_options_context(
         *get_swc_ecma_transform_plugin(project_path, next_config).await?,
         *get_relay_transform_plugin(next_config).await?,
         *get_emotion_transform_plugin(next_config).await?,
+        *get_react_remove_properties_transform_plugin(next_config).await?,
+        *get_keep_component_names_transform_plugin(next_config, mode).await?,
     ]
     .into_iter()
     .flatten()
@@ -391,6 +455,7 @@ pub async fn get_server_module_options_context(
             let mut base_source_transforms: Vec<Vc<TransformPlugin>> = vec![
                 styled_components_transform_plugin,
                 server_directive_transform_plugin,
+                use_cache_transform_plugin,
             ]
             .into_iter()
             .flatten()
@@ -453,6 +518,16 @@ pub async fn get_server_module_options_context(
             }
         }
         ServerContextType::AppRoute { .. } => {
+            let custom_ecma_transform_plugins = Some(CustomEcmascriptTransformPlugins::cell(
+                CustomEcmascriptTransformPlugins {
+                    source_transforms: use_cache_transform_plugin
+                        .into_iter()
+                        .chain(source_transforms)
+                        .collect(),
+                    output_transforms,
+                },
+            ));
+
             let module_options_context = ModuleOptionsContext {
                 execution_context: Some(execution_context),
                 ..Default::default()
@@ -532,7 +607,23 @@ pub async fn get_server_module_options_context(
                 ..module_options_context
             }
         }
-    }
+    };
+    let module_options_context = if webpack_rules != node_modules_webpack_rules {
+        exclude_react_compiler_from_node_modules(
+            module_options_context,
+            webpack_loaders(node_modules_webpack_rules),
+        )
+    } else {
+        module_options_context
+    };
+    let module_options_context = add_package_tsconfig_rules(
+        module_options_context,
+        project_path,
+        next_config,
+        mode,
+        None,
+    )
+    .await?
     .cell();
 
     Ok(module_options_context)
@@ -0,0 +1,158 @@
+use anyhow::{bail, Result};
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::glob::Glob;
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            resolve::{
+                parse::Request,
+                plugin::{ResolvePlugin, ResolvePluginCondition},
+                ResolveResult, ResolveResultOption,
+            },
+            virtual_source::VirtualSource,
+        },
+        ecmascript::utils::StringifyJs,
+    },
+};
+
+/// How a request with an asset query imports the file, bypassing the module
+/// type it would get from the configured rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AssetQuery {
+    /// `?raw`, the contents of the file as a string.
+    Raw,
+    /// `?url`, the URL of the file emitted as a static asset.
+    Url,
+    /// `?inline`, the file as a data URL.
+    Inline,
+}
+
+impl AssetQuery {
+    fn parse(query: &str) -> Option<Self> {
+        match query {
+            "?raw" => Some(AssetQuery::Raw),
+            "?url" => Some(AssetQuery::Url),
+            "?inline" => Some(AssetQuery::Inline),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AssetQuery::Raw => "raw",
+            AssetQuery::Url => "url",
+            AssetQuery::Inline => "inline",
+        }
+    }
+}
+
+/// Replaces requests ending in `?raw`, `?url` or `?inline` with a JavaScript
+/// module exporting the file in the requested form.
+///
+/// This happens while resolving, so it takes precedence over the
+/// `experimental.turbo.rules` matching the file.
+#[turbo_tasks::value]
+pub(crate) struct AssetQueryResolvePlugin {
+    root: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl AssetQueryResolvePlugin {
+    #[turbo_tasks::function]
+    pub fn new(root: Vc<FileSystemPath>) -> Vc<Self> {
+        AssetQueryResolvePlugin { root }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ResolvePlugin for AssetQueryResolvePlugin {
+    #[turbo_tasks::function]
+    fn after_resolve_condition(&self) -> Vc<ResolvePluginCondition> {
+        ResolvePluginCondition::new(self.root.root(), Glob::new("**".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn after_resolve(
+        &self,
+        fs_path: Vc<FileSystemPath>,
+        _context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ResolveResultOption>> {
+        let query = match &*request.await? {
+            Request::Raw { query, .. }
+            | Request::Relative { query, .. }
+            | Request::Module { query, .. }
+            | Request::ServerRelative { query, .. } => *query,
+            _ => return Ok(ResolveResultOption::none()),
+        };
+        let Some(asset_query) = AssetQuery::parse(&query.await?) else {
+            return Ok(ResolveResultOption::none());
+        };
+
+        let path = fs_path.await?;
+        let file_name = path.file_name();
+        let FileContent::Content(file) = &*fs_path.read().await? else {
+            bail!("{} not found", path.path);
+        };
+
+        let code = match asset_query {
+            AssetQuery::Raw => raw_module_code(&file.content().to_bytes()?),
+            AssetQuery::Url => format!(
+                "export default new URL({}, import.meta.url).pathname;\n",
+                StringifyJs(&format!("./{file_name}"))
+            ),
+            AssetQuery::Inline => {
+                let content_type = mime_guess::from_ext(path.extension_ref().unwrap_or_default())
+                    .first_or_octet_stream();
+                let content = file.content().to_bytes()?;
+                format!(
+                    "export default {};\n",
+                    StringifyJs(&format!(
+                        "data:{content_type};base64,{}",
+                        Base64Display::new(&content, &STANDARD)
+                    ))
+                )
+            }
+        };
+
+        let source = VirtualSource::new(
+            fs_path
+                .parent()
+                .join(format!("{file_name}.{}.js", asset_query.name())),
+            AssetContent::file(FileContent::Content(code.into()).cell()),
+        );
+        Ok(ResolveResultOption::some(
+            ResolveResult::source(Vc::upcast(source)).cell(),
+        ))
+    }
+}
+
+/// The module of a `?raw` import. Like the `raw-loader` of webpack, the file is
+/// decoded as UTF-8, invalid sequences are replaced rather than failing the
+/// import.
+fn raw_module_code(content: &[u8]) -> String {
+    format!(
+        "export default {};\n",
+        StringifyJs(&String::from_utf8_lossy(content))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::raw_module_code;
+
+    #[test]
+    fn test_raw_module_code() {
+        assert_eq!(
+            raw_module_code(b"line \"one\"\nline two"),
+            "export default \"line \\\"one\\\"\\nline two\";\n"
+        );
+        assert_eq!(
+            raw_module_code(b"caf\xe9"),
+            "export default \"caf\u{fffd}\";\n"
+        );
+    }
+}
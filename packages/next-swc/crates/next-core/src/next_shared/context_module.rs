@@ -0,0 +1,322 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::glob::Glob;
+use turbopack_binding::{
+    turbo::{
+        tasks_fs::{DirectoryContent, DirectoryEntry, FileContent, FileSystemPath},
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            resolve::{
+                options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+                parse::Request,
+                ResolveResult,
+            },
+            virtual_source::VirtualSource,
+        },
+        ecmascript::utils::StringifyJs,
+    },
+};
+
+/// The request the `require.context` transform replaces calls with. The
+/// options are passed in the query.
+pub(crate) const CONTEXT_MODULE_REQUEST: &str = "@vercel/turbopack-next/internal/context-module";
+
+/// How the modules of a `require.context` are loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ContextModuleMode {
+    /// The modules are bundled with the importer and returned directly.
+    Sync,
+    /// The modules are bundled with the importer and returned as promises.
+    Eager,
+    /// Each module is imported with `import()` when it's requested.
+    Lazy,
+}
+
+impl ContextModuleMode {
+    /// Parses the `mode` argument of `require.context`. `weak` and
+    /// `lazy-once` are treated like `sync` and `lazy`.
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "sync" | "weak" => Some(ContextModuleMode::Sync),
+            "eager" => Some(ContextModuleMode::Eager),
+            "lazy" | "lazy-once" => Some(ContextModuleMode::Lazy),
+            _ => None,
+        }
+    }
+}
+
+/// A context module, created from a `require.context` or `import.meta.glob`
+/// call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum ContextModuleRequest {
+    #[serde(rename_all = "camelCase")]
+    RequireContext {
+        /// The directory, relative to the importer.
+        dir: String,
+        recursive: bool,
+        /// The source of the regular expression the keys are matched against.
+        regexp: String,
+        flags: String,
+        mode: ContextModuleMode,
+    },
+    #[serde(rename_all = "camelCase")]
+    ImportMetaGlob {
+        /// The globs, relative to the importer. Globs starting with `!`
+        /// exclude files.
+        patterns: Vec<String>,
+        eager: bool,
+        /// The export to import instead of the module namespace.
+        import: Option<String>,
+    },
+}
+
+impl ContextModuleRequest {
+    /// Returns the request for the context module.
+    pub fn to_request(&self) -> Result<String> {
+        Ok(format!(
+            "{CONTEXT_MODULE_REQUEST}?{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(self)?)
+        ))
+    }
+
+    fn from_query(query: &str) -> Result<Self> {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let json = URL_SAFE_NO_PAD
+            .decode(query)
+            .context("invalid context module request")?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+/// Generates the context modules requested by the `require.context`
+/// transform. Listing the directories makes the module depend on them, so it's
+/// updated when a matching file is added or removed.
+#[turbo_tasks::value(shared)]
+pub(crate) struct ContextModuleReplacer {}
+
+#[turbo_tasks::value_impl]
+impl ContextModuleReplacer {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        Self::cell(ContextModuleReplacer {})
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for ContextModuleReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module { query, .. } = &*request.await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let query = query.await?;
+        let context_request = ContextModuleRequest::from_query(&query)?;
+
+        let code = match &context_request {
+            ContextModuleRequest::RequireContext {
+                dir,
+                recursive,
+                regexp,
+                flags,
+                mode,
+            } => {
+                // JavaScript allows escaping `/` in regular expression literals.
+                let regex = RegexBuilder::new(&regexp.replace(r"\/", "/"))
+                    .case_insensitive(flags.contains('i'))
+                    .multi_line(flags.contains('m'))
+                    .dot_matches_new_line(flags.contains('s'))
+                    .build()
+                    .with_context(|| {
+                        format!("require.context regular expression /{regexp}/ is not supported")
+                    })?;
+                let files = list_context_files(context.join(dir.clone()), *recursive).await?;
+                let keys = files
+                    .iter()
+                    .map(|file| format!("./{file}"))
+                    .filter(|key| regex.is_match(key));
+                let dir = dir.trim_end_matches('/');
+                let modules = keys
+                    .map(|key| {
+                        let request = format!("{dir}/{}", &key[2..]);
+                        (key, request)
+                    })
+                    .collect::<Vec<_>>();
+                require_context_code(&modules, *mode)?
+            }
+            ContextModuleRequest::ImportMetaGlob {
+                patterns,
+                eager,
+                import,
+            } => {
+                let mut modules = vec![];
+                for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+                    let (base, glob) = split_glob_base(pattern);
+                    let glob = Glob::new(glob.to_string()).await?;
+                    let files = list_context_files(context.join(base.to_string()), true).await?;
+                    for file in files.iter().filter(|file| glob.execute(file)) {
+                        modules.push(format!("{base}/{file}"));
+                    }
+                }
+                for pattern in patterns.iter().filter_map(|p| p.strip_prefix('!')) {
+                    let glob = Glob::new(normalize_relative(pattern).to_string()).await?;
+                    modules.retain(|module| !glob.execute(normalize_relative(module)));
+                }
+                modules.sort();
+                modules.dedup();
+                import_meta_glob_code(&modules, *eager, import.as_deref())?
+            }
+        };
+
+        let hash = hash_xxh3_hash64(&*query) as u32;
+        let source = VirtualSource::new(
+            context.join(format!("__next_context_module_{hash:08x}.js")),
+            AssetContent::file(FileContent::Content(code.into()).cell()),
+        );
+        Ok(ImportMapResult::Result(ResolveResult::source(Vc::upcast(source)).into()).into())
+    }
+}
+
+/// Lists the files in a directory, relative to it and sorted.
+#[turbo_tasks::function]
+async fn list_context_files(dir: Vc<FileSystemPath>, recursive: bool) -> Result<Vc<Vec<String>>> {
+    let mut files = vec![];
+    if let DirectoryContent::Entries(entries) = &*dir.read_dir().await? {
+        for (name, entry) in entries.iter() {
+            match entry {
+                DirectoryEntry::File(_) => files.push(name.clone()),
+                DirectoryEntry::Directory(dir) if recursive => files.extend(
+                    list_context_files(*dir, true)
+                        .await?
+                        .iter()
+                        .map(|file| format!("{name}/{file}")),
+                ),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    Ok(Vc::cell(files))
+}
+
+/// Splits a glob like `./posts/**/*.md` into the directory without glob
+/// characters (`./posts`) and the rest (`**/*.md`).
+fn split_glob_base(pattern: &str) -> (&str, &str) {
+    let glob_start = pattern.find(['*', '?', '[', '{']).unwrap_or(pattern.len());
+    match pattern[..glob_start].rfind('/') {
+        Some(index) => (&pattern[..index], &pattern[index + 1..]),
+        None => (".", pattern),
+    }
+}
+
+fn normalize_relative(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Generates a module exporting a webpack-compatible context function.
+fn require_context_code(modules: &[(String, String)], mode: ContextModuleMode) -> Result<String> {
+    let mut code = "const map = {\n".to_string();
+    for (key, request) in modules {
+        let load = match mode {
+            ContextModuleMode::Sync => format!("require({})", StringifyJs(request)),
+            ContextModuleMode::Eager => {
+                format!("Promise.resolve(require({}))", StringifyJs(request))
+            }
+            ContextModuleMode::Lazy => format!("import({})", StringifyJs(request)),
+        };
+        writeln!(code, "  {}: () => {load},", StringifyJs(key))?;
+    }
+    let missing = match mode {
+        ContextModuleMode::Sync => "throw error",
+        ContextModuleMode::Eager | ContextModuleMode::Lazy => "return Promise.reject(error)",
+    };
+    write!(
+        code,
+        r#"}};
+
+function context(key) {{
+  if (!Object.prototype.hasOwnProperty.call(map, key)) {{
+    const error = new Error("Cannot find module '" + key + "'");
+    error.code = "MODULE_NOT_FOUND";
+    {missing};
+  }}
+  return map[key]();
+}}
+
+context.keys = () => Object.keys(map);
+context.resolve = (key) => key;
+
+module.exports = context;
+"#
+    )?;
+    Ok(code)
+}
+
+/// Generates a module exporting the object `import.meta.glob` returns, keyed by
+/// the path of each module relative to the importer.
+fn import_meta_glob_code(modules: &[String], eager: bool, import: Option<&str>) -> Result<String> {
+    if modules.iter().any(|module| module.starts_with('/')) {
+        bail!("import.meta.glob patterns must be relative to the importing file");
+    }
+    let mut code = "module.exports = {\n".to_string();
+    for module in modules {
+        let request = StringifyJs(module);
+        let value = match (eager, import) {
+            (true, None) => format!("require({request})"),
+            (true, Some(name)) => format!("require({request})[{}]", StringifyJs(name)),
+            (false, None) => format!("() => import({request})"),
+            (false, Some(name)) => format!(
+                "() => import({request}).then((m) => m[{}])",
+                StringifyJs(name)
+            ),
+        };
+        writeln!(code, "  {request}: {value},")?;
+    }
+    code.push_str("};\n");
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_glob_base() {
+        assert_eq!(split_glob_base("./posts/**/*.md"), ("./posts", "**/*.md"));
+        assert_eq!(split_glob_base("../icons/*.svg"), ("../icons", "*.svg"));
+        assert_eq!(split_glob_base("./a/b.js"), ("./a", "b.js"));
+        assert_eq!(split_glob_base("*.js"), (".", "*.js"));
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = ContextModuleRequest::RequireContext {
+            dir: "./locales".to_string(),
+            recursive: false,
+            regexp: r"\.json$".to_string(),
+            flags: String::new(),
+            mode: ContextModuleMode::Lazy,
+        };
+        let encoded = request.to_request().unwrap();
+        let query = encoded.strip_prefix(CONTEXT_MODULE_REQUEST).unwrap();
+        assert_eq!(ContextModuleRequest::from_query(query).unwrap(), request);
+    }
+}
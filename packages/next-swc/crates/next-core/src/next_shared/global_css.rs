@@ -0,0 +1,192 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileSystemPath, FileSystemPathOption},
+    turbopack::{
+        core::{
+            issue::{Issue, IssueExt, IssueSeverity},
+            module::{Module, Modules},
+        },
+        ecmascript::utils::FormatIter,
+    },
+};
+
+use crate::util::{is_in_node_modules, primary_referenced_modules, ModuleWalk};
+
+/// Walks the module graph of a page of the pages router and reports every
+/// global CSS import whose importer isn't `pages/_app`.
+///
+/// Global stylesheets apply to the whole document, their order only stays
+/// stable when they are all imported from the one module that is always
+/// rendered. Stylesheets from node_modules can be imported from anywhere, and
+/// so can any stylesheet in the app router.
+///
+/// `route` is used to refer to the offending page in the reported issue.
+#[turbo_tasks::function]
+pub async fn validate_global_css_imports(
+    entry: Vc<Box<dyn Module>>,
+    app: Vc<FileSystemPathOption>,
+    route: String,
+) -> Result<Vc<()>> {
+    let app = match *app.await? {
+        Some(path) => Some(path.resolve().await?),
+        None => None,
+    };
+
+    // Maps every visited module to the module it was first reached from, so the
+    // import trace can be reconstructed.
+    let mut parents: IndexMap<Vc<Box<dyn Module>>, Option<Vc<Box<dyn Module>>>> = IndexMap::new();
+    parents.insert(entry, None);
+    let mut walk = ModuleWalk::new([entry]);
+
+    while let Some(module) = walk.next().await? {
+        let module_path = module.ident().path().resolve().await?;
+
+        // The global stylesheets themselves aren't walked, their imports are
+        // part of them.
+        let global_css_imports = global_css_imports(module).await?;
+        let referenced_modules = primary_referenced_modules(module).await?;
+        for &referenced in referenced_modules.iter() {
+            parents.entry(referenced).or_insert(Some(module));
+        }
+        walk.extend(
+            referenced_modules
+                .iter()
+                .copied()
+                .filter(|referenced| !global_css_imports.contains(referenced)),
+        );
+        if global_css_imports.is_empty() || Some(module_path) == app {
+            continue;
+        }
+
+        let mut import_trace = Vec::new();
+        let mut current = Some(module);
+        while let Some(importer) = current {
+            import_trace.push(importer.ident().to_string().await?.clone_value());
+            current = parents.get(&importer).copied().flatten();
+        }
+
+        for &css in global_css_imports.iter() {
+            GlobalCssImportIssue {
+                file_path: module_path,
+                css_path: css.ident().path(),
+                route: route.clone(),
+                import_trace: import_trace.clone(),
+            }
+            .cell()
+            .emit();
+        }
+    }
+
+    Ok(Default::default())
+}
+
+/// The global stylesheets a module imports, other than the ones from
+/// node_modules.
+#[turbo_tasks::function]
+async fn global_css_imports(module: Vc<Box<dyn Module>>) -> Result<Vc<Modules>> {
+    let imports = primary_referenced_modules(module)
+        .await?
+        .iter()
+        .map(|&referenced| async move {
+            let path = referenced.ident().path().await?;
+            Ok(
+                (is_global_css(&path.path) && !is_in_node_modules(&path.path))
+                    .then_some(referenced),
+            )
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(imports.into_iter().flatten().collect()))
+}
+
+/// Whether the path refers to a global stylesheet, i.e. a stylesheet which
+/// isn't a CSS module.
+fn is_global_css(path: &str) -> bool {
+    let Some((stem, extension)) = path.rsplit_once('.') else {
+        return false;
+    };
+    matches!(extension, "css" | "scss" | "sass") && !stem.ends_with(".module")
+}
+
+#[turbo_tasks::value(shared)]
+struct GlobalCssImportIssue {
+    file_path: Vc<FileSystemPath>,
+    css_path: Vc<FileSystemPath>,
+    route: String,
+    /// Idents of the importing modules, starting from the direct importer.
+    import_trace: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for GlobalCssImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("css".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(
+            "Global CSS cannot be imported from files other than your Custom <App>".to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "{} is imported by route {}. Global stylesheets apply to every page, so they must be \
+             imported from pages/_app to keep their order stable. Move the import to pages/_app, \
+             or rename the file to use CSS Modules (e.g. `*.module.css`).\n\nImport trace:{}",
+            self.css_path.await?.path,
+            self.route,
+            FormatIter(|| self
+                .import_trace
+                .iter()
+                .flat_map(|importer| vec!["\n  ", importer.as_str()])),
+        )))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell("https://nextjs.org/docs/messages/css-global".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_global_css;
+    use crate::util::is_in_node_modules;
+
+    #[test]
+    fn test_is_global_css() {
+        assert!(is_global_css("styles/globals.css"));
+        assert!(is_global_css("styles/theme.scss"));
+        assert!(!is_global_css("components/button.module.css"));
+        assert!(!is_global_css("components/button.module.sass"));
+        assert!(!is_global_css("components/button.tsx"));
+    }
+
+    #[test]
+    fn test_is_in_node_modules() {
+        assert!(is_in_node_modules(
+            "node_modules/bootstrap/dist/css/bootstrap.css"
+        ));
+        assert!(is_in_node_modules(
+            "packages/app/node_modules/nprogress/nprogress.css"
+        ));
+        assert!(!is_in_node_modules("styles/my_node_modules/globals.css"));
+        assert!(!is_in_node_modules("styles/node_modules.css"));
+    }
+}
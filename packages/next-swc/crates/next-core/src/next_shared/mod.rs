@@ -1,2 +1,6 @@
+pub(crate) mod asset_query;
+pub(crate) mod context_module;
+pub mod global_css;
+pub mod next_script;
 pub(crate) mod resolve;
 pub(crate) mod transforms;
@@ -0,0 +1,581 @@
+use anyhow::Result;
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{source_map::Pos, Span},
+    ecma::{
+        ast::{
+            CallExpr, Callee, Expr, ImportDecl, ImportSpecifier, JSXAttrName, JSXAttrOrSpread,
+            JSXAttrValue, JSXElement, JSXElementChild, JSXElementName, JSXExpr, Lit,
+            ModuleExportName, ObjectLit, Prop, PropName, PropOrSpread,
+        },
+        atoms::JsWord,
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
+};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileSystemPath, FileSystemPathOption},
+    turbopack::{
+        core::{
+            file_source::FileSource,
+            issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+            module::Module,
+        },
+        ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+    },
+};
+
+use crate::{next_client_reference::EcmascriptClientReferenceModule, util::ModuleWalk};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TaskInput, TraceRawVcs, Serialize, Deserialize)]
+pub enum NextScriptRouter {
+    App,
+    Pages,
+}
+
+/// A `<Script strategy="worker">` which is offloaded to a web worker.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub struct NextWorkerScript {
+    pub src: Option<String>,
+    pub id: Option<String>,
+}
+
+/// The `next/script` scripts of a route which run in a web worker, for
+/// `experimental.nextScriptWorkers`.
+#[turbo_tasks::value(shared)]
+#[serde(rename_all = "camelCase")]
+pub struct NextScriptManifest {
+    pub worker_scripts: Vec<NextWorkerScript>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct NextWorkerScripts(Vec<NextWorkerScript>);
+
+/// Walks the module graph of an entry and reports invalid uses of
+/// `next/script`:
+///
+/// * `strategy="beforeInteractive"` in any module other than
+///   `allowed_before_interactive` (the root layout for the app router,
+///   `pages/_document` for the pages router) and the modules which are only
+///   reachable through it, like a component rendered by the root layout. These
+///   scripts are injected into the initial HTML, which only the document can
+///   do.
+/// * Inline scripts without an `id`, which Next.js needs to deduplicate them.
+/// * `strategy="worker"` in the app router, which doesn't support it.
+///
+/// Returns the worker scripts found along the way. The client components of
+/// the app router are followed into their SSR modules.
+#[turbo_tasks::function]
+pub async fn validate_next_script_usage(
+    entry: Vc<Box<dyn Module>>,
+    allowed_before_interactive: Vc<FileSystemPathOption>,
+    router: NextScriptRouter,
+) -> Result<Vc<NextScriptManifest>> {
+    let allowed_before_interactive = match *allowed_before_interactive.await? {
+        Some(path) => Some(path.await?),
+        None => None,
+    };
+
+    // The modules which are reachable without going through the document.
+    let (outside_document, documents) = script_modules(
+        vec![entry],
+        allowed_before_interactive.as_deref(),
+        &IndexSet::new(),
+    )
+    .await?;
+    // The document and the modules which are only reachable through it.
+    let (inside_document, _) = script_modules(documents, None, &outside_document).await?;
+
+    let mut worker_scripts = IndexSet::new();
+    for (modules, before_interactive_allowed) in
+        [(outside_document, false), (inside_document, true)]
+    {
+        for module in modules {
+            worker_scripts.extend(
+                check_module_next_scripts(module, before_interactive_allowed, router)
+                    .await?
+                    .iter()
+                    .cloned(),
+            );
+        }
+    }
+
+    Ok(NextScriptManifest {
+        worker_scripts: worker_scripts.into_iter().collect(),
+    }
+    .cell())
+}
+
+/// Walks the module graph from `entries` and returns the modules whose
+/// `<Script>` elements are checked. Client references are followed into their
+/// SSR modules, node_modules and the modules in `skip` are left out.
+///
+/// The modules at the path `barrier` aren't walked into, they are returned
+/// separately.
+async fn script_modules(
+    entries: Vec<Vc<Box<dyn Module>>>,
+    barrier: Option<&FileSystemPath>,
+    skip: &IndexSet<Vc<Box<dyn Module>>>,
+) -> Result<(IndexSet<Vc<Box<dyn Module>>>, Vec<Vc<Box<dyn Module>>>)> {
+    let mut walk = ModuleWalk::new(entries);
+    walk.skip(skip.iter().copied());
+    let mut modules = IndexSet::new();
+    let mut barrier_modules = vec![];
+
+    while let Some(module) = walk.next().await? {
+        if let Some(client_reference) =
+            Vc::try_resolve_downcast_type::<EcmascriptClientReferenceModule>(module).await?
+        {
+            walk.extend([Vc::upcast(client_reference.await?.ssr_module)]);
+        } else if barrier == Some(&*module.ident().path().await?) {
+            barrier_modules.push(module);
+        } else {
+            modules.insert(module);
+            walk.visit_references(module).await?;
+        }
+    }
+
+    Ok((modules, barrier_modules))
+}
+
+#[turbo_tasks::function]
+async fn check_module_next_scripts(
+    module: Vc<Box<dyn Module>>,
+    before_interactive_allowed: bool,
+    router: NextScriptRouter,
+) -> Result<Vc<NextWorkerScripts>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(vec![]));
+    };
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(Vc::cell(vec![]));
+    };
+
+    let mut visitor = NextScriptVisitor::default();
+    program.visit_with(&mut visitor);
+    if visitor.scripts.is_empty() {
+        return Ok(Vc::cell(vec![]));
+    }
+
+    let path = module.ident().path();
+    let source = Vc::upcast(FileSource::new(path));
+    let mut worker_scripts = vec![];
+    for script in visitor.scripts {
+        if script.strategy.as_deref() == Some("worker") && router == NextScriptRouter::Pages {
+            worker_scripts.push(NextWorkerScript {
+                src: script.src.clone(),
+                id: script.id.clone(),
+            });
+        }
+
+        for violation in script.violations(before_interactive_allowed, router) {
+            NextScriptIssue {
+                file_path: path,
+                violation,
+                router,
+                source: IssueSource::from_byte_offset(
+                    source,
+                    script.span.lo.to_usize(),
+                    script.span.hi.to_usize(),
+                ),
+            }
+            .cell()
+            .emit();
+        }
+    }
+
+    Ok(Vc::cell(worker_scripts))
+}
+
+/// A `<Script>` element, with the props which could be read statically.
+struct NextScriptUsage {
+    strategy: Option<String>,
+    src: Option<String>,
+    id: Option<String>,
+    has_children: bool,
+    has_inner_html: bool,
+    /// Whether props are spread into the element, any of the props above might
+    /// be set that way.
+    has_spread: bool,
+    span: Span,
+}
+
+impl NextScriptUsage {
+    fn new(span: Span) -> Self {
+        NextScriptUsage {
+            strategy: None,
+            src: None,
+            id: None,
+            has_children: false,
+            has_inner_html: false,
+            has_spread: false,
+            span,
+        }
+    }
+
+    fn is_inline(&self) -> bool {
+        self.src.is_none() && (self.has_children || self.has_inner_html)
+    }
+
+    fn violations(
+        &self,
+        before_interactive_allowed: bool,
+        router: NextScriptRouter,
+    ) -> Vec<NextScriptViolation> {
+        let mut violations = vec![];
+        match self.strategy.as_deref() {
+            Some("beforeInteractive") if !before_interactive_allowed => {
+                violations.push(NextScriptViolation::BeforeInteractiveOutsideDocument)
+            }
+            Some("worker") if router == NextScriptRouter::App => {
+                violations.push(NextScriptViolation::WorkerInAppRouter)
+            }
+            _ => {}
+        }
+        if self.is_inline() && self.id.is_none() && !self.has_spread {
+            violations.push(NextScriptViolation::InlineScriptWithoutId);
+        }
+        violations
+    }
+
+    fn set_prop(&mut self, name: &str, value: Option<&Expr>) {
+        let value = match value {
+            Some(Expr::Lit(Lit::Str(str))) => Some(str.value.to_string()),
+            Some(Expr::Tpl(tpl)) if tpl.exprs.is_empty() => {
+                tpl.quasis.first().map(|quasi| quasi.raw.to_string())
+            }
+            _ => None,
+        };
+        match name {
+            "strategy" => self.strategy = value,
+            // Dynamic values still count as set.
+            "src" => self.src = Some(value.unwrap_or_default()),
+            "id" => self.id = Some(value.unwrap_or_default()),
+            "children" => self.has_children = true,
+            "dangerouslySetInnerHTML" => self.has_inner_html = true,
+            _ => {}
+        }
+    }
+}
+
+/// Collects the `<Script>` elements of a module, written as JSX or as calls
+/// of the compiled `jsx(Script, props)` form.
+#[derive(Default)]
+struct NextScriptVisitor {
+    /// The local names of the default import of `next/script`.
+    script_idents: Vec<JsWord>,
+    scripts: Vec<NextScriptUsage>,
+}
+
+impl NextScriptVisitor {
+    fn is_script(&self, sym: &JsWord) -> bool {
+        self.script_idents.contains(sym)
+    }
+
+    fn object_props(&self, props: &ObjectLit, span: Span) -> NextScriptUsage {
+        let mut usage = NextScriptUsage::new(span);
+        for prop in &props.props {
+            match prop {
+                PropOrSpread::Spread(_) => usage.has_spread = true,
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(key_value) => {
+                        let name = match &key_value.key {
+                            PropName::Ident(ident) => ident.sym.to_string(),
+                            PropName::Str(str) => str.value.to_string(),
+                            _ => continue,
+                        };
+                        usage.set_prop(&name, Some(&key_value.value));
+                    }
+                    Prop::Shorthand(ident) => usage.set_prop(&ident.sym, None),
+                    _ => {}
+                },
+            }
+        }
+        usage
+    }
+}
+
+impl Visit for NextScriptVisitor {
+    noop_visit_type!();
+
+    fn visit_import_decl(&mut self, import: &ImportDecl) {
+        if import.type_only || &*import.src.value != "next/script" {
+            return;
+        }
+        for specifier in &import.specifiers {
+            match specifier {
+                ImportSpecifier::Default(default) => {
+                    self.script_idents.push(default.local.sym.clone())
+                }
+                ImportSpecifier::Named(named)
+                    if matches!(
+                        &named.imported,
+                        Some(ModuleExportName::Ident(imported)) if &*imported.sym == "default"
+                    ) =>
+                {
+                    self.script_idents.push(named.local.sym.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_jsx_element(&mut self, element: &JSXElement) {
+        if let JSXElementName::Ident(name) = &element.opening.name {
+            if self.is_script(&name.sym) {
+                let mut usage = NextScriptUsage::new(element.span);
+                for attr in &element.opening.attrs {
+                    let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+                        usage.has_spread = true;
+                        continue;
+                    };
+                    let JSXAttrName::Ident(name) = &attr.name else {
+                        continue;
+                    };
+                    let value = match &attr.value {
+                        Some(JSXAttrValue::Lit(lit)) => Some(Expr::Lit(lit.clone())),
+                        Some(JSXAttrValue::JSXExprContainer(container)) => match &container.expr {
+                            JSXExpr::Expr(expr) => Some((**expr).clone()),
+                            JSXExpr::JSXEmptyExpr(_) => None,
+                        },
+                        _ => None,
+                    };
+                    usage.set_prop(&name.sym, value.as_ref());
+                }
+                usage.has_children |= element.children.iter().any(|child| match child {
+                    JSXElementChild::JSXText(text) => !text.value.trim().is_empty(),
+                    _ => true,
+                });
+                self.scripts.push(usage);
+            }
+        }
+        element.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        // `jsx(Script, { ... })`, `createElement(Script, { ... })` and friends.
+        if let (Callee::Expr(_), [component, props, ..]) = (&call.callee, &*call.args) {
+            if let (Expr::Ident(component), Expr::Object(props)) = (&*component.expr, &*props.expr)
+            {
+                if self.is_script(&component.sym) {
+                    let usage = self.object_props(props, call.span);
+                    self.scripts.push(usage);
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
+enum NextScriptViolation {
+    BeforeInteractiveOutsideDocument,
+    InlineScriptWithoutId,
+    WorkerInAppRouter,
+}
+
+#[turbo_tasks::value(shared)]
+struct NextScriptIssue {
+    file_path: Vc<FileSystemPath>,
+    violation: NextScriptViolation,
+    router: NextScriptRouter,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for NextScriptIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        match self.violation {
+            // Next.js can't inject the script into the initial HTML and silently
+            // drops it.
+            NextScriptViolation::BeforeInteractiveOutsideDocument
+            | NextScriptViolation::WorkerInAppRouter => IssueSeverity::Error.into(),
+            NextScriptViolation::InlineScriptWithoutId => IssueSeverity::Warning.into(),
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("next/script".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(match self.violation {
+            NextScriptViolation::BeforeInteractiveOutsideDocument => {
+                if self.router == NextScriptRouter::Pages {
+                    "`beforeInteractive` scripts can only be used in pages/_document".to_string()
+                } else {
+                    "`beforeInteractive` scripts can only be used in the root layout".to_string()
+                }
+            }
+            NextScriptViolation::InlineScriptWithoutId => {
+                "Inline scripts require an `id` attribute".to_string()
+            }
+            NextScriptViolation::WorkerInAppRouter => {
+                "The `worker` strategy is not supported in the app router".to_string()
+            }
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(match self.violation {
+            NextScriptViolation::BeforeInteractiveOutsideDocument => {
+                "Scripts with `strategy=\"beforeInteractive\"` are injected into the initial HTML \
+                 of every page, which is rendered by the document. Move the script there, or use a \
+                 different strategy."
+                    .to_string()
+            }
+            NextScriptViolation::InlineScriptWithoutId => "Next.js uses the `id` to track and \
+                                                           deduplicate inline scripts. Add a \
+                                                           unique `id` to the <Script> element."
+                .to_string(),
+            NextScriptViolation::WorkerInAppRouter => "Offloading scripts to a web worker with \
+                                                       `strategy=\"worker\"` is only supported by \
+                                                       the pages router."
+                .to_string(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell(match self.violation {
+            NextScriptViolation::BeforeInteractiveOutsideDocument => {
+                "https://nextjs.org/docs/messages/no-before-interactive-script-outside-document"
+                    .to_string()
+            }
+            NextScriptViolation::InlineScriptWithoutId => {
+                "https://nextjs.org/docs/messages/inline-script-id".to_string()
+            }
+            NextScriptViolation::WorkerInAppRouter => {
+                "https://nextjs.org/docs/app/api-reference/components/script#strategy".to_string()
+            }
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn scripts(code: &str) -> Vec<NextScriptUsage> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let mut visitor = NextScriptVisitor::default();
+        program.visit_with(&mut visitor);
+        visitor.scripts
+    }
+
+    #[test]
+    fn test_next_script_visitor() {
+        let scripts = scripts(
+            r#"
+            import Script from "next/script";
+            import { default as NextScript } from "next/script";
+            import Other from "other";
+            export default function Layout() {
+                return <>
+                    <Script src="/a.js" strategy="beforeInteractive" />
+                    <NextScript id="inline">{`console.log(1)`}</NextScript>
+                    <Script dangerouslySetInnerHTML={{ __html: "" }} />
+                    <Script {...props}>{code}</Script>
+                    <Other strategy="beforeInteractive" />
+                    {jsx(Script, { src: `/b.js`, strategy: "worker" })}
+                </>;
+            }
+            "#,
+        );
+        let summary: Vec<_> = scripts
+            .iter()
+            .map(|script| {
+                (
+                    script.strategy.as_deref(),
+                    script.src.as_deref(),
+                    script.id.as_deref(),
+                    script.is_inline(),
+                    script.has_spread,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("beforeInteractive"), Some("/a.js"), None, false, false),
+                (None, None, Some("inline"), true, false),
+                (None, None, None, true, false),
+                (None, None, None, true, true),
+                (Some("worker"), Some("/b.js"), None, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_violations() {
+        let [before_interactive, inline, inline_with_spread, worker] = &scripts(
+            r#"
+            import Script from "next/script";
+            <Script src="/a.js" strategy="beforeInteractive" />;
+            <Script>{code}</Script>;
+            <Script {...props}>{code}</Script>;
+            <Script src="/b.js" strategy="worker" />;
+            "#,
+        )[..] else {
+            panic!("expected 4 scripts");
+        };
+
+        assert_eq!(
+            before_interactive.violations(false, NextScriptRouter::App),
+            vec![NextScriptViolation::BeforeInteractiveOutsideDocument]
+        );
+        assert!(before_interactive
+            .violations(true, NextScriptRouter::App)
+            .is_empty());
+        assert_eq!(
+            inline.violations(true, NextScriptRouter::Pages),
+            vec![NextScriptViolation::InlineScriptWithoutId]
+        );
+        // The `id` might be spread into the element.
+        assert!(inline_with_spread
+            .violations(true, NextScriptRouter::Pages)
+            .is_empty());
+        assert_eq!(
+            worker.violations(false, NextScriptRouter::App),
+            vec![NextScriptViolation::WorkerInAppRouter]
+        );
+        assert!(worker.violations(false, NextScriptRouter::Pages).is_empty());
+    }
+}
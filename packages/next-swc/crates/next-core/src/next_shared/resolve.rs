@@ -2,23 +2,29 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 use lazy_static::lazy_static;
-use turbo_tasks::Vc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use turbo_tasks::{trace::TraceRawVcs, ValueToString, Vc};
 use turbo_tasks_fs::glob::Glob;
 use turbopack_binding::{
-    turbo::tasks_fs::FileSystemPath,
-    turbopack::core::{
-        diagnostics::DiagnosticExt,
-        issue::{unsupported_module::UnsupportedModuleIssue, IssueExt},
-        resolve::{
-            parse::Request,
-            pattern::Pattern,
-            plugin::{ResolvePlugin, ResolvePluginCondition},
-            ResolveResultOption,
+    turbo::tasks_fs::{DirectoryContent, FileJsonContent, FileSystemEntryType, FileSystemPath},
+    turbopack::{
+        core::{
+            diagnostics::DiagnosticExt,
+            issue::{unsupported_module::UnsupportedModuleIssue, Issue, IssueExt, IssueSeverity},
+            resolve::{
+                options::{ImportMapResult, ImportMapping, ImportMappingReplacement},
+                parse::Request,
+                pattern::Pattern,
+                plugin::{ResolvePlugin, ResolvePluginCondition},
+                ResolveResultOption,
+            },
         },
+        ecmascript::utils::FormatIter,
     },
 };
 
-use crate::next_telemetry::ModuleFeatureTelemetry;
+use crate::{next_telemetry::ModuleFeatureTelemetry, util::is_in_node_modules};
 
 lazy_static! {
     static ref UNSUPPORTED_PACKAGES: HashSet<&'static str> = [].into();
@@ -154,3 +160,499 @@ impl ResolvePlugin for ModuleFeatureReportResolvePlugin {
         Ok(ResolveResultOption::none())
     }
 }
+
+/// Reports imports which only resolve because the file system ignores casing,
+/// like `./Button` for `button.tsx` on macOS. They fail to resolve once the
+/// project is built on a case-sensitive file system.
+#[turbo_tasks::value]
+pub(crate) struct ImportCasingResolvePlugin {
+    root: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportCasingResolvePlugin {
+    #[turbo_tasks::function]
+    pub fn new(root: Vc<FileSystemPath>) -> Vc<Self> {
+        ImportCasingResolvePlugin { root }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ResolvePlugin for ImportCasingResolvePlugin {
+    #[turbo_tasks::function]
+    fn after_resolve_condition(&self) -> Vc<ResolvePluginCondition> {
+        ResolvePluginCondition::new(self.root, Glob::new("**".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    async fn after_resolve(
+        &self,
+        fs_path: Vc<FileSystemPath>,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ResolveResultOption>> {
+        let root = self.root.await?;
+        // Walk up from the resolved file, every directory between it and the
+        // root was matched against the request too.
+        let mut path = fs_path;
+        loop {
+            let path_ref = path.await?;
+            if !path_ref.is_inside_ref(&root) || is_in_node_modules(&path_ref.path) {
+                break;
+            }
+            let parent = path.parent();
+            let DirectoryContent::Entries(entries) = &*parent.read_dir().await? else {
+                break;
+            };
+            match entry_casing(
+                path_ref.file_name(),
+                entries.iter().map(|(name, _)| name.as_str()),
+            ) {
+                EntryCasing::Matches => path = parent,
+                EntryCasing::Differs(actual_name) => {
+                    ImportCasingIssue {
+                        file_path: context,
+                        request: request.to_string().await?.clone_value(),
+                        path,
+                        actual_name: actual_name.to_string(),
+                    }
+                    .cell()
+                    .emit();
+                    break;
+                }
+                EntryCasing::Missing => break,
+            }
+        }
+
+        Ok(ResolveResultOption::none())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum EntryCasing<'a> {
+    Matches,
+    /// Only an entry with a different casing exists, which has this name.
+    Differs(&'a str),
+    Missing,
+}
+
+/// How the name of a resolved file or directory compares to the entries of
+/// its parent directory on disk.
+fn entry_casing<'a>(
+    name: &str,
+    entry_names: impl Iterator<Item = &'a str> + Clone,
+) -> EntryCasing<'a> {
+    if entry_names.clone().any(|entry_name| entry_name == name) {
+        return EntryCasing::Matches;
+    }
+    entry_names
+        .into_iter()
+        .find(|entry_name| entry_name.eq_ignore_ascii_case(name))
+        .map_or(EntryCasing::Missing, EntryCasing::Differs)
+}
+
+/// Conditions that are always active during exports field resolution, in
+/// addition to the custom conditions of a resolve options context.
+const IMPLICIT_EXPORTS_CONDITIONS: [&str; 3] = ["import", "require", "default"];
+
+/// Explains failed module requests into packages that declare an `exports`
+/// field when the field doesn't cover the request with the conditions of the
+/// current layer.
+///
+/// The default resolving error only mentions the request, which makes
+/// mismatched conditions very hard to debug. The replacer is part of the
+/// fallback import map, so it only sees requests that failed to resolve. It
+/// emits a [PackageExportsIssue] listing the conditions the package offers
+/// next to the ones that were tried, and leaves the request unresolved.
+#[turbo_tasks::value(shared)]
+pub(crate) struct PackageExportsReplacer {
+    layer: String,
+    conditions: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl PackageExportsReplacer {
+    #[turbo_tasks::function]
+    pub fn new(layer: String, conditions: Vec<String>) -> Vc<Self> {
+        Self::cell(PackageExportsReplacer { layer, conditions })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for PackageExportsReplacer {
+    /// The replacer is registered for all requests, so it keeps handling the
+    /// request whatever the wildcard captured.
+    #[turbo_tasks::function]
+    fn replace(self: Vc<Self>, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Dynamic(Vc::upcast(self)).cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        context: Vc<FileSystemPath>,
+        request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        let Request::Module {
+            module,
+            path: Pattern::Constant(path),
+            query: _,
+        } = &*request.await?
+        else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let Some(package_json) = find_package_json(context, module).await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let FileJsonContent::Content(package) = &*package_json.read_json().await? else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        let Some(exports) = package.get("exports") else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+
+        let mut tried_conditions = self.conditions.clone();
+        tried_conditions.extend(IMPLICIT_EXPORTS_CONDITIONS.iter().map(|c| c.to_string()));
+
+        if let Some(mismatch) =
+            package_exports_mismatch(exports, &format!(".{}", path), &tried_conditions)
+        {
+            let mut available_conditions = Vec::new();
+            collect_exports_conditions(exports, &mut available_conditions);
+            PackageExportsIssue {
+                file_path: context,
+                request: format!("{}{}", module, path),
+                package_json,
+                layer: self.layer.clone(),
+                mismatch,
+                available_conditions,
+                tried_conditions,
+                suggested_target: first_exports_target(exports)
+                    .map(|target| format!("{}/{}", module, target.trim_start_matches("./"))),
+            }
+            .cell()
+            .emit();
+        }
+
+        Ok(ImportMapResult::NoEntry.into())
+    }
+}
+
+/// Finds the `package.json` of `module` in the `node_modules` directories
+/// above `context`, the same ones node_modules resolution looks into.
+async fn find_package_json(
+    context: Vc<FileSystemPath>,
+    module: &str,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    let mut dir = context;
+    loop {
+        let package_json = dir.join(format!("node_modules/{module}/package.json"));
+        if *package_json.get_type().await? == FileSystemEntryType::File {
+            return Ok(Some(package_json));
+        }
+        if dir.await?.path.is_empty() {
+            return Ok(None);
+        }
+        dir = dir.parent();
+    }
+}
+
+/// Whether the `exports` field of a package covers `subpath` with the given
+/// set of active conditions, and why not.
+fn package_exports_mismatch(
+    exports: &JsonValue,
+    subpath: &str,
+    conditions: &[String],
+) -> Option<PackageExportsMismatch> {
+    match exports_subpath_target(exports, subpath) {
+        None => Some(PackageExportsMismatch::SubpathNotExported),
+        Some(target) if !exports_target_matches(target, conditions) => {
+            Some(PackageExportsMismatch::NoMatchingCondition)
+        }
+        Some(_) => None,
+    }
+}
+
+/// Returns the target of the `exports` entry that covers `subpath`, which is
+/// either `.` or starts with `./`.
+fn exports_subpath_target<'a>(exports: &'a JsonValue, subpath: &str) -> Option<&'a JsonValue> {
+    let JsonValue::Object(map) = exports else {
+        // strings and arrays are shorthands for the `.` entry
+        return (subpath == ".").then_some(exports);
+    };
+    if !map.keys().any(|key| key.starts_with('.')) {
+        // a condition object for the `.` entry
+        return (subpath == ".").then_some(exports);
+    }
+    if let Some(target) = map.get(subpath) {
+        return Some(target);
+    }
+    map.iter().find_map(|(key, target)| {
+        let matches = match key.split_once('*') {
+            Some((prefix, suffix)) => {
+                subpath.len() >= prefix.len() + suffix.len()
+                    && subpath.starts_with(prefix)
+                    && subpath.ends_with(suffix)
+            }
+            None => key.ends_with('/') && subpath.starts_with(key.as_str()),
+        };
+        matches.then_some(target)
+    })
+}
+
+/// Whether a target of the `exports` field resolves to a file with the given
+/// set of active conditions.
+fn exports_target_matches(target: &JsonValue, conditions: &[String]) -> bool {
+    match target {
+        JsonValue::String(_) => true,
+        JsonValue::Array(alternatives) => alternatives
+            .iter()
+            .any(|alternative| exports_target_matches(alternative, conditions)),
+        JsonValue::Object(map) => map.iter().any(|(condition, target)| {
+            conditions.contains(condition) && exports_target_matches(target, conditions)
+        }),
+        _ => false,
+    }
+}
+
+/// Collects all conditions that appear anywhere in the `exports` field.
+fn collect_exports_conditions(exports: &JsonValue, conditions: &mut Vec<String>) {
+    match exports {
+        JsonValue::Array(alternatives) => {
+            for alternative in alternatives {
+                collect_exports_conditions(alternative, conditions);
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, target) in map {
+                if !key.starts_with('.') && !conditions.contains(key) {
+                    conditions.push(key.clone());
+                }
+                collect_exports_conditions(target, conditions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds the first file the `exports` field points to, regardless of the
+/// conditions it's nested in.
+fn first_exports_target(exports: &JsonValue) -> Option<&str> {
+    match exports {
+        JsonValue::String(target) if !target.contains('*') => Some(target),
+        JsonValue::Array(alternatives) => alternatives.iter().find_map(first_exports_target),
+        JsonValue::Object(map) => map.values().find_map(first_exports_target),
+        _ => None,
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
+pub(crate) enum PackageExportsMismatch {
+    /// The subpath isn't listed in the `exports` field at all.
+    SubpathNotExported,
+    /// The subpath is exported, but none of its conditions are active.
+    NoMatchingCondition,
+}
+
+#[turbo_tasks::value(shared)]
+pub(crate) struct PackageExportsIssue {
+    file_path: Vc<FileSystemPath>,
+    request: String,
+    package_json: Vc<FileSystemPath>,
+    layer: String,
+    mismatch: PackageExportsMismatch,
+    available_conditions: Vec<String>,
+    tried_conditions: Vec<String>,
+    suggested_target: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PackageExportsIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(match self.mismatch {
+            PackageExportsMismatch::SubpathNotExported => {
+                format!("Package path of \"{}\" is not exported", self.request)
+            }
+            PackageExportsMismatch::NoMatchingCondition => format!(
+                "No export condition of \"{}\" matches the {} layer",
+                self.request, self.layer
+            ),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<String>> {
+        let package_json = self.package_json.await?;
+        let mut description = format!(
+            "The \"exports\" field in {} doesn't cover this request.\n\nAvailable \
+             conditions:{}\n\nConditions tried for the {} layer:{}",
+            package_json.path,
+            FormatIter(|| self
+                .available_conditions
+                .iter()
+                .flat_map(|c| vec!["\n- ", c.as_str()])),
+            self.layer,
+            FormatIter(|| self
+                .tried_conditions
+                .iter()
+                .flat_map(|c| vec!["\n- ", c.as_str()])),
+        );
+        if let Some(target) = &self.suggested_target {
+            description.push_str(&format!(
+                "\n\nIf the package works without the exports field, you can point the request at \
+                 a file directly using `experimental.turbo.resolveAlias` in next.config.js:\n  \
+                 resolveAlias: {{ \"{}\": \"{}\" }}",
+                self.request, target
+            ));
+        }
+        Ok(Vc::cell(description))
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub(crate) struct ImportCasingIssue {
+    file_path: Vc<FileSystemPath>,
+    request: String,
+    path: Vc<FileSystemPath>,
+    actual_name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ImportCasingIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "\"{}\" doesn't match the casing of the file on disk",
+            self.request
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<String>> {
+        let path = self.path.await?;
+        Ok(Vc::cell(format!(
+            "The request resolved to {}, which is named {} on disk. This only works on a \
+             case-insensitive file system, like the default ones of macOS and Windows, and fails \
+             to resolve on Linux.",
+            path.path, self.actual_name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_entry_casing() {
+        let entries = ["Button.tsx", "utils", "index.ts"];
+        assert_eq!(
+            entry_casing("Button.tsx", entries.into_iter()),
+            EntryCasing::Matches
+        );
+        assert_eq!(
+            entry_casing("button.tsx", entries.into_iter()),
+            EntryCasing::Differs("Button.tsx")
+        );
+        assert_eq!(
+            entry_casing("Utils", entries.into_iter()),
+            EntryCasing::Differs("utils")
+        );
+        assert_eq!(
+            entry_casing("header.tsx", entries.into_iter()),
+            EntryCasing::Missing
+        );
+    }
+
+    #[test]
+    fn test_exports_subpath_target() {
+        let exports = json!({
+            ".": { "import": "./index.mjs", "require": "./index.cjs" },
+            "./utils/*": "./dist/utils/*.js",
+            "./package.json": "./package.json",
+        });
+        assert!(exports_subpath_target(&exports, ".").is_some());
+        assert!(exports_subpath_target(&exports, "./utils/foo").is_some());
+        assert!(exports_subpath_target(&exports, "./internal").is_none());
+
+        let exports = json!({ "worker": "./worker.js", "default": "./index.js" });
+        assert!(exports_subpath_target(&exports, ".").is_some());
+        assert!(exports_subpath_target(&exports, "./index.js").is_none());
+    }
+
+    #[test]
+    fn test_package_exports_mismatch() {
+        let exports = json!({
+            ".": { "react-server": "./server.js", "import": "./index.mjs" },
+            "./client": { "browser": "./client.js" },
+        });
+        let conditions = |c: &[&str]| c.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            package_exports_mismatch(&exports, ".", &conditions(&["node", "import"])),
+            None
+        );
+        assert_eq!(
+            package_exports_mismatch(&exports, "./internal", &conditions(&["node", "import"])),
+            Some(PackageExportsMismatch::SubpathNotExported)
+        );
+        assert_eq!(
+            package_exports_mismatch(&exports, "./client", &conditions(&["node", "import"])),
+            Some(PackageExportsMismatch::NoMatchingCondition)
+        );
+    }
+
+    #[test]
+    fn test_exports_target_matches() {
+        let target =
+            json!({ "react-server": { "node": "./server.js" }, "browser": "./browser.js" });
+        let conditions = |c: &[&str]| c.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert!(exports_target_matches(&target, &conditions(&["browser"])));
+        assert!(exports_target_matches(
+            &target,
+            &conditions(&["react-server", "node"])
+        ));
+        assert!(!exports_target_matches(
+            &target,
+            &conditions(&["react-server", "edge-light"])
+        ));
+
+        let mut available = Vec::new();
+        collect_exports_conditions(&target, &mut available);
+        available.sort();
+        assert_eq!(available, conditions(&["browser", "node", "react-server"]));
+    }
+}
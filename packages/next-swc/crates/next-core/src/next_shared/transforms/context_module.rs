@@ -0,0 +1,380 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{
+    common::{Mark, Span, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp,
+            MetaPropExpr, MetaPropKind, ObjectLit, Program, Prop, PropName, PropOrSpread, Str, Tpl,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::issue::{Issue, IssueExt, IssueSeverity},
+        ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+        turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+    },
+};
+
+use super::module_rule_match_js_no_url;
+use crate::{
+    next_shared::context_module::{ContextModuleMode, ContextModuleRequest},
+    util::is_in_node_modules,
+};
+
+/// Returns a rule which replaces `require.context` calls, `import()` and
+/// `require()` of template literals, and `import.meta.glob` calls when
+/// enabled, with a request for the generated context module.
+pub fn get_next_context_module_transform_rule(
+    project_path: Vc<FileSystemPath>,
+    import_meta_glob: bool,
+) -> ModuleRule {
+    let transformer = EcmascriptInputTransform::Plugin(Vc::cell(Box::new(NextJsContextModule {
+        project_path,
+        import_meta_glob,
+    }) as _));
+    ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            transformer,
+        ]))],
+    )
+}
+
+#[derive(Debug)]
+struct NextJsContextModule {
+    project_path: Vc<FileSystemPath>,
+    import_meta_glob: bool,
+}
+
+#[async_trait]
+impl CustomTransformer for NextJsContextModule {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        program.visit_mut_with(&mut ContextModuleVisitor {
+            file_path: self.project_path.root().join(ctx.file_path_str.to_string()),
+            import_meta_glob: self.import_meta_glob,
+            unresolved_mark: ctx.unresolved_mark,
+            in_node_modules: is_in_node_modules(ctx.file_path_str),
+        });
+        Ok(())
+    }
+}
+
+struct ContextModuleVisitor {
+    file_path: Vc<FileSystemPath>,
+    import_meta_glob: bool,
+    unresolved_mark: Mark,
+    /// Packages commonly guard dynamic requests behind runtime checks, they
+    /// aren't warned about.
+    in_node_modules: bool,
+}
+
+impl ContextModuleVisitor {
+    /// Returns the context module for a `require.context(...)` call. Calls
+    /// with arguments that aren't literals can't be resolved statically and
+    /// are left as they are.
+    fn require_context(&self, call: &CallExpr) -> Option<ContextModuleRequest> {
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+        let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = &**callee
+        else {
+            return None;
+        };
+        match &**obj {
+            Expr::Ident(obj)
+                if &*obj.sym == "require"
+                    && obj.span.ctxt.outer() == self.unresolved_mark
+                    && &*prop.sym == "context" => {}
+            _ => return None,
+        }
+
+        let mut args = call.args.iter().map(|arg| &*arg.expr);
+        let Some(Expr::Lit(Lit::Str(dir))) = args.next() else {
+            return None;
+        };
+        let recursive = match args.next() {
+            None => true,
+            Some(Expr::Lit(Lit::Bool(recursive))) => recursive.value,
+            Some(_) => return None,
+        };
+        let (regexp, flags) = match args.next() {
+            None => (r"^\./.*$".to_string(), String::new()),
+            Some(Expr::Lit(Lit::Regex(regex))) => (regex.exp.to_string(), regex.flags.to_string()),
+            Some(_) => return None,
+        };
+        let mode = match args.next() {
+            None => ContextModuleMode::Sync,
+            Some(Expr::Lit(Lit::Str(mode))) => ContextModuleMode::parse(&mode.value)?,
+            Some(_) => return None,
+        };
+        if !dir.value.starts_with('.') {
+            return None;
+        }
+
+        Some(ContextModuleRequest::RequireContext {
+            dir: dir.value.to_string(),
+            recursive,
+            regexp,
+            flags,
+            mode,
+        })
+    }
+
+    /// Returns the context module and the key for an `import()` or `require()`
+    /// of a template literal, e. g. ``import(`./locales/${locale}.json`)``.
+    /// Like webpack, the module contains the files in the directory before the
+    /// first expression which match the rest of the template.
+    fn template_request(&self, call: &CallExpr) -> Option<(ContextModuleRequest, Tpl)> {
+        let mode = match &call.callee {
+            Callee::Import(_) => ContextModuleMode::Lazy,
+            Callee::Expr(callee) => match &**callee {
+                Expr::Ident(ident)
+                    if &*ident.sym == "require"
+                        && ident.span.ctxt.outer() == self.unresolved_mark =>
+                {
+                    ContextModuleMode::Sync
+                }
+                _ => return None,
+            },
+            Callee::Super(_) => return None,
+        };
+        let Some(Expr::Tpl(tpl)) = call.args.first().map(|arg| &*arg.expr) else {
+            return None;
+        };
+        if tpl.exprs.is_empty() {
+            return None;
+        }
+
+        let first = tpl.quasis.first()?;
+        let prefix = first.cooked.as_deref()?;
+        let dir = match prefix.rfind('/') {
+            Some(index) if prefix.starts_with("./") || prefix.starts_with("../") => {
+                &prefix[..index]
+            }
+            _ => {
+                if !self.in_node_modules {
+                    DynamicRequestIssue {
+                        file_path: self.file_path,
+                        request: template_to_string(tpl),
+                    }
+                    .cell()
+                    .emit();
+                }
+                return None;
+            }
+        };
+        let rest = &prefix[dir.len() + 1..];
+        let raw_rest = first.raw.strip_prefix(dir)?.strip_prefix('/')?;
+
+        let mut regexp = format!(r"^\./{}", regex::escape(rest));
+        for quasi in &tpl.quasis[1..] {
+            regexp.push_str(".*");
+            regexp.push_str(&regex::escape(quasi.cooked.as_deref()?));
+        }
+        regexp.push('$');
+
+        let mut key = tpl.clone();
+        key.quasis[0].raw = format!("./{raw_rest}").into();
+        key.quasis[0].cooked = Some(format!("./{rest}").into());
+
+        Some((
+            ContextModuleRequest::RequireContext {
+                dir: dir.to_string(),
+                recursive: true,
+                regexp,
+                flags: String::new(),
+                mode,
+            },
+            key,
+        ))
+    }
+
+    /// Returns the context module for an `import.meta.glob(...)` call.
+    fn import_meta_glob(&self, call: &CallExpr) -> Option<ContextModuleRequest> {
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+        let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = &**callee
+        else {
+            return None;
+        };
+        if !matches!(
+            &**obj,
+            Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            })
+        ) || &*prop.sym != "glob"
+        {
+            return None;
+        }
+
+        let mut args = call.args.iter().map(|arg| &*arg.expr);
+        let patterns = match args.next()? {
+            Expr::Lit(Lit::Str(pattern)) => vec![pattern.value.to_string()],
+            Expr::Array(ArrayLit { elems, .. }) => elems
+                .iter()
+                .map(|elem| match elem.as_ref().map(|elem| &*elem.expr) {
+                    Some(Expr::Lit(Lit::Str(pattern))) => Some(pattern.value.to_string()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?,
+            _ => return None,
+        };
+
+        let mut eager = false;
+        let mut import = None;
+        match args.next() {
+            None => {}
+            Some(Expr::Object(ObjectLit { props, .. })) => {
+                for prop in props {
+                    let PropOrSpread::Prop(prop) = prop else {
+                        return None;
+                    };
+                    let Prop::KeyValue(prop) = &**prop else {
+                        return None;
+                    };
+                    let key = match &prop.key {
+                        PropName::Ident(key) => key.sym.to_string(),
+                        PropName::Str(key) => key.value.to_string(),
+                        _ => return None,
+                    };
+                    match (key.as_str(), &*prop.value) {
+                        ("eager", Expr::Lit(Lit::Bool(value))) => eager = value.value,
+                        ("import", Expr::Lit(Lit::Str(value))) => {
+                            import = Some(value.value.to_string())
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            Some(_) => return None,
+        }
+
+        Some(ContextModuleRequest::ImportMetaGlob {
+            patterns,
+            eager,
+            import,
+        })
+    }
+
+    fn require(&self, request: String, span: Span) -> Expr {
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident::new(
+                "require".into(),
+                DUMMY_SP.apply_mark(self.unresolved_mark),
+            )))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: request.into(),
+                    raw: None,
+                }))),
+            }],
+            type_args: None,
+        })
+    }
+}
+
+impl VisitMut for ContextModuleVisitor {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        if let Some((context_request, key)) = self.template_request(call) {
+            if let Ok(request) = context_request.to_request() {
+                *expr = Expr::Call(CallExpr {
+                    span: call.span,
+                    callee: Callee::Expr(Box::new(self.require(request, DUMMY_SP))),
+                    args: vec![ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(Expr::Tpl(key)),
+                    }],
+                    type_args: None,
+                });
+            }
+            return;
+        }
+        let context_request = match self.require_context(call) {
+            Some(context_request) => context_request,
+            None if self.import_meta_glob => match self.import_meta_glob(call) {
+                Some(context_request) => context_request,
+                None => return,
+            },
+            None => return,
+        };
+        if let Ok(request) = context_request.to_request() {
+            *expr = self.require(request, call.span);
+        }
+    }
+}
+
+/// Prints a template literal with its expressions elided, e. g.
+/// `` `./${...}.json` ``.
+fn template_to_string(tpl: &Tpl) -> String {
+    let quasis = tpl
+        .quasis
+        .iter()
+        .map(|quasi| &*quasi.raw)
+        .collect::<Vec<_>>();
+    format!("`{}`", quasis.join("${...}"))
+}
+
+#[turbo_tasks::value(shared)]
+struct DynamicRequestIssue {
+    file_path: Vc<FileSystemPath>,
+    request: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DynamicRequestIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The request {} is too dynamic to be bundled",
+            self.request
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "Only templates starting with a relative directory, e. g. `./locales/${locale}.json`, \
+             can be bundled as a context module. The files which can be imported have to be known \
+             at build time."
+                .to_string(),
+        )
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::{
+        ast::{
+            AssignExpr, AssignOp, CallExpr, Callee, Class, ClassMember, Decl, DefaultDecl,
+            ExportDecl, ExportDefaultDecl, Expr, ExprStmt, Ident, JSXElement, JSXFragment, Lit,
+            MemberExpr, MemberProp, ModuleDecl, ModuleItem, Pat, PatOrExpr, Program, PropName,
+            Stmt,
+        },
+        visit::{noop_visit_type, Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::ecmascript::{
+    CustomTransformer, OptionTransformPlugin, TransformContext,
+};
+
+use crate::{mode::NextMode, next_config::NextConfig};
+
+/// Returns a transform plugin which sets the `displayName` of the functions
+/// and classes which look like React components, for
+/// `experimental.keepComponentNames`. React prefers it over the function name,
+/// which the minifier mangles, so error reports and the React DevTools show
+/// the original names while everything else is still mangled.
+#[turbo_tasks::function]
+pub async fn get_keep_component_names_transform_plugin(
+    next_config: Vc<NextConfig>,
+    mode: NextMode,
+) -> Result<Vc<OptionTransformPlugin>> {
+    // Nothing is mangled in development.
+    if mode != NextMode::Build || !*next_config.keep_component_names().await? {
+        return Ok(Vc::cell(None));
+    }
+    Ok(Vc::cell(Some(Vc::cell(
+        Box::new(KeepComponentNamesTransformer) as _,
+    ))))
+}
+
+#[derive(Debug)]
+struct KeepComponentNamesTransformer;
+
+#[async_trait]
+impl CustomTransformer for KeepComponentNamesTransformer {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        program.visit_mut_with(&mut AddDisplayNames);
+        Ok(())
+    }
+}
+
+struct AddDisplayNames;
+
+impl VisitMut for AddDisplayNames {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+
+        let mut index = 0;
+        while index < items.len() {
+            let components = match &items[index] {
+                ModuleItem::Stmt(Stmt::Decl(decl))
+                | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+                    decl_components(decl)
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                    decl,
+                    ..
+                })) => default_decl_component(decl).into_iter().collect(),
+                _ => vec![],
+            };
+            index += 1;
+            for ident in components {
+                if !sets_display_name(items.iter().filter_map(ModuleItem::as_stmt), &ident) {
+                    items.insert(index, ModuleItem::Stmt(display_name_stmt(ident)));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+
+        let mut index = 0;
+        while index < stmts.len() {
+            let components = match &stmts[index] {
+                Stmt::Decl(decl) => decl_components(decl),
+                _ => vec![],
+            };
+            index += 1;
+            for ident in components {
+                if !sets_display_name(stmts.iter(), &ident) {
+                    stmts.insert(index, display_name_stmt(ident));
+                    index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// The names of the components a declaration declares: functions and classes
+/// whose name starts with an uppercase letter and which render elements.
+fn decl_components(decl: &Decl) -> Vec<Ident> {
+    match decl {
+        Decl::Fn(fn_decl) => is_component(&fn_decl.ident, &*fn_decl.function)
+            .then(|| fn_decl.ident.clone())
+            .into_iter()
+            .collect(),
+        Decl::Class(class_decl) => is_class_component(&class_decl.ident, &class_decl.class)
+            .then(|| class_decl.ident.clone())
+            .into_iter()
+            .collect(),
+        Decl::Var(var_decl) => var_decl
+            .decls
+            .iter()
+            .filter_map(|declarator| {
+                let (Pat::Ident(ident), Some(init)) = (&declarator.name, &declarator.init) else {
+                    return None;
+                };
+                let is_component = match &**init {
+                    Expr::Fn(_) | Expr::Arrow(_) => is_component(&ident.id, &**init),
+                    Expr::Class(class_expr) => is_class_component(&ident.id, &class_expr.class),
+                    _ => false,
+                };
+                is_component.then(|| ident.id.clone())
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn default_decl_component(decl: &DefaultDecl) -> Option<Ident> {
+    match decl {
+        DefaultDecl::Fn(fn_expr) => {
+            let ident = fn_expr.ident.as_ref()?;
+            is_component(ident, &*fn_expr.function).then(|| ident.clone())
+        }
+        DefaultDecl::Class(class_expr) => {
+            let ident = class_expr.ident.as_ref()?;
+            is_class_component(ident, &class_expr.class).then(|| ident.clone())
+        }
+        DefaultDecl::TsInterfaceDecl(_) => None,
+    }
+}
+
+fn is_component<N: VisitWith<ElementFinder>>(ident: &Ident, node: &N) -> bool {
+    if !ident.sym.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return false;
+    }
+    let mut finder = ElementFinder::default();
+    node.visit_with(&mut finder);
+    finder.found
+}
+
+fn is_class_component(ident: &Ident, class: &Class) -> bool {
+    let has_static_display_name = class.body.iter().any(|member| {
+        matches!(member, ClassMember::ClassProp(prop)
+            if prop.is_static
+                && matches!(&prop.key, PropName::Ident(key) if &*key.sym == "displayName"))
+    });
+    !has_static_display_name && is_component(ident, class)
+}
+
+/// Whether one of the statements already assigns `Name.displayName`.
+fn sets_display_name<'a>(mut stmts: impl Iterator<Item = &'a Stmt>, ident: &Ident) -> bool {
+    stmts.any(|stmt| {
+        let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+            return false;
+        };
+        let Expr::Assign(AssignExpr { left, .. }) = &**expr else {
+            return false;
+        };
+        let member = match left {
+            PatOrExpr::Expr(expr) => expr.as_member(),
+            PatOrExpr::Pat(pat) => pat.as_expr().and_then(|expr| expr.as_member()),
+        };
+        member.map_or(false, |member| {
+            matches!(&*member.obj, Expr::Ident(obj) if obj.to_id() == ident.to_id())
+                && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "displayName")
+        })
+    })
+}
+
+/// `Name.displayName = "Name";`
+fn display_name_stmt(ident: Ident) -> Stmt {
+    let name = ident.sym.to_string();
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(ident)),
+                prop: MemberProp::Ident(Ident::new("displayName".into(), DUMMY_SP)),
+            }))),
+            right: Box::new(Expr::Lit(Lit::Str(name.into()))),
+        })),
+    })
+}
+
+/// Finds JSX elements and calls of the JSX runtime or of `createElement`, for
+/// code which was compiled already. The callee is then usually a property of
+/// the imported module, e.g. `(0, module.jsx)(...)`.
+#[derive(Default)]
+struct ElementFinder {
+    found: bool,
+}
+
+impl Visit for ElementFinder {
+    noop_visit_type!();
+
+    fn visit_jsx_element(&mut self, _element: &JSXElement) {
+        self.found = true;
+    }
+
+    fn visit_jsx_fragment(&mut self, _fragment: &JSXFragment) {
+        self.found = true;
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            if is_jsx_callee(callee) {
+                self.found = true;
+                return;
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+fn is_jsx_callee(expr: &Expr) -> bool {
+    let is_jsx_name = |name: &str| matches!(name, "jsx" | "jsxs" | "jsxDEV" | "createElement");
+    match expr {
+        Expr::Paren(paren) => is_jsx_callee(&paren.expr),
+        Expr::Seq(seq) => seq.exprs.last().map_or(false, |expr| is_jsx_callee(expr)),
+        Expr::Ident(ident) => is_jsx_name(&ident.sym),
+        Expr::Member(member) => match &member.prop {
+            MemberProp::Ident(ident) => is_jsx_name(&ident.sym),
+            MemberProp::Computed(computed) => {
+                matches!(&*computed.expr, Expr::Lit(Lit::Str(str)) if is_jsx_name(&str.value))
+            }
+            MemberProp::PrivateName(_) => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    /// The names which get a `displayName` assigned, in order.
+    fn display_names(code: &str) -> Vec<String> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let mut program = parse_file_as_program(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        program.visit_mut_with(&mut AddDisplayNames);
+
+        let mut collector = DisplayNameCollector::default();
+        program.visit_with(&mut collector);
+        collector.names
+    }
+
+    #[derive(Default)]
+    struct DisplayNameCollector {
+        names: Vec<String>,
+    }
+
+    impl Visit for DisplayNameCollector {
+        fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+            if let (PatOrExpr::Expr(left), Expr::Lit(Lit::Str(value))) =
+                (&assign.left, &*assign.right)
+            {
+                if let Some(member) = left.as_member() {
+                    if matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "displayName")
+                    {
+                        self.names.push(value.value.to_string());
+                    }
+                }
+            }
+            assign.visit_children_with(self);
+        }
+    }
+
+    #[test]
+    fn test_display_names() {
+        assert_eq!(
+            display_names(
+                r#"
+                function Header() { return <h1 />; }
+                export const Footer = () => React.createElement("footer");
+                class Page extends Component { render() { return (0, r["jsxs"])(Header, {}); } }
+                function Helper() { return 1; }
+                function lower() { return <div />; }
+                export default function Layout() {
+                    function Nested() { return <></>; }
+                    return <Nested />;
+                }
+                "#
+            ),
+            vec!["Header", "Footer", "Page", "Nested", "Layout"]
+        );
+    }
+
+    #[test]
+    fn test_existing_display_names() {
+        assert_eq!(
+            display_names(
+                r#"
+                function Header() { return <h1 />; }
+                Header.displayName = "Title";
+                class Page extends Component {
+                    static displayName = "Home";
+                    render() { return <Header />; }
+                }
+                "#
+            ),
+            vec!["Title"]
+        );
+    }
+}
@@ -1,16 +1,23 @@
+pub(crate) mod context_module;
 pub(crate) mod emotion;
+pub(crate) mod keep_component_names;
 pub(crate) mod modularize_imports;
 pub(crate) mod next_dynamic;
 pub(crate) mod next_font;
+pub(crate) mod next_image_remote_patterns;
 pub(crate) mod next_strip_page_exports;
+pub(crate) mod react_remove_properties;
 pub(crate) mod relay;
 pub(crate) mod styled_components;
 pub(crate) mod styled_jsx;
 pub(crate) mod swc_ecma_transform_plugins;
+pub(crate) mod use_cache;
 
+pub use context_module::get_next_context_module_transform_rule;
 pub use modularize_imports::{get_next_modularize_imports_rule, ModularizeImportPackageConfig};
 pub use next_dynamic::get_next_dynamic_transform_rule;
 pub use next_font::get_next_font_transform_rule;
+pub use next_image_remote_patterns::get_next_image_remote_patterns_rule;
 pub use next_strip_page_exports::get_next_pages_transforms_rule;
 pub use relay::get_relay_transform_plugin;
 use turbo_tasks::{Value, Vc};
@@ -0,0 +1,620 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{
+    common::{source_map::Pos, Span},
+    ecma::{
+        ast::{
+            Expr, ImportDecl, ImportSpecifier, JSXAttrName, JSXAttrOrSpread, JSXAttrValue,
+            JSXElementName, JSXExpr, JSXOpeningElement, Lit, Program,
+        },
+        atoms::JsWord,
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            file_source::FileSource,
+            issue::{Issue, IssueExt, IssueSeverity, IssueSource, OptionIssueSource},
+        },
+        ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+        turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+    },
+};
+use url::Url;
+
+use super::module_rule_match_js_no_url;
+use crate::next_config::{ImageConfig, ImageLoader, RemotePattern, RemotePatternProtocal};
+
+/// Returns a rule which checks the remote URLs passed to `next/image` as
+/// literals against `images.remotePatterns` and `images.domains`, so a missing
+/// pattern is caught at compile time instead of by the 400 of the image
+/// optimizer.
+pub fn get_next_image_remote_patterns_rule(
+    project_path: Vc<FileSystemPath>,
+    image_config: Vc<ImageConfig>,
+) -> ModuleRule {
+    let transformer =
+        EcmascriptInputTransform::Plugin(Vc::cell(Box::new(NextJsImageRemotePatterns {
+            project_path,
+            image_config,
+        }) as _));
+    ModuleRule::new(
+        module_rule_match_js_no_url(),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            transformer,
+        ]))],
+    )
+}
+
+#[derive(Debug)]
+struct NextJsImageRemotePatterns {
+    project_path: Vc<FileSystemPath>,
+    image_config: Vc<ImageConfig>,
+}
+
+#[async_trait]
+impl CustomTransformer for NextJsImageRemotePatterns {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        let mut visitor = RemoteImageVisitor::default();
+        program.visit_with(&mut visitor);
+        if visitor.urls.is_empty() {
+            return Ok(());
+        }
+
+        let image_config = self.image_config.await?;
+        // Images which aren't optimized by Next.js can be loaded from anywhere.
+        if image_config.unoptimized || image_config.loader != ImageLoader::Default {
+            return Ok(());
+        }
+
+        let file_path = self.project_path.root().join(ctx.file_path_str.to_string());
+        let source = Vc::upcast(FileSource::new(file_path));
+        for (src, span) in visitor.urls {
+            let Ok(url) = Url::parse(&src) else {
+                continue;
+            };
+            if !matches!(url.scheme(), "http" | "https") || is_allowed(&url, &image_config) {
+                continue;
+            }
+            RemoteImageUrlIssue {
+                file_path,
+                url: src,
+                source: IssueSource::from_byte_offset(
+                    source,
+                    span.lo.to_usize(),
+                    span.hi.to_usize(),
+                ),
+            }
+            .cell()
+            .emit();
+        }
+        Ok(())
+    }
+}
+
+/// Mirrors `hasMatch` of `next/dist/shared/lib/match-remote-pattern`.
+fn is_allowed(url: &Url, image_config: &ImageConfig) -> bool {
+    let Some(hostname) = url.host_str() else {
+        return false;
+    };
+    image_config.domains.iter().any(|domain| domain == hostname)
+        || image_config
+            .remote_patterns
+            .iter()
+            .any(|pattern| matches_remote_pattern(pattern, url, hostname))
+}
+
+/// Mirrors `matchRemotePattern`.
+fn matches_remote_pattern(pattern: &RemotePattern, url: &Url, hostname: &str) -> bool {
+    if let Some(protocol) = &pattern.protocol {
+        let scheme = match protocol {
+            RemotePatternProtocal::Http => "http",
+            RemotePatternProtocal::Https => "https",
+        };
+        if url.scheme() != scheme {
+            return false;
+        }
+    }
+    if let Some(port) = &pattern.port {
+        // Like `URL.port`, the default port of the protocol is an empty string.
+        if url.port().map(|port| port.to_string()).unwrap_or_default() != *port {
+            return false;
+        }
+    }
+    micromatch(&pattern.hostname, hostname)
+        && micromatch(pattern.pathname.as_deref().unwrap_or("**"), url.path())
+}
+
+/// Matches a value against a pattern like micromatch's `makeRe` does, for the
+/// `*`, `**` and `?` wildcards the remote patterns use. Only `/` separates
+/// segments, so in a hostname `*.example.com` matches `a.b.example.com` just
+/// like `**.example.com`, but neither matches `example.com`. `**` only
+/// matches any number of segments when it is a segment of its own, otherwise
+/// it is a `*`. Like micromatch without `dot`, wildcards don't match a `.` at
+/// the start of a segment.
+fn micromatch(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.split('/').collect::<Vec<_>>();
+    let value = value.split('/').collect::<Vec<_>>();
+    segments_match(&pattern, &value)
+}
+
+fn segments_match(pattern: &[&str], value: &[&str]) -> bool {
+    let Some((&segment, pattern_rest)) = pattern.split_first() else {
+        return value.is_empty();
+    };
+    if segment == "**" {
+        // Any number of segments, including none.
+        return (0..=value.len())
+            .take_while(|&count| count == 0 || !value[count - 1].starts_with('.'))
+            .any(|count| segments_match(pattern_rest, &value[count..]));
+    }
+    let Some((&value_segment, value_rest)) = value.split_first() else {
+        return false;
+    };
+    segment_matches(segment.as_bytes(), value_segment.as_bytes())
+        && segments_match(pattern_rest, value_rest)
+}
+
+fn segment_matches(pattern: &[u8], value: &[u8]) -> bool {
+    let starts_with_wildcard = matches!(pattern.first(), Some(b'*' | b'?'));
+    if starts_with_wildcard && value.first() == Some(&b'.') {
+        return false;
+    }
+    // A segment of only `*` needs at least one character.
+    if !pattern.is_empty() && pattern.iter().all(|&c| c == b'*') && value.is_empty() {
+        return false;
+    }
+    wildcard_matches(pattern, value)
+}
+
+fn wildcard_matches(pattern: &[u8], value: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => value.is_empty(),
+        Some((b'*', rest)) => (0..=value.len()).any(|skip| wildcard_matches(rest, &value[skip..])),
+        Some((b'?', rest)) => !value.is_empty() && wildcard_matches(rest, &value[1..]),
+        Some((c, rest)) => value.first() == Some(c) && wildcard_matches(rest, &value[1..]),
+    }
+}
+
+/// Collects the `src` literals of the `next/image` elements of a module.
+#[derive(Default)]
+struct RemoteImageVisitor {
+    /// The local names of the default imports of `next/image`.
+    image_idents: Vec<JsWord>,
+    urls: Vec<(String, Span)>,
+}
+
+impl RemoteImageVisitor {
+    fn check_image_element(&mut self, element: &JSXOpeningElement) {
+        let JSXElementName::Ident(name) = &element.name else {
+            return;
+        };
+        if !self.image_idents.contains(&name.sym) {
+            return;
+        }
+
+        let mut src = None;
+        for attr in &element.attrs {
+            let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+                continue;
+            };
+            let JSXAttrName::Ident(attr_name) = &attr.name else {
+                continue;
+            };
+            match (&*attr_name.sym, &attr.value) {
+                ("src", Some(value)) => src = jsx_attr_string(value).map(|url| (url, attr.span)),
+                // `<Image unoptimized />` loads the image directly.
+                ("unoptimized", None) => return,
+                ("unoptimized", Some(value)) if !is_false(value) => return,
+                _ => {}
+            }
+        }
+        self.urls.extend(src);
+    }
+}
+
+impl Visit for RemoteImageVisitor {
+    noop_visit_type!();
+
+    fn visit_import_decl(&mut self, import: &ImportDecl) {
+        if import.type_only || !matches!(&*import.src.value, "next/image" | "next/legacy/image") {
+            return;
+        }
+        for specifier in &import.specifiers {
+            if let ImportSpecifier::Default(default) = specifier {
+                self.image_idents.push(default.local.sym.clone());
+            }
+        }
+    }
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        self.check_image_element(element);
+        element.visit_children_with(self);
+    }
+}
+
+/// The value of a string attribute, `src="..."`, `src={"..."}` or
+/// ``src={`...`}`` without expressions.
+fn jsx_attr_string(value: &JSXAttrValue) -> Option<String> {
+    match value {
+        JSXAttrValue::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+        JSXAttrValue::JSXExprContainer(container) => match &container.expr {
+            JSXExpr::Expr(expr) => match &**expr {
+                Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+                Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+                    Some(tpl.quasis.first()?.cooked.as_deref()?.to_string())
+                }
+                _ => None,
+            },
+            JSXExpr::JSXEmptyExpr(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the value is `{false}`.
+fn is_false(value: &JSXAttrValue) -> bool {
+    let JSXAttrValue::JSXExprContainer(container) = value else {
+        return false;
+    };
+    let JSXExpr::Expr(expr) = &container.expr else {
+        return false;
+    };
+    matches!(&**expr, Expr::Lit(Lit::Bool(bool)) if !bool.value)
+}
+
+/// A remote image URL which the image optimizer would reject.
+#[turbo_tasks::value(shared)]
+struct RemoteImageUrlIssue {
+    file_path: Vc<FileSystemPath>,
+    url: String,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RemoteImageUrlIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("image".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The image URL {} isn't allowed by images.remotePatterns",
+            self.url
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        let example = Url::parse(&self.url).ok().and_then(|url| {
+            Some(format!(
+                "{{ protocol: \"{}\", hostname: \"{}\" }}",
+                url.scheme(),
+                url.host_str()?
+            ))
+        });
+        Vc::cell(format!(
+            "The image optimizer only loads images from the hosts configured in next.config.js \
+             and responds with a 400 for this URL. Add a pattern to `images.remotePatterns`{}.",
+            example.map_or_else(String::new, |example| format!(", like {example}"))
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell("https://nextjs.org/docs/messages/next-image-unconfigured-host".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        OptionIssueSource::some(self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            parser::{parse_file_as_program, EsConfig, Syntax},
+        },
+    };
+
+    use super::*;
+
+    fn remote_image_urls(code: &str) -> Vec<String> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let program = parse_file_as_program(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let mut visitor = RemoteImageVisitor::default();
+        program.visit_with(&mut visitor);
+        visitor.urls.into_iter().map(|(url, _)| url).collect()
+    }
+
+    #[test]
+    fn test_remote_image_visitor() {
+        assert_eq!(
+            remote_image_urls(
+                r#"
+                import Photo from "next/image";
+                import Legacy from "next/legacy/image";
+                import Other from "./image";
+                export default function Page() {
+                    return <>
+                        <Photo src="https://a.example.com/1.jpg" />
+                        <Legacy src={`https://b.example.com/2.jpg`} />
+                        <Photo src={"https://c.example.com/3.jpg"} unoptimized={false} />
+                        <Photo src="https://d.example.com/4.jpg" unoptimized />
+                        <Photo src={`https://e.example.com/${id}.jpg`} />
+                        <Other src="https://f.example.com/6.jpg" />
+                    </>;
+                }
+                "#
+            ),
+            vec![
+                "https://a.example.com/1.jpg",
+                "https://b.example.com/2.jpg",
+                "https://c.example.com/3.jpg",
+            ]
+        );
+    }
+
+    fn pattern(
+        protocol: Option<RemotePatternProtocal>,
+        hostname: &str,
+        port: Option<&str>,
+        pathname: Option<&str>,
+    ) -> RemotePattern {
+        RemotePattern {
+            hostname: hostname.to_string(),
+            protocol,
+            port: port.map(ToString::to_string),
+            pathname: pathname.map(ToString::to_string),
+        }
+    }
+
+    fn hostname(hostname: &str) -> RemotePattern {
+        pattern(None, hostname, None, None)
+    }
+
+    /// Asserts which of the URLs match the pattern, like the cases of
+    /// match-remote-pattern.test.ts.
+    fn assert_matches(pattern: &RemotePattern, cases: &[(&str, bool)]) {
+        for &(url, expected) in cases {
+            let parsed = Url::parse(url).unwrap();
+            let hostname = parsed.host_str().unwrap();
+            assert_eq!(
+                matches_remote_pattern(pattern, &parsed, hostname),
+                expected,
+                "{pattern:?} with {url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_literal_hostname() {
+        assert_matches(
+            &hostname("example.com"),
+            &[
+                ("https://example.com", true),
+                ("https://example.com.uk", false),
+                ("https://sub.example.com", false),
+                ("https://example.com/path/to/file", true),
+                ("https://example.com:81/path/to/file?q=1", true),
+                ("http://example.com:81/path/to/file", true),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_protocol_and_port() {
+        assert_matches(
+            &pattern(
+                Some(RemotePatternProtocal::Https),
+                "example.com",
+                None,
+                None,
+            ),
+            &[
+                ("https://example.com/path/to", true),
+                ("https://example.com:81/path/to/file", true),
+                ("http://example.com:81/path/to/file", false),
+                ("ftp://example.com:81/path/to/file", false),
+            ],
+        );
+        assert_matches(
+            &pattern(
+                Some(RemotePatternProtocal::Https),
+                "example.com",
+                Some(""),
+                None,
+            ),
+            &[
+                ("https://example.com/path/to/file", true),
+                ("https://example.com:443/path/to/file", true),
+                ("https://example.com:81/path/to/file", false),
+            ],
+        );
+        assert_matches(
+            &pattern(
+                Some(RemotePatternProtocal::Https),
+                "example.com",
+                Some("42"),
+                Some("/path/to/file"),
+            ),
+            &[
+                ("https://example.com:42/path/to/file", true),
+                ("https://example.com:42/path/to/file?q=1", true),
+                ("https://example.com:42/path/to", false),
+                ("https://example.com/path/to/file", false),
+                ("http://example.com:42/path/to/file", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_hostname_single_asterisk() {
+        assert_matches(
+            &hostname("avatars.*.example.com"),
+            &[
+                ("https://example.com", false),
+                ("https://avatars.example.com", false),
+                ("https://avatars.sfo1.example.com", true),
+                ("https://more.avatars.iad1.example.com", false),
+            ],
+        );
+        assert_matches(
+            &hostname("avatars.*1.example.com"),
+            &[
+                ("https://avatars.sfo1.example.com", true),
+                ("https://avatars.sfo2.example.com", false),
+                ("https://avatars.1.example.com", true),
+            ],
+        );
+        assert_matches(
+            &hostname("avatars.*a*.example.com"),
+            &[
+                ("https://avatars.sfo1.example.com", false),
+                ("https://avatars.iad2.example.com", true),
+                ("https://avatars.a.example.com", true),
+            ],
+        );
+        assert_matches(
+            &hostname("avatars.ia*.example.com"),
+            &[
+                ("https://avatars.iad1.example.com", true),
+                ("https://avatars.ia.example.com", true),
+                ("https://avatars.sfo1.example.com", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_hostname_asterisks() {
+        // Hostnames have no `/`, so `*` matches any number of subdomains, just
+        // like `**`, but at least one.
+        for pattern in ["*.example.com", "**.example.com"] {
+            assert_matches(
+                &hostname(pattern),
+                &[
+                    ("https://com", false),
+                    ("https://example.com", false),
+                    ("https://sub.example.com", true),
+                    ("https://deep.sub.example.com", true),
+                    ("https://example.com.uk", false),
+                    ("https://sub.example.com.uk", false),
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn test_pathname_single_asterisk() {
+        assert_matches(
+            &pattern(None, "example.com", None, Some("/act123/*/pic.jpg")),
+            &[
+                ("https://example.com", false),
+                ("https://example.com/act123/usr4", false),
+                ("https://example.com/act123/usr4/picsjpg", false),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/team/pic.jpg", true),
+                ("https://example.com/act123/a/b/pic.jpg", false),
+                ("https://example.com/act456/team/pic.jpg", false),
+            ],
+        );
+        assert_matches(
+            &pattern(None, "example.com", None, Some("/act123/*sr*/pic.jpg")),
+            &[
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act123/sr/pic.jpg", true),
+                ("https://example.com/act123/team4/pic.jpg", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_pathname_double_asterisk() {
+        assert_matches(
+            &pattern(None, "example.com", None, Some("/act123/**")),
+            &[
+                ("https://example.com", false),
+                ("https://example.com/act123", true),
+                ("https://example.com/act123/usr4", true),
+                ("https://example.com/act123/usr4/pic.jpg", true),
+                ("https://example.com/act456/team/pic.jpg", false),
+                ("https://sub.example.com/act123/team/pic.jpg", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_micromatch_dot_segments() {
+        assert!(!micromatch("/images/*", "/images/.secret"));
+        assert!(!micromatch("/images/**", "/images/.cache/pic.jpg"));
+        assert!(micromatch("/images/pic*", "/images/pic.jpg"));
+        assert!(!micromatch("*.example.com", ".example.com"));
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let url = Url::parse("https://example.com").unwrap();
+        let config = |domains: &[&str], remote_patterns: Vec<RemotePattern>| ImageConfig {
+            domains: domains.iter().map(ToString::to_string).collect(),
+            remote_patterns,
+            ..Default::default()
+        };
+        assert!(!is_allowed(&url, &config(&[], vec![])));
+        assert!(is_allowed(&url, &config(&["example.com"], vec![])));
+        // Domains are compared literally.
+        assert!(!is_allowed(&url, &config(&["*.example.com"], vec![])));
+        assert!(is_allowed(
+            &url,
+            &config(&[], vec![hostname("foo.com"), hostname("example.com")])
+        ));
+        assert!(!is_allowed(
+            &url,
+            &config(
+                &[],
+                vec![pattern(None, "example.com", None, Some("/act123/**"))]
+            )
+        ));
+        assert!(!is_allowed(
+            &url,
+            &config(
+                &[],
+                vec![pattern(
+                    Some(RemotePatternProtocal::Http),
+                    "example.com",
+                    None,
+                    None
+                )]
+            )
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use swc_core::ecma::{
+    ast::{JSXAttr, JSXAttrName, JSXAttrOrSpread, JSXOpeningElement, Program},
+    visit::{VisitMut, VisitMutWith},
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::ecmascript::{
+    CustomTransformer, OptionTransformPlugin, TransformContext,
+};
+
+use crate::next_config::{NextConfig, ReactRemoveProperties};
+
+/// The properties which are removed when no pattern is configured, the same
+/// as the ones of `babel-plugin-react-remove-properties`.
+const DEFAULT_PROPERTY_PATTERN: &str = "^data-test";
+
+/// Returns a transform plugin which removes the JSX properties matching
+/// `compiler.reactRemoveProperties`. It runs on the source, so the properties
+/// are gone before JSX is compiled and minified.
+#[turbo_tasks::function]
+pub async fn get_react_remove_properties_transform_plugin(
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<OptionTransformPlugin>> {
+    let patterns = match next_config
+        .await?
+        .compiler
+        .as_ref()
+        .and_then(|compiler| compiler.react_remove_properties.as_ref())
+    {
+        None | Some(ReactRemoveProperties::Boolean(false)) => {
+            return Ok(Vc::cell(None));
+        }
+        Some(ReactRemoveProperties::Boolean(true)) => vec![],
+        Some(ReactRemoveProperties::Config { properties }) => {
+            properties.clone().unwrap_or_default()
+        }
+    };
+
+    let mut properties = patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).with_context(|| {
+                format!("invalid compiler.reactRemoveProperties pattern `{pattern}`")
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if properties.is_empty() {
+        properties.push(Regex::new(DEFAULT_PROPERTY_PATTERN)?);
+    }
+
+    Ok(Vc::cell(Some(Vc::cell(
+        Box::new(ReactRemovePropertiesTransformer { properties }) as _,
+    ))))
+}
+
+#[derive(Debug)]
+struct ReactRemovePropertiesTransformer {
+    properties: Vec<Regex>,
+}
+
+#[async_trait]
+impl CustomTransformer for ReactRemovePropertiesTransformer {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        program.visit_mut_with(&mut RemoveProperties {
+            properties: &self.properties,
+        });
+        Ok(())
+    }
+}
+
+struct RemoveProperties<'a> {
+    properties: &'a [Regex],
+}
+
+impl VisitMut for RemoveProperties<'_> {
+    fn visit_mut_jsx_opening_element(&mut self, element: &mut JSXOpeningElement) {
+        element.attrs.retain(|attr| {
+            !matches!(attr, JSXAttrOrSpread::JSXAttr(JSXAttr {
+                name: JSXAttrName::Ident(ident),
+                ..
+            }) if self.properties.iter().any(|property| property.is_match(&ident.sym)))
+        });
+        element.visit_mut_children_with(self);
+    }
+}
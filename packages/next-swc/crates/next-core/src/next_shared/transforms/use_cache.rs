@@ -0,0 +1,58 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use next_transform_use_cache::{use_cache, Config};
+use swc_core::ecma::{ast::Program, visit::VisitMutWith};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::ecmascript::{CustomTransformer, OptionTransformPlugin, TransformContext},
+};
+
+use crate::next_config::NextConfig;
+
+/// Returns a transform plugin which compiles the `"use cache"` directive of
+/// server modules, for `experimental.useCache`. The cached functions are
+/// wrapped with `private-next-rsc-cache-wrapper`.
+#[turbo_tasks::function]
+pub async fn get_use_cache_transform_plugin(
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<OptionTransformPlugin>> {
+    if !*next_config.enable_use_cache().await? {
+        return Ok(Vc::cell(None));
+    }
+    Ok(Vc::cell(Some(Vc::cell(
+        Box::new(UseCacheTransformer { project_path }) as _,
+    ))))
+}
+
+#[derive(Debug)]
+struct UseCacheTransformer {
+    project_path: Vc<FileSystemPath>,
+}
+
+#[async_trait]
+impl CustomTransformer for UseCacheTransformer {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        // The ids are generated from the path relative to the project, like
+        // webpack does, so they don't depend on where the project is checked
+        // out.
+        let file_path = self
+            .project_path
+            .root()
+            .join(ctx.file_path_str.to_string())
+            .await?;
+        let relative_file_path_from_root = self
+            .project_path
+            .await?
+            .get_path_to(&file_path)
+            .unwrap_or(ctx.file_path_str)
+            .into();
+
+        program.visit_mut_with(&mut use_cache(Config {
+            is_server: true,
+            relative_file_path_from_root,
+        }));
+        Ok(())
+    }
+}
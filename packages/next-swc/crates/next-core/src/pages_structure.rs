@@ -5,7 +5,7 @@ use turbopack_binding::turbo::tasks_fs::{
     DirectoryContent, DirectoryEntry, FileSystemEntryType, FileSystemPath,
 };
 
-use crate::embed_js::next_js_file_path;
+use crate::{embed_js::next_js_file_path, util::resolve_directory_entry};
 
 /// A final route in the pages directory.
 #[turbo_tasks::value]
@@ -174,7 +174,7 @@ async fn get_pages_structure_for_root_directory(
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
             for (name, entry) in entries.iter() {
-                match entry {
+                match &resolve_directory_entry(*entry).await? {
                     DirectoryEntry::File(file_project_path) => {
                         let Some(basename) = page_basename(name, page_extensions_raw) else {
                             continue;
@@ -326,7 +326,7 @@ async fn get_pages_structure_for_directory(
     let dir_content = project_path.read_dir().await?;
     if let DirectoryContent::Entries(entries) = &*dir_content {
         for (name, entry) in entries.iter() {
-            match entry {
+            match &resolve_directory_entry(*entry).await? {
                 DirectoryEntry::File(file_project_path) => {
                     let Some(basename) = page_basename(name, page_extensions_raw) else {
                         continue;
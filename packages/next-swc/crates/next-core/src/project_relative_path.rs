@@ -0,0 +1,102 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{bail, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+
+/// The path of the project directory relative to the root directory of the
+/// project filesystem, with `/` separators.
+///
+/// The directories are passed in as system paths, which on Windows can mix
+/// `\` and `/` separators and disagree on the casing of the drive letter, e.g.
+/// `C:\repo\app` and `c:/repo`. Both are normalized before the project
+/// directory is resolved against the root, so the same project never ends up
+/// at two different paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectRelativePath(String);
+
+impl ProjectRelativePath {
+    pub fn new(root_dir: &str, project_dir: &str) -> Result<Self> {
+        let root = normalize_dir(root_dir);
+        let project = normalize_dir(project_dir);
+        if project == root {
+            return Ok(ProjectRelativePath(String::new()));
+        }
+        // Only strip whole components, `/repo` doesn't contain `/repository`.
+        let Some(relative) = project
+            .strip_prefix(&root)
+            .and_then(|relative| relative.strip_prefix('/'))
+        else {
+            bail!(
+                "the project directory ({}) must be inside of the root directory ({})",
+                project_dir,
+                root_dir
+            );
+        };
+        Ok(ProjectRelativePath(relative.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The project directory in the filesystem rooted at the root directory.
+    pub fn join(&self, root: Vc<FileSystemPath>) -> Vc<FileSystemPath> {
+        root.join(self.0.clone())
+    }
+}
+
+impl Display for ProjectRelativePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Uses `/` separators, lowercases the drive letter and removes trailing
+/// separators, so `/` becomes an empty string.
+fn normalize_dir(dir: &str) -> String {
+    let mut dir = dir.replace('\\', "/");
+    if let [drive, b':', ..] = dir.as_bytes() {
+        if drive.is_ascii_alphabetic() {
+            dir[..1].make_ascii_lowercase();
+        }
+    }
+    dir.trim_end_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(root_dir: &str, project_dir: &str) -> Option<String> {
+        ProjectRelativePath::new(root_dir, project_dir)
+            .ok()
+            .map(|path| path.to_string())
+    }
+
+    #[test]
+    fn test_project_relative_path() {
+        assert_eq!(
+            relative("/repo", "/repo/apps/web").as_deref(),
+            Some("apps/web")
+        );
+        assert_eq!(relative("/repo/", "/repo").as_deref(), Some(""));
+        assert_eq!(relative("/", "/repo").as_deref(), Some("repo"));
+        assert_eq!(relative("/repo", "/repository"), None);
+        assert_eq!(relative("/repo", "/other"), None);
+    }
+
+    #[test]
+    fn test_project_relative_path_windows() {
+        assert_eq!(
+            relative("C:\\repo", "c:\\repo\\apps\\web").as_deref(),
+            Some("apps/web")
+        );
+        assert_eq!(
+            relative("c:/repo", "C:\\repo/apps\\web\\").as_deref(),
+            Some("apps/web")
+        );
+        assert_eq!(relative("C:\\", "C:\\repo").as_deref(), Some("repo"));
+        assert_eq!(relative("C:\\repo", "D:\\repo"), None);
+    }
+}
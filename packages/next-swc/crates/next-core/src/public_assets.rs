@@ -0,0 +1,90 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemPath},
+    turbopack::core::issue::{Issue, IssueSeverity},
+};
+
+/// Lists the pathnames (e. g. `/images/logo.png`) of all files in the
+/// `public` directory of the project, sorted.
+#[turbo_tasks::function]
+pub async fn find_public_assets(project_path: Vc<FileSystemPath>) -> Result<Vc<Vec<String>>> {
+    Ok(get_public_assets_for_directory(
+        project_path.join("public".to_string()),
+        String::new(),
+    ))
+}
+
+#[turbo_tasks::function]
+async fn get_public_assets_for_directory(
+    dir: Vc<FileSystemPath>,
+    pathname: String,
+) -> Result<Vc<Vec<String>>> {
+    let mut assets = vec![];
+    if let DirectoryContent::Entries(entries) = &*dir.read_dir().await? {
+        for (name, entry) in entries.iter() {
+            let entry_pathname = format!("{pathname}/{name}");
+            match entry {
+                DirectoryEntry::File(_) => assets.push(entry_pathname),
+                DirectoryEntry::Directory(dir) => assets.extend(
+                    get_public_assets_for_directory(*dir, entry_pathname)
+                        .await?
+                        .iter()
+                        .cloned(),
+                ),
+                _ => {}
+            }
+        }
+    }
+    // Ensure deterministic order since read_dir is not deterministic
+    assets.sort();
+    Ok(Vc::cell(assets))
+}
+
+/// A file in the `public` directory is served at the same pathname as a
+/// route, so one of them is unreachable.
+#[turbo_tasks::value(shared)]
+pub struct PublicAssetConflictIssue {
+    pub project_path: Vc<FileSystemPath>,
+    pub pathname: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PublicAssetConflictIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("routing".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path.join(format!("public{}", self.pathname))
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "A conflicting public file and page file was found for path {}",
+            self.pathname
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "Files in the public directory are served at the same path as routes. Rename the \
+             public file or move the route."
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<String> {
+        Vc::cell("https://nextjs.org/docs/messages/conflicting-public-file-page".to_string())
+    }
+}
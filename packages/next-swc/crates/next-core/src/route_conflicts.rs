@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::issue::{Issue, IssueSeverity},
+        ecmascript::utils::FormatIter,
+    },
+};
+
+/// The path prefix Next.js serves its own assets from.
+const RESERVED_PREFIX: &str = "/_next";
+
+/// A route of the pages directory, as seen by [find_route_conflicts].
+pub struct PagesRoute<'a> {
+    pub pathname: &'a str,
+    /// Whether the route is an API route in `pages/api`.
+    pub is_api: bool,
+}
+
+/// Finds the routes which can't be reached: routes under the reserved `/_next`
+/// prefix, and routes of the app directory and the pages directory which
+/// resolve to the same path.
+pub fn find_route_conflicts<'a>(
+    app_pathnames: impl IntoIterator<Item = &'a str>,
+    pages_routes: impl IntoIterator<Item = PagesRoute<'a>>,
+) -> Vec<String> {
+    let mut conflicts = vec![];
+
+    let mut app_pathnames_set = HashSet::new();
+    for pathname in app_pathnames {
+        if is_reserved(pathname) {
+            conflicts.push(format!(
+                "The app route {} is shadowed by the reserved {} prefix",
+                pathname, RESERVED_PREFIX
+            ));
+        }
+        app_pathnames_set.insert(pathname);
+    }
+
+    for route in pages_routes {
+        let kind = if route.is_api { "pages/api" } else { "pages" };
+        if is_reserved(route.pathname) {
+            conflicts.push(format!(
+                "The {} route {} is shadowed by the reserved {} prefix",
+                kind, route.pathname, RESERVED_PREFIX
+            ));
+        }
+        if app_pathnames_set.contains(route.pathname) {
+            conflicts.push(format!(
+                "The app route and the {} route {} resolve to the same path",
+                kind, route.pathname
+            ));
+        }
+    }
+
+    conflicts
+}
+
+fn is_reserved(pathname: &str) -> bool {
+    pathname
+        .strip_prefix(RESERVED_PREFIX)
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Routes which can't be reached, reported together so all of them can be
+/// fixed at once.
+#[turbo_tasks::value(shared)]
+pub struct RouteConflictIssue {
+    pub project_path: Vc<FileSystemPath>,
+    pub conflicts: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for RouteConflictIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("routing".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Conflicting routes were found".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "These routes can't be reached:{}\n\nPaths under {} are reserved for the assets of \
+             Next.js, and a path can only be served by either the app or the pages directory. \
+             Rename or move the conflicting routes.",
+            FormatIter(|| self
+                .conflicts
+                .iter()
+                .flat_map(|conflict| vec!["\n- ", conflict.as_str()])),
+            RESERVED_PREFIX
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages_route(pathname: &str) -> PagesRoute<'_> {
+        PagesRoute {
+            pathname,
+            is_api: pathname.starts_with("/api"),
+        }
+    }
+
+    #[test]
+    fn test_find_route_conflicts() {
+        assert_eq!(
+            find_route_conflicts(
+                ["/", "/_next/data", "/api/hello", "/_nextjs"],
+                ["/about", "/api/hello", "/api/other", "/_next"]
+                    .into_iter()
+                    .map(pages_route),
+            ),
+            vec![
+                "The app route /_next/data is shadowed by the reserved /_next prefix",
+                "The app route and the pages/api route /api/hello resolve to the same path",
+                "The pages route /_next is shadowed by the reserved /_next prefix",
+            ]
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::issue::{Issue, IssueSeverity},
+};
+
+use crate::{
+    next_config::{Header, HeaderValue, Headers, RouteHas},
+    next_edge::route_regex::get_route_regex,
+};
+
+/// A `headers()` entry of next.config.js with its source compiled to a regex,
+/// in the format of the `headers` of the routes manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderRoute {
+    /// The index of the entry in `headers()`, which is also its index in the
+    /// `headers` of the routes manifest written by `next build`.
+    #[serde(skip)]
+    pub index: usize,
+    pub source: String,
+    pub regex: String,
+    pub headers: Vec<HeaderValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has: Option<Vec<RouteHas>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing: Option<Vec<RouteHas>>,
+}
+
+/// The compiled `headers()` of next.config.js, and the ones which can apply to
+/// each app route.
+#[turbo_tasks::value(shared, serialization = "none", eq = "manual")]
+#[derive(Debug, Default)]
+pub struct RouteHeaderTable {
+    pub routes: Vec<HeaderRoute>,
+    /// The regexes of the routes, `None` for the ones using features of
+    /// JavaScript regexes, like lookarounds, which can't be evaluated here.
+    #[turbo_tasks(trace_ignore)]
+    regexes: Vec<Option<Regex>>,
+    /// The indices in `headers()` of the routes which can match each app
+    /// route, keyed by its pathname. Whether they apply to a request still
+    /// depends on their `has` and `missing` conditions.
+    pub app_paths: IndexMap<String, Vec<usize>>,
+}
+
+impl PartialEq for RouteHeaderTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.routes == other.routes && self.app_paths == other.app_paths
+    }
+}
+
+impl Eq for RouteHeaderTable {}
+
+impl RouteHeaderTable {
+    fn new(routes: Vec<HeaderRoute>, app_pathnames: &[String]) -> Self {
+        let regexes = routes
+            .iter()
+            .map(|route| {
+                RegexBuilder::new(&route.regex)
+                    .case_insensitive(true)
+                    .build()
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        let app_paths = app_pathnames
+            .iter()
+            .map(|pathname| {
+                let route_regex = if pathname == "/" {
+                    Regex::new("^/$").ok()
+                } else {
+                    Regex::new(&get_route_regex(pathname).regex).ok()
+                };
+                let sample_pathname = sample_app_pathname(pathname);
+                let indices = routes
+                    .iter()
+                    .zip(&regexes)
+                    .filter(|(route, regex)| {
+                        let Some(regex) = regex else {
+                            return true;
+                        };
+                        // The header can apply to some requests of the route
+                        // when it matches a pathname of the route, or when
+                        // the route matches a pathname of the header.
+                        regex.is_match(&sample_pathname)
+                            || route_regex.as_ref().map_or(true, |route_regex| {
+                                route_regex.is_match(&sample_source(&route.source))
+                            })
+                    })
+                    .map(|(route, _)| route.index)
+                    .collect();
+                (pathname.clone(), indices)
+            })
+            .collect();
+        RouteHeaderTable {
+            routes,
+            regexes,
+            app_paths,
+        }
+    }
+
+    /// The routes whose source matches the pathname of a request, in the
+    /// order the headers are applied. Routes which can't be evaluated are
+    /// included.
+    pub fn matching<'a>(&'a self, pathname: &'a str) -> impl Iterator<Item = &'a HeaderRoute> {
+        self.routes
+            .iter()
+            .zip(&self.regexes)
+            .filter(move |(_, regex)| {
+                regex
+                    .as_ref()
+                    .map_or(true, |regex| regex.is_match(pathname))
+            })
+            .map(|(route, _)| route)
+    }
+
+    /// The routes which can apply to the app route with the given pathname.
+    pub fn for_app_path<'a>(&'a self, pathname: &str) -> impl Iterator<Item = &'a HeaderRoute> {
+        let indices = self.app_paths.get(pathname);
+        self.routes
+            .iter()
+            .filter(move |route| indices.map_or(false, |indices| indices.contains(&route.index)))
+    }
+}
+
+/// A pathname the app route serves, with its dynamic segments replaced by
+/// their names, e.g. `/blog/slug` for `/blog/[slug]`.
+fn sample_app_pathname(pathname: &str) -> String {
+    pathname
+        .split('/')
+        .map(|segment| {
+            segment
+                .strip_prefix('[')
+                .and_then(|segment| segment.strip_suffix(']'))
+                .map(|param| {
+                    param
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .trim_start_matches("...")
+                })
+                .unwrap_or(segment)
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A pathname matched by a header source, with its parameters replaced by
+/// their names, e.g. `/blog/slug` for `/blog/:slug`. Only used to match it
+/// against route regexes, which accept anything for dynamic segments.
+fn sample_source(source: &str) -> String {
+    let mut sample = String::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ':' => {
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    sample.push(c);
+                }
+                if chars.next_if_eq(&'(').is_some() {
+                    parse_pattern(&mut chars);
+                }
+                chars.next_if(|c| matches!(c, '?' | '*' | '+'));
+            }
+            '(' => {
+                parse_pattern(&mut chars);
+                chars.next_if(|c| matches!(c, '?' | '*' | '+'));
+                sample.push('_');
+            }
+            '\\' => sample.extend(chars.next()),
+            _ => sample.push(c),
+        }
+    }
+    sample
+}
+
+/// Compiles the `headers()` of next.config.js and matches them against the
+/// pathnames of the app routes. Invalid entries are reported and left out.
+#[turbo_tasks::function]
+pub async fn route_header_table(
+    project_path: Vc<FileSystemPath>,
+    headers: Vc<Headers>,
+    app_pathnames: Vc<Vec<String>>,
+) -> Result<Vc<RouteHeaderTable>> {
+    let mut routes = vec![];
+    for (index, header) in headers.await?.iter().enumerate() {
+        match compile_header(index, header) {
+            Ok(route) => routes.push(route),
+            Err(message) => InvalidHeaderIssue {
+                project_path,
+                source: header.source.clone(),
+                message,
+            }
+            .cell()
+            .emit(),
+        }
+    }
+
+    Ok(RouteHeaderTable::new(routes, &app_pathnames.await?).cell())
+}
+
+fn compile_header(index: usize, header: &Header) -> Result<HeaderRoute, String> {
+    for HeaderValue { key, value } in &header.headers {
+        if !is_valid_header_name(key) {
+            return Err(format!("`{key}` is not a valid header name"));
+        }
+        if !is_valid_header_value(value) {
+            return Err(format!(
+                "The value of the header `{key}` contains invalid characters"
+            ));
+        }
+    }
+    Ok(HeaderRoute {
+        index,
+        source: header.source.clone(),
+        regex: source_to_regex(&header.source)?,
+        headers: header.headers.clone(),
+        has: header.has.clone(),
+        missing: header.missing.clone(),
+    })
+}
+
+/// Header names are tokens, see RFC 7230.
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Matches the characters Node.js accepts in header values.
+fn is_valid_header_value(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| matches!(c, '\t' | ' '..='~' | '\u{80}'..='\u{ff}'))
+}
+
+const DEFAULT_PARAM_PATTERN: &str = "[^/#?]+?";
+
+/// Compiles a source in the path-to-regexp syntax of next.config.js, e.g.
+/// `/blog/:slug`, `/docs/:path*` or `/(.*)`, to the regex of the routes
+/// manifest.
+pub fn source_to_regex(source: &str) -> Result<String, String> {
+    if !source.starts_with('/') {
+        return Err(format!("The source `{source}` must start with `/`"));
+    }
+
+    let mut regex = "^".to_string();
+    let mut chars = source.chars().peekable();
+    // A `/` is held back until the next token, as it becomes the prefix of a
+    // following parameter.
+    let mut pending_slash = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '/' => {
+                if pending_slash {
+                    regex.push('/');
+                }
+                pending_slash = true;
+            }
+            ':' | '(' => {
+                if c == ':' {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if !(c.is_ascii_alphanumeric() || c == '_') {
+                            break;
+                        }
+                        name.push(c);
+                        chars.next();
+                    }
+                    if name.is_empty() {
+                        return Err(format!("Missing parameter name in the source `{source}`"));
+                    }
+                }
+                let pattern = if c == '(' || chars.next_if_eq(&'(').is_some() {
+                    parse_pattern(&mut chars)
+                        .ok_or_else(|| format!("Unbalanced pattern in the source `{source}`"))?
+                } else {
+                    DEFAULT_PARAM_PATTERN.to_string()
+                };
+                let modifier = chars.next_if(|c| matches!(c, '?' | '*' | '+'));
+                let prefix = if pending_slash { "/" } else { "" };
+                pending_slash = false;
+                if prefix.is_empty() {
+                    regex.push_str(&format!("({pattern})"));
+                    regex.extend(modifier);
+                    continue;
+                }
+                regex.push_str(&match modifier {
+                    None => format!("/({pattern})"),
+                    Some('?') => format!("(?:/({pattern}))?"),
+                    Some('+') => format!("/((?:{pattern})(?:/(?:{pattern}))*)"),
+                    Some(_) => format!("(?:/((?:{pattern})(?:/(?:{pattern}))*))?"),
+                });
+            }
+            _ => {
+                if pending_slash {
+                    regex.push('/');
+                    pending_slash = false;
+                }
+                let c = if c == '\\' {
+                    chars.next().unwrap_or('\\')
+                } else {
+                    c
+                };
+                regex.push_str(&regex::escape(&c.to_string()));
+            }
+        }
+    }
+    if pending_slash {
+        regex.push('/');
+    }
+    regex.push('$');
+    Ok(regex)
+}
+
+/// Parses a parenthesized pattern after the opening parenthesis, up to and
+/// excluding the matching closing one.
+fn parse_pattern(chars: &mut impl Iterator<Item = char>) -> Option<String> {
+    let mut pattern = String::new();
+    let mut depth = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                pattern.push(c);
+                pattern.push(chars.next()?);
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pattern);
+                }
+            }
+            _ => {}
+        }
+        pattern.push(c);
+    }
+    None
+}
+
+/// A `headers()` entry of next.config.js can't be compiled.
+#[turbo_tasks::value(shared)]
+pub struct InvalidHeaderIssue {
+    pub project_path: Vc<FileSystemPath>,
+    pub source: String,
+    pub message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for InvalidHeaderIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "Invalid `headers()` entry for the source {}",
+            self.source
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(self.message.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_to_regex() {
+        assert_eq!(
+            source_to_regex("/blog/:slug").unwrap(),
+            "^/blog/([^/#?]+?)$"
+        );
+        assert_eq!(
+            source_to_regex("/docs/:path*").unwrap(),
+            "^/docs(?:/((?:[^/#?]+?)(?:/(?:[^/#?]+?))*))?$"
+        );
+        assert_eq!(source_to_regex("/(.*)").unwrap(), "^/(.*)$");
+        assert_eq!(
+            source_to_regex("/:path((?!_next).*)").unwrap(),
+            "^/((?!_next).*)$"
+        );
+        assert_eq!(source_to_regex("/about.html").unwrap(), "^/about\\.html$");
+        assert!(source_to_regex("/:id(\\d+").is_err());
+        assert!(source_to_regex("blog").is_err());
+    }
+
+    fn table(sources: &[&str], app_pathnames: &[&str]) -> RouteHeaderTable {
+        let routes = sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| HeaderRoute {
+                index,
+                source: source.to_string(),
+                regex: source_to_regex(source).unwrap(),
+                headers: vec![],
+                has: None,
+                missing: None,
+            })
+            .collect();
+        let app_pathnames = app_pathnames
+            .iter()
+            .map(|pathname| pathname.to_string())
+            .collect::<Vec<_>>();
+        RouteHeaderTable::new(routes, &app_pathnames)
+    }
+
+    fn matching_sources(table: &RouteHeaderTable, pathname: &str) -> Vec<String> {
+        table
+            .matching(pathname)
+            .map(|route| route.source.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_matching() {
+        let table = table(&["/docs/:path*", "/Blog/:slug", "/:path((?!_next).*)"], &[]);
+        assert_eq!(
+            matching_sources(&table, "/docs"),
+            vec!["/docs/:path*", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            matching_sources(&table, "/docs/a/b"),
+            vec!["/docs/:path*", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            matching_sources(&table, "/blog/first"),
+            vec!["/Blog/:slug", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            matching_sources(&table, "/docsearch"),
+            vec!["/:path((?!_next).*)"]
+        );
+    }
+
+    #[test]
+    fn test_app_paths() {
+        let table = table(
+            &[
+                "/blog/:slug",
+                "/blog/first",
+                "/docs/:path*",
+                "/(.*)",
+                "/posts/:id(\\d+)",
+                "/:path((?!_next).*)",
+            ],
+            &[
+                "/",
+                "/blog/[slug]",
+                "/blog/first",
+                "/docs/[[...slug]]",
+                "/posts/new",
+                "/posts/[id]",
+            ],
+        );
+        let app_paths = |pathname: &str| {
+            table
+                .for_app_path(pathname)
+                .map(|route| route.source.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(app_paths("/"), vec!["/(.*)", "/:path((?!_next).*)"]);
+        assert_eq!(
+            app_paths("/blog/[slug]"),
+            vec!["/blog/:slug", "/blog/first", "/(.*)", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            app_paths("/blog/first"),
+            vec!["/blog/:slug", "/blog/first", "/(.*)", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            app_paths("/docs/[[...slug]]"),
+            vec!["/docs/:path*", "/(.*)", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            app_paths("/posts/new"),
+            vec!["/(.*)", "/:path((?!_next).*)"]
+        );
+        assert_eq!(
+            app_paths("/posts/[id]"),
+            vec!["/(.*)", "/posts/:id(\\d+)", "/:path((?!_next).*)"]
+        );
+        assert_eq!(app_paths("/unknown"), Vec::<String>::new());
+        assert_eq!(table.app_paths["/posts/[id]"], vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_sample_pathnames() {
+        assert_eq!(sample_app_pathname("/blog/[slug]"), "/blog/slug");
+        assert_eq!(sample_app_pathname("/docs/[[...path]]"), "/docs/path");
+        assert_eq!(sample_app_pathname("/shop/[...item]/buy"), "/shop/item/buy");
+        assert_eq!(sample_source("/blog/:slug"), "/blog/slug");
+        assert_eq!(sample_source("/docs/:path*"), "/docs/path");
+        assert_eq!(sample_source("/posts/:id(\\d+)/edit"), "/posts/id/edit");
+        assert_eq!(sample_source("/(.*)"), "/_");
+        assert_eq!(sample_source("/about\\.html"), "/about.html");
+    }
+
+    #[test]
+    fn test_header_syntax() {
+        assert!(is_valid_header_name("X-Frame-Options"));
+        assert!(!is_valid_header_name("X Frame"));
+        assert!(!is_valid_header_name(""));
+        assert!(is_valid_header_value("max-age=63072000; includeSubDomains"));
+        assert!(!is_valid_header_value("a\r\nSet-Cookie: b"));
+    }
+}
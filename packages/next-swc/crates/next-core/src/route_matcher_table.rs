@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{next_edge::route_regex::get_route_regex, url_node::get_sorted_routes};
+
+/// Whether a route is served by the app or the pages router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RouteMatcherKind {
+    App,
+    Pages,
+}
+
+/// A parameter of a dynamic route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteMatcherParam {
+    pub name: String,
+    /// A catch-all parameter, i.e. `[...name]`.
+    pub repeat: bool,
+    /// An optional catch-all parameter, i.e. `[[...name]]`.
+    pub optional: bool,
+}
+
+/// A route with dynamic segments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicRouteMatcher {
+    pub page: String,
+    pub kind: RouteMatcherKind,
+    /// Matches the pathnames of the route, with one capture group per
+    /// parameter. Uses the syntax shared by JavaScript and Rust regexes.
+    pub regex: String,
+    /// The parameters, in the order of the capture groups.
+    pub params: Vec<RouteMatcherParam>,
+}
+
+/// All routes of an application, computed at build time so servers don't
+/// need to compile a regex for every route on every request. Static routes
+/// are looked up by pathname, and only when none matches the dynamic routes
+/// are tried, in order.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteMatcherTable {
+    pub static_routes: BTreeMap<String, RouteMatcherKind>,
+    /// Sorted like the router sorts them, most specific first.
+    pub dynamic_routes: Vec<DynamicRouteMatcher>,
+}
+
+impl RouteMatcherTable {
+    /// Builds the table for the normalized pathnames of the app routes and
+    /// the pages. App routes take precedence over pages with the same
+    /// pathname, and internal pages like `/_app` are left out.
+    pub fn new(app_routes: &[String], pages: &[String]) -> Result<Self> {
+        let mut kinds = IndexMap::new();
+        for route in app_routes {
+            kinds.insert(route.clone(), RouteMatcherKind::App);
+        }
+        for page in pages {
+            if !page.starts_with("/_") {
+                kinds.entry(page.clone()).or_insert(RouteMatcherKind::Pages);
+            }
+        }
+
+        let mut table = RouteMatcherTable::default();
+        for page in get_sorted_routes(&kinds.keys().cloned().collect::<Vec<_>>())? {
+            let kind = kinds[&page];
+            if !page.contains('[') {
+                table.static_routes.insert(page, kind);
+                continue;
+            }
+            let route_regex = get_route_regex(&page);
+            let mut params = route_regex.groups.into_iter().collect::<Vec<_>>();
+            params.sort_by_key(|(_, group)| group.pos);
+            table.dynamic_routes.push(DynamicRouteMatcher {
+                page,
+                kind,
+                regex: route_regex.regex,
+                params: params
+                    .into_iter()
+                    .map(|(name, group)| RouteMatcherParam {
+                        name,
+                        repeat: group.repeat,
+                        optional: group.optional,
+                    })
+                    .collect(),
+            });
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_matcher_table() {
+        let table = RouteMatcherTable::new(
+            &[
+                "/".to_string(),
+                "/blog/[slug]".to_string(),
+                "/docs/[[...path]]".to_string(),
+            ],
+            &[
+                "/_app".to_string(),
+                "/".to_string(),
+                "/blog/[slug]/[...rest]".to_string(),
+                "/about".to_string(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            table.static_routes,
+            BTreeMap::from([
+                ("/".to_string(), RouteMatcherKind::App),
+                ("/about".to_string(), RouteMatcherKind::Pages),
+            ])
+        );
+        assert_eq!(
+            table
+                .dynamic_routes
+                .iter()
+                .map(|route| (route.page.as_str(), route.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/blog/[slug]", RouteMatcherKind::App),
+                ("/blog/[slug]/[...rest]", RouteMatcherKind::Pages),
+                ("/docs/[[...path]]", RouteMatcherKind::App),
+            ]
+        );
+
+        let route = &table.dynamic_routes[1];
+        assert_eq!(route.regex, r"^/blog/([^/]+?)/(.+?)(?:/)?$");
+        assert_eq!(
+            route.params,
+            vec![
+                RouteMatcherParam {
+                    name: "slug".to_string(),
+                    repeat: false,
+                    optional: false,
+                },
+                RouteMatcherParam {
+                    name: "rest".to_string(),
+                    repeat: true,
+                    optional: false,
+                },
+            ]
+        );
+        assert!(regex::Regex::new(&route.regex)
+            .unwrap()
+            .is_match("/blog/hello/a/b"));
+    }
+}
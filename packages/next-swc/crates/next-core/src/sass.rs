@@ -1,9 +1,36 @@
-use anyhow::{bail, Result};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, bail, Result};
+use codemap::SpanLoc;
 use serde_json::Value as JsonValue;
-use turbo_tasks::Vc;
-use turbopack_binding::turbopack::{
-    node::transforms::webpack::WebpackLoaderItem,
-    turbopack::module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules},
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{to_sys_path, FileContent, FileSystemEntryType, FileSystemPath},
+    turbopack::{
+        core::{
+            asset::{Asset, AssetContent},
+            context::AssetContext,
+            ident::AssetIdent,
+            issue::{Issue, IssueExt, IssueSeverity},
+            module::Module,
+            reference_type::ReferenceType,
+            resolve::ModulePart,
+            source::Source,
+        },
+        node::transforms::webpack::WebpackLoaderItem,
+        turbopack::{
+            module_options::{
+                CustomModuleType, LoaderRuleItem, ModuleRule, ModuleRuleCondition,
+                ModuleRuleEffect, ModuleType, OptionWebpackRules, WebpackRules,
+            },
+            ModuleAssetContext,
+        },
+    },
 };
 
 #[turbo_tasks::function]
@@ -66,3 +93,337 @@ pub async fn maybe_add_sass_loader(
 
     Ok(Vc::cell(Some(Vc::cell(rules))))
 }
+
+/// Returns the rules which compile Sass files with the embedded grass
+/// compiler instead of sass-loader, when `experimental.turbo.useGrass` is
+/// enabled.
+pub fn get_grass_rules(sass_options: Vc<JsonValue>) -> Vec<ModuleRule> {
+    // The last matching rule determines the module type, so CSS Modules come last.
+    [
+        (".scss", "*.css"),
+        (".sass", "*.css"),
+        (".module.scss", "*.module.css"),
+        (".module.sass", "*.module.css"),
+    ]
+    .into_iter()
+    .map(|(extension, rename_as)| {
+        ModuleRule::new(
+            ModuleRuleCondition::ResourcePathEndsWith(extension.to_string()),
+            vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                Vc::upcast(GrassModuleType::new(sass_options, rename_as.to_string())),
+            ))],
+        )
+    })
+    .collect()
+}
+
+#[turbo_tasks::value]
+struct GrassModuleType {
+    sass_options: Vc<JsonValue>,
+    rename_as: String,
+}
+
+#[turbo_tasks::value_impl]
+impl GrassModuleType {
+    #[turbo_tasks::function]
+    fn new(sass_options: Vc<JsonValue>, rename_as: String) -> Vc<Self> {
+        GrassModuleType {
+            sass_options,
+            rename_as,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for GrassModuleType {
+    #[turbo_tasks::function]
+    fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Vc<Box<dyn Module>> {
+        context.process(
+            Vc::upcast(
+                GrassSource {
+                    source,
+                    sass_options: self.sass_options,
+                    rename_as: self.rename_as.clone(),
+                }
+                .cell(),
+            ),
+            Value::new(ReferenceType::Undefined),
+        )
+    }
+}
+
+/// A source which compiles a Sass file to CSS.
+///
+/// The files imported by the stylesheet are read through the turbo-tasks
+/// filesystem, so changes to partials invalidate the compiled CSS.
+#[turbo_tasks::value]
+struct GrassSource {
+    source: Vc<Box<dyn Source>>,
+    sass_options: Vc<JsonValue>,
+    rename_as: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for GrassSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().rename_as(self.rename_as.clone())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for GrassSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let path = self.source.ident().path();
+        let AssetContent::File(file) = *self.source.content().await? else {
+            bail!("Sass source is not a file");
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            bail!("Sass source {} not found", path.to_string().await?);
+        };
+        let root = path.await?.fs.root();
+        let (Some(sys_path), Some(root_sys_path)) =
+            (to_sys_path(path).await?, to_sys_path(root).await?)
+        else {
+            bail!("grass requires a disk path to compile Sass files");
+        };
+
+        let sass_options = self.sass_options.await?;
+        let include_paths: Vec<&str> = sass_options["includePaths"]
+            .as_array()
+            .map(|paths| paths.iter().filter_map(|path| path.as_str()).collect())
+            .unwrap_or_default();
+        let quiet_deps = sass_options["quietDeps"].as_bool().unwrap_or(false);
+
+        let mut fs = GrassFs::default();
+        fs.entries.insert(
+            sys_path.clone(),
+            GrassFsEntry::File(file.content().to_bytes()?.into_owned()),
+        );
+
+        // grass reads files synchronously, while turbo-tasks reads them
+        // asynchronously. The stylesheet is compiled until all the files grass
+        // asked for have been read, each pass reading the ones it was missing.
+        loop {
+            let logger = GrassLogger::default();
+            let result = grass::from_path(
+                &sys_path,
+                &grass::Options::default()
+                    .fs(&fs)
+                    .logger(&logger)
+                    .load_paths(&include_paths),
+            )
+            .map_err(|err| err.to_string());
+
+            let missing = std::mem::take(&mut *fs.missing.lock().unwrap());
+            if missing.is_empty() {
+                let css = result
+                    .map_err(|err| anyhow!("failed to compile {}: {}", sys_path.display(), err))?;
+                for (file, message) in logger.warnings.into_inner().unwrap() {
+                    if quiet_deps
+                        && is_deprecation(&message)
+                        && is_dependency(&file, &include_paths)
+                    {
+                        continue;
+                    }
+                    GrassWarningIssue {
+                        path,
+                        file,
+                        message,
+                    }
+                    .cell()
+                    .emit();
+                }
+                return Ok(AssetContent::file(FileContent::Content(css.into()).cell()));
+            }
+
+            for missing_path in missing {
+                let entry = read_entry(root, &root_sys_path, &missing_path).await?;
+                fs.entries.insert(missing_path, entry);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum GrassFsEntry {
+    File(Vec<u8>),
+    Directory,
+    NotFound,
+}
+
+/// The files grass can read while compiling a stylesheet. Paths which haven't
+/// been read yet are recorded as missing.
+#[derive(Debug, Default)]
+struct GrassFs {
+    entries: HashMap<PathBuf, GrassFsEntry>,
+    missing: Mutex<Vec<PathBuf>>,
+}
+
+impl GrassFs {
+    fn entry(&self, path: &Path) -> Option<&GrassFsEntry> {
+        let entry = self.entries.get(path);
+        if entry.is_none() {
+            self.missing.lock().unwrap().push(path.to_path_buf());
+        }
+        entry
+    }
+}
+
+impl grass::Fs for GrassFs {
+    fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.entry(path), Some(GrassFsEntry::Directory))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        matches!(self.entry(path), Some(GrassFsEntry::File(_)))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.entry(path) {
+            Some(GrassFsEntry::File(content)) => Ok(content.clone()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )),
+        }
+    }
+}
+
+/// Reads a path grass asked for through the filesystem of the stylesheet. Paths
+/// outside of it are treated as missing.
+async fn read_entry(
+    root: Vc<FileSystemPath>,
+    root_sys_path: &Path,
+    path: &Path,
+) -> Result<GrassFsEntry> {
+    let relative = if path.is_absolute() {
+        match path.strip_prefix(root_sys_path) {
+            Ok(relative) => relative,
+            Err(_) => return Ok(GrassFsEntry::NotFound),
+        }
+    } else {
+        path
+    };
+    let Some(relative) = relative.to_str() else {
+        return Ok(GrassFsEntry::NotFound);
+    };
+    let Some(fs_path) = *root.try_join(relative.replace('\\', "/")).await? else {
+        return Ok(GrassFsEntry::NotFound);
+    };
+
+    Ok(match *fs_path.get_type().await? {
+        FileSystemEntryType::Directory => GrassFsEntry::Directory,
+        FileSystemEntryType::File | FileSystemEntryType::Symlink => match &*fs_path.read().await? {
+            FileContent::Content(file) => {
+                GrassFsEntry::File(file.content().to_bytes()?.into_owned())
+            }
+            FileContent::NotFound => GrassFsEntry::NotFound,
+        },
+        _ => GrassFsEntry::NotFound,
+    })
+}
+
+/// Collects the warnings of a compilation, so they can be reported as issues.
+#[derive(Debug, Default)]
+struct GrassLogger {
+    /// The file each warning comes from, and its message.
+    warnings: Mutex<Vec<(String, String)>>,
+}
+
+impl grass::Logger for GrassLogger {
+    fn debug(&self, _location: SpanLoc, _message: &str) {}
+
+    fn warn(&self, location: SpanLoc, message: &str) {
+        self.warnings
+            .lock()
+            .unwrap()
+            .push((location.file.name().to_string(), message.to_string()));
+    }
+}
+
+fn is_deprecation(message: &str) -> bool {
+    message.contains("deprecated")
+}
+
+/// Whether a file is a dependency of the project, which `quietDeps` silences
+/// the deprecation warnings of, like Dart Sass does.
+fn is_dependency(file: &str, include_paths: &[&str]) -> bool {
+    Path::new(file)
+        .components()
+        .any(|component| component.as_os_str() == "node_modules")
+        || include_paths
+            .iter()
+            .any(|include_path| Path::new(file).starts_with(include_path))
+}
+
+#[turbo_tasks::value(shared)]
+struct GrassWarningIssue {
+    path: Vc<FileSystemPath>,
+    /// The file the warning comes from, which can be a partial imported by
+    /// `path`.
+    file: String,
+    message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for GrassWarningIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("css".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell("Sass warning".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!("{}\n\nin {}", self.message, self.file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_dependency, is_deprecation};
+
+    #[test]
+    fn test_quiet_deps() {
+        assert!(is_deprecation(
+            "Using / for division is deprecated and will be removed in Dart Sass 2.0.0."
+        ));
+        assert!(!is_deprecation("custom @warn message"));
+
+        assert!(is_dependency(
+            "/project/node_modules/bootstrap/scss/_functions.scss",
+            &[]
+        ));
+        assert!(is_dependency(
+            "/project/vendor/styles/_mixins.scss",
+            &["/project/vendor"]
+        ));
+        assert!(!is_dependency(
+            "/project/styles/_mixins.scss",
+            &["/project/vendor"]
+        ));
+        assert!(!is_dependency("/project/my_node_modules/_a.scss", &[]));
+    }
+}
@@ -0,0 +1,481 @@
+use anyhow::{anyhow, bail, Result};
+use graphql_parser::query as gql;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileContent,
+    turbopack::{
+        core::{
+            asset::{Asset, AssetContent},
+            context::AssetContext,
+            ident::AssetIdent,
+            module::Module,
+            reference_type::ReferenceType,
+            resolve::ModulePart,
+            source::Source,
+        },
+        turbopack::{
+            module_options::{
+                CustomModuleType, ModuleRule, ModuleRuleCondition, ModuleRuleEffect, ModuleType,
+                OptionWebpackRules,
+            },
+            ModuleAssetContext,
+        },
+    },
+};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, TaskInput, TraceRawVcs, Serialize, Deserialize)]
+enum StructuredDataFormat {
+    Yaml,
+    Toml,
+    Graphql,
+}
+
+/// Returns the rules which parse YAML, TOML and GraphQL files at build time
+/// and turn them into JSON modules, when `experimental.turbo.structuredData`
+/// is enabled.
+///
+/// Extensions which are configured in `experimental.turbo.rules` are left to
+/// the configured loaders. Other files can use these module types by renaming
+/// them with `as`, e. g. `as: "*.yaml"`.
+pub async fn get_structured_data_rules(
+    webpack_rules: Vc<OptionWebpackRules>,
+) -> Result<Vec<ModuleRule>> {
+    let webpack_rules = &*webpack_rules.await?;
+    let configured = match webpack_rules {
+        Some(rules) => rules.await?.keys().cloned().collect(),
+        None => vec![],
+    };
+
+    Ok([
+        (".yaml", StructuredDataFormat::Yaml),
+        (".yml", StructuredDataFormat::Yaml),
+        (".toml", StructuredDataFormat::Toml),
+        (".graphql", StructuredDataFormat::Graphql),
+        (".gql", StructuredDataFormat::Graphql),
+    ]
+    .into_iter()
+    .filter(|(extension, _)| !configured.contains(&format!("*{extension}")))
+    .map(|(extension, format)| {
+        ModuleRule::new(
+            ModuleRuleCondition::ResourcePathEndsWith(extension.to_string()),
+            vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+                Vc::upcast(StructuredDataModuleType::new(format)),
+            ))],
+        )
+    })
+    .collect())
+}
+
+#[turbo_tasks::value]
+struct StructuredDataModuleType {
+    format: StructuredDataFormat,
+}
+
+#[turbo_tasks::value_impl]
+impl StructuredDataModuleType {
+    #[turbo_tasks::function]
+    fn new(format: StructuredDataFormat) -> Vc<Self> {
+        StructuredDataModuleType { format }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for StructuredDataModuleType {
+    #[turbo_tasks::function]
+    fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Vc<Box<dyn Module>> {
+        context.process(
+            Vc::upcast(
+                StructuredDataSource {
+                    source,
+                    format: self.format,
+                }
+                .cell(),
+            ),
+            Value::new(ReferenceType::Undefined),
+        )
+    }
+}
+
+/// A source which converts a structured data file to JSON.
+#[turbo_tasks::value]
+struct StructuredDataSource {
+    source: Vc<Box<dyn Source>>,
+    format: StructuredDataFormat,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for StructuredDataSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().rename_as("*.json".to_string())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for StructuredDataSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let path = self.source.ident().path().await?;
+        let AssetContent::File(file) = *self.source.content().await? else {
+            bail!("{} is not a file", path.path);
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            bail!("{} not found", path.path);
+        };
+        let content = file.content().to_str()?;
+
+        let value: JsonValue = match self.format {
+            StructuredDataFormat::Yaml => serde_yaml::from_str(&content)
+                .map_err(|err| anyhow!("failed to parse {}: {}", path.path, err))?,
+            StructuredDataFormat::Toml => toml::from_str(&content)
+                .map_err(|err| anyhow!("failed to parse {}: {}", path.path, err))?,
+            StructuredDataFormat::Graphql => graphql_document(&content)
+                .map_err(|err| anyhow!("failed to parse {}: {}", path.path, err))?,
+        };
+
+        Ok(AssetContent::file(
+            FileContent::Content(serde_json::to_string(&value)?.into()).cell(),
+        ))
+    }
+}
+
+/// Parses a GraphQL document into the AST format of graphql-js, which is what
+/// `graphql-tag` and GraphQL clients expect.
+fn graphql_document(source: &str) -> Result<JsonValue> {
+    let (sanitized, int_literals) = extract_int_literals(source);
+    let document = gql::parse_query::<String>(&sanitized)?;
+    let ints = &mut int_literals.into_iter();
+    Ok(json!({
+        "kind": "Document",
+        "definitions": document
+            .definitions
+            .iter()
+            .map(|definition| graphql_definition(definition, ints))
+            .collect::<Vec<_>>(),
+        "loc": { "start": 0, "end": source.len(), "source": { "body": source } },
+    }))
+}
+
+/// The integer literals of a GraphQL document, in the order they are written.
+type IntLiterals = std::vec::IntoIter<String>;
+
+/// graphql-parser stores integers as `i64` and fails to parse larger ones,
+/// while graphql-js keeps them as they are written. Replaces every integer
+/// literal of the document with `0`, and returns them in document order so the
+/// AST can be built with the original values.
+fn extract_int_literals(source: &str) -> (String, Vec<String>) {
+    let chars: Vec<char> = source.chars().collect();
+    let mut sanitized = String::with_capacity(source.len());
+    let mut literals = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' && chars[i] != '\r' {
+                    i += 1;
+                }
+            }
+            '"' if chars[i..].starts_with(&['"', '"', '"']) => {
+                i += 3;
+                while i < chars.len() && !chars[i..].starts_with(&['"', '"', '"']) {
+                    i += if chars[i..].starts_with(&['\\', '"', '"', '"']) {
+                        4
+                    } else {
+                        1
+                    };
+                }
+                i += 3;
+            }
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' && chars[i] != '\n' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                if i < chars.len() && matches!(chars[i], '.' | 'e' | 'E') {
+                    // A float, graphql-parser keeps those as `f64`.
+                    while i < chars.len()
+                        && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | 'e' | 'E' | '+'))
+                        || (i < chars.len() && chars[i] == '-' && matches!(chars[i - 1], 'e' | 'E'))
+                    {
+                        i += 1;
+                    }
+                } else if i > start + 1 || c != '-' {
+                    literals.push(chars[start..i].iter().collect());
+                    sanitized.push('0');
+                    continue;
+                }
+            }
+            _ => i += 1,
+        }
+        let end = i.min(chars.len());
+        sanitized.extend(&chars[start..end]);
+        i = end;
+    }
+
+    (sanitized, literals)
+}
+
+fn graphql_name(name: &str) -> JsonValue {
+    json!({ "kind": "Name", "value": name })
+}
+
+fn graphql_definition(definition: &gql::Definition<String>, ints: &mut IntLiterals) -> JsonValue {
+    let (operation, name, variable_definitions, directives, selection_set) = match definition {
+        gql::Definition::Fragment(fragment) => {
+            let gql::TypeCondition::On(type_condition) = &fragment.type_condition;
+            return json!({
+                "kind": "FragmentDefinition",
+                "name": graphql_name(&fragment.name),
+                "typeCondition": { "kind": "NamedType", "name": graphql_name(type_condition) },
+                "directives": graphql_directives(&fragment.directives, ints),
+                "selectionSet": graphql_selection_set(&fragment.selection_set, ints),
+            });
+        }
+        gql::Definition::Operation(gql::OperationDefinition::SelectionSet(selection_set)) => {
+            ("query", None, &[][..], &[][..], selection_set)
+        }
+        gql::Definition::Operation(gql::OperationDefinition::Query(query)) => (
+            "query",
+            query.name.as_deref(),
+            &query.variable_definitions[..],
+            &query.directives[..],
+            &query.selection_set,
+        ),
+        gql::Definition::Operation(gql::OperationDefinition::Mutation(mutation)) => (
+            "mutation",
+            mutation.name.as_deref(),
+            &mutation.variable_definitions[..],
+            &mutation.directives[..],
+            &mutation.selection_set,
+        ),
+        gql::Definition::Operation(gql::OperationDefinition::Subscription(subscription)) => (
+            "subscription",
+            subscription.name.as_deref(),
+            &subscription.variable_definitions[..],
+            &subscription.directives[..],
+            &subscription.selection_set,
+        ),
+    };
+    json!({
+        "kind": "OperationDefinition",
+        "operation": operation,
+        "name": name.map(graphql_name),
+        "variableDefinitions": variable_definitions
+            .iter()
+            .map(|variable| json!({
+                "kind": "VariableDefinition",
+                "variable": { "kind": "Variable", "name": graphql_name(&variable.name) },
+                "type": graphql_type(&variable.var_type),
+                "defaultValue": variable
+                    .default_value
+                    .as_ref()
+                    .map(|value| graphql_value(value, ints)),
+                "directives": [],
+            }))
+            .collect::<Vec<_>>(),
+        "directives": graphql_directives(directives, ints),
+        "selectionSet": graphql_selection_set(selection_set, ints),
+    })
+}
+
+fn graphql_selection_set(
+    selection_set: &gql::SelectionSet<String>,
+    ints: &mut IntLiterals,
+) -> JsonValue {
+    let selections: Vec<_> = selection_set
+        .items
+        .iter()
+        .map(|selection| match selection {
+            gql::Selection::Field(field) => json!({
+                "kind": "Field",
+                "alias": field.alias.as_deref().map(graphql_name),
+                "name": graphql_name(&field.name),
+                "arguments": graphql_arguments(&field.arguments, ints),
+                "directives": graphql_directives(&field.directives, ints),
+                "selectionSet": (!field.selection_set.items.is_empty())
+                    .then(|| graphql_selection_set(&field.selection_set, ints)),
+            }),
+            gql::Selection::FragmentSpread(spread) => json!({
+                "kind": "FragmentSpread",
+                "name": graphql_name(&spread.fragment_name),
+                "directives": graphql_directives(&spread.directives, ints),
+            }),
+            gql::Selection::InlineFragment(fragment) => json!({
+                "kind": "InlineFragment",
+                "typeCondition": fragment.type_condition.as_ref().map(
+                    |gql::TypeCondition::On(name)| json!({
+                        "kind": "NamedType",
+                        "name": graphql_name(name),
+                    })
+                ),
+                "directives": graphql_directives(&fragment.directives, ints),
+                "selectionSet": graphql_selection_set(&fragment.selection_set, ints),
+            }),
+        })
+        .collect();
+    json!({ "kind": "SelectionSet", "selections": selections })
+}
+
+fn graphql_arguments(
+    arguments: &[(String, gql::Value<String>)],
+    ints: &mut IntLiterals,
+) -> JsonValue {
+    arguments
+        .iter()
+        .map(|(name, value)| {
+            json!({
+                "kind": "Argument",
+                "name": graphql_name(name),
+                "value": graphql_value(value, ints),
+            })
+        })
+        .collect()
+}
+
+fn graphql_directives(directives: &[gql::Directive<String>], ints: &mut IntLiterals) -> JsonValue {
+    directives
+        .iter()
+        .map(|directive| {
+            json!({
+                "kind": "Directive",
+                "name": graphql_name(&directive.name),
+                "arguments": graphql_arguments(&directive.arguments, ints),
+            })
+        })
+        .collect()
+}
+
+fn graphql_type(ty: &gql::Type<String>) -> JsonValue {
+    match ty {
+        gql::Type::NamedType(name) => json!({ "kind": "NamedType", "name": graphql_name(name) }),
+        gql::Type::ListType(ty) => json!({ "kind": "ListType", "type": graphql_type(ty) }),
+        gql::Type::NonNullType(ty) => json!({ "kind": "NonNullType", "type": graphql_type(ty) }),
+    }
+}
+
+fn graphql_value(value: &gql::Value<String>, ints: &mut IntLiterals) -> JsonValue {
+    match value {
+        gql::Value::Variable(name) => json!({ "kind": "Variable", "name": graphql_name(name) }),
+        // graphql-js keeps numbers as they are written.
+        gql::Value::Int(_) => json!({
+            "kind": "IntValue",
+            "value": ints.next().unwrap_or_default(),
+        }),
+        gql::Value::Float(number) => json!({ "kind": "FloatValue", "value": number.to_string() }),
+        gql::Value::String(string) => json!({ "kind": "StringValue", "value": string }),
+        gql::Value::Boolean(boolean) => json!({ "kind": "BooleanValue", "value": boolean }),
+        gql::Value::Null => json!({ "kind": "NullValue" }),
+        gql::Value::Enum(name) => json!({ "kind": "EnumValue", "value": name }),
+        gql::Value::List(values) => json!({
+            "kind": "ListValue",
+            "values": values
+                .iter()
+                .map(|value| graphql_value(value, ints))
+                .collect::<Vec<_>>(),
+        }),
+        gql::Value::Object(fields) => json!({
+            "kind": "ObjectValue",
+            "fields": fields
+                .iter()
+                .map(|(name, value)| json!({
+                    "kind": "ObjectField",
+                    "name": graphql_name(name),
+                    "value": graphql_value(value, ints),
+                }))
+                .collect::<Vec<_>>(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphql_document() {
+        let document = graphql_document(
+            "query User($id: ID!) { user(id: $id) { name ...Avatar } }\nfragment Avatar on User { \
+             avatar(size: 64) }",
+        )
+        .unwrap();
+        let definitions = document["definitions"].as_array().unwrap();
+        assert_eq!(definitions.len(), 2);
+
+        let query = &definitions[0];
+        assert_eq!(query["operation"], "query");
+        assert_eq!(query["name"]["value"], "User");
+        assert_eq!(
+            query["variableDefinitions"][0]["type"],
+            json!({
+                "kind": "NonNullType",
+                "type": { "kind": "NamedType", "name": { "kind": "Name", "value": "ID" } },
+            })
+        );
+        let user = &query["selectionSet"]["selections"][0];
+        assert_eq!(user["arguments"][0]["value"]["kind"], "Variable");
+        assert_eq!(
+            user["selectionSet"]["selections"][1]["kind"],
+            "FragmentSpread"
+        );
+
+        let fragment = &definitions[1];
+        assert_eq!(fragment["kind"], "FragmentDefinition");
+        assert_eq!(
+            fragment["selectionSet"]["selections"][0]["arguments"][0]["value"],
+            json!({ "kind": "IntValue", "value": "64" })
+        );
+    }
+
+    #[test]
+    fn test_graphql_int_values() {
+        let document = graphql_document(
+            "# 1 comment\nquery Q($first: Int = 10) { items(first: $first, after: \
+             123456789012345678901234567890, ratio: 1.5e-3, label: \"a1 2\", sizes: [-1, 2]) { \
+             id2 } }",
+        )
+        .unwrap();
+        let query = &document["definitions"][0];
+        assert_eq!(
+            query["variableDefinitions"][0]["defaultValue"],
+            json!({ "kind": "IntValue", "value": "10" })
+        );
+        let arguments = &query["selectionSet"]["selections"][0]["arguments"];
+        assert_eq!(
+            arguments[1]["value"],
+            json!({ "kind": "IntValue", "value": "123456789012345678901234567890" })
+        );
+        assert_eq!(arguments[2]["value"]["kind"], "FloatValue");
+        assert_eq!(
+            arguments[3]["value"],
+            json!({ "kind": "StringValue", "value": "a1 2" })
+        );
+        assert_eq!(
+            arguments[4]["value"]["values"],
+            json!([
+                { "kind": "IntValue", "value": "-1" },
+                { "kind": "IntValue", "value": "2" },
+            ])
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileContent,
+    turbopack::{
+        core::{
+            asset::{Asset, AssetContent},
+            context::AssetContext,
+            ident::AssetIdent,
+            module::Module,
+            reference_type::ReferenceType,
+            resolve::ModulePart,
+            source::Source,
+        },
+        ecmascript::utils::StringifyJs,
+        turbopack::{
+            module_options::{
+                CustomModuleType, ModuleRule, ModuleRuleCondition, ModuleRuleEffect, ModuleType,
+            },
+            ModuleAssetContext,
+        },
+    },
+};
+
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Returns a rule which turns imported SVG files into React components, like
+/// `@svgr/webpack`, when `experimental.turbo.svgr` is enabled.
+///
+/// It needs to come after the image rule, the last matching rule determines
+/// the module type.
+pub fn get_svg_component_rule() -> ModuleRule {
+    ModuleRule::new(
+        ModuleRuleCondition::ResourcePathEndsWith(".svg".to_string()),
+        vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+            Vc::upcast(SvgComponentModuleType::new()),
+        ))],
+    )
+}
+
+#[turbo_tasks::value]
+struct SvgComponentModuleType {}
+
+#[turbo_tasks::value_impl]
+impl SvgComponentModuleType {
+    #[turbo_tasks::function]
+    fn new() -> Vc<Self> {
+        SvgComponentModuleType {}.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for SvgComponentModuleType {
+    #[turbo_tasks::function]
+    fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Vc<Box<dyn Module>> {
+        context.process(
+            Vc::upcast(SvgComponentSource { source }.cell()),
+            Value::new(ReferenceType::Undefined),
+        )
+    }
+}
+
+/// A source which generates a JavaScript module exporting the SVG as a React
+/// component. Props passed to the component are set on the `<svg>` element.
+#[turbo_tasks::value]
+struct SvgComponentSource {
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SvgComponentSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().rename_as("*.js".to_string())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SvgComponentSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let path = self.source.ident().path().await?;
+        let AssetContent::File(file) = *self.source.content().await? else {
+            bail!("SVG source is not a file");
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            bail!("{} not found", path.path);
+        };
+        let code = svg_to_component(&file.content().to_str()?)
+            .map_err(|err| anyhow!("failed to convert {} to a component: {}", path.path, err))?;
+        Ok(AssetContent::file(FileContent::Content(code.into()).cell()))
+    }
+}
+
+/// Generates a module with a React component rendering the SVG document.
+fn svg_to_component(svg: &str) -> Result<String> {
+    let document = roxmltree::Document::parse(svg)?;
+    let mut element = String::new();
+    write_element(&mut element, document.root_element(), true)?;
+    Ok(format!(
+        "import * as React from \"react\";\n\nfunction SvgComponent(props) {{\n  return \
+         {element};\n}}\n\nexport default SvgComponent;\n"
+    ))
+}
+
+fn write_element(out: &mut String, node: roxmltree::Node, is_root: bool) -> Result<()> {
+    write!(
+        out,
+        "React.createElement({}, {{",
+        StringifyJs(node.tag_name().name())
+    )?;
+    for attribute in node.attributes() {
+        let name = attribute_name(attribute.name(), attribute.namespace());
+        if name == "style" {
+            write!(out, "style: {{")?;
+            for (property, value) in attribute.value().split(';').filter_map(|declaration| {
+                let (property, value) = declaration.split_once(':')?;
+                Some((property.trim(), value.trim()))
+            }) {
+                write!(
+                    out,
+                    "{}: {}, ",
+                    StringifyJs(&style_property_name(property)),
+                    StringifyJs(value)
+                )?;
+            }
+            write!(out, "}}, ")?;
+        } else {
+            write!(
+                out,
+                "{}: {}, ",
+                StringifyJs(&name),
+                StringifyJs(attribute.value())
+            )?;
+        }
+    }
+    if is_root {
+        write!(out, "...props")?;
+    }
+    write!(out, "}}")?;
+
+    for child in node.children() {
+        if child.is_element() {
+            write!(out, ", ")?;
+            write_element(out, child, false)?;
+        } else if let Some(text) = child.text().filter(|_| child.is_text()) {
+            if !text.trim().is_empty() {
+                write!(out, ", {}", StringifyJs(text))?;
+            }
+        }
+    }
+    write!(out, ")")?;
+    Ok(())
+}
+
+/// Maps an SVG attribute to its React prop, e. g. `stroke-width` to
+/// `strokeWidth` and `xlink:href` to `xlinkHref`.
+fn attribute_name(name: &str, namespace: Option<&str>) -> String {
+    let prefix = match namespace {
+        Some(XLINK_NAMESPACE) => "xlink",
+        Some(XML_NAMESPACE) => "xml",
+        _ => "",
+    };
+    if !prefix.is_empty() {
+        return format!("{prefix}{}", upper_first(name));
+    }
+    match name {
+        "class" => "className".to_string(),
+        "for" => "htmlFor".to_string(),
+        _ if name.starts_with("data-") || name.starts_with("aria-") => name.to_string(),
+        _ => camel_case(name),
+    }
+}
+
+/// Maps a CSS property to its name in a React style object, e. g.
+/// `fill-opacity` to `fillOpacity`. Custom properties are kept as they are.
+fn style_property_name(property: &str) -> String {
+    if property.starts_with("--") {
+        property.to_string()
+    } else {
+        camel_case(property)
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split(['-', ':']);
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        result.push_str(&upper_first(part));
+    }
+    result
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_to_component() {
+        let code = svg_to_component(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 24 24" class="icon">
+                <title>Logo</title>
+                <path stroke-width="2" style="fill-opacity: 0.5; --tint: red" d="M0 0h24v24H0z"/>
+                <use xlink:href="#a" data-id="1"/>
+            </svg>"##,
+        )
+        .unwrap();
+        assert!(code.contains(
+            r#"React.createElement("svg", {"viewBox": "0 0 24 24", "className": "icon", ...props}"#
+        ));
+        assert!(code.contains(r#"React.createElement("title", {}, "Logo")"#));
+        assert!(code.contains(
+            r#"{"strokeWidth": "2", style: {"fillOpacity": "0.5", "--tint": "red", }, "d": "M0 0h24v24H0z", }"#
+        ));
+        assert!(code.contains(r##"{"xlinkHref": "#a", "data-id": "1", }"##));
+    }
+}
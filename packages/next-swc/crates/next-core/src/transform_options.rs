@@ -1,25 +1,33 @@
 use anyhow::Result;
-use turbo_tasks::Vc;
+use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
-    turbo::tasks_fs::{FileJsonContent, FileSystemPath},
+    turbo::tasks_fs::{FileJsonContent, FileSystemEntryType, FileSystemPath},
     turbopack::{
         core::{
             file_source::FileSource,
-            resolve::{find_context_file, node::node_cjs_resolve_options, FindContextFileResult},
+            issue::{Issue, IssueExt, IssueSeverity},
+            resolve::{
+                find_context_file, node::node_cjs_resolve_options, parse::Request,
+                pattern::Pattern, resolve, FindContextFileResult,
+            },
             source::Source,
         },
         dev::react_refresh::assert_can_resolve_react_refresh,
         ecmascript::typescript::resolve::{read_from_tsconfigs, read_tsconfigs, tsconfig},
         turbopack::{
+            condition::ContextCondition,
             module_options::{
-                DecoratorsKind, DecoratorsOptions, JsxTransformOptions, TypescriptTransformOptions,
+                DecoratorsKind, DecoratorsOptions, JsxTransformOptions, ModuleOptionsContext,
+                TypescriptTransformOptions,
             },
             resolve_options_context::ResolveOptionsContext,
         },
     },
 };
 
-use crate::mode::NextMode;
+use crate::{
+    mode::NextMode, next_config::NextConfig, next_import_map::package_lookup_resolve_options,
+};
 
 async fn get_typescript_options(
     project_path: Vc<FileSystemPath>,
@@ -168,3 +176,206 @@ pub async fn get_jsx_transform_options(
 
     Ok(react_transform_options.cell())
 }
+
+#[turbo_tasks::value(transparent)]
+pub struct PackageDirectories(Vec<Vc<FileSystemPath>>);
+
+/// The directories of the transpiled packages which come with their own
+/// tsconfig/jsconfig. In monorepos, workspace packages often have different
+/// settings than the app which uses them.
+#[turbo_tasks::function]
+pub async fn get_tsconfig_package_directories(
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<Vc<PackageDirectories>> {
+    let mut directories = vec![];
+    for package in next_config.transpile_packages().await?.iter() {
+        let result = resolve(
+            project_path,
+            Request::parse(Value::new(Pattern::Constant(format!(
+                "{package}/package.json"
+            )))),
+            package_lookup_resolve_options(project_path),
+        );
+        let Some(package_json) = *result.first_source().await? else {
+            continue;
+        };
+        let package_dir = package_json.ident().path().parent().resolve().await?;
+        for name in tsconfig().await?.iter() {
+            if *package_dir.join(name.clone()).get_type().await? == FileSystemEntryType::File {
+                directories.push(package_dir);
+                break;
+            }
+        }
+    }
+    Ok(Vc::cell(directories))
+}
+
+/// Adds a rule for each transpiled package with its own tsconfig/jsconfig,
+/// so its files are transformed with the settings nearest to them instead of
+/// the project's. Path mappings are already resolved through the nearest
+/// config by the resolver.
+pub async fn add_package_tsconfig_rules(
+    module_options_context: ModuleOptionsContext,
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    mode: NextMode,
+    resolve_options_context: Option<Vc<ResolveOptionsContext>>,
+) -> Result<ModuleOptionsContext> {
+    let package_directories = get_tsconfig_package_directories(project_path, next_config).await?;
+    if package_directories.is_empty() {
+        return Ok(module_options_context);
+    }
+
+    let mut rules = module_options_context.rules.clone();
+    for &package_dir in package_directories.iter() {
+        let package_module_options_context = ModuleOptionsContext {
+            enable_typescript_transform: Some(get_typescript_transform_options(package_dir)),
+            decorators: Some(get_decorators_transform_options(package_dir)),
+            enable_jsx: module_options_context
+                .enable_jsx
+                .map(|_| get_jsx_transform_options(package_dir, mode, resolve_options_context)),
+            ..module_options_context.clone()
+        };
+        rules.push((
+            ContextCondition::InPath(package_dir),
+            package_module_options_context.cell(),
+        ));
+    }
+
+    Ok(ModuleOptionsContext {
+        rules,
+        ..module_options_context
+    })
+}
+
+/// Warns about `compilerOptions.paths` mappings in the tsconfig/jsconfig which
+/// point outside of the root of the project filesystem, which is the
+/// workspace root in monorepos. Files there aren't watched and can't be
+/// transpiled like the project's own files. Mappings to sibling workspace
+/// packages are fine.
+///
+/// The mappings themselves are applied by the resolver, which reads the same
+/// files, so changes to them take effect without restarting the dev server.
+#[turbo_tasks::function]
+pub async fn validate_tsconfig_paths(project_path: Vc<FileSystemPath>) -> Result<Vc<()>> {
+    let Some(tsconfig) = get_typescript_options(project_path).await else {
+        return Ok(Default::default());
+    };
+    let Some((paths, base_url, source)) = read_from_tsconfigs(&tsconfig, |json, source| {
+        let compiler_options = &json["compilerOptions"];
+        let paths = compiler_options["paths"].as_object()?.clone();
+        let base_url = compiler_options["baseUrl"].as_str().map(|s| s.to_string());
+        Some((paths, base_url, source))
+    })
+    .await?
+    else {
+        return Ok(Default::default());
+    };
+
+    let tsconfig_path = source.ident().path();
+    let config_dir = tsconfig_path.parent().await?;
+    let base_dir = match &base_url {
+        Some(base_url) => join_normalized(&config_dir.path, base_url),
+        None => Some(config_dir.path.clone()),
+    };
+    for (pattern, targets) in paths {
+        for target in targets.as_array().into_iter().flatten() {
+            let Some(target) = target.as_str() else {
+                continue;
+            };
+            let resolved = base_dir
+                .as_deref()
+                .and_then(|base_dir| join_normalized(base_dir, target));
+            if resolved.is_none() {
+                TsConfigPathOutsideProjectIssue {
+                    file_path: tsconfig_path,
+                    pattern: pattern.clone(),
+                    target: target.to_string(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+    }
+
+    Ok(Default::default())
+}
+
+/// Joins a relative path onto a directory, both relative to the filesystem
+/// root. Returns `None` when the result would be above the root.
+fn join_normalized(dir: &str, path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[turbo_tasks::value(shared)]
+struct TsConfigPathOutsideProjectIssue {
+    file_path: Vc<FileSystemPath>,
+    pattern: String,
+    target: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TsConfigPathOutsideProjectIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "The path mapping \"{}\" points outside of the project's root directory",
+            self.pattern
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(format!(
+            "\"{}\" resolves to a directory outside of the project's root directory (the \
+             workspace root in monorepos). Files there are not watched or transformed like the \
+             project's own files. Move them into the workspace, or set \
+             `experimental.outputFileTracingRoot` to a directory which contains them.",
+            self.target
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_normalized() {
+        assert_eq!(
+            join_normalized("app", "./src/*"),
+            Some("app/src/*".to_string())
+        );
+        assert_eq!(
+            join_normalized("apps/web", "../../packages/ui"),
+            Some("packages/ui".to_string())
+        );
+        assert_eq!(join_normalized("app", "../../outside"), None);
+    }
+}
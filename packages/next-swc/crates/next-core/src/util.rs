@@ -1,17 +1,24 @@
+use std::collections::VecDeque;
+
 use anyhow::{bail, Result};
+use indexmap::IndexSet;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use swc_core::ecma::ast::Program;
-use turbo_tasks::{trace::TraceRawVcs, TaskInput, ValueDefault, ValueToString, Vc};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, TryJoinIterExt, ValueDefault, ValueToString, Vc};
 use turbo_tasks_fs::rope::Rope;
 use turbopack_binding::{
-    turbo::tasks_fs::{json::parse_json_rope_with_source_context, FileContent, FileSystemPath},
+    turbo::tasks_fs::{
+        json::parse_json_rope_with_source_context, DirectoryEntry, FileContent, FileSystemPath,
+        LinkContent,
+    },
     turbopack::{
         core::{
             environment::{ServerAddr, ServerInfo},
             ident::AssetIdent,
             issue::{Issue, IssueExt, IssueSeverity},
-            module::Module,
+            module::{Module, Modules},
+            reference::ModuleReference,
         },
         ecmascript::{
             analyzer::{JsValue, ObjectPart},
@@ -80,6 +87,94 @@ pub fn get_asset_path_from_pathname(pathname: &str, ext: &str) -> String {
     format!("{}{}", get_asset_prefix_from_pathname(pathname), ext)
 }
 
+/// Percent-encodes every segment of an asset path, so it can be used in a URL
+/// or a `Link` header. Route directories can contain unicode or characters
+/// like `%` and `#`, which end up in the paths of their chunks.
+///
+/// Only for paths which are used as URLs: chunk paths which are passed to the
+/// runtime to load a chunk are matched as they are.
+pub fn encode_uri_path(path: &str) -> String {
+    path.split('/')
+        .map(urlencoding::encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether a path is inside a `node_modules` directory. Only whole segments
+/// match, so `my_node_modules/` doesn't.
+pub fn is_in_node_modules(path: &str) -> bool {
+    path.split('/').any(|segment| segment == "node_modules")
+}
+
+/// The modules a module references, cached so the graph walks of different
+/// endpoints share the work.
+#[turbo_tasks::function]
+pub async fn primary_referenced_modules(module: Vc<Box<dyn Module>>) -> Result<Vc<Modules>> {
+    let modules = module
+        .references()
+        .await?
+        .iter()
+        .map(|reference| async move {
+            Ok(reference
+                .resolve_reference()
+                .primary_modules()
+                .await?
+                .clone_value())
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(modules.into_iter().flatten().collect()))
+}
+
+/// A breadth-first walk of the module graph which leaves out the packages in
+/// node_modules. Every module is visited once, the caller decides which modules
+/// a visited module leads to.
+pub struct ModuleWalk {
+    visited: IndexSet<Vc<Box<dyn Module>>>,
+    queue: VecDeque<Vc<Box<dyn Module>>>,
+}
+
+impl ModuleWalk {
+    pub fn new(entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>) -> Self {
+        let mut walk = ModuleWalk {
+            visited: IndexSet::new(),
+            queue: VecDeque::new(),
+        };
+        walk.extend(entries);
+        walk
+    }
+
+    /// Marks the modules as visited without walking them.
+    pub fn skip(&mut self, modules: impl IntoIterator<Item = Vc<Box<dyn Module>>>) {
+        self.visited.extend(modules);
+    }
+
+    /// Queues the modules which weren't visited yet.
+    pub fn extend(&mut self, modules: impl IntoIterator<Item = Vc<Box<dyn Module>>>) {
+        for module in modules {
+            if self.visited.insert(module) {
+                self.queue.push_back(module);
+            }
+        }
+    }
+
+    /// Queues the modules referenced by the module.
+    pub async fn visit_references(&mut self, module: Vc<Box<dyn Module>>) -> Result<()> {
+        self.extend(primary_referenced_modules(module).await?.iter().copied());
+        Ok(())
+    }
+
+    /// The next module to visit which isn't in node_modules.
+    pub async fn next(&mut self) -> Result<Option<Vc<Box<dyn Module>>>> {
+        while let Some(module) = self.queue.pop_front() {
+            if !is_in_node_modules(&module.ident().path().await?.path) {
+                return Ok(Some(module));
+            }
+        }
+        Ok(None)
+    }
+}
+
 pub async fn foreign_code_context_condition(
     next_config: Vc<NextConfig>,
 ) -> Result<ContextCondition> {
@@ -140,6 +235,74 @@ impl ValueDefault for NextSourceConfig {
     }
 }
 
+/// Resolves a symlink among the entries of the app or pages directory to the
+/// file or directory it points to. Links which can't be followed are reported
+/// and resolved to [DirectoryEntry::Error], so they are skipped.
+pub async fn resolve_directory_entry(entry: DirectoryEntry) -> Result<DirectoryEntry> {
+    let DirectoryEntry::Symlink(link) = entry else {
+        return Ok(entry);
+    };
+    if let Some(reason) = unfollowable_link_reason(&*link.read_link().await?) {
+        UnfollowableSymlinkIssue {
+            path: link,
+            reason: reason.to_string(),
+        }
+        .cell()
+        .emit();
+        return Ok(DirectoryEntry::Error);
+    }
+    entry.resolve_symlink().await
+}
+
+/// Why a symlink can't be followed, if it can't. The file system only reads
+/// paths inside of its root, which is the workspace root.
+fn unfollowable_link_reason(link: &LinkContent) -> Option<&'static str> {
+    match link {
+        LinkContent::Link { .. } => None,
+        LinkContent::Invalid => Some("its target is outside of the root directory"),
+        LinkContent::NotFound => Some("its target doesn't exist"),
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct UnfollowableSymlinkIssue {
+    path: Vc<FileSystemPath>,
+    reason: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnfollowableSymlinkIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(format!("The symlink can't be followed, {}", self.reason))
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("other".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "The files it links to are not used as routes. Symlinks are followed within the root \
+             directory, set it with `experimental.outputFileTracingRoot` when the target is in a \
+             parent directory."
+                .to_string(),
+        )
+    }
+}
+
 /// An issue that occurred while parsing the page config.
 #[turbo_tasks::value(shared)]
 pub struct NextSourceConfigParsingIssue {
@@ -407,3 +570,32 @@ pub async fn render_data(
     })?;
     Ok(Vc::cell(value))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfollowable_link_reason() {
+        assert_eq!(
+            unfollowable_link_reason(&LinkContent::Invalid),
+            Some("its target is outside of the root directory")
+        );
+        assert_eq!(
+            unfollowable_link_reason(&LinkContent::NotFound),
+            Some("its target doesn't exist")
+        );
+    }
+
+    #[test]
+    fn test_encode_uri_path() {
+        assert_eq!(
+            encode_uri_path("static/chunks/app/(marketing)/[slug]/page.css"),
+            "static/chunks/app/%28marketing%29/%5Bslug%5D/page.css"
+        );
+        assert_eq!(
+            encode_uri_path("static/chunks/app/café/#1.css"),
+            "static/chunks/app/caf%C3%A9/%231.css"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+/// The lockfiles marking the root of a workspace. Keep in sync with
+/// `findRootLockFile` in `packages/next/src/lib/find-root.ts`.
+const LOCKFILES: &[&str] = &["pnpm-lock.yaml", "package-lock.json", "yarn.lock"];
+
+/// Infers the root of the workspace containing a project, i.e. the closest
+/// directory at or above the project directory which contains a lockfile.
+/// This is the default of `experimental.outputFileTracingRoot`. Files of the
+/// workspace outside of the project, like shared packages of a monorepo, are
+/// only accessible when the filesystem is rooted there.
+pub fn find_workspace_root(project_dir: &Path) -> Option<PathBuf> {
+    project_dir
+        .ancestors()
+        .find(|dir| {
+            LOCKFILES
+                .iter()
+                .any(|lockfile| dir.join(lockfile).is_file())
+        })
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_find_workspace_root() {
+        let root = std::env::temp_dir().join(format!("next-workspace-root-{}", std::process::id()));
+        let project = root.join("apps/web");
+        fs::create_dir_all(project.join("src/yarn.lock")).unwrap();
+        fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(find_workspace_root(&project), Some(root.clone()));
+
+        // The closest lockfile wins, directories named like one don't count.
+        fs::write(project.join("package-lock.json"), "").unwrap();
+        assert_eq!(find_workspace_root(&project), Some(project.clone()));
+        assert_eq!(
+            find_workspace_root(&project.join("src")),
+            Some(project.clone())
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::issue::{Issue, IssueSeverity},
+};
+
+use crate::next_config::ZoneConfig;
+
+/// Finds the claims of a multi-zone setup which overlap: path prefixes of two
+/// zones nested inside each other, routes of this app served by a zone, and
+/// zones sharing an asset prefix with each other or with this app.
+pub fn find_zone_conflicts<'a>(
+    zones: &[ZoneConfig],
+    asset_prefix: &str,
+    pathnames: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut conflicts = vec![];
+
+    for (i, zone) in zones.iter().enumerate() {
+        for other in &zones[i + 1..] {
+            for prefix in &zone.path_prefixes {
+                for other_prefix in &other.path_prefixes {
+                    if is_nested(prefix, other_prefix) || is_nested(other_prefix, prefix) {
+                        conflicts.push(format!(
+                            "The zones \"{}\" and \"{}\" both claim the path prefix {}",
+                            zone.name,
+                            other.name,
+                            if prefix.len() > other_prefix.len() {
+                                prefix
+                            } else {
+                                other_prefix
+                            }
+                        ));
+                    }
+                }
+            }
+            if normalize_prefix(zone.asset_prefix.as_deref().unwrap_or_default())
+                == normalize_prefix(other.asset_prefix.as_deref().unwrap_or_default())
+            {
+                conflicts.push(format!(
+                    "The zones \"{}\" and \"{}\" use the same assetPrefix",
+                    zone.name, other.name
+                ));
+            }
+        }
+        if normalize_prefix(zone.asset_prefix.as_deref().unwrap_or_default())
+            == normalize_prefix(asset_prefix)
+        {
+            conflicts.push(format!(
+                "The zone \"{}\" uses the same assetPrefix as this app",
+                zone.name
+            ));
+        }
+    }
+
+    for pathname in pathnames {
+        for zone in zones {
+            if zone
+                .path_prefixes
+                .iter()
+                .any(|prefix| is_nested(pathname, prefix))
+            {
+                conflicts.push(format!(
+                    "The route {} is shadowed by the zone \"{}\"",
+                    pathname, zone.name
+                ));
+            }
+        }
+    }
+
+    conflicts
+}
+
+fn normalize_prefix(prefix: &str) -> &str {
+    prefix.trim_end_matches('/')
+}
+
+/// Whether the pathname equals the prefix or is nested inside of it, matching
+/// whole segments only.
+fn is_nested(pathname: &str, prefix: &str) -> bool {
+    let prefix = normalize_prefix(prefix);
+    prefix.is_empty()
+        || pathname
+            .strip_prefix(prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The zones of a multi-zone setup claim overlapping paths, so some requests
+/// can't be delegated unambiguously.
+#[turbo_tasks::value(shared)]
+pub struct ZoneConflictIssue {
+    pub project_path: Vc<FileSystemPath>,
+    pub conflict: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ZoneConflictIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("routing".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<String> {
+        Vc::cell(self.conflict.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<String> {
+        Vc::cell(
+            "Every path prefix and assetPrefix of a multi-zone setup must be claimed by a single \
+             zone. Update `experimental.zones` in next.config.js."
+                .to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(name: &str, path_prefixes: &[&str], asset_prefix: Option<&str>) -> ZoneConfig {
+        ZoneConfig {
+            name: name.to_string(),
+            path_prefixes: path_prefixes.iter().map(|p| p.to_string()).collect(),
+            destination: format!("https://{name}.example.com"),
+            asset_prefix: asset_prefix.map(|p| p.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_find_zone_conflicts() {
+        let zones = [
+            zone("blog", &["/blog"], Some("/blog-static")),
+            zone("docs", &["/docs", "/blog/docs"], Some("/docs-static/")),
+            zone("shop", &["/shop"], None),
+        ];
+        assert_eq!(
+            find_zone_conflicts(&zones, "", ["/", "/blogger", "/shop/cart"]),
+            vec![
+                "The zones \"blog\" and \"docs\" both claim the path prefix /blog/docs",
+                "The zone \"shop\" uses the same assetPrefix as this app",
+                "The route /shop/cart is shadowed by the zone \"shop\"",
+            ]
+        );
+        let zones = [zones[0].clone(), zones[2].clone()];
+        assert!(find_zone_conflicts(&zones, "/app", ["/", "/blogger"]).is_empty());
+    }
+}
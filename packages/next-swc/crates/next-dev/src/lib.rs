@@ -12,7 +12,7 @@ use std::{
     future::{join, Future},
     io::{stdout, Write},
     net::{IpAddr, SocketAddr},
-    path::{Path, PathBuf, MAIN_SEPARATOR},
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -30,6 +30,7 @@ use next_core::{
     next_config::{load_next_config, load_rewrites},
     next_image::NextImageContentSource,
     pages_structure::find_pages_structure,
+    project_relative_path::ProjectRelativePath,
     router_source::NextRouterContentSource,
     source_map::NextSourceMapTraceContentSource,
     tracing_presets::{
@@ -305,16 +306,7 @@ async fn source(
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let output_fs = output_fs(project_dir.clone());
     let fs = project_fs(root_dir.clone());
-    let project_relative = project_dir.strip_prefix(&root_dir).unwrap_or_else(|| {
-        panic!(
-            "project directory '{project_dir}' exists outside of the root directory '{root_dir}'"
-        )
-    });
-    let project_relative = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/");
-    let project_path = fs.root().join(project_relative);
+    let project_path = ProjectRelativePath::new(&root_dir, &project_dir)?.join(fs.root());
 
     let server_addr = ServerAddr::new(*server_addr).cell();
 
@@ -364,7 +356,8 @@ async fn source(
         browserslist_query.clone(),
         next_config,
     );
-    let client_compile_time_info = get_client_compile_time_info(mode, browserslist_query);
+    let client_compile_time_info =
+        get_client_compile_time_info(mode, browserslist_query, next_config, None);
     let client_chunking_context = get_client_chunking_context(
         project_path,
         dev_server_root,
@@ -385,7 +378,7 @@ async fn source(
         next_config,
         server_addr,
     );
-    let app_dir = find_app_dir_if_enabled(project_path);
+    let app_dir = find_app_dir_if_enabled(project_path, next_config);
     let app_source = create_app_source(
         app_dir,
         project_path,
@@ -0,0 +1,488 @@
+use hex::encode as hex_encode;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use swc_core::{
+    common::{errors::HANDLER, util::take::Take, Span, Spanned, DUMMY_SP},
+    ecma::{
+        ast::*,
+        atoms::JsWord,
+        utils::{quote_ident, ExprFactory},
+        visit::{
+            as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitWith,
+        },
+    },
+};
+
+/// The local name of the cache wrapper imported into modules with cached
+/// functions.
+const CACHE_WRAPPER: &str = "$$cache__";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Config {
+    pub is_server: bool,
+    /// The path of the module relative to the project, which the ids are
+    /// generated from.
+    pub relative_file_path_from_root: JsWord,
+}
+
+/// Compiles the `"use cache"` directive. Functions annotated with it, or all
+/// exported functions of a module starting with it, are wrapped with the cache
+/// wrapper:
+///
+/// ```js
+/// export const getUser = $$cache__("<id>", async () => { ... })
+/// ```
+///
+/// Function declarations stay declarations, so they are still hoisted. They
+/// call the wrapped function, which is moved to a declaration of its own:
+///
+/// ```js
+/// export async function getUser(...args) {
+///     return $$cache__("<id>", $$cache_original__getUser)(...args);
+/// }
+/// async function $$cache_original__getUser(id) { ... }
+/// ```
+///
+/// The id is a hash of the project-relative path of the module and the
+/// function name, so it is stable across builds and machines. Only top-level
+/// async functions can be cached, their arguments become part of the cache
+/// key.
+pub fn use_cache(config: Config) -> impl VisitMut + Fold {
+    as_folder(UseCache {
+        config,
+        in_cache_file: false,
+        exported_idents: vec![],
+        cached_idents: vec![],
+        hoisted_items: vec![],
+        has_cache_wrapper: false,
+    })
+}
+
+struct UseCache {
+    config: Config,
+    in_cache_file: bool,
+    /// The local names exported with `export { ... }`.
+    exported_idents: Vec<JsWord>,
+    /// The local names of the cached functions.
+    cached_idents: Vec<JsWord>,
+    /// The original functions of the cached function declarations, which are
+    /// inserted after the item they were taken from.
+    hoisted_items: Vec<ModuleItem>,
+    has_cache_wrapper: bool,
+}
+
+impl VisitMut for UseCache {
+    noop_visit_mut_type!();
+
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        self.in_cache_file = take_cache_directive_in_module(&mut module.body);
+
+        if !self.config.is_server {
+            if self.in_cache_file {
+                emit_error(
+                    module.span,
+                    "The \"use cache\" directive can only be used in Server Components.",
+                );
+            }
+            module.visit_with(&mut NestedCacheDirectiveVisitor { is_server: false });
+            return;
+        }
+
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src: None,
+                ..
+            })) = item
+            {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(orig),
+                        ..
+                    }) = specifier
+                    {
+                        self.exported_idents.push(orig.sym.clone());
+                    }
+                }
+            }
+        }
+
+        for item in module.body.take() {
+            let item = self.transform_item(item);
+            module.body.push(item);
+            module.body.append(&mut self.hoisted_items);
+        }
+
+        module.visit_with(&mut NestedCacheDirectiveVisitor { is_server: true });
+        module.visit_with(&mut CacheArgumentsVisitor {
+            cached_idents: &self.cached_idents,
+        });
+
+        if self.has_cache_wrapper {
+            // import { cacheWrapper as $$cache__ } from 'private-next-rsc-cache-wrapper'
+            module.body.insert(
+                0,
+                ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    span: DUMMY_SP,
+                    specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+                        span: DUMMY_SP,
+                        local: quote_ident!(CACHE_WRAPPER),
+                        imported: Some(ModuleExportName::Ident(quote_ident!("cacheWrapper"))),
+                        is_type_only: false,
+                    })],
+                    src: Box::new(Str {
+                        span: DUMMY_SP,
+                        value: "private-next-rsc-cache-wrapper".into(),
+                        raw: None,
+                    }),
+                    type_only: false,
+                    asserts: None,
+                })),
+            );
+        }
+    }
+}
+
+impl UseCache {
+    fn transform_item(&mut self, item: ModuleItem) -> ModuleItem {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { span, decl })) => {
+                let decl = match decl {
+                    Decl::Fn(f) => self.transform_fn_decl(f, true),
+                    Decl::Var(var) => Decl::Var(self.transform_var_decl(var, true)),
+                    Decl::Class(class) => {
+                        if self.in_cache_file {
+                            emit_error(class.ident.span, ONLY_ASYNC_FUNCTIONS_EXPORTED);
+                        }
+                        Decl::Class(class)
+                    }
+                    decl => decl,
+                };
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { span, decl }))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                span,
+                decl: DefaultDecl::Fn(mut f),
+            })) => {
+                if !self.is_cached_fn(&mut f.function, f.function.span, true) {
+                    return ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(
+                        ExportDefaultDecl {
+                            span,
+                            decl: DefaultDecl::Fn(f),
+                        },
+                    ));
+                }
+                // Anonymous functions can't be referenced, so they don't need
+                // to stay hoisted.
+                let Some(ident) = f.ident.clone() else {
+                    return ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+                        ExportDefaultExpr {
+                            span,
+                            expr: Box::new(self.wrap("default", Expr::Fn(f))),
+                        },
+                    ));
+                };
+                f.function = self.wrap_fn_decl("default", &ident.sym, f.function);
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                    span,
+                    decl: DefaultDecl::Fn(f),
+                }))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                span,
+                mut expr,
+            })) => {
+                if self.is_cached_expr(&mut expr, true) {
+                    expr = Box::new(self.wrap("default", *expr));
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                    span,
+                    expr,
+                }))
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(f))) => {
+                let is_exported = self.exported_idents.contains(&f.ident.sym);
+                ModuleItem::Stmt(Stmt::Decl(self.transform_fn_decl(f, is_exported)))
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(self.transform_var_decl(var, false))))
+            }
+            item => item,
+        }
+    }
+
+    fn transform_fn_decl(&mut self, mut f: FnDecl, is_exported: bool) -> Decl {
+        if self.is_cached_fn(&mut f.function, f.ident.span, is_exported) {
+            let name = f.ident.sym.clone();
+            f.function = self.wrap_fn_decl(&name, &name, f.function);
+        }
+        Decl::Fn(f)
+    }
+
+    /// Moves a cached function to a declaration of its own and returns the
+    /// function which calls it wrapped, for the declaration it was taken from.
+    fn wrap_fn_decl(&mut self, name: &str, local: &str, function: Box<Function>) -> Box<Function> {
+        let original = quote_ident!(format!("$$cache_original__{local}"));
+        let args = quote_ident!("args");
+        self.hoisted_items
+            .push(ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl {
+                ident: original.clone(),
+                declare: false,
+                function,
+            }))));
+
+        // return $$cache__("<id>", $$cache_original__name)(...args)
+        let call = self.wrap(name, Expr::Ident(original)).as_call(
+            DUMMY_SP,
+            vec![ExprOrSpread {
+                spread: Some(DUMMY_SP),
+                expr: Box::new(Expr::Ident(args.clone())),
+            }],
+        );
+        Box::new(Function {
+            params: vec![Param {
+                span: DUMMY_SP,
+                decorators: vec![],
+                pat: Pat::Rest(RestPat {
+                    span: DUMMY_SP,
+                    dot3_token: DUMMY_SP,
+                    arg: Box::new(Pat::Ident(args.into())),
+                    type_ann: None,
+                }),
+            }],
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(call)),
+                })],
+            }),
+            is_generator: false,
+            is_async: true,
+            type_params: None,
+            return_type: None,
+        })
+    }
+
+    fn transform_var_decl(&mut self, mut var: Box<VarDecl>, is_exported: bool) -> Box<VarDecl> {
+        for decl in &mut var.decls {
+            let Pat::Ident(name) = &decl.name else {
+                continue;
+            };
+            let name = name.id.sym.clone();
+            let is_exported = is_exported || self.exported_idents.contains(&name);
+            let Some(init) = &mut decl.init else {
+                continue;
+            };
+            if !matches!(&**init, Expr::Arrow(_) | Expr::Fn(_)) {
+                if self.in_cache_file && is_exported {
+                    emit_error(decl.span, ONLY_ASYNC_FUNCTIONS_EXPORTED);
+                }
+                continue;
+            }
+            if self.is_cached_expr(init, is_exported) {
+                let init = decl.init.take().unwrap();
+                decl.init = Some(Box::new(self.wrap(&name, *init)));
+            }
+        }
+        var
+    }
+
+    /// Whether a function or arrow expression is cached, removing its
+    /// directive.
+    fn is_cached_expr(&mut self, expr: &mut Expr, is_exported: bool) -> bool {
+        match expr {
+            Expr::Fn(f) => {
+                let span = f.function.span;
+                self.is_cached_fn(&mut f.function, span, is_exported)
+            }
+            Expr::Arrow(arrow) => {
+                let has_directive = match &mut *arrow.body {
+                    BlockStmtOrExpr::BlockStmt(body) => take_cache_directive_in_fn(&mut body.stmts),
+                    BlockStmtOrExpr::Expr(_) => false,
+                };
+                let is_cached = has_directive || (self.in_cache_file && is_exported);
+                if is_cached && !arrow.is_async {
+                    emit_error(arrow.span, ASYNC_FUNCTIONS_ONLY);
+                    return false;
+                }
+                is_cached
+            }
+            _ => false,
+        }
+    }
+
+    fn is_cached_fn(&mut self, function: &mut Function, span: Span, is_exported: bool) -> bool {
+        let has_directive = function
+            .body
+            .as_mut()
+            .map_or(false, |body| take_cache_directive_in_fn(&mut body.stmts));
+        let is_cached = has_directive || (self.in_cache_file && is_exported);
+        if is_cached && !function.is_async {
+            emit_error(span, ASYNC_FUNCTIONS_ONLY);
+            return false;
+        }
+        is_cached
+    }
+
+    fn wrap(&mut self, name: &str, expr: Expr) -> Expr {
+        self.has_cache_wrapper = true;
+        if name != "default" {
+            self.cached_idents.push(name.into());
+        }
+        CallExpr {
+            span: DUMMY_SP,
+            callee: quote_ident!(CACHE_WRAPPER).as_callee(),
+            args: vec![
+                Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: generate_cache_id(&self.config.relative_file_path_from_root, name)
+                        .into(),
+                    raw: None,
+                }))
+                .as_arg(),
+                expr.as_arg(),
+            ],
+            type_args: None,
+        }
+        .into()
+    }
+}
+
+const ASYNC_FUNCTIONS_ONLY: &str = "\"use cache\" functions must be async functions.";
+const ONLY_ASYNC_FUNCTIONS_EXPORTED: &str =
+    "Only async functions are allowed to be exported in a \"use cache\" file.";
+
+fn emit_error(span: Span, message: &str) {
+    HANDLER.with(|handler| handler.struct_span_err(span, message).emit());
+}
+
+fn generate_cache_id(file_name: &str, name: &str) -> String {
+    // $$id = sha1('relative_file_path_from_root' + ':' + 'name');
+    let mut hasher = Sha1::new();
+    hasher.update(file_name.as_bytes());
+    hasher.update(b":");
+    hasher.update(name.as_bytes());
+    hex_encode(hasher.finalize())
+}
+
+fn is_cache_directive(stmt: &Stmt) -> Option<Span> {
+    match stmt {
+        Stmt::Expr(ExprStmt { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Str(Str { value, span, .. })) if &**value == "use cache" => Some(*span),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(..))))
+}
+
+/// Removes `"use cache"` from the directive prologue of a module.
+fn take_cache_directive_in_module(items: &mut Vec<ModuleItem>) -> bool {
+    let prologue_len = items
+        .iter()
+        .take_while(|item| matches!(item, ModuleItem::Stmt(stmt) if is_directive(stmt)))
+        .count();
+    let Some(index) = items[..prologue_len].iter().position(
+        |item| matches!(item, ModuleItem::Stmt(stmt) if is_cache_directive(stmt).is_some()),
+    ) else {
+        return false;
+    };
+    items.remove(index);
+    true
+}
+
+/// Removes `"use cache"` from the directive prologue of a function body.
+fn take_cache_directive_in_fn(stmts: &mut Vec<Stmt>) -> bool {
+    let prologue_len = stmts.iter().take_while(|stmt| is_directive(stmt)).count();
+    let Some(index) = stmts[..prologue_len]
+        .iter()
+        .position(|stmt| is_cache_directive(stmt).is_some())
+    else {
+        return false;
+    };
+    stmts.remove(index);
+    true
+}
+
+/// Reports the `"use cache"` directives left after the top-level functions
+/// were transformed, i.e. in nested functions, or any in client modules.
+struct NestedCacheDirectiveVisitor {
+    is_server: bool,
+}
+
+impl NestedCacheDirectiveVisitor {
+    fn check_body(&self, stmts: &[Stmt]) {
+        for stmt in stmts.iter().take_while(|stmt| is_directive(stmt)) {
+            if let Some(span) = is_cache_directive(stmt) {
+                emit_error(
+                    span,
+                    if self.is_server {
+                        "\"use cache\" functions must be defined at the top level of a module, \
+                         their result can't depend on variables of an outer function."
+                    } else {
+                        "The \"use cache\" directive can only be used in Server Components."
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl Visit for NestedCacheDirectiveVisitor {
+    noop_visit_type!();
+
+    fn visit_function(&mut self, function: &Function) {
+        if let Some(body) = &function.body {
+            self.check_body(&body.stmts);
+        }
+        function.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+        if let BlockStmtOrExpr::BlockStmt(body) = &*arrow.body {
+            self.check_body(&body.stmts);
+        }
+        arrow.visit_children_with(self);
+    }
+}
+
+/// Reports arguments of calls to cached functions which can't be serialized
+/// into the cache key.
+struct CacheArgumentsVisitor<'a> {
+    cached_idents: &'a [JsWord],
+}
+
+impl Visit for CacheArgumentsVisitor<'_> {
+    noop_visit_type!();
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Some(Expr::Ident(callee)) = call.callee.as_expr().map(|callee| &**callee) {
+            if self.cached_idents.contains(&callee.sym) {
+                for arg in &call.args {
+                    if matches!(
+                        &*arg.expr,
+                        Expr::Arrow(_)
+                            | Expr::Fn(_)
+                            | Expr::Class(_)
+                            | Expr::JSXElement(_)
+                            | Expr::JSXFragment(_)
+                    ) {
+                        emit_error(
+                            arg.expr.span(),
+                            "Arguments of \"use cache\" functions must be serializable, \
+                             functions, classes and JSX can't be passed to them.",
+                        );
+                    }
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
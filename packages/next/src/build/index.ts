@@ -147,6 +147,7 @@ import { buildDataRoute } from '../server/lib/router-utils/build-data-route'
 import { baseOverrides, experimentalOverrides } from '../server/require-hook'
 import { initialize } from '../server/lib/incremental-cache-server'
 import { nodeFs } from '../server/lib/node-fs-methods'
+import { needsExperimentalReact } from '../lib/needs-experimental-react'
 
 export type SsgRoute = {
   initialRevalidateSeconds: number | false
@@ -208,6 +209,11 @@ export type RoutesManifest = {
         fallback: Array<ManifestRewriteRoute>
       }
   headers: Array<ManifestHeaderRoute>
+  /**
+   * The indices of the `headers` whose source can match a pathname of each
+   * app route, keyed by its pathname. Only written by `next build --turbo`.
+   */
+  appPathHeaders?: Record<string, number[]>
   staticRoutes: Array<ManifestRoute>
   dynamicRoutes: Array<ManifestRoute>
   dataRoutes: Array<ManifestDataRoute>
@@ -406,7 +412,10 @@ export default async function build(
 
       const publicDir = path.join(dir, 'public')
       const isAppDirEnabled = true
-      const { pagesDir, appDir } = findPagesDir(dir)
+      const { pagesDir, appDir } = findPagesDir(
+        dir,
+        config.experimental.appDirPath
+      )
       NextBuildContext.pagesDir = pagesDir
       NextBuildContext.appDir = appDir
       hasAppDir = Boolean(appDir)
@@ -994,6 +1003,8 @@ export default async function build(
 
         let root =
           turboNextBuildRoot ??
+          config.experimental.outputFileTracingRoot ??
+          binding.findWorkspaceRoot?.(dir) ??
           (turboJson
             ? path.dirname(turboJson)
             : packagePath
@@ -1002,6 +1013,7 @@ export default async function build(
         await binding.turbo.nextBuild({
           ...NextBuildContext,
           root,
+          minifyThreads: config.experimental.turbo?.minifyThreads,
         })
 
         const [duration] = process.hrtime(turboNextBuildStart)
@@ -1247,7 +1259,7 @@ export default async function build(
               __NEXT_INCREMENTAL_CACHE_IPC_KEY: ipcValidationKey,
               __NEXT_PRIVATE_PREBUNDLED_REACT:
                 type === 'app'
-                  ? config.experimental.serverActions
+                  ? needsExperimentalReact(config)
                     ? 'experimental'
                     : 'next'
                   : '',
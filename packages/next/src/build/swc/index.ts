@@ -164,7 +164,18 @@ export interface Binding {
   transformSync: any
   parse: any
   parseSync: any
+  optimizeImage?: (
+    buffer: Buffer,
+    options: {
+      contentType: string
+      width: number
+      height?: number
+      quality: number
+    }
+  ) => Promise<Buffer>
+  optimizeImageContentTypes?: () => string[]
   getTargetTriple(): string | undefined
+  findWorkspaceRoot?(dir: string): string | undefined
   initCustomTraceSubscriber?: any
   teardownTraceSubscriber?: any
   initHeapProfiler?: any
@@ -402,6 +413,33 @@ interface ProjectOptions {
    * Whether to watch he filesystem for file changes.
    */
   watch: boolean
+
+  /**
+   * Only collect the app and pages routes whose pathnames start with one of
+   * these prefixes. Used to split the build into shards, whose manifests are
+   * merged afterwards.
+   */
+  routePrefixes?: string[]
+
+  /**
+   * How the dev server is reached, when running in dev.
+   */
+  devServer?: {
+    hostname: string
+    port: number
+    /**
+     * The key and certificate paths when the dev server uses HTTPS.
+     */
+    https?: {
+      key: string
+      cert: string
+    }
+    /**
+     * Hostnames other than the dev server itself which may connect to it. Any
+     * origin may connect when this isn't set.
+     */
+    allowedOrigins?: string[]
+  }
 }
 
 interface TurboEngineOptions {
@@ -447,6 +485,7 @@ export interface Middleware {
 
 export interface Entrypoints {
   routes: Map<string, Route>
+  publicAssets: string[]
   middleware?: Middleware
   pagesDocumentEndpoint: Endpoint
   pagesAppEndpoint: Endpoint
@@ -461,11 +500,32 @@ export interface HmrIdentifiers {
   identifiers: string[]
 }
 
+export interface ClientAssets {
+  assets: {
+    /**
+     * The path relative to the `_next` directory.
+     */
+    path: string
+    hash: string
+    size: number
+  }[]
+}
+
 export interface UpdateInfo {
   duration: number
   tasks: number
 }
 
+export interface RouteStatus {
+  pathname: string
+  status: 'queued' | 'compiling' | 'ready' | 'errored'
+  /**
+   * The compilation time in milliseconds, for ready and errored routes which
+   * were compiled on request rather than after a change.
+   */
+  duration?: number
+}
+
 export enum ServerClientChangeType {
   Server = 'Server',
   Client = 'Client',
@@ -483,6 +543,51 @@ export interface Project {
     TurbopackResult<HmrIdentifiers>
   >
   updateInfoSubscribe(): AsyncIterableIterator<TurbopackResult<UpdateInfo>>
+  /**
+   * Streams the list of emitted client assets after every build or HMR
+   * update, e.g. to generate a service worker precache manifest.
+   */
+  clientAssetsSubscribe(): AsyncIterableIterator<TurbopackResult<ClientAssets>>
+  /**
+   * Streams the compilation status of the individual routes.
+   */
+  routeStatusSubscribe(): AsyncIterableIterator<RouteStatus>
+  /**
+   * Compiles the routes matching `experimental.turbo.warmUp` and returns the
+   * routes which compiled successfully.
+   */
+  warmUp(): Promise<string[]>
+  /**
+   * The routes which are currently kept compiled, the most recently requested
+   * first.
+   */
+  hotEntries(): string[]
+  /**
+   * Whether a browser with the given `Origin` header may connect to the dev
+   * server, e.g. for HMR.
+   */
+  isAllowedDevOrigin(origin: string): Promise<boolean>
+  /**
+   * The import chains through which the file at the given project-relative
+   * path ends up in the bundles of the endpoint, across layers.
+   */
+  whyIncluded(endpoint: Endpoint, path: string): Promise<WhyIncluded>
+  /**
+   * The chunk groups of an app page or route endpoint, with the modules they
+   * are created for and the modules in every chunk.
+   */
+  chunkGraph(endpoint: Endpoint): Promise<ChunkGraph>
+  /**
+   * The chunk graph of an app page or route endpoint as an HTML page.
+   */
+  chunkGraphHtml(endpoint: Endpoint): Promise<string>
+  /**
+   * Forgets the routes which haven't been requested for longer than
+   * `onDemandEntries.maxInactiveAge` and returns them. Routes whose changes
+   * are still subscribed to are kept, unsubscribe from the returned routes to
+   * free their tasks.
+   */
+  disposeInactiveEntries(): string[]
 }
 
 export type Route =
@@ -508,6 +613,38 @@ export type Route =
       endpoint: Endpoint
     }
 
+export interface WhyIncluded {
+  path: string
+  /**
+   * Module identifiers from an entry of the endpoint to the module, including
+   * the layer.
+   */
+  chains: string[][]
+  /** Whether chains were left out because there are too many. */
+  truncated: boolean
+}
+
+export interface ChunkGraph {
+  page: string
+  chunkGroups: {
+    name: 'shared' | 'server components' | 'client reference'
+    /** The module identifier of the module the chunks are created for. */
+    entry: string | null
+    /** The browser and SSR modules of a client reference. */
+    references: string[]
+    /** Relative to `_next`. */
+    clientChunks: ChunkGraphChunk[]
+    /** Relative to `.next`. */
+    serverChunks: ChunkGraphChunk[]
+  }[]
+}
+
+export interface ChunkGraphChunk {
+  path: string
+  /** The module identifiers of the modules in the chunk. */
+  modules: string[]
+}
+
 export interface Endpoint {
   /** Write files for the endpoint to disk. */
   writeToDisk(): Promise<TurbopackResult<WrittenEndpoint>>
@@ -710,6 +847,7 @@ function bindingToApi(binding: any, _wasm: boolean) {
 
       type NapiEntrypoints = {
         routes: NapiRoute[]
+        publicAssets: string[]
         middleware?: NapiMiddleware
         pagesDocumentEndpoint: NapiEndpoint
         pagesAppEndpoint: NapiEndpoint
@@ -810,6 +948,7 @@ function bindingToApi(binding: any, _wasm: boolean) {
             : undefined
           yield {
             routes,
+            publicAssets: entrypoints.publicAssets,
             middleware,
             pagesDocumentEndpoint: new EndpointImpl(
               entrypoints.pagesDocumentEndpoint
@@ -851,6 +990,72 @@ function bindingToApi(binding: any, _wasm: boolean) {
       )
       return subscription
     }
+
+    clientAssetsSubscribe() {
+      const subscription = subscribe<TurbopackResult<ClientAssets>>(
+        false,
+        async (callback) =>
+          binding.projectClientAssetsSubscribe(this._nativeProject, callback)
+      )
+      return subscription
+    }
+
+    routeStatusSubscribe() {
+      const subscription = subscribe<RouteStatus>(true, async (callback) =>
+        binding.projectRouteStatusSubscribe(this._nativeProject, callback)
+      )
+      return subscription
+    }
+
+    async warmUp(): Promise<string[]> {
+      return await withErrorCause(() =>
+        binding.projectWarmUp(this._nativeProject)
+      )
+    }
+
+    hotEntries(): string[] {
+      return binding.projectHotEntries(this._nativeProject)
+    }
+
+    async isAllowedDevOrigin(origin: string): Promise<boolean> {
+      return await withErrorCause(() =>
+        binding.projectIsAllowedDevOrigin(this._nativeProject, origin)
+      )
+    }
+
+    async whyIncluded(endpoint: Endpoint, path: string): Promise<WhyIncluded> {
+      const json = await withErrorCause(() =>
+        binding.projectWhyIncluded(
+          this._nativeProject,
+          (endpoint as EndpointImpl)['_nativeEndpoint'],
+          path
+        )
+      )
+      return JSON.parse(json)
+    }
+
+    async chunkGraph(endpoint: Endpoint): Promise<ChunkGraph> {
+      const json = await withErrorCause(() =>
+        binding.projectChunkGraph(
+          this._nativeProject,
+          (endpoint as EndpointImpl)['_nativeEndpoint']
+        )
+      )
+      return JSON.parse(json)
+    }
+
+    async chunkGraphHtml(endpoint: Endpoint): Promise<string> {
+      return withErrorCause(() =>
+        binding.projectChunkGraphHtml(
+          this._nativeProject,
+          (endpoint as EndpointImpl)['_nativeEndpoint']
+        )
+      )
+    }
+
+    disposeInactiveEntries(): string[] {
+      return binding.projectDisposeInactiveEntries(this._nativeProject)
+    }
   }
 
   class EndpointImpl implements Endpoint {
@@ -1234,7 +1439,10 @@ function loadNative(importPath?: string) {
         return bindings.parse(src, toBuffer(options ?? {}))
       },
 
+      optimizeImage: bindings.optimizeImage,
+      optimizeImageContentTypes: bindings.optimizeImageContentTypes,
       getTargetTriple: bindings.getTargetTriple,
+      findWorkspaceRoot: bindings.findWorkspaceRoot,
       initCustomTraceSubscriber: bindings.initCustomTraceSubscriber,
       teardownTraceSubscriber: bindings.teardownTraceSubscriber,
       initHeapProfiler: bindings.initHeapProfiler,
@@ -183,6 +183,9 @@ function getBaseSWCOptions({
           isServer: !!isServerLayer,
         }
       : undefined,
+    // Record React Refresh boundaries statically, so the runtime doesn't need
+    // to inspect the module exports.
+    reactRefreshBoundary: !!hasReactRefresh && !jest,
     disableChecks: false,
   }
 }
@@ -315,6 +318,7 @@ export function getLoaderSWCOptions({
   hasServerComponents,
   isServerLayer,
   isServerActionsEnabled,
+  isUseCacheEnabled,
   optimizeBarrelExports,
 }: // This is not passed yet as "paths" resolving is handled by webpack currently.
 // resolvedBaseUrl,
@@ -340,6 +344,7 @@ export function getLoaderSWCOptions({
   hasServerComponents?: boolean
   isServerLayer: boolean
   isServerActionsEnabled?: boolean
+  isUseCacheEnabled?: boolean
   optimizeBarrelExports?: string[]
 }) {
   let baseOptions: any = getBaseSWCOptions({
@@ -368,6 +373,12 @@ export function getLoaderSWCOptions({
     ],
     relativeFilePathFromRoot,
   }
+  if (hasServerComponents && isUseCacheEnabled) {
+    baseOptions.useCache = {
+      isServer: !!isServerLayer,
+      relativeFilePathFromRoot,
+    }
+  }
   baseOptions.cjsRequireOptimizer = {
     packages: {
       'next/server': {
@@ -0,0 +1,73 @@
+import '../../server/web/globals'
+import type { AdapterOptions } from '../../server/web/adapter'
+import type { NextConfigComplete } from '../../server/config-shared'
+import { adapter } from '../../server/web/adapter'
+import { getRender } from '../webpack/loaders/next-edge-ssr-loader/render'
+import { IncrementalCache } from '../../server/lib/incremental-cache'
+import { renderToHTMLOrFlight as renderToHTML } from '../../server/app-render/app-render'
+
+// Import the userland code.
+// @ts-expect-error - replaced by webpack/turbopack loader
+import * as pageMod from 'VAR_USERLAND'
+
+declare const nextConfig: NextConfigComplete
+declare const sriEnabled: boolean
+declare const dev: boolean
+// INJECT:nextConfig
+// INJECT:sriEnabled
+// INJECT:dev
+
+const page = 'VAR_PAGE'
+
+const maybeJSONParse = (str?: string) => (str ? JSON.parse(str) : undefined)
+
+// The manifests are loaded as globals by the edge runtime.
+const manifests = self as any
+
+const buildManifest = manifests.__BUILD_MANIFEST
+const prerenderManifest = maybeJSONParse(manifests.__PRERENDER_MANIFEST)
+const reactLoadableManifest = maybeJSONParse(
+  manifests.__REACT_LOADABLE_MANIFEST
+)
+const rscManifest = maybeJSONParse(manifests.__RSC_MANIFEST?.[page])
+const rscServerManifest = maybeJSONParse(manifests.__RSC_SERVER_MANIFEST)
+const subresourceIntegrityManifest = sriEnabled
+  ? maybeJSONParse(manifests.__SUBRESOURCE_INTEGRITY_MANIFEST)
+  : undefined
+const nextFontManifest = maybeJSONParse(manifests.__NEXT_FONT_MANIFEST)
+
+const render = getRender({
+  pagesType: 'app',
+  dev,
+  page,
+  appMod: null,
+  pageMod,
+  errorMod: null,
+  error500Mod: null,
+  Document: null as any,
+  buildManifest,
+  prerenderManifest,
+  renderToHTML,
+  reactLoadableManifest,
+  clientReferenceManifest: rscManifest,
+  serverActionsManifest: rscServerManifest,
+  serverActionsBodySizeLimit: nextConfig.experimental.serverActionsBodySizeLimit,
+  subresourceIntegrityManifest,
+  config: nextConfig,
+  buildId: process.env.__NEXT_BUILD_ID!,
+  nextFontManifest,
+  incrementalCacheHandler: null,
+  appServerMod: null,
+})
+
+export const ComponentMod = pageMod
+
+export default function (
+  opts: Omit<AdapterOptions, 'IncrementalCache' | 'handler'>
+) {
+  return adapter({
+    ...opts,
+    IncrementalCache,
+    handler: render,
+  })
+}
@@ -1985,7 +1985,7 @@ const nextConfig = ${JSON.stringify({
     })}
 
 process.env.__NEXT_PRIVATE_STANDALONE_CONFIG = JSON.stringify(nextConfig)
-process.env.__NEXT_PRIVATE_PREBUNDLED_REACT = nextConfig.experimental && nextConfig.experimental.serverActions
+process.env.__NEXT_PRIVATE_PREBUNDLED_REACT = nextConfig.experimental && (nextConfig.experimental.serverActions || nextConfig.experimental.taint)
   ? 'experimental'
   : 'next'
 
@@ -16,6 +16,7 @@ import {
   RSC_ACTION_PROXY_ALIAS,
   RSC_ACTION_CLIENT_WRAPPER_ALIAS,
   RSC_ACTION_VALIDATE_ALIAS,
+  RSC_CACHE_WRAPPER_ALIAS,
   WEBPACK_RESOURCE_QUERIES,
 } from '../lib/constants'
 import { CustomRoutes } from '../lib/load-custom-routes.js'
@@ -68,6 +69,7 @@ import { NextFontManifestPlugin } from './webpack/plugins/next-font-manifest-plu
 import { getSupportedBrowsers } from './utils'
 import { MemoryWithGcCachePlugin } from './webpack/plugins/memory-with-gc-cache-plugin'
 import { getBabelConfigFile } from './get-babel-config-file'
+import { needsExperimentalReact } from '../lib/needs-experimental-react'
 
 type ExcludesFalse = <T>(x: T | false) => x is T
 type ClientEntries = {
@@ -238,6 +240,10 @@ export function getDefineEnv({
     'process.env.NEXT_RUNTIME': JSON.stringify(
       isEdgeServer ? 'edge' : isNodeServer ? 'nodejs' : undefined
     ),
+    'process.env.__NEXT_TAINT': JSON.stringify(!!config.experimental.taint),
+    'process.env.__NEXT_EXPERIMENTAL_REACT': JSON.stringify(
+      needsExperimentalReact(config)
+    ),
     'process.env.__NEXT_ACTIONS_DEPLOYMENT_ID': JSON.stringify(
       config.experimental.useDeploymentIdServerActions
     ),
@@ -776,7 +782,8 @@ export default async function getBaseWebpackConfig(
   const disableOptimizedLoading = true
   const enableTypedRoutes = !!config.experimental.typedRoutes && hasAppDir
   const useServerActions = !!config.experimental.serverActions && hasAppDir
-  const bundledReactChannel = useServerActions ? '-experimental' : ''
+  const bundledReactChannel =
+    needsExperimentalReact(config) && hasAppDir ? '-experimental' : ''
 
   if (isClient) {
     if (
@@ -1156,6 +1163,9 @@ export default async function getBaseWebpackConfig(
       [RSC_ACTION_PROXY_ALIAS]:
         'next/dist/build/webpack/loaders/next-flight-loader/action-proxy',
 
+      [RSC_CACHE_WRAPPER_ALIAS]:
+        'next/dist/build/webpack/loaders/next-flight-loader/cache-wrapper',
+
       ...(isClient || isEdgeServer
         ? {
             [clientResolveRewrites]: hasRewrites
@@ -1367,7 +1377,7 @@ export default async function getBaseWebpackConfig(
       }
 
       const notExternalModules =
-        /^(?:private-next-pages\/|next\/(?:dist\/pages\/|(?:app|document|link|image|legacy\/image|constants|dynamic|script|navigation|headers)$)|string-hash|private-next-rsc-action-validate|private-next-rsc-action-client-wrapper|private-next-rsc-action-proxy$)/
+        /^(?:private-next-pages\/|next\/(?:dist\/pages\/|(?:app|document|link|image|legacy\/image|constants|dynamic|script|navigation|headers)$)|string-hash|private-next-rsc-action-validate|private-next-rsc-action-client-wrapper|private-next-rsc-action-proxy|private-next-rsc-cache-wrapper$)/
       if (notExternalModules.test(request)) {
         return
       }
@@ -2580,6 +2590,7 @@ export default async function getBaseWebpackConfig(
           dir,
           distDir: config.distDir,
           appDir,
+          pagesDir,
           dev,
           isEdgeServer,
           pageExtensions: config.pageExtensions,
@@ -0,0 +1,21 @@
+import { unstable_cache } from '../../../../server/web/spec-extension/unstable-cache'
+
+const cachedFunctions = new Map<string, (...args: any[]) => Promise<any>>()
+
+// Wraps a "use cache" function with the data cache. The id is generated by the
+// compiler from the module and the function name, the arguments of each call
+// are added to the cache key by `unstable_cache`.
+//
+// Cached function declarations call the wrapper every time they are called, so
+// they stay hoisted. The wrapped function is created once per id.
+export function cacheWrapper<T extends (...args: any[]) => Promise<any>>(
+  id: string,
+  fn: T
+): T {
+  let cached = cachedFunctions.get(id)
+  if (!cached) {
+    cached = unstable_cache(fn, [id])
+    cachedFunctions.set(id, cached)
+  }
+  return cached as T
+}
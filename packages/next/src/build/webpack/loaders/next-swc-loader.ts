@@ -84,6 +84,7 @@ async function loaderTransform(
     relativeFilePathFromRoot,
     hasServerComponents,
     isServerActionsEnabled: nextConfig?.experimental?.serverActions,
+    isUseCacheEnabled: nextConfig?.experimental?.useCache,
     isServerLayer,
     optimizeBarrelExports,
   })
@@ -6,6 +6,7 @@ import {
 } from '../../../shared/lib/constants'
 import { getEntrypointFiles } from './build-manifest-plugin'
 import getAppRouteFromEntrypoint from '../../../server/get-app-route-from-entrypoint'
+import type { PreloadHint } from '../../../server/get-page-files'
 
 type Options = {
   dev: boolean
@@ -13,6 +14,10 @@ type Options = {
 
 export type AppBuildManifest = {
   pages: Record<string, string[]>
+  /**
+   * Files to preload for each page, only emitted by `next --turbo`.
+   */
+  preloadHints?: Record<string, PreloadHint[]>
 }
 
 const PLUGIN_NAME = 'AppBuildManifestPlugin'
@@ -28,6 +28,7 @@ interface Options {
   dir: string
   distDir: string
   appDir: string
+  pagesDir: string | undefined
   dev: boolean
   isEdgeServer: boolean
   pageExtensions: string[]
@@ -76,6 +77,8 @@ checkFields<Diff<{
       : `
   metadata?: any
   generateMetadata?: Function
+  viewport?: any
+  generateViewport?: Function
   `
   }
 }, TEntry, ''>>()
@@ -122,6 +125,13 @@ if ('generateMetadata' in entry) {
   }, FirstArg<MaybeField<TEntry, 'generateMetadata'>>, 'generateMetadata'>>()
   checkFields<Diff<ResolvingMetadata, SecondArg<MaybeField<TEntry, 'generateMetadata'>>, 'generateMetadata'>>()
 }
+
+// Check the arguments of the generateViewport function
+if ('generateViewport' in entry) {
+  checkFields<Diff<${
+    options.type === 'page' ? 'PageProps' : 'LayoutProps'
+  }, FirstArg<MaybeField<TEntry, 'generateViewport'>>, 'generateViewport'>>()
+}
 `
 }
 // Check the arguments and return type of the generateStaticParams function
@@ -485,7 +495,9 @@ export class NextTypesPlugin {
     this.dev = options.dev
     this.isEdgeServer = options.isEdgeServer
     this.pageExtensions = options.pageExtensions
-    this.pagesDir = path.join(this.appDir, '..', 'pages')
+    // The pages directory can live next to `src/app`, or `app` was moved with
+    // `experimental.appDirPath`, so it isn't derived from the app directory.
+    this.pagesDir = options.pagesDir ?? path.join(this.dir, 'pages')
     this.typedRoutes = options.typedRoutes
     this.distDirAbsolutePath = path.join(this.dir, this.distDir)
     if (this.typedRoutes && !redirectsRewritesTypesProcessed) {
@@ -26,6 +26,8 @@ function formatRSCErrorMessage(
   const NEXT_RSC_ERR_INVALID_API = /.+NEXT_RSC_ERR_INVALID_API: (.*?)\n/s
   const NEXT_RSC_ERR_ERROR_FILE_SERVER_COMPONENT =
     /.+NEXT_RSC_ERR_ERROR_FILE_SERVER_COMPONENT/
+  const NEXT_RSC_ERR_CLIENT_NAVIGATION_API =
+    /.+NEXT_RSC_ERR_CLIENT_NAVIGATION_API: (.*?)\n/s
 
   if (NEXT_RSC_ERR_REACT_API.test(message)) {
     const matches = message.match(NEXT_RSC_ERR_REACT_API)
@@ -113,6 +115,13 @@ function formatRSCErrorMessage(
       `\n\n"metadata" and "generateMetadata" cannot be exported at the same time, please keep one of them. Read more: https://nextjs.org/docs/app/api-reference/file-conventions/metadata\n\n`
     )
 
+    formattedVerboseMessage = '\n\nFile path:\n'
+  } else if (NEXT_RSC_ERR_CLIENT_NAVIGATION_API.test(message)) {
+    formattedMessage = message.replace(
+      NEXT_RSC_ERR_CLIENT_NAVIGATION_API,
+      `\n\n"$1()" is called from an event handler. It can only be called while rendering, use the router from useRouter() to navigate from event handlers instead.\nLearn more: https://nextjs.org/docs/app/api-reference/functions/redirect\n\n`
+    )
+
     formattedVerboseMessage = '\n\nFile path:\n'
   }
 
@@ -28,6 +28,7 @@ import { createSelfSignedCertificate } from '../lib/mkcert'
 import uploadTrace from '../trace/upload-trace'
 import { loadEnvConfig } from '@next/env'
 import { trace } from '../trace'
+import { needsExperimentalReact } from '../lib/needs-experimental-react'
 
 let dir: string
 let config: NextConfigComplete
@@ -144,7 +145,7 @@ async function createRouterWorker(fullConfig: NextConfigComplete): Promise<{
           : {}),
         WATCHPACK_WATCHER_LIMIT: '20',
         TURBOPACK: process.env.TURBOPACK,
-        __NEXT_PRIVATE_PREBUNDLED_REACT: !!fullConfig.experimental.serverActions
+        __NEXT_PRIVATE_PREBUNDLED_REACT: needsExperimentalReact(fullConfig)
           ? 'experimental'
           : 'next',
       },
@@ -182,7 +182,10 @@ const nextLint: CliCommand = async (argv) => {
 
   const distDir = join(baseDir, nextConfig.distDir)
   const defaultCacheLocation = join(distDir, 'cache', 'eslint/')
-  const { pagesDir, appDir } = findPagesDir(baseDir)
+  const { pagesDir, appDir } = findPagesDir(
+    baseDir,
+    nextConfig.experimental.appDirPath
+  )
 
   await verifyTypeScriptSetup({
     dir: baseDir,
@@ -1,6 +1,12 @@
 function getSocketProtocol(assetPrefix: string): string {
   let protocol = window.location.protocol
 
+  // Turbopack passes the origin of the dev server, e.g. to connect with wss
+  // when it uses `--experimental-https`.
+  if (process.env.__NEXT_DEV_SERVER_ORIGIN) {
+    protocol = new URL(process.env.__NEXT_DEV_SERVER_ORIGIN).protocol
+  }
+
   try {
     // assetPrefix is a url
     protocol = new URL(assetPrefix).protocol
@@ -1,5 +1,6 @@
 import fs from 'fs'
 import path from 'path'
+import * as Log from '../build/output/log'
 
 export const existsSync = (f: string): boolean => {
   try {
@@ -10,10 +11,24 @@ export const existsSync = (f: string): boolean => {
   }
 }
 
+let warnedAboutSrcAppDir = false
+
 export function findDir(dir: string, name: 'pages' | 'app'): string | null {
   // prioritize ./${name} over ./src/${name}
   let curDir = path.join(dir, name)
-  if (existsSync(curDir)) return curDir
+  if (existsSync(curDir)) {
+    if (
+      name === 'app' &&
+      !warnedAboutSrcAppDir &&
+      existsSync(path.join(dir, 'src', name))
+    ) {
+      warnedAboutSrcAppDir = true
+      Log.warn(
+        'Both `app` and `src/app` directories exist, `src/app` is ignored. Move the routes into one of them.'
+      )
+    }
+    return curDir
+  }
 
   curDir = path.join(dir, 'src', name)
   if (existsSync(curDir)) return curDir
@@ -21,12 +36,27 @@ export function findDir(dir: string, name: 'pages' | 'app'): string | null {
   return null
 }
 
-export function findPagesDir(dir: string): {
+export function findPagesDir(
+  dir: string,
+  appDirPath?: string
+): {
   pagesDir: string | undefined
   appDir: string | undefined
 } {
   const pagesDir = findDir(dir, 'pages') || undefined
-  const appDir = findDir(dir, 'app') || undefined
+  let appDir: string | undefined
+
+  if (appDirPath) {
+    // `experimental.appDirPath` replaces the lookup of `app` and `src/app`.
+    appDir = path.join(dir, appDirPath)
+    if (!existsSync(appDir)) {
+      throw new Error(
+        `> The app directory configured with \`experimental.appDirPath\` doesn't exist: ${appDirPath}`
+      )
+    }
+  } else {
+    appDir = findDir(dir, 'app') || undefined
+  }
 
   if (appDir == null && pagesDir == null) {
     throw new Error(
@@ -591,10 +591,46 @@ async function loadRedirects(config: NextConfig) {
   return redirects
 }
 
+/**
+ * Rewrites delegating the path prefixes and assets of the other zones of a
+ * multi-zone setup to their destination.
+ */
+function getZoneRewrites(config: NextConfig): Rewrite[] {
+  const rewrites: Rewrite[] = []
+  for (const zone of config.experimental?.zones ?? []) {
+    const destination = zone.destination.replace(/\/$/, '')
+    for (const pathPrefix of zone.pathPrefixes) {
+      const prefix = pathPrefix.replace(/\/$/, '')
+      rewrites.push(
+        {
+          source: prefix || '/',
+          destination: `${destination}${prefix}`,
+          basePath: false,
+        },
+        {
+          source: `${prefix}/:path*`,
+          destination: `${destination}${prefix}/:path*`,
+          basePath: false,
+        }
+      )
+    }
+    if (zone.assetPrefix) {
+      const assetPrefix = zone.assetPrefix.replace(/\/$/, '')
+      rewrites.push({
+        source: `${assetPrefix}/_next/:path+`,
+        destination: `${destination}${assetPrefix}/_next/:path+`,
+        basePath: false,
+      })
+    }
+  }
+  return rewrites
+}
+
 async function loadRewrites(config: NextConfig) {
+  const zoneRewrites = getZoneRewrites(config)
   if (typeof config.rewrites !== 'function') {
     return {
-      beforeFiles: [],
+      beforeFiles: processRoutes(zoneRewrites, config, 'rewrite'),
       afterFiles: [],
       fallback: [],
     }
@@ -631,7 +667,11 @@ async function loadRewrites(config: NextConfig) {
     fallback: fallback.map((r) => ({ ...r })),
   }
 
-  beforeFiles = processRoutes(beforeFiles, config, 'rewrite')
+  beforeFiles = processRoutes(
+    [...zoneRewrites, ...beforeFiles],
+    config,
+    'rewrite'
+  )
   afterFiles = processRoutes(afterFiles, config, 'rewrite')
   fallback = processRoutes(fallback, config, 'rewrite')
 
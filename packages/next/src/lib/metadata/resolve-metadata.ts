@@ -256,6 +256,7 @@ async function getDefinedMetadata(
   if (isClientReference(mod)) {
     return null
   }
+  const hasGenerateViewport = typeof mod.generateViewport === 'function'
   if (typeof mod.generateMetadata === 'function') {
     const { route } = tracingProps
     return (parent: ResolvingMetadata) =>
@@ -267,12 +268,40 @@ async function getDefinedMetadata(
             'next.page': route,
           },
         },
-        () => mod.generateMetadata(props, parent)
+        async () => ({
+          ...(await mod.generateMetadata(props, parent)),
+          ...viewportToMetadata(
+            hasGenerateViewport
+              ? await mod.generateViewport(props)
+              : mod.viewport
+          ),
+        })
       )
   }
+  if (hasGenerateViewport) {
+    return async () => ({
+      ...mod.metadata,
+      ...viewportToMetadata(await mod.generateViewport(props)),
+    })
+  }
+  if (mod.viewport) {
+    return { ...mod.metadata, ...viewportToMetadata(mod.viewport) }
+  }
   return mod.metadata || null
 }
 
+// The `viewport` and `generateViewport` exports replace the `viewport`,
+// `themeColor` and `colorScheme` fields of the metadata.
+function viewportToMetadata(viewport: any): Metadata {
+  if (!viewport) return {}
+  const { themeColor, colorScheme, ...rest } = viewport
+  const metadata: Metadata = {}
+  if (themeColor !== undefined) metadata.themeColor = themeColor
+  if (colorScheme !== undefined) metadata.colorScheme = colorScheme
+  if (Object.keys(rest).length > 0) metadata.viewport = rest
+  return metadata
+}
+
 async function collectStaticImagesFiles(
   metadata: ComponentsType['metadata'],
   props: any,
@@ -6,7 +6,8 @@ function isStringOrURL(icon: any): icon is string | URL {
 }
 
 function createLocalMetadataBase() {
-  return new URL(`http://localhost:${process.env.PORT || 3000}`)
+  const protocol = process.env.__NEXT_EXPERIMENTAL_HTTPS ? 'https' : 'http'
+  return new URL(`${protocol}://localhost:${process.env.PORT || 3000}`)
 }
 
 // For deployment url for metadata routes, prefer to use the deployment url if possible
@@ -9,7 +9,7 @@ import type {
   ItunesApp,
   ResolvedAppleWebApp,
   ResolvedAppLinks,
-  Viewport,
+  Viewport as ViewportLayout,
 } from './extra-types'
 import type {
   DeprecatedMetadataFields,
@@ -168,7 +168,7 @@ interface Metadata extends DeprecatedMetadataFields {
    * <meta name="viewport" content="width=device-width, initial-scale=1" />
    * ```
    */
-  viewport?: null | string | Viewport
+  viewport?: null | string | ViewportLayout
 
   /**
    * The creator of the document.
@@ -570,6 +570,24 @@ type SitemapFile = Array<{
 }>
 
 type ResolvingMetadata = Promise<ResolvedMetadata>
+
+/**
+ * The `viewport` export of a layout or page, or the return value of its
+ * `generateViewport` function.
+ * @example
+ * ```tsx
+ * export const viewport: Viewport = {
+ *   width: "device-width",
+ *   initialScale: 1,
+ *   themeColor: "black",
+ * }
+ * ```
+ */
+interface Viewport extends ViewportLayout {
+  themeColor?: Metadata['themeColor']
+  colorScheme?: Metadata['colorScheme']
+}
+
 declare namespace MetadataRoute {
   // eslint-disable-next-line @typescript-eslint/no-shadow
   export type Robots = RobotsFile
@@ -577,4 +595,10 @@ declare namespace MetadataRoute {
   export type Manifest = ManifestFile
 }
 
-export { Metadata, ResolvedMetadata, ResolvingMetadata, MetadataRoute }
+export {
+  Metadata,
+  ResolvedMetadata,
+  ResolvingMetadata,
+  MetadataRoute,
+  Viewport,
+}
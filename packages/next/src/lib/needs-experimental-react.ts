@@ -0,0 +1,11 @@
+import type { NextConfig } from '../server/config-shared'
+
+/**
+ * Whether the app directory uses the `react@experimental` builds bundled with
+ * Next.js. Server Actions and the taint APIs are only available there.
+ */
+export function needsExperimentalReact(config: NextConfig) {
+  return Boolean(
+    config.experimental?.serverActions || config.experimental?.taint
+  )
+}
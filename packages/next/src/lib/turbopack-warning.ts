@@ -11,6 +11,7 @@ const supportedTurbopackNextConfigOptions = [
   'compiler.relay',
   'compiler.styledComponents',
   'images',
+  'crossOrigin',
   'pageExtensions',
   'onDemandEntries',
   'rewrites',
@@ -24,6 +25,7 @@ const supportedTurbopackNextConfigOptions = [
   'experimental.turbo',
   'experimental.mdxRs',
   'experimental.forceSwcTransforms',
+  'experimental.sri',
   // options below are not really supported, but ignored
   'webpack',
   'devIndicators',
@@ -58,6 +60,7 @@ const prodSpecificTurboNextConfigOptions = [
   'experimental.outputFileTracingIgnores',
   'experiemental.outputFileTracingIncludes',
   'experimental.gzipSize',
+  'experimental.bundleBudgets',
 ]
 
 // check for babelrc, swc plugins
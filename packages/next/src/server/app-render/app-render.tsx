@@ -185,6 +185,7 @@ export async function renderToHTMLOrFlight(
     buildId,
     deploymentId,
     appDirDevErrorLogger,
+    crossOrigin,
   } = renderOpts
 
   const extraRenderResultMeta: RenderResultMetadata = {}
@@ -445,6 +446,7 @@ export async function renderToHTMLOrFlight(
                 href={fullHref}
                 // @ts-ignore
                 precedence={precedence}
+                crossOrigin={crossOrigin}
                 key={index}
               />
             )
@@ -524,7 +526,7 @@ export async function renderToHTMLOrFlight(
             const precedence =
               process.env.NODE_ENV === 'development' ? 'next_' + href : 'next'
 
-            ComponentMod.preloadStyle(fullHref)
+            ComponentMod.preloadStyle(fullHref, crossOrigin)
 
             return (
               <link
@@ -532,6 +534,7 @@ export async function renderToHTMLOrFlight(
                 href={fullHref}
                 // @ts-ignore
                 precedence={precedence}
+                crossOrigin={crossOrigin}
                 key={index}
               />
             )
@@ -1431,11 +1434,13 @@ export async function renderToHTMLOrFlight(
               false
             )}`,
             integrity: subresourceIntegrityManifest?.[polyfill],
+            crossOrigin,
           }))
 
         const [preinitScripts, bootstrapScript] = getRequiredScripts(
           buildManifest,
           assetPrefix,
+          crossOrigin,
           subresourceIntegrityManifest,
           getAssetQueryString(true),
           nonce
@@ -1509,6 +1514,7 @@ export async function renderToHTMLOrFlight(
                           key={polyfill.src}
                           src={polyfill.src}
                           integrity={polyfill.integrity}
+                          crossOrigin={polyfill.crossOrigin}
                           noModule={true}
                           nonce={nonce}
                         />
@@ -1620,6 +1626,7 @@ export async function renderToHTMLOrFlight(
             getRequiredScripts(
               buildManifest,
               assetPrefix,
+              crossOrigin,
               subresourceIntegrityManifest,
               getAssetQueryString(false),
               nonce
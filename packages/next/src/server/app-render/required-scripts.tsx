@@ -5,13 +5,24 @@ import ReactDOM from 'react-dom'
 export function getRequiredScripts(
   buildManifest: BuildManifest,
   assetPrefix: string,
+  crossOrigin: undefined | '' | 'anonymous' | 'use-credentials',
   SRIManifest: undefined | Record<string, string>,
   qs: string,
   nonce: string | undefined
-): [() => void, string | { src: string; integrity: string }] {
+): [
+  () => void,
+  { src: string; integrity?: string; crossOrigin?: string | undefined }
+] {
   let preinitScripts: () => void
   let preinitScriptCommands: string[] = []
-  let bootstrapScript: string | { src: string; integrity: string } = ''
+  const bootstrapScript: {
+    src: string
+    integrity?: string
+    crossOrigin?: string | undefined
+  } = {
+    src: '',
+    crossOrigin,
+  }
   const files = buildManifest.rootMainFiles
   if (files.length === 0) {
     throw new Error(
@@ -19,10 +30,8 @@ export function getRequiredScripts(
     )
   }
   if (SRIManifest) {
-    bootstrapScript = {
-      src: `${assetPrefix}/_next/` + files[0] + qs,
-      integrity: SRIManifest[files[0]],
-    }
+    bootstrapScript.src = `${assetPrefix}/_next/` + files[0] + qs
+    bootstrapScript.integrity = SRIManifest[files[0]]
     for (let i = 1; i < files.length; i++) {
       const src = `${assetPrefix}/_next/` + files[i] + qs
       const integrity = SRIManifest[files[i]]
@@ -34,12 +43,13 @@ export function getRequiredScripts(
         ReactDOM.preinit(preinitScriptCommands[i], {
           as: 'script',
           integrity: preinitScriptCommands[i + 1],
+          crossOrigin,
           nonce,
         })
       }
     }
   } else {
-    bootstrapScript = `${assetPrefix}/_next/` + files[0] + qs
+    bootstrapScript.src = `${assetPrefix}/_next/` + files[0] + qs
     for (let i = 1; i < files.length; i++) {
       const src = `${assetPrefix}/_next/` + files[i] + qs
       preinitScriptCommands.push(src)
@@ -49,6 +59,7 @@ export function getRequiredScripts(
       for (let i = 0; i < preinitScriptCommands.length; i++) {
         ReactDOM.preinit(preinitScriptCommands[i], {
           as: 'script',
+          crossOrigin,
           nonce,
         })
       }
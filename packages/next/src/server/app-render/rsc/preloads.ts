@@ -6,8 +6,12 @@ Files in the rsc directory are meant to be packaged as part of the RSC graph usi
 
 import ReactDOM from 'react-dom'
 
-export function preloadStyle(href: string) {
-  ReactDOM.preload(href, { as: 'style' })
+export function preloadStyle(href: string, crossOrigin?: string) {
+  const opts: any = { as: 'style' }
+  if (typeof crossOrigin === 'string') {
+    opts.crossOrigin = crossOrigin
+  }
+  ReactDOM.preload(href, opts)
 }
 
 export function preloadFont(href: string, type: string) {
@@ -301,6 +301,19 @@ const configSchema = {
         serverActions: {
           type: 'boolean',
         },
+        taint: {
+          type: 'boolean',
+        },
+        useCache: {
+          type: 'boolean',
+        },
+        appDirPath: {
+          minLength: 1,
+          type: 'string',
+        },
+        validateRemoteImages: {
+          type: 'boolean',
+        },
         serverActionsBodySizeLimit: {
           oneOf: [
             {
@@ -344,6 +357,9 @@ const configSchema = {
         isrMemoryCacheSize: {
           type: 'number',
         },
+        keepComponentNames: {
+          type: 'boolean',
+        },
         largePageDataBytes: {
           type: 'number',
         },
@@ -437,9 +453,98 @@ const configSchema = {
             enum: ['CLS', 'FCP', 'FID', 'INP', 'LCP', 'TTFB'],
           } as any,
         },
+        allowedDevOrigins: {
+          type: 'array',
+          items: {
+            type: 'string',
+          },
+        },
+        zones: {
+          type: 'array',
+          items: {
+            type: 'object',
+            additionalProperties: false,
+            required: ['name', 'pathPrefixes', 'destination'],
+            properties: {
+              name: {
+                type: 'string',
+              },
+              pathPrefixes: {
+                type: 'array',
+                items: {
+                  type: 'string',
+                },
+              },
+              destination: {
+                type: 'string',
+              },
+              assetPrefix: {
+                type: 'string',
+              },
+            },
+          },
+        },
+        appI18n: {
+          type: 'object',
+          additionalProperties: false,
+          required: ['locales', 'defaultLocale'],
+          properties: {
+            locales: {
+              type: 'array',
+              items: {
+                type: 'string',
+              },
+            },
+            defaultLocale: {
+              type: 'string',
+            },
+          },
+        },
+        bundleBudgets: {
+          type: 'array',
+          items: {
+            type: 'object',
+            additionalProperties: false,
+            required: ['path', 'maxFirstLoadJs'],
+            properties: {
+              path: {
+                type: 'string',
+              },
+              maxFirstLoadJs: {
+                type: 'number',
+              },
+              level: {
+                enum: ['warning', 'error'],
+              },
+            },
+          },
+        },
         mdxRs: {
           type: 'boolean',
         },
+        legacyBrowsers: {
+          type: 'boolean',
+        },
+        useLightningcss: {
+          type: 'boolean',
+        },
+        reactCompiler: {
+          anyOf: [
+            { type: 'boolean' },
+            {
+              type: 'object',
+              additionalProperties: false,
+              properties: {
+                compilationMode: {
+                  enum: ['infer', 'annotation', 'all'],
+                },
+                panicThreshold: {
+                  enum: ['ALL_ERRORS', 'CRITICAL_ERRORS', 'NONE'],
+                },
+              },
+            },
+          ] as any,
+        },
         typedRoutes: {
           type: 'boolean',
         },
@@ -456,6 +561,50 @@ const configSchema = {
             resolveAlias: {
               type: 'object',
             },
+            useGrass: {
+              type: 'boolean',
+            },
+            svgr: {
+              type: 'boolean',
+            },
+            structuredData: {
+              type: 'boolean',
+            },
+            importMetaGlob: {
+              type: 'boolean',
+            },
+            babelRules: {
+              type: 'array',
+              items: {
+                type: 'string',
+              },
+            },
+            resolveFallback: {
+              type: 'object',
+              additionalProperties: {
+                anyOf: [{ type: 'string' }, { type: 'boolean' }],
+              },
+            },
+            warmUp: {
+              type: 'array',
+              items: {
+                type: 'string',
+              },
+            },
+            conditions: {
+              type: 'object',
+              additionalProperties: false,
+              properties: {
+                client: { type: 'array', items: { type: 'string' } },
+                server: { type: 'array', items: { type: 'string' } },
+                edge: { type: 'array', items: { type: 'string' } },
+                reactServer: { type: 'array', items: { type: 'string' } },
+              },
+            },
+            minifyThreads: {
+              type: 'number',
+              minimum: 1,
+            },
           },
         },
         optimizePackageImports: {
@@ -715,6 +864,9 @@ const configSchema = {
         pagesBufferLength: {
           type: 'number',
         },
+        maxConcurrentCompilations: {
+          type: 'number',
+        },
       },
       type: 'object',
     },
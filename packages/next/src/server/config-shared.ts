@@ -110,6 +110,64 @@ export interface ExperimentalTurboOptions {
    * @see [Turbopack Loaders](https://nextjs.org/docs/app/api-reference/next-config-js/turbo#webpack-loaders)
    */
   loaders?: Record<string, TurboLoaderItem[]>
+
+  /**
+   * (`next --turbo` only) Compile Sass files with the embedded grass compiler instead of the `sass` package.
+   * `sassOptions.includePaths` and `sassOptions.quietDeps` are supported.
+   */
+  useGrass?: boolean
+
+  /**
+   * (`next --turbo` only) Import SVG files as React components, like `@svgr/webpack`, without running a webpack loader.
+   * Props passed to the component are set on the `<svg>` element.
+   */
+  svgr?: boolean
+
+  /**
+   * (`next --turbo` only) Parse `.yaml`/`.yml`, `.toml` and `.graphql`/`.gql` files at build time and import them as
+   * JSON modules. Extensions configured in `rules` keep using their loaders.
+   */
+  structuredData?: boolean
+
+  /**
+   * (`next --turbo` only) Support Vite's `import.meta.glob` to import all files matching a glob.
+   * `require.context` is always supported.
+   */
+  importMetaGlob?: boolean
+
+  /**
+   * (`next --turbo` only) The rules, e. g. `"*.jsx"` or `"legacy/**\/*.js"`, which are transformed with babel-loader when
+   * the project has a babel config. Defaults to all JavaScript and TypeScript files.
+   */
+  babelRules?: string[]
+
+  /**
+   * (`next --turbo` only) Replaces Node.js built-ins imported from client code, like webpack's `resolve.fallback`.
+   * A module request is used as the polyfill, `false` resolves to an empty module.
+   */
+  resolveFallback?: Record<string, string | boolean>
+
+  /**
+   * (`next --turbo` only) Globs matching the pathnames of routes which are compiled when the dev server starts,
+   * instead of when they are first requested.
+   */
+  warmUp?: string[]
+
+  /**
+   * (`next --turbo` only) Additional conditions to use when resolving the `exports` and `imports` fields of packages.
+   * `reactServer` conditions only apply to server components and route handlers.
+   */
+  conditions?: {
+    client?: string[]
+    server?: string[]
+    edge?: string[]
+    reactServer?: string[]
+  }
+
+  /**
+   * (`next build --turbo` only) The number of threads minifying JavaScript. Defaults to the number of CPUs.
+   */
+  minifyThreads?: number
 }
 
 export interface WebpackConfigContext {
@@ -144,6 +202,22 @@ export interface NextJsWebpackConfig {
   ): any
 }
 
+export interface ZoneConfig {
+  name: string
+  /**
+   * The pathname prefixes of the routes served by the zone.
+   */
+  pathPrefixes: string[]
+  /**
+   * The origin the routes are delegated to, e.g. `https://blog.example.com`.
+   */
+  destination: string
+  /**
+   * The `assetPrefix` of the zone, which its `_next` assets are served under.
+   */
+  assetPrefix?: string
+}
+
 export interface ExperimentalConfig {
   caseSensitiveRoutes?: boolean
   useDeploymentId?: boolean
@@ -230,6 +304,42 @@ export interface ExperimentalConfig {
 
   webVitalsAttribution?: Array<(typeof WEB_VITALS)[number]>
 
+  /**
+   * The other apps of a multi-zone setup. Requests for their path prefixes and
+   * assets are rewritten to their destination.
+   */
+  zones?: ZoneConfig[]
+
+  /**
+   * (`next --turbo` only) Serves every app router page under a `/<locale>`
+   * prefix as well, for each of `locales` other than `defaultLocale`. Each
+   * variant is a route of its own, so `generateStaticParams` runs for every
+   * locale. Pages read the locale from the first segment of the pathname.
+   */
+  appI18n?: {
+    locales: string[]
+    defaultLocale: string
+  }
+
+  /**
+   * (`next build --turbo` only) The maximum First Load JS, in bytes, of the
+   * app routes whose pathname matches the `path` glob. Exceeding a budget
+   * reports a warning, or an error when `level` is `'error'`.
+   */
+  bundleBudgets?: {
+    path: string
+    maxFirstLoadJs: number
+    level?: 'warning' | 'error'
+  }[]
+
+  /**
+   * (`next --turbo` only) Hostnames other than the dev server itself, e.g. `dev.example.com`,
+   * `*.example.com` or `192.168.0.10`, which may connect to the dev server for HMR. Loopback
+   * addresses may always connect, other devices on the network have to be listed. Any origin may
+   * connect when this isn't set.
+   */
+  allowedDevOrigins?: string[]
+
   /**
    * Automatically apply the "modularizeImports" optimization to imports of the specified packages.
    */
@@ -265,6 +375,33 @@ export interface ExperimentalConfig {
    */
   mdxRs?: boolean
 
+  /**
+   * (`next --turbo` only) Target the browserslist `defaults` instead of modern browsers when the project doesn't configure `browserslist`.
+   */
+  legacyBrowsers?: boolean
+
+  /**
+   * (`next --turbo` only) Transform and minify CSS with Lightning CSS.
+   */
+  useLightningcss?: boolean
+
+  /**
+   * (`next --turbo` only) Compile client and SSR code with babel-plugin-react-compiler, which needs to be installed
+   * alongside babel-loader.
+   */
+  reactCompiler?:
+    | boolean
+    | {
+        compilationMode?: 'infer' | 'annotation' | 'all'
+        panicThreshold?: 'ALL_ERRORS' | 'CRITICAL_ERRORS' | 'NONE'
+      }
+
+  /**
+   * (`next --turbo` only) Keep the names of functions and classes which look like React components when minifying,
+   * so production error reports and the React DevTools show them.
+   */
+  keepComponentNames?: boolean
+
   /**
    * Generate Route types and enable type checking for Link and Router.push, etc.
    * This option requires `appDir` to be enabled first.
@@ -292,6 +429,33 @@ export interface ExperimentalConfig {
    */
   serverActionsBodySizeLimit?: SizeLimit
 
+  /**
+   * Enables React's taint APIs, which keep objects and values from being
+   * passed to Client Components. Uses the `react@experimental` channel for the
+   * `app` directory.
+   */
+  taint?: boolean
+
+  /**
+   * Compiles the `"use cache"` directive, which caches the results of the
+   * marked async functions, or of all the exported functions of a module, in
+   * the data cache.
+   */
+  useCache?: boolean
+
+  /**
+   * The location of the `app` directory relative to the project root. By
+   * default `app` is used, or `src/app` when it doesn't exist.
+   */
+  appDirPath?: string
+
+  /**
+   * Checks the remote image URLs written as literals in `next/image` usages
+   * against `images.remotePatterns` and `images.domains` at compile time.
+   * Only supported by Turbopack.
+   */
+  validateRemoteImages?: boolean
+
   /**
    * enables the minification of server code.
    */
@@ -484,6 +648,8 @@ export interface NextConfig extends Record<string, any> {
     maxInactiveAge?: number
     /** number of pages that should be kept simultaneously without being disposed */
     pagesBufferLength?: number
+    /** (`next --turbo` only) number of pages that are compiled concurrently */
+    maxConcurrentCompilations?: number
   }
 
   /** @see [`next/amp`](https://nextjs.org/docs/api-reference/next/amp) */
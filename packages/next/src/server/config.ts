@@ -361,6 +361,18 @@ function assignDefaults(
     }
   }
 
+  if (result.experimental?.taint) {
+    // The taint APIs are only exported by the server builds of React.
+    const React = require(
+      'next/dist/compiled/react-experimental/react.shared-subset'
+    )
+    if (typeof React.experimental_taintObjectReference !== 'function') {
+      throw new Error(
+        '`experimental.taint` requires the taint APIs of React, but the bundled `react@experimental` channel does not provide them. Upgrade Next.js to a version bundling a newer `react@experimental`.'
+      )
+    }
+  }
+
   warnOptionHasBeenMovedOutOfExperimental(
     result,
     'transpilePackages',
@@ -180,13 +180,19 @@ export default class DevServer extends Server {
       })
     }
 
-    const { pagesDir, appDir } = findPagesDir(this.dir)
+    const { pagesDir, appDir } = findPagesDir(
+      this.dir,
+      this.nextConfig.experimental.appDirPath
+    )
     this.pagesDir = pagesDir
     this.appDir = appDir
   }
 
   protected getRouteMatchers(): RouteMatcherManager {
-    const { pagesDir, appDir } = findPagesDir(this.dir)
+    const { pagesDir, appDir } = findPagesDir(
+      this.dir,
+      this.nextConfig.experimental.appDirPath
+    )
 
     const ensurer: RouteEnsurer = {
       ensure: async (match) => {
@@ -12,6 +12,21 @@ export type BuildManifest = {
     [page: string]: readonly string[]
   }
   ampFirstPages: readonly string[]
+  /**
+   * Files to preload for each page, only emitted by `next --turbo`.
+   */
+  preloadHints?: {
+    [page: string]: readonly PreloadHint[]
+  }
+}
+
+export type PreloadHint = {
+  href: string
+  as: 'script' | 'style' | 'font'
+  /**
+   * Set for fonts, which are fetched in CORS mode.
+   */
+  crossOrigin?: 'anonymous'
 }
 
 export function getPageFiles(
@@ -413,6 +413,22 @@ export async function optimizeImage({
   height?: number
   nextConfigOutput?: 'standalone' | 'export'
 }): Promise<Buffer> {
+  if (process.env.TURBOPACK) {
+    // With Turbopack the images are optimized by next-swc, so the dev server
+    // doesn't need sharp or squoosh for the formats it supports.
+    const { loadBindings } =
+      require('../build/swc') as typeof import('../build/swc')
+    const bindings = await loadBindings()
+    if (bindings.optimizeImageContentTypes?.().includes(contentType)) {
+      return bindings.optimizeImage!(buffer, {
+        contentType,
+        width,
+        height,
+        quality,
+      })
+    }
+  }
+
   let optimizedBuffer = buffer
   if (sharp) {
     // Begin sharp transformation logic
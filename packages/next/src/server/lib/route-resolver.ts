@@ -66,7 +66,10 @@ export async function makeResolver(
     minimalMode: false,
     config: nextConfig,
   })
-  const { appDir, pagesDir } = findPagesDir(dir)
+  const { appDir, pagesDir } = findPagesDir(
+    dir,
+    nextConfig.experimental.appDirPath
+  )
   // we format the hostname so that it can be fetched
   const fetchHostname = formatHostname(hostname)
 
@@ -28,6 +28,7 @@ import { NextUrlWithParsedQuery, getRequestMeta } from '../request-meta'
 import { pathHasPrefix } from '../../shared/lib/router/utils/path-has-prefix'
 import { removePathPrefix } from '../../shared/lib/router/utils/remove-path-prefix'
 import setupCompression from 'next/dist/compiled/compression'
+import { needsExperimentalReact } from '../../lib/needs-experimental-react'
 
 import {
   PHASE_PRODUCTION_SERVER,
@@ -63,6 +64,10 @@ export async function initialize(opts: {
   keepAliveTimeout?: number
   customServer?: boolean
   experimentalTestProxy?: boolean
+  selfSignedCertificate?: {
+    key: string
+    cert: string
+  }
 }): Promise<[WorkerRequestHandler, WorkerUpgradeHandler]> {
   process.title = 'next-router-worker'
 
@@ -104,7 +109,10 @@ export async function initialize(opts: {
     const telemetry = new Telemetry({
       distDir: path.join(opts.dir, config.distDir),
     })
-    const { pagesDir, appDir } = findPagesDir(opts.dir)
+    const { pagesDir, appDir } = findPagesDir(
+      opts.dir,
+      config.experimental.appDirPath
+    )
 
     const { setupDev } =
       (await require('./router-utils/setup-dev')) as typeof import('./router-utils/setup-dev')
@@ -119,6 +127,9 @@ export async function initialize(opts: {
       nextConfig: config,
       isCustomServer: opts.customServer,
       turbo: !!process.env.TURBOPACK,
+      port: opts.port,
+      hostname: opts.hostname,
+      selfSignedCertificate: opts.selfSignedCertificate,
     })
   }
 
@@ -186,8 +197,7 @@ export async function initialize(opts: {
   // Set global environment variables for the app render server to use.
   process.env.__NEXT_PRIVATE_ROUTER_IPC_PORT = ipcPort + ''
   process.env.__NEXT_PRIVATE_ROUTER_IPC_KEY = ipcValidationKey
-  process.env.__NEXT_PRIVATE_PREBUNDLED_REACT = config.experimental
-    .serverActions
+  process.env.__NEXT_PRIVATE_PREBUNDLED_REACT = needsExperimentalReact(config)
     ? 'experimental'
     : 'next'
 
@@ -59,10 +59,16 @@ import {
   COMPILER_NAMES,
   DEV_CLIENT_PAGES_MANIFEST,
   DEV_MIDDLEWARE_MANIFEST,
+  EARLY_HINTS_MANIFEST,
+  FUNCTIONS_CONFIG_MANIFEST,
   MIDDLEWARE_MANIFEST,
   NEXT_FONT_MANIFEST,
+  NEXT_SCRIPT_MANIFEST,
   PAGES_MANIFEST,
   PHASE_DEVELOPMENT_SERVER,
+  SEGMENT_BOUNDARIES_MANIFEST,
+  STATIC_METADATA_MANIFEST,
+  SUBRESOURCE_INTEGRITY_MANIFEST,
 } from '../../../shared/lib/constants'
 
 import {
@@ -118,6 +124,12 @@ type SetupOpts = {
     ReturnType<typeof import('./filesystem').setupFsCheck>
   >
   nextConfig: NextConfigComplete
+  port: number
+  hostname?: string
+  selfSignedCertificate?: {
+    key: string
+    cert: string
+  }
 }
 
 async function verifyTypeScript(opts: SetupOpts) {
@@ -202,8 +214,19 @@ async function startWatcher(opts: SetupOpts) {
       jsConfig,
       watch: true,
       env: process.env as Record<string, string>,
+      devServer: {
+        hostname: opts.hostname ?? 'localhost',
+        port: opts.port,
+        https: opts.selfSignedCertificate,
+        allowedOrigins: opts.nextConfig.experimental.allowedDevOrigins,
+      },
     })
     const iter = project.entrypointsSubscribe()
+    if (opts.nextConfig.experimental.turbo?.warmUp?.length) {
+      project.warmUp().catch((err) => {
+        Log.warn('Failed to warm up routes:', err)
+      })
+    }
     const curEntries: Map<string, Route> = new Map()
     const changeSubscriptions: Map<string, AsyncIterator<any>> = new Map()
     let prevMiddleware: boolean | undefined = undefined
@@ -406,6 +429,24 @@ async function startWatcher(opts: SetupOpts) {
     const pagesManifests = new Map<string, PagesManifest>()
     const appPathsManifests = new Map<string, PagesManifest>()
     const middlewareManifests = new Map<string, MiddlewareManifest>()
+    // Manifests which map each app route to an entry under `key`. Every
+    // endpoint writes its own, they are merged into `server/<name>`.
+    const appRouteManifests = [
+      { name: FUNCTIONS_CONFIG_MANIFEST, key: 'functions' },
+      { name: EARLY_HINTS_MANIFEST, key: 'pages' },
+      { name: STATIC_METADATA_MANIFEST, key: 'pages' },
+      { name: SEGMENT_BOUNDARIES_MANIFEST, key: 'pages' },
+    ].map(({ name, key }) => ({
+      name,
+      key,
+      manifests: new Map<string, Record<string, any>>(),
+    }))
+    // The subresource integrity hashes of the client scripts of each app
+    // route, only written with `experimental.sri`.
+    const subresourceIntegrityManifests = new Map<
+      string,
+      Record<string, string>
+    >()
     const clientToHmrSubscription = new Map<
       ws,
       Map<string, AsyncIterator<any>>
@@ -456,6 +497,65 @@ async function startWatcher(opts: SetupOpts) {
       )
     }
 
+    // The pages whose `<Script strategy="worker">` scripts were reported as
+    // not loading because `experimental.nextScriptWorkers` is disabled.
+    const reportedWorkerScriptPages = new Set<string>()
+
+    async function loadNextScriptManifest(pageName: string): Promise<void> {
+      let manifest: { workerScripts: { src?: string; id?: string }[] }
+      try {
+        manifest = await loadPartialManifest(NEXT_SCRIPT_MANIFEST, pageName)
+      } catch {
+        // Only pages with worker scripts have the manifest.
+        reportedWorkerScriptPages.delete(pageName)
+        return
+      }
+      if (
+        opts.nextConfig.experimental.nextScriptWorkers ||
+        manifest.workerScripts.length === 0 ||
+        reportedWorkerScriptPages.has(pageName)
+      ) {
+        return
+      }
+      reportedWorkerScriptPages.add(pageName)
+      const scripts = manifest.workerScripts
+        .map(({ src, id }) => src ?? id ?? 'an inline script')
+        .join(', ')
+      Log.warn(
+        `${pageName} uses <Script strategy="worker"> for ${scripts}, which is only loaded with \`experimental.nextScriptWorkers\` enabled`
+      )
+    }
+
+    async function loadAppRouteManifests(
+      pageName: string,
+      type: 'app' | 'app-route'
+    ): Promise<void> {
+      for (const { name, manifests } of appRouteManifests) {
+        try {
+          manifests.set(
+            pageName,
+            await loadPartialManifest(name, pageName, type)
+          )
+        } catch {
+          // Not every route has every manifest, e.g. route handlers have no
+          // segment boundaries and only static pages have static metadata.
+          manifests.delete(pageName)
+        }
+      }
+      try {
+        subresourceIntegrityManifests.set(
+          pageName,
+          await loadPartialManifest(
+            `${SUBRESOURCE_INTEGRITY_MANIFEST}.json`,
+            pageName,
+            type
+          )
+        )
+      } catch {
+        subresourceIntegrityManifests.delete(pageName)
+      }
+    }
+
     const buildingReported = new Set<string>()
 
     async function changeSubscription(
@@ -604,6 +704,12 @@ async function startWatcher(opts: SetupOpts) {
       }
       for (const m of manifests) {
         Object.assign(manifest.pages, m.pages)
+        if (m.preloadHints) {
+          manifest.preloadHints = Object.assign(
+            manifest.preloadHints ?? {},
+            m.preloadHints
+          )
+        }
         if (m.rootMainFiles.length) manifest.rootMainFiles = m.rootMainFiles
       }
       return manifest
@@ -615,6 +721,12 @@ async function startWatcher(opts: SetupOpts) {
       }
       for (const m of manifests) {
         Object.assign(manifest.pages, m.pages)
+        if (m.preloadHints) {
+          manifest.preloadHints = Object.assign(
+            manifest.preloadHints ?? {},
+            m.preloadHints
+          )
+        }
       }
       return manifest
     }
@@ -746,6 +858,48 @@ async function startWatcher(opts: SetupOpts) {
       )
     }
 
+    async function writeAppRouteManifests(): Promise<void> {
+      for (const { name, key, manifests } of appRouteManifests) {
+        const manifest = { version: 1, [key]: {} }
+        for (const m of manifests.values()) {
+          Object.assign(manifest[key], m[key])
+        }
+        const manifestPath = path.join(distDir, 'server', name)
+        await clearCache(manifestPath)
+        await writeFile(
+          manifestPath,
+          JSON.stringify(manifest, null, 2),
+          'utf-8'
+        )
+      }
+
+      if (subresourceIntegrityManifests.size > 0) {
+        const manifest: Record<string, string> = {}
+        for (const m of subresourceIntegrityManifests.values()) {
+          Object.assign(manifest, m)
+        }
+        const manifestPath = path.join(
+          distDir,
+          'server',
+          SUBRESOURCE_INTEGRITY_MANIFEST
+        )
+        await clearCache(`${manifestPath}.json`)
+        await writeFile(
+          `${manifestPath}.json`,
+          JSON.stringify(manifest, null, 2),
+          'utf-8'
+        )
+        // The `.js` variant is loaded by the edge runtime.
+        await writeFile(
+          `${manifestPath}.js`,
+          `self.__SUBRESOURCE_INTEGRITY_MANIFEST=${JSON.stringify(
+            JSON.stringify(manifest)
+          )}`,
+          'utf-8'
+        )
+      }
+    }
+
     async function writeFontManifest(): Promise<void> {
       // TODO: turbopack should write the correct
       // version of this
@@ -845,6 +999,7 @@ async function startWatcher(opts: SetupOpts) {
     await writePagesManifest()
     await writeAppPathsManifest()
     await writeMiddlewareManifest()
+    await writeAppRouteManifests()
     await writeOtherManifests()
     await writeFontManifest()
 
@@ -875,7 +1030,14 @@ async function startWatcher(opts: SetupOpts) {
       },
 
       // TODO: Figure out if socket type can match the NextJsHotReloaderInterface
-      onHMR(req, socket: Socket, head) {
+      async onHMR(req, socket: Socket, head) {
+        const origin = req.headers.origin
+        if (origin && !(await project.isAllowedDevOrigin(origin))) {
+          Log.warn(`Blocked HMR connection from disallowed origin ${origin}`)
+          socket.destroy()
+          return
+        }
+
         wsServer.handleUpgrade(req, socket, head, (client) => {
           clients.add(client)
           client.on('close', () => clients.delete(client))
@@ -957,8 +1119,16 @@ async function startWatcher(opts: SetupOpts) {
       async getCompilationErrors(_page) {
         return []
       },
-      invalidate(/* Unused parameter: { reloadAfterInvalidation } */) {
-        // Not implemented yet.
+      invalidate({ reloadAfterInvalidation }) {
+        // Turbopack watches the env and tsconfig/jsconfig files itself, so
+        // there is nothing to rebuild. Modules which didn't resolve before a
+        // path mapping changed aren't part of any HMR update though, so the
+        // open pages are reloaded instead.
+        if (reloadAfterInvalidation) {
+          turbopackHotReloader.send({
+            action: HMR_ACTIONS_SENT_TO_BROWSER.RELOAD_PAGE,
+          })
+        }
       },
       async buildFallbackError() {
         // Not implemented yet.
@@ -1101,6 +1271,7 @@ async function startWatcher(opts: SetupOpts) {
 
             await loadBuildManifest(page)
             await loadPagesManifest(page)
+            await loadNextScriptManifest(page)
             if (type === 'edge') {
               await loadMiddlewareManifest(page, 'pages')
             } else {
@@ -1162,10 +1333,12 @@ async function startWatcher(opts: SetupOpts) {
             await loadAppBuildManifest(page)
             await loadBuildManifest(page, 'app')
             await loadAppPathManifest(page, 'app')
+            await loadAppRouteManifests(page, 'app')
 
             await writeAppBuildManifest()
             await writeBuildManifest()
             await writeAppPathsManifest()
+            await writeAppRouteManifests()
             await writeMiddlewareManifest()
             await writeOtherManifests()
 
@@ -1181,6 +1354,7 @@ async function startWatcher(opts: SetupOpts) {
             const type = writtenEndpoint?.type
 
             await loadAppPathManifest(page, 'app-route')
+            await loadAppRouteManifests(page, 'app-route')
             if (type === 'edge') {
               await loadMiddlewareManifest(page, 'app-route')
             } else {
@@ -1189,6 +1363,7 @@ async function startWatcher(opts: SetupOpts) {
 
             await writeAppBuildManifest()
             await writeAppPathsManifest()
+            await writeAppRouteManifests()
             await writeMiddlewareManifest()
             await writeMiddlewareManifest()
             await writeOtherManifests()
@@ -1675,7 +1850,7 @@ async function startWatcher(opts: SetupOpts) {
           }
         })
         hotReloader.invalidate({
-          reloadAfterInvalidation: envChange,
+          reloadAfterInvalidation: envChange || (!!opts.turbo && tsconfigChange),
         })
       }
 
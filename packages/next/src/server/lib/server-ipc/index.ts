@@ -9,6 +9,7 @@ import isError from '../../../lib/is-error'
 import { genRenderExecArgv } from '../worker-utils'
 import { deserializeErr } from './request-utils'
 import type { Env } from '@next/env'
+import { needsExperimentalReact } from '../../../lib/needs-experimental-react'
 
 // we can't use process.send as jest-worker relies on
 // it already and can cause unexpected message errors
@@ -91,7 +92,7 @@ export const createWorker = async (
   nextConfig: NextConfigComplete,
   initialEnv: NodeJS.ProcessEnv | Env = process.env
 ): Promise<RenderWorker> => {
-  const useServerActions = !!nextConfig.experimental.serverActions
+  const useExperimentalReact = needsExperimentalReact(nextConfig)
   const { Worker } =
     require('next/dist/compiled/jest-worker') as typeof import('next/dist/compiled/jest-worker')
 
@@ -116,7 +117,11 @@ export const createWorker = async (
           process.env.__NEXT_PRIVATE_STANDALONE_CONFIG,
         NODE_ENV: process.env.NODE_ENV,
         __NEXT_PRIVATE_PREBUNDLED_REACT:
-          type === 'app' ? (useServerActions ? 'experimental' : 'next') : '',
+          type === 'app'
+            ? useExperimentalReact
+              ? 'experimental'
+              : 'next'
+            : '',
         ...(process.env.NEXT_CPU_PROF
           ? { __NEXT_PRIVATE_CPU_PROFILE: `CPU.${type}-renderer` }
           : {}),
@@ -55,6 +55,7 @@ export async function getRequestHandlers({
   isNodeDebugging,
   keepAliveTimeout,
   experimentalTestProxy,
+  selfSignedCertificate,
 }: {
   dir: string
   port: number
@@ -64,6 +65,7 @@ export async function getRequestHandlers({
   isNodeDebugging?: boolean
   keepAliveTimeout?: number
   experimentalTestProxy?: boolean
+  selfSignedCertificate?: StartServerOptions['selfSignedCertificate']
 }): ReturnType<typeof initialize> {
   return initialize({
     dir,
@@ -75,6 +77,7 @@ export async function getRequestHandlers({
     isNodeDebugging: isNodeDebugging || false,
     keepAliveTimeout,
     experimentalTestProxy,
+    selfSignedCertificate,
   })
 }
 
@@ -255,6 +258,10 @@ export async function startServer({
         }
         // expose the main port to render workers
         process.env.PORT = port + ''
+        // and whether the dev server uses HTTPS, for the URLs of metadata assets
+        if (selfSignedCertificate) {
+          process.env.__NEXT_EXPERIMENTAL_HTTPS = '1'
+        }
 
         // New line after the bootstrap info
         Log.info('')
@@ -285,6 +292,7 @@ export async function startServer({
           isNodeDebugging: Boolean(isNodeDebugging),
           keepAliveTimeout,
           experimentalTestProxy: !!isExperimentalTestProxy,
+          selfSignedCertificate,
         })
         requestHandler = initResult[0]
         upgradeHandler = initResult[1]
@@ -17,6 +17,7 @@ import {
   REACT_LOADABLE_MANIFEST,
   CLIENT_REFERENCE_MANIFEST,
   SERVER_REFERENCE_MANIFEST,
+  SUBRESOURCE_INTEGRITY_MANIFEST,
 } from '../shared/lib/constants'
 import { join } from 'path'
 import { requirePage } from './require'
@@ -148,6 +149,7 @@ async function loadComponentsImpl({
     reactLoadableManifest,
     clientReferenceManifest,
     serverActionsManifest,
+    subresourceIntegrityManifest,
   ] = await Promise.all([
     loadManifestWithRetries<BuildManifest>(join(distDir, BUILD_MANIFEST)),
     loadManifestWithRetries<ReactLoadableManifest>(
@@ -173,6 +175,13 @@ async function loadComponentsImpl({
           join(distDir, 'server', SERVER_REFERENCE_MANIFEST + '.json')
         ).catch(() => null)
       : null,
+    // Only written with `experimental.sri`, so it isn't retried.
+    hasClientManifest
+      ? loadManifestWithRetries<Record<string, string>>(
+          join(distDir, 'server', SUBRESOURCE_INTEGRITY_MANIFEST + '.json'),
+          1
+        ).catch(() => undefined)
+      : undefined,
   ])
 
   const Component = interopDefault(ComponentMod)
@@ -195,6 +204,7 @@ async function loadComponentsImpl({
     getStaticPaths,
     clientReferenceManifest,
     serverActionsManifest,
+    subresourceIntegrityManifest,
     isAppPath,
     pathname,
     routeModule,
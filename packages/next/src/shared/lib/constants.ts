@@ -32,7 +32,11 @@ export const APP_PATH_ROUTES_MANIFEST = 'app-path-routes-manifest.json'
 export const BUILD_MANIFEST = 'build-manifest.json'
 export const APP_BUILD_MANIFEST = 'app-build-manifest.json'
 export const FUNCTIONS_CONFIG_MANIFEST = 'functions-config-manifest.json'
+export const EARLY_HINTS_MANIFEST = 'early-hints-manifest.json'
+export const STATIC_METADATA_MANIFEST = 'static-metadata-manifest.json'
+export const SEGMENT_BOUNDARIES_MANIFEST = 'segment-boundaries-manifest.json'
 export const SUBRESOURCE_INTEGRITY_MANIFEST = 'subresource-integrity-manifest'
+export const NEXT_SCRIPT_MANIFEST = 'next-script-manifest.json'
 export const NEXT_FONT_MANIFEST = 'next-font-manifest'
 export const EXPORT_MARKER = 'export-marker.json'
 export const EXPORT_DETAIL = 'export-detail.json'
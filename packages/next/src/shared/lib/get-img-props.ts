@@ -14,6 +14,7 @@ export interface StaticImageData {
   blurDataURL?: string
   blurWidth?: number
   blurHeight?: number
+  animated?: boolean
 }
 
 export interface StaticRequire {
@@ -334,6 +335,7 @@ export function getImgProps(
   let heightInt = getInt(height)
   let blurWidth: number | undefined
   let blurHeight: number | undefined
+  let isAnimated = false
   if (isStaticImport(src)) {
     const staticImageData = isStaticRequire(src) ? src.default : src
 
@@ -356,6 +358,7 @@ export function getImgProps(
     blurHeight = staticImageData.blurHeight
     blurDataURL = blurDataURL || staticImageData.blurDataURL
     staticSrc = staticImageData.src
+    isAnimated = !!staticImageData.animated
 
     if (!fill) {
       if (!widthInt && !heightInt) {
@@ -382,6 +385,11 @@ export function getImgProps(
   if (config.unoptimized) {
     unoptimized = true
   }
+  if (isDefaultLoader && isAnimated) {
+    // The image optimizer serves animated images as they are, so there's no
+    // need to proxy them. Custom loaders may still handle them.
+    unoptimized = true
+  }
   if (isDefaultLoader && src.endsWith('.svg') && !config.dangerouslyAllowSVG) {
     // Special case to make svg serve as-is to avoid proxying
     // through the built-in Image Optimization API.
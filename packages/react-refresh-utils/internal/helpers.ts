@@ -54,6 +54,7 @@ function isSafeExport(key: string): boolean {
     key === '__esModule' ||
     key === '__N_SSG' ||
     key === '__N_SSP' ||
+    key === '__N_REFRESH_BOUNDARY' ||
     // TODO: remove this key from page config instead of allow listing it
     key === 'config'
   )
@@ -106,6 +107,11 @@ function isReactRefreshBoundary(moduleExports: unknown): boolean {
     // Exit if we can't iterate over exports.
     return false
   }
+  // The SWC transform marks the modules it knows to be boundaries ahead of
+  // time. The exports of all other modules are inspected below.
+  if ((moduleExports as any).__N_REFRESH_BOUNDARY === true) {
+    return true
+  }
   var hasExports = false
   var areAllExportsComponents = true
   for (var key in moduleExports) {